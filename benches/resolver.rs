@@ -0,0 +1,96 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use transformer::dataset::{Dataset, Model};
+use transformer::rdf;
+use transformer::resolver::Resolver;
+use transformer::testing::{SYNTHETIC_SCHEMA, synthetic_dataset, synthetic_fields, synthetic_triples};
+
+
+const RECORDS: &[usize] = &[100, 1_000, 10_000];
+const FIELDS: usize = 10;
+const GRAPHS: usize = 2;
+
+
+fn load(c: &mut Criterion) {
+    let mut group = c.benchmark_group("load");
+
+    for records in RECORDS {
+        group.bench_with_input(BenchmarkId::from_parameter(records), records, |b, &records| {
+            b.iter_batched(
+                || synthetic_triples(records, FIELDS, 0),
+                |triples| {
+                    let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+                    dataset.load(triples, "synthetic_0").unwrap()
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+
+fn field_map(c: &mut Criterion) {
+    let dataset = synthetic_dataset(100, FIELDS, GRAPHS).unwrap();
+    let scope = dataset.scope_iris(&[Model::Name]).unwrap();
+    let resolver = Resolver::new(&dataset);
+
+    let synthetic = synthetic_fields(FIELDS);
+    let fields: Vec<&iref::Iri> = synthetic.iter().map(|field| field.as_iri()).collect();
+
+    c.bench_function("field_map", |b| b.iter(|| resolver.field_map(&fields, &scope).unwrap()));
+}
+
+
+fn records(c: &mut Criterion) {
+    let mut group = c.benchmark_group("records");
+    group.sample_size(10);
+
+    let synthetic = synthetic_fields(FIELDS);
+    let mut fields: Vec<&iref::Iri> = synthetic.iter().map(|field| field.as_iri()).collect();
+    fields.push(rdf::Name::EntityId.into());
+    fields.push(rdf::Name::ScientificName.into());
+
+    let mut linked = fields.clone();
+    linked.push(rdf::Name::ScientificNameAuthorship.into());
+
+    for records in RECORDS {
+        let dataset = synthetic_dataset(*records, FIELDS, GRAPHS).unwrap();
+        let scope = dataset.scope_iris(&[Model::Name]).unwrap();
+
+        // a new resolver is used for every iteration so that each one has to index the scope
+        group.bench_with_input(BenchmarkId::new("without_from", records), &fields, |b, fields| {
+            b.iter(|| Resolver::new(&dataset).records(fields, &scope, false).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("with_from", records), &linked, |b, fields| {
+            b.iter(|| Resolver::new(&dataset).records(fields, &scope, false).unwrap())
+        });
+
+        // reusing the resolver only indexes the scope on the first iteration
+        let resolver = Resolver::new(&dataset);
+        group.bench_with_input(BenchmarkId::new("with_from_indexed", records), &linked, |b, fields| {
+            b.iter(|| resolver.records(fields, &scope, false).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+
+fn get_all(c: &mut Criterion) {
+    let mut group = c.benchmark_group("names_get_all");
+    group.sample_size(10);
+
+    for records in RECORDS {
+        let dataset = synthetic_dataset(*records, FIELDS, GRAPHS).unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(records), &dataset, |b, dataset| {
+            b.iter(|| transformer::models::name::get_all(dataset).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+
+criterion_group!(benches, load, field_map, records, get_all);
+criterion_main!(benches);
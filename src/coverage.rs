@@ -0,0 +1,47 @@
+use crate::dataset::{Dataset, Model};
+use crate::errors::TransformError;
+use crate::resolver::{FieldCoverage, Resolver};
+
+
+/// The mapping coverage of a single model.
+#[derive(Debug, serde::Serialize)]
+pub struct ModelCoverage {
+    pub model: String,
+    pub fields: Vec<FieldReport>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct FieldReport {
+    pub field: String,
+    pub coverage: FieldCoverage,
+}
+
+
+/// Get the mapping coverage of the canonical fields for every model.
+///
+/// This is useful when a new source schema is added to see which fields
+/// will actually have data after the transform.
+pub fn report(dataset: &Dataset) -> Result<Vec<ModelCoverage>, TransformError> {
+    let resolver = Resolver::new(dataset);
+    let mut report = Vec::new();
+
+    for model in Model::ALL {
+        let scope = dataset.scope_iris(&[*model])?;
+
+        let fields = model.fields();
+        let coverage = resolver.coverage(&fields, &scope)?;
+
+        report.push(ModelCoverage {
+            model: model.name().to_string(),
+            fields: coverage
+                .into_iter()
+                .map(|(field, coverage)| FieldReport {
+                    field: field.to_string(),
+                    coverage,
+                })
+                .collect(),
+        });
+    }
+
+    Ok(report)
+}
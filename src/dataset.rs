@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::BufReader;
 
 use iref::IriBuf;
@@ -7,14 +7,34 @@ use sophia::api::dataset::Dataset as DatasetTrait;
 use sophia::api::graph::adapter::PartialUnionGraph;
 use sophia::api::ns::Namespace;
 use sophia::api::prelude::*;
-use sophia::api::term::matcher::GraphNameMatcher;
+use sophia::api::sparql::{Query, SparqlDataset};
 use sophia::api::term::{GraphName, SimpleTerm};
 use sophia::inmem::dataset::FastDataset;
+use sophia::sparql::{SparqlQuery, SparqlWrapper};
+use sophia::term::{ArcTerm, GenericLiteral};
 use sophia::turtle::parser::trig;
+use sophia::turtle::serializer::nq::NqSerializer;
+use sophia::turtle::serializer::trig::TrigSerializer;
 use tracing::{debug, info};
 
 use crate::errors::TransformError;
-use crate::rdf::{DataTypes, IntoIriTerm, Literal};
+pub use crate::matchers::{NamedGraphsOrDefault, SingleGraph};
+use crate::rdf::{
+    Condition,
+    DataTypes,
+    FromCondition,
+    IntoIriTerm,
+    Literal,
+    Map,
+    Mapping,
+    MappingCondition,
+    Provenance,
+    ScaleCondition,
+    SparqlRow,
+    TrimCondition,
+    Value,
+};
+use crate::resolver::{ResolveOptions, Resolver};
 
 
 /// index, field, value, source
@@ -24,7 +44,206 @@ pub type Quad = (usize, String, Literal, String);
 pub type Triple = (usize, String, Literal);
 
 
-pub type PartialGraph<'a> = PartialUnionGraph<&'a FastDataset, GraphIri<'a>>;
+/// A summary of the data loaded from a source.
+#[derive(Debug, Default)]
+pub struct TransformStats {
+    /// The amount of rows inserted into the dataset.
+    pub rows_loaded: usize,
+    /// The amount of quads inserted into the dataset.
+    pub quads_inserted: usize,
+    /// The amount of rows that weren't inserted because every value was blank or
+    /// the columns the entity_id is mapped from were blank.
+    pub rows_skipped_empty: usize,
+    /// Triples that couldn't be read along with their position in the source.
+    /// Only populated by a tolerant load as otherwise the first error is returned.
+    pub parse_errors: Vec<(usize, TransformError)>,
+    /// Rows that replaced an existing record with the same key. Only populated by an upsert.
+    pub rows_replaced: usize,
+    /// Quads removed from the source graph before new data was inserted.
+    pub quads_removed: usize,
+    /// The amount of values in loaded rows that weren't inserted because they were one
+    /// of the null tokens.
+    pub values_skipped_null: usize,
+}
+
+
+/// A summary of everything loaded into the dataset.
+///
+/// Graphs that contain loaded records are source graphs, any other named graph holds
+/// schema definitions or metadata like the load provenance. The mappings of a source are usually declared in its
+/// own graph so a source graph can hold schema definitions as well.
+#[derive(Debug, Default)]
+pub struct DatasetStats {
+    /// The amount of quads in all graphs, including the default graph.
+    pub total_quads: usize,
+    /// The IRI and amount of quads of every named graph.
+    pub named_graphs: Vec<(String, usize)>,
+    /// The named graphs that don't contain any loaded records.
+    pub schema_graphs: Vec<String>,
+    /// The named graphs that contain loaded records.
+    pub source_graphs: Vec<String>,
+    /// The amount of unique predicates across all graphs.
+    pub distinct_predicates: usize,
+}
+
+impl std::fmt::Display for DatasetStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .named_graphs
+            .iter()
+            .map(|(graph, _)| graph.len())
+            .max()
+            .unwrap_or(0)
+            .max(5);
+
+        writeln!(f, "{:<width$}  {:>10}  kind", "graph", "quads")?;
+        for (graph, quads) in &self.named_graphs {
+            let kind = match self.source_graphs.contains(graph) {
+                true => "source",
+                false => "schema",
+            };
+            writeln!(f, "{graph:<width$}  {quads:>10}  {kind}")?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "total quads: {}", self.total_quads)?;
+        writeln!(f, "schema graphs: {}", self.schema_graphs.len())?;
+        writeln!(f, "source graphs: {}", self.source_graphs.len())?;
+        write!(f, "distinct predicates: {}", self.distinct_predicates)
+    }
+}
+
+
+pub type PartialGraph<'a> = PartialUnionGraph<&'a FastDataset, NamedGraphsOrDefault<'a>>;
+
+/// The state shared by all rows loaded from a source.
+struct LoadContext<'a> {
+    source: &'a str,
+    /// The source graph to load the data into.
+    base: IriBuf,
+    /// The columns that the entity_id is mapped from.
+    entity_id_headers: Vec<IriBuf>,
+    /// The namespace of the column IRIs, which is the dataset schema unless overridden.
+    namespace: IriBuf,
+    /// instead of recreating the header iri for each record we store it cache
+    header_cache: HashMap<String, IriBuf>,
+    /// The values that aren't inserted as they mean the value is missing.
+    null_tokens: NullTokens,
+}
+
+/// How the subject of each loaded row is determined.
+enum RowSubjects<'a> {
+    /// Use the row index with an optional prefix.
+    Index(Option<&'a str>),
+    /// Reuse the subject of an existing record that has the same key value.
+    Key(KeyIndex),
+}
+
+/// An index of the records already in a source graph by the value of a key column.
+struct KeyIndex {
+    header: String,
+    subjects: HashMap<String, Vec<SimpleTerm<'static>>>,
+    /// The next numeric subject that isn't used by an existing record.
+    next_idx: usize,
+}
+
+type ExportQuad<'a> = (GraphName<&'a SimpleTerm<'static>>, [&'a SimpleTerm<'static>; 3]);
+
+
+/// The RDF format to export the dataset as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    NQuads,
+    TriG,
+}
+
+
+/// The quads to include when exporting the dataset.
+#[derive(Debug, Clone)]
+pub enum ExportGraphs {
+    /// Every quad in the dataset.
+    All,
+    /// Only the quads in the graphs of the specified sources along with their provenance.
+    Sources(Vec<String>),
+    /// Only the mapping definitions from the schemas.
+    Schemas,
+}
+
+
+/// Details about a source load that can't be determined from the data itself.
+#[derive(Debug, Clone, Default)]
+pub struct LoadMeta {
+    /// A checksum of the source file, eg. a sha256 hex digest.
+    pub checksum: Option<String>,
+    /// The null tokens to use for this source instead of the ones set on the dataset.
+    pub null_tokens: Option<NullTokens>,
+}
+
+
+/// The values used in place of a missing value when none is loaded by default.
+pub const DEFAULT_NULL_TOKENS: &[&str] = &["NA", "N/A", "null", "-", "not provided"];
+
+
+/// The values that mean a value is missing, eg. `NA` or `not provided`.
+///
+/// Tokens are compared after trimming the value and ignoring case. Blank values are always
+/// missing whether or not an empty token is included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NullTokens(HashSet<String>);
+
+impl Default for NullTokens {
+    fn default() -> Self {
+        NullTokens::new(DEFAULT_NULL_TOKENS)
+    }
+}
+
+impl NullTokens {
+    pub fn new<T: AsRef<str>>(tokens: impl IntoIterator<Item = T>) -> NullTokens {
+        NullTokens(
+            tokens
+                .into_iter()
+                .map(|token| token.as_ref().trim().to_lowercase())
+                .collect(),
+        )
+    }
+
+    /// Only treat blank values as missing.
+    pub fn blank_only() -> NullTokens {
+        NullTokens(HashSet::new())
+    }
+
+    pub fn is_null(&self, value: &str) -> bool {
+        let value = value.trim();
+        value.is_empty() || self.0.contains(&value.to_lowercase())
+    }
+
+    /// Whether the literal is a string that is one of the null tokens. Numbers are never missing.
+    pub fn is_null_literal(&self, literal: &Literal) -> bool {
+        match literal {
+            Literal::String(value) => self.is_null(value),
+            Literal::UInt64(_) => false,
+        }
+    }
+}
+
+/// The provenance recorded for a source when it was last loaded.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SourceMetadata {
+    /// The IRI of the source graph.
+    pub source: String,
+    /// The time the source was loaded as an RFC 3339 timestamp.
+    pub loaded_at: Option<String>,
+    /// The amount of records loaded from the source.
+    pub records: Option<u64>,
+    pub checksum: Option<String>,
+}
+
+
+/// The graph that contains the provenance of every loaded source.
+pub const PROVENANCE_GRAPH: &str = "http://arga.org.au/provenance";
+
+/// The default namespace for the graphs of loaded sources.
+pub const DEFAULT_SOURCE_BASE: &str = "http://arga.org.au/source/";
 
 
 pub struct Dataset {
@@ -32,6 +251,19 @@ pub struct Dataset {
     pub source: FastDataset,
     pub map: String,
     pub schema: IriBuf,
+    pub source_base: IriBuf,
+    /// Fail when resolving records finds problems with the schemas or data instead of
+    /// logging a warning. See `ResolveOptions::strict` for the problems that are checked.
+    pub strict: bool,
+    /// Allow a model to have no data loaded in any of its sources. When unset the
+    /// model is reported with `TransformError::EmptyScope` by `check_scope`.
+    pub allow_empty: bool,
+    /// The values that are skipped when loading and treated as absent when resolving.
+    /// A load can override them with `LoadMeta::null_tokens`.
+    pub null_tokens: NullTokens,
+    /// Match misspelt country names to a country code when they don't match a known name.
+    /// See `validation::country::country_code_fuzzy`.
+    pub fuzzy_countries: bool,
 }
 
 
@@ -103,6 +335,7 @@ impl DatasetTerm {
 }
 
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Model {
     Agent,
     Annotation,
@@ -114,14 +347,93 @@ pub enum Model {
     Library,
     Name,
     Organism,
+    ProjectAssembly,
     ProjectMember,
     Project,
     Publication,
     SequencingRun,
+    Sequence,
     Subsample,
+    Taxon,
     Tissue,
 }
 
+impl Model {
+    pub const ALL: &[Model] = {
+        use Model::*;
+        &[
+            Agent,
+            Annotation,
+            Assembly,
+            Collecting,
+            DataProduct,
+            Deposition,
+            Extraction,
+            Library,
+            Name,
+            Organism,
+            ProjectAssembly,
+            ProjectMember,
+            Project,
+            Publication,
+            SequencingRun,
+            Sequence,
+            Subsample,
+            Taxon,
+            Tissue,
+        ]
+    };
+
+    /// The name of the model as used in the model schema IRI.
+    pub fn name(&self) -> &str {
+        self.to_iri_segment().as_str()
+    }
+
+    /// Get the model with the specified name as used in the model schema IRI.
+    pub fn from_name(name: &str) -> Result<Model, TransformError> {
+        Model::ALL
+            .iter()
+            .find(|model| model.name() == name)
+            .copied()
+            .ok_or_else(|| TransformError::UnknownModel(name.to_string()))
+    }
+
+    /// The canonical list of field IRIs that the model can resolve.
+    pub fn fields(&self) -> Vec<&'static iref::Iri> {
+        use crate::rdf;
+
+        fn iris<T>(fields: &'static [T]) -> Vec<&'static iref::Iri>
+        where
+            &'static iref::Iri: From<&'static T>,
+        {
+            fields.iter().map(|f| f.into()).collect()
+        }
+
+        match self {
+            // agents are derived from the fields of other models
+            Model::Agent => vec![],
+            Model::Annotation => iris(rdf::Annotation::ALL),
+            Model::Assembly => iris(rdf::Assembly::ALL),
+            Model::Collecting => iris(rdf::Collecting::ALL),
+            Model::DataProduct => iris(rdf::DataProduct::ALL),
+            Model::Deposition => iris(rdf::Deposition::ALL),
+            Model::Extraction => iris(rdf::Extraction::ALL),
+            Model::Library => iris(rdf::Library::ALL),
+            Model::Name => iris(rdf::Name::ALL),
+            Model::Organism => iris(rdf::Organism::ALL),
+            Model::ProjectAssembly => iris(rdf::ProjectAssembly::ALL),
+            Model::ProjectMember => iris(rdf::ProjectMember::ALL),
+            Model::Project => iris(rdf::Project::ALL),
+            Model::Publication => iris(rdf::Publication::ALL),
+            Model::SequencingRun => iris(rdf::SequencingRun::ALL),
+            Model::Sequence => iris(rdf::Sequence::ALL),
+            Model::Subsample => iris(rdf::Subsample::ALL),
+            Model::Taxon => iris(rdf::Taxon::ALL),
+            Model::Tissue => iris(rdf::Tissue::ALL),
+        }
+    }
+}
+
 
 trait ToIriSegment {
     fn to_iri_segment(&self) -> &iref::iri::Segment;
@@ -140,11 +452,14 @@ impl ToIriSegment for Model {
             Model::Library => "library",
             Model::Name => "names",
             Model::Organism => "organisms",
+            Model::ProjectAssembly => "project_assemblies",
             Model::ProjectMember => "project_member",
             Model::Project => "projecct",
             Model::Publication => "publication",
             Model::SequencingRun => "sequencing_runs",
+            Model::Sequence => "sequences",
             Model::Subsample => "subsamples",
+            Model::Taxon => "taxa",
             Model::Tissue => "tissues",
         };
 
@@ -157,6 +472,14 @@ impl ToIriSegment for Model {
 
 impl Dataset {
     pub fn new(map_iri: &str) -> Result<Dataset, TransformError> {
+        Self::new_with_base(map_iri, DEFAULT_SOURCE_BASE)
+    }
+
+    /// Create a dataset that loads sources into graphs under the specified base IRI.
+    ///
+    /// This allows sources to be namespaced per provider so that files with the same
+    /// name from different pipelines don't end up in the same graph.
+    pub fn new_with_base(map_iri: &str, source_base: &str) -> Result<Dataset, TransformError> {
         let source = FastDataset::new();
         // let store = oxigraph::store::Store::open("./triples.db").unwrap();
 
@@ -165,9 +488,58 @@ impl Dataset {
             source,
             map: map_iri.to_string(),
             schema: IriBuf::new(map_iri.to_string())?,
+            source_base: IriBuf::new(source_base.to_string())?,
+            strict: false,
+            allow_empty: false,
+            null_tokens: NullTokens::default(),
+            fuzzy_countries: false,
         })
     }
 
+    /// Fail when resolving records instead of logging a warning for schema and data problems.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Allow models without any loaded data rather than failing when checking their scope.
+    pub fn set_allow_empty(&mut self, allow_empty: bool) {
+        self.allow_empty = allow_empty;
+    }
+
+    /// Change the values that are skipped when loading and treated as absent when resolving.
+    pub fn set_null_tokens(&mut self, null_tokens: NullTokens) {
+        self.null_tokens = null_tokens;
+    }
+
+    /// Match misspelt country names when deriving country codes instead of only known names.
+    pub fn set_fuzzy_countries(&mut self, fuzzy_countries: bool) {
+        self.fuzzy_countries = fuzzy_countries;
+    }
+
+    /// Change the base IRI that sources are loaded under.
+    pub fn set_source_base(&mut self, source_base: &str) -> Result<(), TransformError> {
+        self.source_base = IriBuf::new(source_base.to_string())?;
+        Ok(())
+    }
+
+    /// Get the graph IRI for a source.
+    ///
+    /// A source that is already a full IRI with an authority, eg. `https://example.org/names`,
+    /// is used verbatim, otherwise it is treated as a name within the source base. This keeps
+    /// names with a colon such as `ala:names` in the source base even though they parse as IRIs.
+    pub fn source_iri(&self, source: &str) -> Result<IriBuf, TransformError> {
+        if let Ok(iri) = IriBuf::new(source.to_string())
+            && iri.authority().is_some()
+        {
+            return Ok(iri);
+        }
+
+        let segment = Segment::new(source).map_err(|_| TransformError::InvalidSegment(source.to_string()))?;
+        let base = self.source_base.as_str();
+        let separator = if base.ends_with('/') { "" } else { "/" };
+        Ok(IriBuf::new(format!("{base}{separator}{segment}"))?)
+    }
+
     pub fn model_schema(&self, model: &Model) -> iref::IriBuf {
         let mut iri = self.schema.clone();
         iri.path_mut().push(model.to_iri_segment());
@@ -216,7 +588,9 @@ impl Dataset {
     /// and all source models that contribute to the final output. This function
     /// does exactly that by including all sources and all models relevant to the
     /// list of model names specified.
-    pub fn scope(&self, models: &[Model]) -> Vec<iref::IriBuf> {
+    ///
+    /// The IRIs can be passed directly to any of the `Resolver` methods as the scope.
+    pub fn scope_iris(&self, models: &[Model]) -> Result<Vec<iref::IriBuf>, TransformError> {
         let mut scope = Vec::new();
 
         // include all model schemas as they are the target transformation
@@ -226,15 +600,98 @@ impl Dataset {
 
         // include any source model data based on the model mapping in the schema
         for model in models {
-            let schemas = self.source_schema(model).unwrap();
+            let schemas = self.source_schema(model)?;
             scope.extend(schemas);
         }
 
-        scope
+        Ok(scope)
+    }
+
+    /// Check that at least one of the models has data loaded into its sources.
+    ///
+    /// A scope without any data resolves to no records which is indistinguishable from
+    /// a source that has no matching records. This is almost always a missing or misnamed
+    /// source so it is reported as `TransformError::EmptyScope` unless `allow_empty` is set.
+    pub fn check_scope(&self, models: &[Model]) -> Result<(), TransformError> {
+        if self.allow_empty {
+            return Ok(());
+        }
+
+        let mut scope = Vec::new();
+        for model in models {
+            for source in self.source_schema(model)? {
+                // only loaded data has a literal subject, the mappings in the
+                // source graph always use IRIs
+                let mut data = self
+                    .source
+                    .quads_matching(TermKind::Literal, Any, Any, SingleGraph(source.as_str()));
+                if data.next().is_some() {
+                    return Ok(());
+                }
+                scope.push(source.to_string());
+            }
+        }
+
+        Err(TransformError::EmptyScope {
+            model: models.iter().map(|model| model.name()).collect::<Vec<_>>().join(", "),
+            scope,
+        })
+    }
+
+    /// Get the source graphs of the models that have records present in all of the models.
+    ///
+    /// A record is present in a model when its `entity_id` is resolved from any source of
+    /// the model. Only the sources with at least one record whose entity id is present in
+    /// every model are returned, which makes it possible to report on how complete the
+    /// data is for a set of models, eg. assemblies that also have annotations and depositions.
+    pub fn scope_intersection(&self, models: &[&str]) -> Result<Vec<String>, TransformError> {
+        let entity_id: &iref::Iri = crate::rdf::Name::EntityId.as_ref();
+        let resolver = Resolver::new(self);
+        let options = ResolveOptions::default();
+
+        // the entity ids in each source graph of every model
+        let mut models_ids: Vec<Vec<(IriBuf, HashSet<Literal>)>> = Vec::new();
+        for name in models {
+            let model = Model::from_name(name)?;
+            let schema = self.model_schema(&model);
+
+            let mut graphs = Vec::new();
+            for source in self.source_schema(&model)? {
+                let records = resolver.resolve_values(&[entity_id], &[schema.as_iri(), source.as_iri()], &options)?;
+                let ids = records
+                    .iter_records()
+                    .flat_map(|(_subject, record)| record.get(entity_id).into_iter().flatten().cloned())
+                    .collect();
+                graphs.push((source, ids));
+            }
+            models_ids.push(graphs);
+        }
+
+        // the entity ids found in at least one graph of every model
+        let mut shared: Option<HashSet<Literal>> = None;
+        for graphs in models_ids.iter() {
+            let ids: HashSet<Literal> = graphs.iter().flat_map(|(_graph, ids)| ids.iter().cloned()).collect();
+            shared = Some(match shared {
+                Some(shared) => shared.intersection(&ids).cloned().collect(),
+                None => ids,
+            });
+        }
+        let shared = shared.unwrap_or_default();
+
+        let mut graphs: Vec<String> = models_ids
+            .into_iter()
+            .flatten()
+            .filter(|(_graph, ids)| !ids.is_disjoint(&shared))
+            .map(|(graph, _ids)| graph.to_string())
+            .collect();
+
+        graphs.sort();
+        graphs.dedup();
+        Ok(graphs)
     }
 
     pub fn quads_matching(&self, s: DatasetTerm, p: DatasetTerm, o: DatasetTerm, g: &iref::Iri) {
-        self.source.quads_matching(s, p, o, GraphIriName(g));
+        self.source.quads_matching(s, p, o, SingleGraph(g.as_str()));
     }
 
     // pub fn scope(&self, models: &[&str]) -> Vec<String> {
@@ -251,7 +708,7 @@ impl Dataset {
     // }
 
     // pub fn graph<'a>(&'a self, graphs: &'a Vec<&'a str>) -> PartialGraph<'a> {
-    //     let selector = GraphIri(&graphs);
+    //     let selector = NamedGraphsOrDefault(&graphs);
     //     self.source.partial_union_graph(selector)
     // }
 
@@ -264,6 +721,16 @@ impl Dataset {
         Ok(())
     }
 
+    /// Load the mappings of a schema builder into the graph of the source.
+    ///
+    /// The source still needs to be associated with a model, either with `register_source`
+    /// or a `mapping:transforms_into` triple, for its mappings to be used.
+    pub fn load_schema(&mut self, source: &str, schema: &SchemaBuilder) -> Result<(), TransformError> {
+        let graph = self.source_iri(source)?;
+        let trig = schema.to_trig(graph.as_str());
+        self.load_trig(BufReader::new(trig.as_bytes()))
+    }
+
     /// Load data into the dataset.
     ///
     /// Designed to load any data source that implements a triples iterator into
@@ -290,51 +757,461 @@ impl Dataset {
     ///   (1, http://arga.org.au/schemas/maps/bpa/genome_status, "Full", http://arga.org.au/source/assemblies.csv)
     ///   (1, http://arga.org.au/schemas/maps/bpa/number_of_scaffolds, 104434, http://arga.org.au/source/assemblies.csv)
     ///
+    /// The source graph is created within the `source_base` of the dataset unless the source
+    /// is already a full IRI, in which case it is used as is.
+    ///
     /// For bevity we omit the XSD types that are associated with the object, but rest assured they
     /// are used when determining the value within the library.
     ///
     /// An important consideration here is that this function does not care what format or structure
     /// the source is. So long as it can stream `Triple`s as an iterable it can be loaded. It is thus
     /// up to the caller to ensure that data is loaded into the RDF dataset appropriately.
-    pub fn load<I, E: std::fmt::Debug>(&mut self, triples: I, source: &str) -> Result<usize, TransformError>
+    pub fn load<I, E: std::fmt::Debug>(&mut self, triples: I, source: &str) -> Result<TransformStats, TransformError>
+    where
+        I: IntoIterator<Item = Result<Triple, E>>,
+    {
+        self.load_with_prefix(triples, source, None)
+    }
+
+    /// Load data into the dataset along with extra provenance details about the source.
+    ///
+    /// Every load records when the source was loaded and how many records it had in the
+    /// `PROVENANCE_GRAPH`. This also records the details in the metadata, such as a checksum
+    /// of the file, so that exports can be traced back to the version of the file that produced
+    /// them. See `source_metadata` to read it back.
+    pub fn load_with_meta<I, E: std::fmt::Debug>(
+        &mut self,
+        triples: I,
+        source: &str,
+        meta: &LoadMeta,
+    ) -> Result<TransformStats, TransformError>
+    where
+        I: IntoIterator<Item = Result<Triple, E>>,
+    {
+        self.load_triples(triples, source, &mut RowSubjects::Index(None), false, meta, None)
+    }
+
+    /// Load data into the dataset with a namespaced entity index.
+    ///
+    /// This is the same as `load` except that when a prefix is provided it will be prepended
+    /// to the record index when creating the subject. Sources that start counting from the same
+    /// row index will otherwise end up with the same subjects and have their records merged
+    /// when resolved. For example, a prefix of `source_a:` will produce the subjects `source_a:1`,
+    /// `source_a:2`, etc.
+    pub fn load_with_prefix<I, E: std::fmt::Debug>(
+        &mut self,
+        triples: I,
+        source: &str,
+        entity_id_prefix: Option<&str>,
+    ) -> Result<TransformStats, TransformError>
+    where
+        I: IntoIterator<Item = Result<Triple, E>>,
+    {
+        self.load_triples(triples, source, &mut RowSubjects::Index(entity_id_prefix), false, &LoadMeta::default(), None)
+    }
+
+    /// Load data into the dataset with the column IRIs in a different namespace.
+    ///
+    /// This is the same as `load` except that the column IRIs are created under the namespace
+    /// instead of the dataset schema, eg. a namespace of `http://example.org/fields` turns the
+    /// `name` column into `http://example.org/fields/name`. This allows data published under
+    /// a provider's own namespace to be mapped without changing the schema of the dataset.
+    pub fn load_with_namespace<I, E: std::fmt::Debug>(
+        &mut self,
+        triples: I,
+        source: &str,
+        namespace: &str,
+    ) -> Result<TransformStats, TransformError>
+    where
+        I: IntoIterator<Item = Result<Triple, E>>,
+    {
+        let subjects = &mut RowSubjects::Index(None);
+        self.load_triples(triples, source, subjects, false, &LoadMeta::default(), Some(namespace))
+    }
+
+    /// Load data into the dataset without failing on triples that couldn't be read.
+    ///
+    /// This is the same as `load` except that any errors yielded by the triples iterator
+    /// are collected into the `parse_errors` of the stats and the load carries on with
+    /// the next triple.
+    pub fn load_tolerant<I, E: std::fmt::Debug>(
+        &mut self,
+        triples: I,
+        source: &str,
+    ) -> Result<TransformStats, TransformError>
+    where
+        I: IntoIterator<Item = Result<Triple, E>>,
+    {
+        self.load_triples(triples, source, &mut RowSubjects::Index(None), true, &LoadMeta::default(), None)
+    }
+
+    /// Load data into the dataset replacing any data previously loaded from the source.
+    ///
+    /// All records in the source graph are removed before loading. The mappings defined for
+    /// the source live in the same graph and are left untouched.
+    pub fn load_replace<I, E: std::fmt::Debug>(
+        &mut self,
+        triples: I,
+        source: &str,
+    ) -> Result<TransformStats, TransformError>
+    where
+        I: IntoIterator<Item = Result<Triple, E>>,
+    {
+        let base = self.source_iri(source)?;
+
+        // loaded records always have a literal index as the subject which
+        // distinguishes them from the mapping definitions
+        let removed = self
+            .source
+            .remove_matching(TermKind::Literal, Any, Any, SingleGraph(base.as_str()))?;
+
+        let mut stats = self.load(triples, source)?;
+        stats.quads_removed = removed;
+        Ok(stats)
+    }
+
+    /// Load data into the dataset replacing existing records that have the same key.
+    ///
+    /// For every incoming row the value of the `key_header` column is used to find records
+    /// in the source graph that were loaded with the same value. Those records are removed
+    /// and the row is inserted with the subject of the replaced record, which means reloading
+    /// an updated file doesn't duplicate the records that were already loaded. Rows with a key
+    /// that hasn't been seen before are inserted with a new subject that doesn't clash with
+    /// any existing record in the graph.
+    ///
+    /// Records in the graph that don't appear in the incoming data are kept. Rows with an empty
+    /// key are always inserted as new records.
+    pub fn load_upsert<I, E: std::fmt::Debug>(
+        &mut self,
+        triples: I,
+        source: &str,
+        key_header: &str,
+    ) -> Result<TransformStats, TransformError>
+    where
+        I: IntoIterator<Item = Result<Triple, E>>,
+    {
+        let base = self.source_iri(source)?;
+        let key_iri = self.header_iri(key_header);
+
+        let mut index = KeyIndex {
+            header: key_header.to_string(),
+            subjects: HashMap::new(),
+            next_idx: 0,
+        };
+
+        for quad in self
+            .source
+            .quads_matching(TermKind::Literal, Any, Any, SingleGraph(base.as_str()))
+        {
+            let (_g, [s, p, o]) = quad?;
+
+            if let Literal::UInt64(idx) = Literal::try_from(s)? {
+                index.next_idx = index.next_idx.max(idx as usize + 1);
+            }
+
+            if let SimpleTerm::Iri(iri) = p
+                && iri.as_str() == key_iri.as_str()
+            {
+                let key = Literal::try_from(o)?.lexical();
+                if key.trim().is_empty() {
+                    continue;
+                }
+                index.subjects.entry(key).or_default().push(s.clone());
+            }
+        }
+
+        self.load_triples(triples, source, &mut RowSubjects::Key(index), false, &LoadMeta::default(), None)
+    }
+
+    fn load_triples<I, E: std::fmt::Debug>(
+        &mut self,
+        triples: I,
+        source: &str,
+        subjects: &mut RowSubjects,
+        tolerant: bool,
+        meta: &LoadMeta,
+        namespace: Option<&str>,
+    ) -> Result<TransformStats, TransformError>
     where
         I: IntoIterator<Item = Result<Triple, E>>,
     {
         // get the source data namespace for all loaded data
-        let mut base = iref::IriBuf::new("http://arga.org.au/source".to_string())?;
-        base.path_mut().push(Segment::new(source).unwrap());
+        let base = self.source_iri(source)?;
+        let entity_id_headers = self.entity_id_headers(&base)?;
+        let namespace = match namespace {
+            // the column is pushed as a path segment which already adds the separator
+            Some(namespace) => IriBuf::new(namespace.trim_end_matches('/').to_string())?,
+            None => self.schema.clone(),
+        };
 
-        // instead of recreating the header iri for each record we store it cache
-        let mut header_cache = HashMap::new();
+        let mut context = LoadContext {
+            source,
+            base,
+            entity_id_headers,
+            namespace,
+            header_cache: HashMap::new(),
+            null_tokens: meta.null_tokens.clone().unwrap_or_else(|| self.null_tokens.clone()),
+        };
 
-        let mut total = 0;
-        for triple in triples {
-            let (idx, header, literal) = triple.unwrap();
+        let mut stats = TransformStats::default();
+
+        // readers yield all the triples for a row together so we buffer them until the
+        // row changes in order to skip rows that don't have any values at all
+        let mut row: Vec<(String, Literal)> = Vec::new();
+        let mut current_idx = None;
+
+        for (position, triple) in triples.into_iter().enumerate() {
+            let (idx, header, literal) = match triple {
+                Ok(triple) => triple,
+                Err(err) => {
+                    let err = TransformError::InvalidTriple(format!("{err:?}"));
+                    match tolerant {
+                        true => {
+                            stats.parse_errors.push((position, err));
+                            continue;
+                        }
+                        false => return Err(err),
+                    }
+                }
+            };
 
-            // get the header iri if it exists. if not create one and store it in the cache
-            let header_iri = header_cache.entry(header).or_insert_with_key(|header| {
-                let mut iri = self.schema.clone();
-                // sanitise the header to make sure it only has valid characters
-                let header = header.replace("#", "");
-                iri.path_mut().push(Segment::new(&header).unwrap());
-                iri
+            if current_idx != Some(idx) {
+                if let Some(current_idx) = current_idx {
+                    let result = self.insert_row(&mut stats, &mut context, subjects, current_idx, &row);
+                    skip_empty_entity_id(result, &mut stats)?;
+                }
+                current_idx = Some(idx);
+                row.clear();
+            }
+
+            row.push((header, literal));
+        }
+
+        if let Some(current_idx) = current_idx {
+            let result = self.insert_row(&mut stats, &mut context, subjects, current_idx, &row);
+            skip_empty_entity_id(result, &mut stats)?;
+        }
+
+        self.insert_provenance(&context.base, &stats, meta)?;
+        Ok(stats)
+    }
+
+    /// Record the provenance of a source load, replacing the provenance of any previous load.
+    fn insert_provenance(
+        &mut self,
+        source: &IriBuf,
+        stats: &TransformStats,
+        meta: &LoadMeta,
+    ) -> Result<(), TransformError> {
+        let graph = iref::Iri::new(PROVENANCE_GRAPH).unwrap();
+        let graph_term = graph.into_iri_term()?;
+        let subject = source.into_iri_term()?;
+
+        self.source
+            .remove_matching([&subject], Any, Any, SingleGraph(graph.as_str()))?;
+
+        let loaded_at = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let loaded_at_iri: &iref::Iri = Provenance::LoadedAt.as_ref();
+        self.source
+            .insert(&subject, loaded_at_iri.into_iri_term()?, loaded_at.as_str(), Some(&graph_term))?;
+
+        let records_iri: &iref::Iri = Provenance::Records.as_ref();
+        self.source
+            .insert(&subject, records_iri.into_iri_term()?, stats.rows_loaded, Some(&graph_term))?;
+
+        if let Some(checksum) = &meta.checksum {
+            let checksum_iri: &iref::Iri = Provenance::Checksum.as_ref();
+            self.source
+                .insert(&subject, checksum_iri.into_iri_term()?, checksum.as_str(), Some(&graph_term))?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the provenance recorded when the source was last loaded.
+    ///
+    /// Returns `None` if the source hasn't been loaded into the dataset.
+    pub fn source_metadata(&self, source: &str) -> Result<Option<SourceMetadata>, TransformError> {
+        let source = self.source_iri(source)?;
+        let graph = iref::Iri::new(PROVENANCE_GRAPH).unwrap();
+        let subject = source.into_iri_term()?;
+
+        let mut metadata = SourceMetadata {
+            source: source.to_string(),
+            loaded_at: None,
+            records: None,
+            checksum: None,
+        };
+
+        let mut found = false;
+        for quad in self
+            .source
+            .quads_matching([&subject], Any, Any, SingleGraph(graph.as_str()))
+        {
+            let (_g, [_s, p, o]) = quad?;
+            let Ok(provenance) = Provenance::try_from(p)
+            else {
+                continue;
+            };
+
+            found = true;
+            match (provenance, Literal::try_from(o)?) {
+                (Provenance::LoadedAt, value) => metadata.loaded_at = Some(value.lexical()),
+                (Provenance::Records, Literal::UInt64(value)) => metadata.records = Some(value),
+                (Provenance::Records, Literal::String(value)) => metadata.records = Some(value.parse()?),
+                (Provenance::Checksum, value) => metadata.checksum = Some(value.lexical()),
+            }
+        }
+
+        Ok(found.then_some(metadata))
+    }
+
+    fn insert_row(
+        &mut self,
+        stats: &mut TransformStats,
+        context: &mut LoadContext,
+        subjects: &mut RowSubjects,
+        idx: usize,
+        row: &[(String, Literal)],
+    ) -> Result<(), TransformError> {
+        let is_empty = |literal: &Literal| context.null_tokens.is_null_literal(literal);
+        if row.iter().all(|(_header, literal)| is_empty(literal)) {
+            stats.rows_skipped_empty += 1;
+            return Ok(());
+        }
+
+        // a row without a value for the columns that make up the entity_id would be merged
+        // with every other row missing it when resolved, so we don't load it at all
+        if !context.entity_id_headers.is_empty() {
+            let has_entity_id = row.iter().any(|(header, literal)| {
+                let header_iri = context
+                    .header_cache
+                    .entry(header.clone())
+                    .or_insert_with_key(|header| namespaced_iri(&context.namespace, header));
+                context.entity_id_headers.contains(header_iri) && !is_empty(literal)
             });
 
-            match literal {
-                Literal::String(val) => {
-                    self.source
-                        .insert(idx, header_iri.into_iri_term()?, val.as_str(), Some(&base.into_iri_term()?))?
-                }
-                Literal::UInt64(val) => {
-                    self.source
-                        .insert(idx, header_iri.into_iri_term()?, val as usize, Some(&base.into_iri_term()?))?
+            if !has_entity_id {
+                return Err(TransformError::EmptyEntityId {
+                    row: idx,
+                    source_name: context.source.to_string(),
+                });
+            }
+        }
+
+        let subject: SimpleTerm = match subjects {
+            RowSubjects::Index(Some(prefix)) => format!("{prefix}{idx}").as_str().into_term(),
+            RowSubjects::Index(None) => idx.into_term(),
+            RowSubjects::Key(index) => {
+                let key = row.iter().find(|(header, _)| header == &index.header);
+                let key = key
+                    .map(|(_, literal)| literal.lexical())
+                    .filter(|key| !context.null_tokens.is_null(key));
+
+                match key {
+                    Some(key) if index.subjects.contains_key(&key) => {
+                        let existing = index.subjects.get_mut(&key).unwrap();
+                        for subject in existing.iter() {
+                            stats.quads_removed +=
+                                self.source
+                                    .remove_matching([subject], Any, Any, SingleGraph(context.base.as_str()))?;
+                        }
+
+                        // duplicate records for the key collapse into the one being inserted
+                        existing.truncate(1);
+                        stats.rows_replaced += 1;
+                        existing[0].clone()
+                    }
+                    key => {
+                        let idx = idx.max(index.next_idx);
+                        index.next_idx = idx + 1;
+
+                        let subject: SimpleTerm = idx.into_term();
+                        if let Some(key) = key {
+                            index.subjects.insert(key, vec![subject.clone()]);
+                        }
+                        subject
+                    }
                 }
+            }
+        };
+
+        for (header, literal) in row {
+            if context.null_tokens.is_null_literal(literal) {
+                stats.values_skipped_null += 1;
+                continue;
+            }
+
+            // get the header iri if it exists. if not create one and store it in the cache
+            let header_iri = context
+                .header_cache
+                .entry(header.clone())
+                .or_insert_with_key(|header| namespaced_iri(&context.namespace, header));
+
+            match literal {
+                Literal::String(val) => self.source.insert(
+                    &subject,
+                    header_iri.into_iri_term()?,
+                    val.as_str(),
+                    Some(&context.base.into_iri_term()?),
+                )?,
+                Literal::UInt64(val) => self.source.insert(
+                    &subject,
+                    header_iri.into_iri_term()?,
+                    *val as usize,
+                    Some(&context.base.into_iri_term()?),
+                )?,
             };
 
-            total += 1;
+            stats.quads_inserted += 1;
         }
 
-        Ok(total)
+        stats.rows_loaded += 1;
+        Ok(())
+    }
+
+    /// The IRIs of the loaded data columns that the entity_id of a source is mapped from.
+    ///
+    /// Only mappings that take the value directly from a column, or from fields that are
+    /// mapped from a column, can be checked before resolving. If the entity_id is linked
+    /// from another source or there is no mapping for it then no columns are returned.
+    fn entity_id_headers(&self, source: &IriBuf) -> Result<Vec<IriBuf>, TransformError> {
+        let entity_id: &iref::Iri = crate::rdf::Name::EntityId.as_ref();
+        let resolver = Resolver::new(self);
+        let map = resolver.field_map(&[entity_id], &[source.as_iri()])?;
+
+        let mut headers = Vec::new();
+        for mapping in map.get(entity_id).into_iter().flatten() {
+            match mapping {
+                Map::Same(iri) | Map::Hash(iri) | Map::Uppercase(iri) | Map::Lowercase(iri) | Map::Trim(iri) => {
+                    headers.push(iri.clone())
+                }
+                Map::Convert { source, .. }
+                | Map::Scale { source, .. }
+                | Map::Replace { source, .. }
+                | Map::PadLeft { source, .. }
+                | Map::PadRight { source, .. }
+                | Map::TrimChars { source, .. } => headers.push(source.clone()),
+                Map::HashFirst(fields) | Map::Coalesce(fields) | Map::Combines(fields) => {
+                    let fields: Vec<&iref::Iri> = fields.iter().map(|f| f.as_iri()).collect();
+                    let field_map = resolver.field_map(&fields, &[source.as_iri()])?;
+                    for mapping in field_map.values().flatten() {
+                        if let Map::Same(iri) = mapping {
+                            headers.push(iri.clone());
+                        }
+                    }
+                }
+                Map::From { .. } => return Ok(Vec::new()),
+                Map::When(..) => {}
+            }
+        }
+
+        Ok(headers)
+    }
+
+    /// The IRI of a loaded data column within the dataset schema.
+    fn header_iri(&self, header: &str) -> IriBuf {
+        namespaced_iri(&self.schema, header)
     }
 
     // fn get_source_models(&self, model: &str) -> Result<Vec<Iri<String>>, TransformError> {
@@ -360,6 +1237,24 @@ impl Dataset {
     //     Ok(sources)
     // }
 
+    /// Declare that the source transforms into the model.
+    ///
+    /// This is the same as a `mapping:transforms_into` triple in a schema and allows
+    /// ad-hoc sources to be associated with a model without editing the schemas.
+    pub fn register_source(&mut self, source: &str, model: &Model) -> Result<(), TransformError> {
+        let source = self.source_iri(source)?;
+        let model = self.model_schema(model);
+        let predicate: &iref::Iri = crate::rdf::Source::TransformsInto.as_ref();
+
+        self.source.insert(
+            source.into_iri_term()?,
+            predicate.into_iri_term()?,
+            model.into_iri_term()?,
+            None as Option<&SimpleTerm>,
+        )?;
+        Ok(())
+    }
+
     pub fn get_source_from_model(&self, model: &iref::Iri) -> Result<Vec<iref::IriBuf>, TransformError> {
         debug!(?model, "getting source from model");
 
@@ -384,12 +1279,9 @@ impl Dataset {
 
     /// Get the triples loaded into the specified source graph.
     pub fn triples(&self, source: &str) -> Result<(), TransformError> {
-        let source = format!("http://arga.org.au/source/{source}");
+        let source = self.source_iri(source)?;
 
-        for quad in self
-            .source
-            .quads_matching(Any, Any, Any, ExclusiveGraphIri(source.as_str()))
-        {
+        for quad in self.source.quads_matching(Any, Any, Any, SingleGraph(source.as_str())) {
             let (_g, [s, p, o]) = quad?;
             println!("{s:?}  {p:?}  {o:?}");
         }
@@ -397,6 +1289,140 @@ impl Dataset {
         Ok(())
     }
 
+    /// Write the quads in the dataset as N-Quads.
+    ///
+    /// Loaded data uses the record index as the subject which isn't valid in strict RDF
+    /// so other tooling may refuse to read anything other than the schema quads.
+    pub fn write_nquads<W: std::io::Write>(&self, writer: W, graphs: &ExportGraphs) -> Result<(), TransformError> {
+        let quads = self.export_quads(graphs)?;
+        NqSerializer::new(writer)
+            .serialize_quads(quads.into_iter().map(Ok::<_, std::convert::Infallible>))
+            .map_err(|err| err.unwrap_sink_error())?;
+        Ok(())
+    }
+
+    /// Write the quads in the dataset as TriG.
+    ///
+    /// See `write_nquads` for the caveats of exporting loaded data.
+    pub fn write_trig<W: std::io::Write>(&self, writer: W, graphs: &ExportGraphs) -> Result<(), TransformError> {
+        let quads = self.export_quads(graphs)?;
+        TrigSerializer::new(writer)
+            .serialize_quads(quads.into_iter().map(Ok::<_, std::convert::Infallible>))
+            .map_err(|err| err.unwrap_sink_error())?;
+        Ok(())
+    }
+
+    fn export_quads(&self, graphs: &ExportGraphs) -> Result<Vec<ExportQuad<'_>>, TransformError> {
+        let sources = match graphs {
+            ExportGraphs::Sources(sources) => {
+                let iris: Result<Vec<IriBuf>, TransformError> = sources.iter().map(|s| self.source_iri(s)).collect();
+                iris?.into_iter().map(|iri| iri.to_string()).collect()
+            }
+            _ => Vec::new(),
+        };
+
+        let mut quads = Vec::new();
+        for quad in self.source.quads() {
+            let (g, [s, p, o]) = quad?;
+
+            let include = match graphs {
+                ExportGraphs::All => true,
+                ExportGraphs::Sources(_) => match (g, s) {
+                    // the provenance of a source is kept with the source
+                    (Some(SimpleTerm::Iri(iri)), SimpleTerm::Iri(subject)) if iri.as_str() == PROVENANCE_GRAPH => {
+                        sources.iter().any(|source| source == subject.as_str())
+                    }
+                    (Some(SimpleTerm::Iri(iri)), _) => sources.iter().any(|source| source == iri.as_str()),
+                    _ => false,
+                },
+                ExportGraphs::Schemas => is_schema_predicate(p),
+            };
+
+            if include {
+                quads.push((g, [s, p, o]));
+            }
+        }
+
+        Ok(quads)
+    }
+
+    /// Run a SPARQL query against all quads in the dataset.
+    ///
+    /// This is intended for analysis that the mapping DSL can't express such as joins
+    /// across models or aggregations. Each row contains the bound values in the order
+    /// of the selected variables.
+    ///
+    /// The query is run against the union of all graphs in the dataset as the default graph
+    /// since named graphs are not supported by the underlying SPARQL implementation.
+    pub fn query(&self, query: &str) -> Result<Vec<SparqlRow>, TransformError> {
+        let graph = self.source.union_graph();
+        sparql_rows(&graph, query)
+    }
+
+    /// Get the distinct values of a field across all graphs.
+    ///
+    /// The field IRI is bound to a prepared query so a field name from user config that
+    /// isn't a valid IRI, eg. one with a quote or a space in it, is rejected with
+    /// `TransformError::InvalidQueryBinding` instead of producing a malformed query.
+    pub fn field_values(&self, field: &str) -> Result<Vec<Value>, TransformError> {
+        let query = crate::query::Query::new(FIELD_VALUES_QUERY).bind("field", Value::Iri(field.to_string()))?;
+        let rows = self.query(&query.render()?)?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| row.into_iter().next().flatten())
+            .collect())
+    }
+
+    /// Run a SPARQL query against the quads within the scope.
+    ///
+    /// This is the same as `query` except that only the graphs in the scope, such as those
+    /// returned by `scope_iris`, and the default graph are included in the union.
+    pub fn query_scoped<S: AsRef<iref::Iri>>(
+        &self,
+        query: &str,
+        scope: &[S],
+    ) -> Result<Vec<SparqlRow>, TransformError> {
+        let scope: Vec<&str> = scope.iter().map(|s| s.as_ref().as_str()).collect();
+        let graph: PartialGraph = self.source.partial_union_graph(NamedGraphsOrDefault(&scope));
+        sparql_rows(&graph, query)
+    }
+
+    /// Summarise the quads loaded into each graph of the dataset.
+    pub fn stats(&self) -> DatasetStats {
+        let mut total_quads = 0;
+        let mut graphs: std::collections::BTreeMap<String, (usize, bool)> = Default::default();
+        let mut predicates = HashSet::new();
+
+        // reading from the in memory store can't fail so the result is flattened
+        for (g, [s, p, _o]) in self.source.quads().flatten() {
+            total_quads += 1;
+            predicates.insert(stringify_term(p));
+
+            if let Some(g) = g {
+                let (quads, has_records) = graphs.entry(stringify_term(g)).or_default();
+                *quads += 1;
+                // loaded records use the row index literal as the subject
+                *has_records |= matches!(s, SimpleTerm::LiteralDatatype(..) | SimpleTerm::LiteralLanguage(..));
+            }
+        }
+
+        let mut stats = DatasetStats {
+            total_quads,
+            distinct_predicates: predicates.len(),
+            ..Default::default()
+        };
+
+        for (graph, (quads, has_records)) in graphs {
+            match has_records {
+                true => stats.source_graphs.push(graph.clone()),
+                false => stats.schema_graphs.push(graph.clone()),
+            }
+            stats.named_graphs.push((graph, quads));
+        }
+
+        stats
+    }
+
     pub fn dump_triples(&self) {
         for quad in self.source.quads() {
             let (g, [s, p, o]) = quad.unwrap();
@@ -406,7 +1432,299 @@ impl Dataset {
 }
 
 
-fn stringify_term(term: &SimpleTerm) -> String {
+/// Construct the mapping definitions of a source without writing TriG by hand.
+///
+/// Each method adds a mapping from the IRI of a source column into the IRI of a field in
+/// the same way as the equivalent `mapping:` predicate in a schema file. The result can be
+/// loaded with `Dataset::load_schema` or rendered with `to_trig` to bootstrap a schema file.
+///
+/// The builder isn't converted into a `Vec<Triple>` for `Dataset::load` since a `Triple` is a
+/// row of data with a row index subject and literal object, while mappings need IRI objects,
+/// lists, blank nodes, and quoted triples.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaBuilder {
+    statements: Vec<String>,
+}
+
+impl SchemaBuilder {
+    pub fn new() -> SchemaBuilder {
+        SchemaBuilder::default()
+    }
+
+    /// Map the value of the source into the target field as is.
+    pub fn same(self, source_iri: &str, target_iri: &str) -> Self {
+        self.statement(target_iri, Mapping::Same, format!("<{source_iri}>"))
+    }
+
+    /// Map the hash of the source value into the target field.
+    pub fn hash(self, source_iri: &str, target_iri: &str) -> Self {
+        self.statement(target_iri, Mapping::Hash, format!("<{source_iri}>"))
+    }
+
+    /// Map the hash of the first field with a value into the target field.
+    ///
+    /// The listed IRIs are other fields which must be mapped with `same`.
+    pub fn hash_first(self, target_iri: &str, source_iris: &[&str]) -> Self {
+        self.statement(target_iri, Mapping::HashFirst, trig_list(source_iris))
+    }
+
+    /// Map the first of the listed fields with a non-empty value into the target field.
+    ///
+    /// The listed IRIs are other fields which must be mapped with `same`.
+    pub fn coalesce(self, target_iri: &str, source_iris: &[&str]) -> Self {
+        self.statement(target_iri, Mapping::Coalesce, trig_list(source_iris))
+    }
+
+    /// Map the values of the listed fields joined with a space into the target field.
+    ///
+    /// The listed IRIs are other fields which must be mapped themselves.
+    pub fn combines(self, target_iri: &str, source_iris: &[&str]) -> Self {
+        self.statement(target_iri, Mapping::Combines, trig_list(source_iris))
+    }
+
+    /// Only include records where the value of a field meets the condition.
+    ///
+    /// The condition is checked against the resolved value of the field so the field
+    /// must also be mapped and resolved along with the target.
+    pub fn when(self, field_iri: &str, target_iri: &str, condition: Condition) -> Self {
+        self.statement(target_iri, Mapping::When, trig_condition(field_iri, condition))
+    }
+
+    /// Load the target field from the sources of another model.
+    ///
+    /// Records are linked to the records of the other model that have the same value in
+    /// the `via` field, which must be mapped in both sources.
+    pub fn from_model(self, target_iri: &str, model_iri: &str, via_iri: &str) -> Self {
+        let predicate: &iref::Iri = FromCondition::Via.as_ref();
+        let object = format!("<< <{model_iri}> <{predicate}> <{via_iri}> >>");
+        self.statement(target_iri, Mapping::From, object)
+    }
+
+    /// Load the target field from the sources of another model linked on several fields.
+    ///
+    /// Records are linked when the values of all the `via` fields match, which requires
+    /// each of the fields to have a single value in both sources.
+    pub fn from_model_composite(self, target_iri: &str, model_iri: &str, via_iris: &[&str]) -> Self {
+        let graph: &iref::Iri = FromCondition::Graph.as_ref();
+        let via: &iref::Iri = FromCondition::Via.as_ref();
+        let object = format!("[ <{graph}> <{model_iri}> ; <{via}> {} ]", trig_list(via_iris));
+        self.statement(target_iri, Mapping::From, object)
+    }
+
+    /// Load the target field from the records of another model that pass the condition.
+    ///
+    /// The same as `from_model` except linked records without the conditional field, or
+    /// with a value that fails the condition, are not joined.
+    pub fn from_model_when(
+        self,
+        target_iri: &str,
+        model_iri: &str,
+        via_iri: &str,
+        field_iri: &str,
+        condition: Condition,
+    ) -> Self {
+        let graph: &iref::Iri = FromCondition::Graph.as_ref();
+        let via: &iref::Iri = FromCondition::Via.as_ref();
+        let when: &iref::Iri = FromCondition::When.as_ref();
+        let condition = trig_condition(field_iri, condition);
+        let object = format!("[ <{graph}> <{model_iri}> ; <{via}> <{via_iri}> ; <{when}> {condition} ]");
+        self.statement(target_iri, Mapping::From, object)
+    }
+
+    /// Map the numeric value of the source multiplied by the factor into the target field.
+    pub fn scale(self, source_iri: &str, target_iri: &str, factor: f64) -> Self {
+        let source: &iref::Iri = ScaleCondition::Source.as_ref();
+        let factor_iri: &iref::Iri = ScaleCondition::Factor.as_ref();
+        let object = format!("[ <{source}> <{source_iri}> ; <{factor_iri}> \"{factor}\" ]");
+        self.statement(target_iri, Mapping::Scale, object)
+    }
+
+    /// Map the value of the source into the target field in upper case.
+    pub fn uppercase(self, source_iri: &str, target_iri: &str) -> Self {
+        self.statement(target_iri, Mapping::Uppercase, format!("<{source_iri}>"))
+    }
+
+    /// Map the value of the source into the target field in lower case.
+    pub fn lowercase(self, source_iri: &str, target_iri: &str) -> Self {
+        self.statement(target_iri, Mapping::Lowercase, format!("<{source_iri}>"))
+    }
+
+    /// Map the value of the source into the target field without surrounding whitespace.
+    pub fn trim(self, source_iri: &str, target_iri: &str) -> Self {
+        self.statement(target_iri, Mapping::Trim, format!("<{source_iri}>"))
+    }
+
+    /// Map the value of the source into the target field with any of the characters
+    /// removed from the start and end of it.
+    pub fn trim_chars(self, source_iri: &str, target_iri: &str, chars: &str) -> Self {
+        let source: &iref::Iri = TrimCondition::Source.as_ref();
+        let characters: &iref::Iri = TrimCondition::Characters.as_ref();
+        let chars = trig_literal(&Literal::String(chars.to_string()));
+        let object = format!("[ <{source}> <{source_iri}> ; <{characters}> {chars} ]");
+        self.statement(target_iri, Mapping::TrimChars, object)
+    }
+
+    /// Render the mappings as a TriG document with all statements in the specified graph.
+    pub fn to_trig(&self, graph_iri: &str) -> String {
+        let mut trig = format!("<{graph_iri}> {{\n");
+        for statement in self.statements.iter() {
+            trig.push_str("  ");
+            trig.push_str(statement);
+            trig.push_str(" .\n");
+        }
+        trig.push_str("}\n");
+        trig
+    }
+
+    fn statement(mut self, target_iri: &str, mapping: Mapping, object: String) -> Self {
+        let predicate: &iref::Iri = mapping.as_ref();
+        self.statements.push(format!("<{target_iri}> <{predicate}> {object}"));
+        self
+    }
+}
+
+
+/// The distinct values of the `field` parameter in any graph.
+const FIELD_VALUES_QUERY: &str = "SELECT DISTINCT ?value WHERE { ?record {{field}} ?value }";
+
+
+/// The IRI of a loaded data column within a namespace.
+fn namespaced_iri(namespace: &IriBuf, header: &str) -> IriBuf {
+    let mut iri = namespace.clone();
+    // sanitise the header to make sure it only has valid characters
+    let header = header.replace("#", "");
+    iri.path_mut().push(Segment::new(&header).unwrap());
+    iri
+}
+
+fn trig_list(iris: &[&str]) -> String {
+    let iris: Vec<String> = iris.iter().map(|iri| format!("<{iri}>")).collect();
+    format!("( {} )", iris.join(" "))
+}
+
+fn trig_condition(field_iri: &str, condition: Condition) -> String {
+    let (predicate, value) = match condition {
+        Condition::Is(value) => (MappingCondition::Is, value),
+        Condition::GreaterThan(value) => (MappingCondition::GreaterThan, value),
+        Condition::LessThan(value) => (MappingCondition::LessThan, value),
+        Condition::And(conditions) => return trig_compound(field_iri, MappingCondition::And, conditions),
+        Condition::Or(conditions) => return trig_compound(field_iri, MappingCondition::Or, conditions),
+    };
+
+    let predicate: &iref::Iri = predicate.as_ref();
+    format!("<< <{field_iri}> <{predicate}> {} >>", trig_literal(&value))
+}
+
+/// A list can't be quoted so compound conditions are a blank node holding the list instead.
+fn trig_compound(field_iri: &str, predicate: MappingCondition, conditions: Vec<Condition>) -> String {
+    let predicate: &iref::Iri = predicate.as_ref();
+    let members: Vec<String> = conditions
+        .into_iter()
+        .map(|condition| trig_condition(field_iri, condition))
+        .collect();
+    format!("[ <{predicate}> ( {} ) ]", members.join(" "))
+}
+
+fn trig_literal(literal: &Literal) -> String {
+    match literal {
+        Literal::UInt64(val) => val.to_string(),
+        Literal::String(val) => {
+            let mut escaped = String::with_capacity(val.len() + 2);
+            escaped.push('"');
+            for ch in val.chars() {
+                match ch {
+                    '"' => escaped.push_str("\\\""),
+                    '\\' => escaped.push_str("\\\\"),
+                    '\n' => escaped.push_str("\\n"),
+                    '\r' => escaped.push_str("\\r"),
+                    '\t' => escaped.push_str("\\t"),
+                    ch => escaped.push(ch),
+                }
+            }
+            escaped.push('"');
+            escaped
+        }
+    }
+}
+
+/// Count rows without an entity_id as skipped rather than failing the load.
+fn skip_empty_entity_id(result: Result<(), TransformError>, stats: &mut TransformStats) -> Result<(), TransformError> {
+    match result {
+        Err(err @ TransformError::EmptyEntityId { .. }) => {
+            debug!(%err, "skipping row");
+            stats.rows_skipped_empty += 1;
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+
+/// Run a SPARQL query with the graph as the default graph and convert the bindings into rows.
+fn sparql_rows<G: Graph>(graph: &G, query: &str) -> Result<Vec<SparqlRow>, TransformError> {
+    let graph = graph.as_dataset();
+    let dataset = SparqlWrapper(&graph);
+    let query = SparqlQuery::parse(query).map_err(|err| TransformError::SparqlParse(err.to_string()))?;
+
+    let bindings = dataset
+        .query(&query)
+        .map_err(|err| TransformError::SparqlQuery(err.to_string()))?
+        .into_bindings();
+
+    let mut rows = Vec::new();
+    for binding in bindings {
+        let binding = binding.map_err(|err| TransformError::SparqlQuery(err.to_string()))?;
+
+        let mut row = Vec::with_capacity(binding.len());
+        for (index, atom) in binding.into_iter().enumerate() {
+            let value = match atom {
+                Some(term) => Some(sparql_value(index, term.inner())?),
+                None => None,
+            };
+            row.push(value);
+        }
+
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+
+/// Convert a SPARQL result term into a value.
+///
+/// Integer literals become a `Literal::UInt64` if they fit while all other literals,
+/// including language tagged strings, are converted into a `Literal::String`. Terms that
+/// can't be represented as a value, such as quoted triples, return an error with the
+/// index of the variable they were bound to.
+fn sparql_value(index: usize, term: &ArcTerm) -> Result<Value, TransformError> {
+    let integer: &iref::Iri = DataTypes::Integer.as_ref();
+
+    let value = match term {
+        ArcTerm::Iri(iri) => Value::Iri(iri.to_string()),
+        ArcTerm::BlankNode(id) => Value::Bnode(id.to_string()),
+        ArcTerm::Literal(GenericLiteral::Typed(value, datatype)) => {
+            match (datatype.as_str() == integer.as_str(), value.parse::<u64>()) {
+                (true, Ok(value)) => Value::Literal(Literal::UInt64(value)),
+                _ => Value::Literal(Literal::String(value.to_string())),
+            }
+        }
+        ArcTerm::Literal(GenericLiteral::LanguageString(value, _tag)) => {
+            Value::Literal(Literal::String(value.to_string()))
+        }
+        term => {
+            return Err(TransformError::UnsupportedSparqlTerm {
+                index,
+                term: format!("{term:?}"),
+            });
+        }
+    };
+
+    Ok(value)
+}
+
+
+pub(crate) fn stringify_term(term: &SimpleTerm) -> String {
     match term {
         SimpleTerm::Iri(iri_ref) => iri_ref.to_string(),
         SimpleTerm::BlankNode(bnode_id) => bnode_id.to_string(),
@@ -418,60 +1736,388 @@ fn stringify_term(term: &SimpleTerm) -> String {
 }
 
 
-#[derive(Clone, Copy)]
-pub struct GraphIri<'a>(&'a Vec<&'a str>);
-
-impl<'a> GraphNameMatcher for GraphIri<'a> {
-    type Term = SimpleTerm<'static>;
-
-    fn matches<T2: Term + ?Sized>(&self, graph_name: GraphName<&T2>) -> bool {
-        match graph_name {
-            // only include matching graph names
-            Some(t) => match t.as_simple() {
-                SimpleTerm::Iri(iri) => self.0.contains(&iri.as_str()),
-                _ => false,
-            },
-            // always include the default graph
-            None => true,
+/// Whether the predicate is part of a mapping definition rather than loaded data.
+///
+/// Lists in the mapping definitions are made up of blank nodes linked with `rdf:first`
+/// and `rdf:rest` so they are included as well.
+fn is_schema_predicate(predicate: &SimpleTerm) -> bool {
+    match predicate {
+        SimpleTerm::Iri(iri) => {
+            iri.as_str().starts_with("http://arga.org.au/schemas/mapping/")
+                || iri.as_str().starts_with("http://www.w3.org/1999/02/22-rdf-syntax-ns#")
         }
+        _ => false,
     }
 }
 
 
-#[derive(Clone, Copy)]
-pub struct ExclusiveGraphIri<'a>(&'a str);
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::resolver::ValueMapExt;
+    use crate::testing::{SOURCE_PREFIXES, SYNTHETIC_SCHEMA, column_iri, load_source, row_triples};
+
+    const NAME_MAPPINGS: &str = r#"
+        fields:entity_id mapping:same col:id .
+        fields:scientific_name mapping:same col:name .
+    "#;
+
+    fn lexical(values: Vec<Value>) -> Vec<String> {
+        let mut values: Vec<String> = values
+            .into_iter()
+            .map(|value| match value {
+                Value::Literal(literal) => literal.lexical(),
+                other => panic!("expected a literal but found {other:?}"),
+            })
+            .collect();
+        values.sort();
+        values
+    }
 
-impl<'a> GraphNameMatcher for ExclusiveGraphIri<'a> {
-    type Term = SimpleTerm<'static>;
+    #[test]
+    fn field_values_are_distinct() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let rows: &[&[(&str, &str)]] = &[
+            &[("id", "a"), ("name", "Felis catus")],
+            &[("id", "b"), ("name", "Canis lupus")],
+            &[("id", "c"), ("name", "Felis catus")],
+        ];
+        load_source(&mut dataset, &Model::Name, "names", NAME_MAPPINGS, rows).unwrap();
+
+        let values = dataset.field_values(&column_iri("name")).unwrap();
+        assert_eq!(lexical(values), vec!["Canis lupus", "Felis catus"]);
+    }
 
-    fn matches<T2: Term + ?Sized>(&self, graph_name: GraphName<&T2>) -> bool {
-        match graph_name {
-            // only include matching graph names
-            Some(t) => match t.as_simple() {
-                SimpleTerm::Iri(iri) => self.0 == iri.as_str(),
-                _ => false,
-            },
-            None => false,
+    #[test]
+    fn field_values_rejects_fields_that_would_break_the_query() {
+        let dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+
+        for field in [
+            "http://arga.org.au/schemas/fields/scientific name",
+            "http://arga.org.au/\"> ?p ?o . <x",
+        ] {
+            let result = dataset.field_values(field);
+            assert!(
+                matches!(&result, Err(TransformError::InvalidQueryBinding { name, .. }) if name == "field"),
+                "{field} was not rejected: {result:?}"
+            );
         }
     }
-}
 
+    #[test]
+    fn source_iri_only_uses_iris_with_an_authority() {
+        let dataset = Dataset::new_with_base(SYNTHETIC_SCHEMA, "http://arga.org.au/sources/").unwrap();
+        let source_iri = |source: &str| dataset.source_iri(source).unwrap().to_string();
 
-#[derive(Clone, Copy)]
-pub struct GraphIriName<'a>(&'a iref::Iri);
+        assert_eq!(source_iri("names"), "http://arga.org.au/sources/names");
+        assert_eq!(source_iri("ala:names"), "http://arga.org.au/sources/ala:names");
+        assert_eq!(source_iri("https://example.org/names"), "https://example.org/names");
+    }
 
-impl<'a> GraphNameMatcher for GraphIriName<'a> {
-    type Term = SimpleTerm<'static>;
+    #[test]
+    fn check_scope_rejects_a_scope_without_graphs() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
 
-    fn matches<T2: Term + ?Sized>(&self, graph_name: GraphName<&T2>) -> bool {
-        match graph_name {
-            // only include matching graph names
-            Some(t) => match t.as_simple() {
-                SimpleTerm::Iri(iri) => self.0.eq(&iri.as_str()),
-                _ => false,
-            },
-            // always include the default graph
-            None => false,
+        let result = dataset.check_scope(&[Model::Name]);
+        assert!(
+            matches!(&result, Err(TransformError::EmptyScope { model, scope }) if model == "names" && scope.is_empty()),
+            "{result:?}"
+        );
+
+        dataset.set_allow_empty(true);
+        assert!(dataset.check_scope(&[Model::Name]).is_ok());
+    }
+
+    #[test]
+    fn check_scope_rejects_graphs_without_records() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        load_source(&mut dataset, &Model::Name, "names", NAME_MAPPINGS, &[]).unwrap();
+        let graph = dataset.source_iri("names").unwrap().to_string();
+
+        let result = dataset.check_scope(&[Model::Name]);
+        assert!(
+            matches!(&result, Err(TransformError::EmptyScope { scope, .. }) if scope == &[graph.as_str()]),
+            "{result:?}"
+        );
+
+        dataset.set_allow_empty(true);
+        assert!(dataset.check_scope(&[Model::Name]).is_ok());
+    }
+
+    #[test]
+    fn check_scope_accepts_graphs_with_records() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let rows: &[&[(&str, &str)]] = &[&[("id", "a"), ("name", "Felis catus")]];
+        load_source(&mut dataset, &Model::Name, "names", NAME_MAPPINGS, rows).unwrap();
+
+        assert!(dataset.check_scope(&[Model::Name]).is_ok());
+        assert!(dataset.check_scope(&[Model::Name, Model::Organism]).is_ok());
+    }
+
+    fn scientific_names(dataset: &Dataset) -> BTreeMap<String, String> {
+        let entity_id: &iref::Iri = crate::rdf::Name::EntityId.as_ref();
+        let scientific_name: &iref::Iri = crate::rdf::Name::ScientificName.as_ref();
+        let scope = dataset.scope_iris(&[Model::Name]).unwrap();
+        let records = Resolver::new(dataset)
+            .resolve_values(&[entity_id, scientific_name], &scope, &ResolveOptions::default())
+            .unwrap();
+
+        records
+            .iter_records()
+            .map(|(_subject, record)| {
+                let id = record.get_first_string(entity_id).unwrap().to_string();
+                let name = record.get_first_string(scientific_name).unwrap().to_string();
+                (id, name)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn schema_builder_loads_the_same_mappings_as_trig() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let field = |name: &str| format!("http://arga.org.au/schemas/fields/{name}");
+        let (canonical, authorship) = (field("canonical_name"), field("scientific_name_authorship"));
+
+        let schema = SchemaBuilder::new()
+            .same(&column_iri("id"), &field("entity_id"))
+            .same(&column_iri("name"), &canonical)
+            .same(&column_iri("authorship"), &authorship)
+            .combines(&field("scientific_name"), &[&canonical, &authorship]);
+        dataset.load_schema("built", &schema).unwrap();
+        dataset.register_source("built", &Model::Name).unwrap();
+        let rows: &[&[(&str, &str)]] = &[&[("id", "a"), ("name", "Felis catus"), ("authorship", "Linnaeus, 1758")]];
+        dataset
+            .load_with_prefix(row_triples(rows), "built", Some("built:"))
+            .unwrap();
+
+        let mappings = r#"
+            fields:entity_id mapping:same col:id .
+            fields:canonical_name mapping:same col:name .
+            fields:scientific_name_authorship mapping:same col:authorship .
+            fields:scientific_name mapping:combines ( fields:canonical_name fields:scientific_name_authorship ) .
+        "#;
+        let rows: &[&[(&str, &str)]] = &[&[("id", "b"), ("name", "Felis catus"), ("authorship", "Linnaeus, 1758")]];
+        load_source(&mut dataset, &Model::Name, "written", mappings, rows).unwrap();
+
+        let names = scientific_names(&dataset);
+        assert_eq!(names["a"], "Felis catus Linnaeus, 1758");
+        assert_eq!(names["b"], names["a"]);
+    }
+
+    const ORGANISM_MAPPINGS: &str = r#"
+        fields:entity_id mapping:same col:organism_id .
+        fields:scientific_name mapping:same col:name .
+        fields:scientific_name_authorship mapping:same col:authorship .
+    "#;
+
+    fn query_dataset() -> Dataset {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let names: &[&[(&str, &str)]] = &[
+            &[("id", "a"), ("name", "Felis catus")],
+            &[("id", "b"), ("name", "Canis lupus")],
+        ];
+        let organisms: &[&[(&str, &str)]] = &[&[
+            ("organism_id", "o1"),
+            ("name", "Felis catus"),
+            ("authorship", "Linnaeus, 1758"),
+        ]];
+        load_source(&mut dataset, &Model::Name, "names", NAME_MAPPINGS, names).unwrap();
+        load_source(&mut dataset, &Model::Organism, "organisms", ORGANISM_MAPPINGS, organisms).unwrap();
+        dataset
+    }
+
+    fn lexical_rows(rows: Vec<SparqlRow>) -> Vec<Vec<String>> {
+        let mut rows: Vec<Vec<String>> = rows
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|value| match value {
+                        Some(Value::Literal(literal)) => literal.lexical(),
+                        other => panic!("expected a literal but found {other:?}"),
+                    })
+                    .collect()
+            })
+            .collect();
+        rows.sort();
+        rows
+    }
+
+    #[test]
+    fn query_joins_sources_of_different_models() {
+        let dataset = query_dataset();
+        let (id, name, authorship) = (column_iri("id"), column_iri("name"), column_iri("authorship"));
+        let query = format!(
+            "SELECT ?id ?authorship WHERE {{
+                ?name <{id}> ?id ; <{name}> ?value .
+                ?organism <{name}> ?value ; <{authorship}> ?authorship .
+            }}"
+        );
+
+        let rows = dataset.query(&query).unwrap();
+        assert_eq!(lexical_rows(rows), vec![vec!["a", "Linnaeus, 1758"]]);
+    }
+
+    #[test]
+    fn query_scoped_only_includes_the_graphs_in_scope() {
+        let dataset = query_dataset();
+        let query = format!("SELECT ?name WHERE {{ ?record <{}> ?name }}", column_iri("name"));
+
+        let all = dataset.query(&query).unwrap();
+        assert_eq!(lexical_rows(all), vec![vec!["Canis lupus"], vec!["Felis catus"], vec!["Felis catus"]]);
+
+        let scope = dataset.scope_iris(&[Model::Organism]).unwrap();
+        let scoped = dataset.query_scoped(&query, &scope).unwrap();
+        assert_eq!(lexical_rows(scoped), vec![vec!["Felis catus"]]);
+    }
+
+    #[test]
+    fn query_reports_invalid_sparql() {
+        let dataset = query_dataset();
+        assert!(matches!(dataset.query("SELECT ?id WHERE"), Err(TransformError::SparqlParse(_))));
+    }
+
+    /// Load two name sources that both count their rows from zero.
+    fn overlapping_sources(prefixes: [Option<&str>; 2]) -> Dataset {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let sources: [(&str, &[&[(&str, &str)]]); 2] = [
+            (
+                "source_a",
+                &[
+                    &[("id", "a1"), ("name", "Felis catus")],
+                    &[("id", "a2"), ("name", "Canis lupus")],
+                ],
+            ),
+            (
+                "source_b",
+                &[
+                    &[("id", "b1"), ("name", "Vulpes vulpes")],
+                    &[("id", "b2"), ("name", "Mus musculus")],
+                ],
+            ),
+        ];
+
+        for ((source, rows), prefix) in sources.into_iter().zip(prefixes) {
+            let graph = dataset.source_iri(source).unwrap();
+            let trig = format!("{SOURCE_PREFIXES}<{graph}> {{ {NAME_MAPPINGS} }}");
+            dataset.load_trig(BufReader::new(trig.as_bytes())).unwrap();
+            dataset.register_source(source, &Model::Name).unwrap();
+            dataset.load_with_prefix(row_triples(rows), source, prefix).unwrap();
         }
+        dataset
+    }
+
+    fn record_subjects(dataset: &Dataset) -> Vec<String> {
+        let entity_id: &iref::Iri = crate::rdf::Name::EntityId.as_ref();
+        let scope = dataset.scope_iris(&[Model::Name]).unwrap();
+        let records = Resolver::new(dataset)
+            .resolve_values(&[entity_id], &scope, &ResolveOptions::default())
+            .unwrap();
+
+        let mut subjects: Vec<String> = records.iter_records().map(|(subject, _)| subject.lexical()).collect();
+        subjects.sort();
+        subjects
+    }
+
+    #[test]
+    fn load_with_prefix_keeps_overlapping_sources_apart() {
+        let merged = overlapping_sources([None, None]);
+        assert_eq!(record_subjects(&merged).len(), 2);
+
+        let prefixed = overlapping_sources([Some("source_a:"), Some("source_b:")]);
+        assert_eq!(record_subjects(&prefixed), vec!["source_a:0", "source_a:1", "source_b:0", "source_b:1"]);
+    }
+
+    const LIST_MAPPINGS: &str = r#"
+        fields:entity_id mapping:hash_first ( fields:canonical_name fields:scientific_name ) .
+        fields:canonical_name mapping:same col:canonical .
+        fields:scientific_name mapping:same col:name .
+        fields:scientific_name_authorship mapping:coalesce ( col:authorship col:author ) .
+        fields:size mapping:convert [ mapping:source col:size_kb ; mapping:from_unit "kb" ; mapping:to_unit "bp" ] .
+    "#;
+
+    /// The mappings of every field in the names scope with the maps of a field sorted.
+    fn name_field_map(dataset: &Dataset) -> BTreeMap<String, Vec<String>> {
+        let scope = dataset.scope_iris(&[Model::Name]).unwrap();
+        let resolver = Resolver::new(dataset);
+        let fields = resolver.list_mapped_fields(&scope).unwrap();
+        let fields: Vec<&iref::Iri> = fields.iter().map(|field| field.as_iri()).collect();
+
+        resolver
+            .field_map(&fields, &scope)
+            .unwrap()
+            .into_iter()
+            .map(|(field, maps)| {
+                let mut maps: Vec<String> = maps.iter().map(|map| format!("{map:?}")).collect();
+                maps.sort();
+                (field.to_string(), maps)
+            })
+            .collect()
+    }
+
+    fn reload_trig(dataset: &Dataset, graphs: &ExportGraphs) -> Dataset {
+        let mut trig = Vec::new();
+        dataset.write_trig(&mut trig, graphs).unwrap();
+
+        let mut reloaded = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        reloaded.load_trig(BufReader::new(trig.as_slice())).unwrap();
+        reloaded
+    }
+
+    #[test]
+    fn trig_export_round_trips_the_store() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        load_source(&mut dataset, &Model::Name, "names", LIST_MAPPINGS, &[]).unwrap();
+
+        let reloaded = reload_trig(&dataset, &ExportGraphs::All);
+        assert_eq!(reloaded.source.quads().count(), dataset.source.quads().count());
+        assert_eq!(name_field_map(&reloaded), name_field_map(&dataset));
+        assert_eq!(name_field_map(&dataset).len(), 5);
+    }
+
+    #[test]
+    fn trig_export_of_schemas_leaves_out_the_loaded_data() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let rows: &[&[(&str, &str)]] = &[&[("canonical", "Felis catus"), ("size_kb", "2")]];
+        load_source(&mut dataset, &Model::Name, "names", LIST_MAPPINGS, rows).unwrap();
+
+        let schema_quads = dataset
+            .source
+            .quads()
+            .filter(|quad| is_schema_predicate(quad.as_ref().unwrap().1[1]))
+            .count();
+
+        let reloaded = reload_trig(&dataset, &ExportGraphs::Schemas);
+        assert_eq!(reloaded.source.quads().count(), schema_quads);
+        assert!(schema_quads < dataset.source.quads().count());
+        assert_eq!(name_field_map(&reloaded), name_field_map(&dataset));
+    }
+
+    #[test]
+    fn load_upsert_replaces_records_with_the_same_key() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let v1: &[&[(&str, &str)]] = &[
+            &[("id", "a"), ("name", "Felis catus")],
+            &[("id", "b"), ("name", "Canis lupis")],
+            &[("id", "c"), ("name", "Vulpes vulpes")],
+        ];
+        load_source(&mut dataset, &Model::Name, "names", NAME_MAPPINGS, v1).unwrap();
+
+        let v2: &[&[(&str, &str)]] = &[
+            &[("id", "b"), ("name", "Canis lupus")],
+            &[("id", "c"), ("name", "Vulpes vulpes")],
+        ];
+        dataset.load_upsert(row_triples(v2), "names", "id").unwrap();
+
+        let names = scientific_names(&dataset);
+        assert_eq!(names["a"], "Felis catus");
+        assert_eq!(names["b"], "Canis lupus");
+        assert_eq!(names["c"], "Vulpes vulpes");
+        assert_eq!(record_subjects(&dataset).len(), 3);
+
+        // the replaced record doesn't keep any of the old values
+        let values = dataset.field_values(&column_iri("name")).unwrap();
+        assert_eq!(lexical(values), vec!["Canis lupus", "Felis catus", "Vulpes vulpes"]);
     }
 }
@@ -3,9 +3,18 @@ pub enum TransformError {
     #[error("A mapping for entity_id must exist for all data transforms")]
     MissingEntityId,
 
+    #[error("The entity_id for row {row} in {source_name} is empty")]
+    EmptyEntityId { row: usize, source_name: String },
+
     #[error("Cannot find the header '{0}'")]
     NoHeader(String),
 
+    #[error("Unknown model '{0}'")]
+    UnknownModel(String),
+
+    #[error("Failed to read triple: {0}")]
+    InvalidTriple(String),
+
     #[error("The IRI used in the mapping is invalid")]
     InvalidMappingIri(String),
 
@@ -33,8 +42,49 @@ pub enum TransformError {
     #[error(transparent)]
     Resolve(#[from] ResolveError),
 
-    // #[error(transparent)]
-    // Json(#[from] serde_json::Error),
+    #[error("Failed to parse the SPARQL query: {0}")]
+    SparqlParse(String),
+
+    #[error("Failed to execute the SPARQL query: {0}")]
+    SparqlQuery(String),
+
+    #[error("Invalid value bound to the query parameter '{name}': {reason}")]
+    InvalidQueryBinding { name: String, reason: String },
+
+    #[error("The query parameter '{0}' has no bound value")]
+    UnboundQueryParameter(String),
+
+    #[error("Unknown query '{0}'")]
+    UnknownQuery(String),
+
+    #[error("Unsupported SPARQL result term for variable {index}: {term}")]
+    UnsupportedSparqlTerm { index: usize, term: String },
+
+    #[error("No data is loaded into any source of the {model} model. Sources: {scope:?}")]
+    EmptyScope { model: String, scope: Vec<String> },
+
+    #[error("The mapping:from link into '{0}' leads back to itself")]
+    CircularGraphLink(String),
+
+    #[error("Cannot parse the date '{0}'")]
+    DateParse(String),
+
+    #[error(transparent)]
+    Reader(#[from] ReaderError),
+
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+
+    #[cfg(feature = "parquet")]
+    #[error(transparent)]
+    Parquet(#[from] parquet::errors::ParquetError),
+
     #[error(transparent)]
     Io(#[from] std::io::Error),
 
@@ -56,6 +106,24 @@ pub enum ResolveError {
 
     #[error("Ambiguous mapping for {0:?}. Found values: {1:?}")]
     AmbiguousMapping(iref::IriBuf, Vec<super::rdf::Literal>),
+
+    #[error("Unsupported unit '{0}'")]
+    UnsupportedUnit(String),
+
+    #[error("Cannot convert from {0:?} to {1:?}")]
+    IncompatibleUnits(super::rdf::Unit, super::rdf::Unit),
+
+    #[error("Missing mapping qualifier {0}")]
+    MissingQualifier(&'static str),
+
+    #[error("Invalid value for the mapping qualifier {0}: {1}")]
+    InvalidQualifier(&'static str, String),
+
+    #[error("The source field {source_iri} is mapped into more than one field: {targets:?}")]
+    ConflictingMapping { source_iri: String, targets: Vec<String> },
+
+    #[error("The entity id {entity_id} is shared by more than one record: {subjects:?}")]
+    DuplicateEntityId { entity_id: String, subjects: Vec<String> },
 }
 
 
@@ -63,4 +131,62 @@ pub enum ResolveError {
 pub enum ReaderError {
     #[error(transparent)]
     Csv(#[from] csv::Error),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("The value '{value}' in row {row} of column '{column}' does not match the column type")]
+    TypeMismatch { row: usize, column: String, value: String },
+
+    #[error("Found table row {0} before the table header")]
+    MissingTableHeader(usize),
+
+    #[error("The columns in {file} don't match the other files. Missing: {missing:?}, extra: {extra:?}")]
+    HeaderMismatch {
+        file: String,
+        missing: Vec<String>,
+        extra: Vec<String>,
+    },
+
+    #[error(transparent)]
+    Xml(#[from] quick_xml::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error("Invalid record {row}: {reason}")]
+    InvalidRecord { row: usize, reason: String },
+
+    #[cfg(feature = "xlsx")]
+    #[error("Failed to read the workbook: {0}")]
+    Xlsx(String),
+
+    #[cfg(feature = "parquet")]
+    #[error(transparent)]
+    Parquet(#[from] parquet::errors::ParquetError),
+
+    #[cfg(feature = "parquet")]
+    #[error("The column '{0}' is a nested type which cannot be read as a triple")]
+    NestedColumn(String),
+
+    #[cfg(feature = "http")]
+    #[error(transparent)]
+    Request(#[from] ureq::Error),
+
+    #[cfg(feature = "http")]
+    #[error("Request for {url} failed with status {status}")]
+    Http { status: u16, url: String },
+}
+
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    #[error("The ORCID '{0}' is not 16 digits with an optional X check digit")]
+    MalformedOrcid(String),
+
+    #[error("The ORCID '{0}' has an invalid check digit")]
+    OrcidChecksum(String),
+
+    #[error("The DOI '{0}' is not a 10. prefix and suffix separated by a slash")]
+    MalformedDoi(String),
 }
@@ -0,0 +1,546 @@
+use std::io::Write;
+
+use serde_json::Value;
+use tracing::{info, instrument};
+
+use crate::dataset::{Dataset, Model};
+use crate::errors::TransformError;
+use crate::models::{self, ColumnSchema, LIST_SEPARATOR};
+
+
+/// A writer of model records that writes them a chunk at a time.
+pub trait ChunkedExport {
+    /// Write every record in the chunk.
+    fn write_chunk<T: serde::Serialize>(&mut self, records: Vec<T>) -> Result<(), TransformError>;
+}
+
+
+/// Write model records as CSV one chunk at a time.
+///
+/// The header is written up front from the columns of the model so every chunk has the
+/// same columns in the same order regardless of which fields its records have values for.
+/// The writer is flushed after each chunk so the records can be dropped once written.
+pub struct CsvExport<W: Write> {
+    writer: csv::Writer<W>,
+    columns: Vec<String>,
+    written: usize,
+}
+
+impl<W: Write> CsvExport<W> {
+    pub fn new(writer: W, columns: &[ColumnSchema]) -> Result<CsvExport<W>, TransformError> {
+        let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(writer);
+        let columns: Vec<String> = columns.iter().map(|column| column.name.clone()).collect();
+        writer.write_record(&columns)?;
+
+        Ok(CsvExport {
+            writer,
+            columns,
+            written: 0,
+        })
+    }
+
+    /// Flush the writer and get the amount of records written.
+    pub fn finish(mut self) -> Result<usize, TransformError> {
+        self.writer.flush()?;
+        Ok(self.written)
+    }
+}
+
+impl<W: Write> ChunkedExport for CsvExport<W> {
+    /// Write every record in the chunk and flush the writer.
+    fn write_chunk<T: serde::Serialize>(&mut self, records: Vec<T>) -> Result<(), TransformError> {
+        for record in records {
+            let value = serde_json::to_value(record)?;
+            let row: Vec<String> = self.columns.iter().map(|column| to_cell(value.get(column))).collect();
+            self.writer.write_record(&row)?;
+            self.written += 1;
+        }
+
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+
+/// Write the records of a model as CSV in chunks of `chunk_size` records.
+///
+/// See [`write_model`] for the models that are built a chunk at a time.
+///
+/// Returns the amount of records written.
+#[instrument(skip(dataset, writer))]
+pub fn write_csv<W: Write>(
+    dataset: &Dataset,
+    model: &Model,
+    writer: W,
+    chunk_size: usize,
+) -> Result<usize, TransformError> {
+    let mut export = CsvExport::new(writer, &models::columns(model))?;
+    write_model(dataset, model, &mut export, chunk_size)?;
+    export.finish()
+}
+
+
+/// The amount of records written to each row group when exporting a whole model as Parquet.
+#[cfg(feature = "parquet")]
+pub const DEFAULT_ROW_GROUP_SIZE: usize = 100_000;
+
+
+/// The Parquet type a model column is written as.
+#[cfg(feature = "parquet")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParquetColumn {
+    Utf8,
+    UInt64,
+    Boolean,
+}
+
+#[cfg(feature = "parquet")]
+impl ParquetColumn {
+    /// Lists like `Vec<String>` are joined into a single string the same way as a CSV export
+    /// so that the file stays flat and can be read back with `ParquetReader`. Enums and any
+    /// other type are written as the string they serialize to.
+    fn from_rust_type(rust_type: &str) -> ParquetColumn {
+        match rust_type {
+            "u64" => ParquetColumn::UInt64,
+            "bool" => ParquetColumn::Boolean,
+            _ => ParquetColumn::Utf8,
+        }
+    }
+}
+
+
+/// Write model records as Parquet with one row group for each chunk.
+///
+/// The schema is built up front from the columns of the model. Strings are written as
+/// UTF8 byte arrays and `u64` values as unsigned 64 bit integers, and `Option` columns
+/// are nullable while the others are required.
+#[cfg(feature = "parquet")]
+pub struct ParquetExport<W: Write + Send> {
+    writer: parquet::file::writer::SerializedFileWriter<W>,
+    columns: Vec<(String, ParquetColumn, bool)>,
+    written: usize,
+}
+
+#[cfg(feature = "parquet")]
+impl<W: Write + Send> ParquetExport<W> {
+    pub fn new(writer: W, columns: &[ColumnSchema]) -> Result<ParquetExport<W>, TransformError> {
+        use std::sync::Arc;
+
+        use parquet::basic::{Compression, LogicalType, Repetition, Type as PhysicalType};
+        use parquet::file::properties::WriterProperties;
+        use parquet::schema::types::Type;
+
+        let columns: Vec<(String, ParquetColumn, bool)> = columns
+            .iter()
+            .map(|column| (column.name.clone(), ParquetColumn::from_rust_type(&column.rust_type), column.optional))
+            .collect();
+
+        let mut fields = Vec::new();
+        for (name, kind, optional) in columns.iter() {
+            let (physical, logical) = match kind {
+                ParquetColumn::Utf8 => (PhysicalType::BYTE_ARRAY, Some(LogicalType::String)),
+                ParquetColumn::UInt64 => (
+                    PhysicalType::INT64,
+                    Some(LogicalType::Integer {
+                        bit_width: 64,
+                        is_signed: false,
+                    }),
+                ),
+                ParquetColumn::Boolean => (PhysicalType::BOOLEAN, None),
+            };
+
+            let repetition = match optional {
+                true => Repetition::OPTIONAL,
+                false => Repetition::REQUIRED,
+            };
+
+            let field = Type::primitive_type_builder(name, physical)
+                .with_repetition(repetition)
+                .with_logical_type(logical)
+                .build()?;
+            fields.push(Arc::new(field));
+        }
+
+        let schema = Type::group_type_builder("schema").with_fields(fields).build()?;
+        let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+        let writer = parquet::file::writer::SerializedFileWriter::new(writer, Arc::new(schema), Arc::new(properties))?;
+
+        Ok(ParquetExport {
+            writer,
+            columns,
+            written: 0,
+        })
+    }
+
+    /// Write the file footer and get the amount of records written.
+    pub fn finish(self) -> Result<usize, TransformError> {
+        self.writer.close()?;
+        Ok(self.written)
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl<W: Write + Send> ChunkedExport for ParquetExport<W> {
+    /// Write every record in the chunk as a new row group.
+    fn write_chunk<T: serde::Serialize>(&mut self, records: Vec<T>) -> Result<(), TransformError> {
+        use parquet::data_type::{BoolType, ByteArray, ByteArrayType, Int64Type};
+
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        let records: Vec<Value> = records
+            .into_iter()
+            .map(serde_json::to_value)
+            .collect::<Result<_, _>>()?;
+        let mut row_group = self.writer.next_row_group()?;
+
+        for (name, kind, optional) in self.columns.iter() {
+            let Some(mut column) = row_group.next_column()?
+            else {
+                break;
+            };
+
+            let values: Vec<Option<&Value>> = records
+                .iter()
+                .map(|record| record.get(name).filter(|value| !value.is_null()))
+                .collect();
+
+            // only the present values are written with a definition level of 1 marking them
+            let levels: Vec<i16> = values.iter().map(|value| value.is_some() as i16).collect();
+            let levels = optional.then_some(levels.as_slice());
+
+            match kind {
+                ParquetColumn::Utf8 => {
+                    let values: Vec<ByteArray> =
+                        present(&values, *optional, |value| to_cell(Some(value)).into_bytes().into());
+                    column.typed::<ByteArrayType>().write_batch(&values, levels, None)?;
+                }
+                ParquetColumn::UInt64 => {
+                    // unsigned values are stored in the bits of a signed integer
+                    let values: Vec<i64> =
+                        present(&values, *optional, |value| value.as_u64().unwrap_or_default() as i64);
+                    column.typed::<Int64Type>().write_batch(&values, levels, None)?;
+                }
+                ParquetColumn::Boolean => {
+                    let values: Vec<bool> = present(&values, *optional, |value| value.as_bool().unwrap_or_default());
+                    column.typed::<BoolType>().write_batch(&values, levels, None)?;
+                }
+            }
+
+            column.close()?;
+        }
+
+        row_group.close()?;
+        self.written += records.len();
+        Ok(())
+    }
+}
+
+
+/// Convert the values of a column, leaving out the missing values of an optional column.
+///
+/// A required column has a value for every record so missing values fall back to the default.
+#[cfg(feature = "parquet")]
+fn present<T: Default>(values: &[Option<&Value>], optional: bool, convert: impl Fn(&Value) -> T) -> Vec<T> {
+    match optional {
+        true => values.iter().flatten().map(|value| convert(value)).collect(),
+        false => values
+            .iter()
+            .map(|value| value.map(&convert).unwrap_or_default())
+            .collect(),
+    }
+}
+
+
+/// Write records as Parquet to the specified path in a single row group.
+///
+/// The columns are usually the ones described by the `columns` function of the model.
+///
+/// Returns the amount of records written.
+#[cfg(feature = "parquet")]
+pub fn write_parquet<T, P>(records: Vec<T>, columns: &[ColumnSchema], path: P) -> Result<usize, TransformError>
+where
+    T: serde::Serialize,
+    P: AsRef<std::path::Path>,
+{
+    let mut export = ParquetExport::new(std::fs::File::create(path)?, columns)?;
+    export.write_chunk(records)?;
+    export.finish()
+}
+
+
+/// Write the records of a model as Parquet with a row group for every `chunk_size` records.
+///
+/// See [`write_model`] for the models that are built a chunk at a time.
+///
+/// Returns the amount of records written.
+#[cfg(feature = "parquet")]
+#[instrument(skip(dataset, writer))]
+pub fn write_model_parquet<W: Write + Send>(
+    dataset: &Dataset,
+    model: &Model,
+    writer: W,
+    chunk_size: usize,
+) -> Result<usize, TransformError> {
+    let mut export = ParquetExport::new(writer, &models::columns(model))?;
+    write_model(dataset, model, &mut export, chunk_size)?;
+    export.finish()
+}
+
+
+/// Write the records of a model to the export in chunks of `chunk_size` records.
+///
+/// Assemblies, depositions, organisms and sequences are resolved and built one chunk at a time
+/// with their `get_chunked` functions and are written in entity id order, so the memory used by
+/// their records is bounded by `chunk_size`. The other models merge or deduplicate records across
+/// the whole model, such as names and publications, so their records are still all transformed
+/// at once and only written in chunks.
+pub fn write_model<E: ChunkedExport>(
+    dataset: &Dataset,
+    model: &Model,
+    export: &mut E,
+    chunk_size: usize,
+) -> Result<(), TransformError> {
+    info!(?model, "Exporting records");
+    match model {
+        Model::Assembly => models::assembly::get_chunked(dataset, chunk_size, |chunk| export.write_chunk(chunk)),
+        Model::Agent => write_chunks(export, models::agent::get_all(dataset)?, chunk_size),
+        Model::Annotation => write_chunks(export, models::annotation::get_all(dataset)?, chunk_size),
+        Model::Collecting => write_chunks(export, models::collecting::get_all(dataset)?, chunk_size),
+        Model::DataProduct => write_chunks(export, models::data_products::get_all(dataset)?, chunk_size),
+        Model::Deposition => models::deposition::get_chunked(dataset, chunk_size, |chunk| export.write_chunk(chunk)),
+        Model::Extraction => write_chunks(export, models::extraction::get_all(dataset)?, chunk_size),
+        Model::Library => write_chunks(export, models::library::get_all(dataset)?, chunk_size),
+        Model::Name => write_chunks(export, models::name::get_all(dataset)?, chunk_size),
+        Model::Organism => models::organism::get_chunked(dataset, chunk_size, |chunk| export.write_chunk(chunk)),
+        Model::ProjectAssembly => write_chunks(export, models::project_assemblies::get_all(dataset)?, chunk_size),
+        Model::ProjectMember => write_chunks(export, models::project_members::get_all(dataset)?, chunk_size),
+        Model::Project => write_chunks(export, models::projects::get_all(dataset)?, chunk_size),
+        Model::Publication => write_chunks(export, models::publications::get_all(dataset)?, chunk_size),
+        Model::SequencingRun => write_chunks(export, models::sequencing_run::get_all(dataset)?, chunk_size),
+        Model::Sequence => models::sequence::get_chunked(dataset, chunk_size, |chunk| export.write_chunk(chunk)),
+        Model::Subsample => write_chunks(export, models::subsample::get_all(dataset)?, chunk_size),
+        Model::Taxon => write_chunks(export, models::taxon::get_all(dataset)?, chunk_size),
+        Model::Tissue => write_chunks(export, models::tissue::get_all(dataset)?, chunk_size),
+    }
+}
+
+
+fn write_chunks<E, T>(export: &mut E, records: Vec<T>, chunk_size: usize) -> Result<(), TransformError>
+where
+    E: ChunkedExport,
+    T: serde::Serialize,
+{
+    let mut records = records.into_iter();
+    loop {
+        let chunk: Vec<T> = records.by_ref().take(chunk_size.max(1)).collect();
+        if chunk.is_empty() {
+            return Ok(());
+        }
+        export.write_chunk(chunk)?;
+    }
+}
+
+
+fn to_cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(value)) => value.clone(),
+        Some(Value::Array(values)) => values
+            .iter()
+            .map(|value| to_cell(Some(value)))
+            .collect::<Vec<String>>()
+            .join(LIST_SEPARATOR),
+        Some(value) => value.to_string(),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde::de::DeserializeOwned;
+
+    use super::*;
+    use crate::models::{Assembly, Collecting, Project};
+    use crate::testing::{SYNTHETIC_SCHEMA, load_source, synthetic_dataset};
+
+    fn assemblies() -> Vec<Assembly> {
+        vec![
+            Assembly {
+                entity_id: "GCA_1".to_string(),
+                r#type: Some("haploid".to_string()),
+                size: Some(2_500_000),
+                number_of_scaffolds: Some(0),
+                genome_coverage: Some("40.5x".to_string()),
+                ..Default::default()
+            },
+            Assembly {
+                entity_id: "GCA_2".to_string(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn collecting() -> Vec<Collecting> {
+        vec![
+            Collecting {
+                entity_id: "C1".to_string(),
+                collected_by: vec!["Jane Smith".to_string(), "John Citizen".to_string()],
+                permit: vec!["P-2021/04".to_string()],
+                latitude: Some("-35.28".to_string()),
+                ..Default::default()
+            },
+            Collecting {
+                entity_id: "C2".to_string(),
+                collected_by: vec!["Jane Smith".to_string()],
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn projects() -> Vec<Project> {
+        vec![Project {
+            entity_id: "P1".to_string(),
+            data_types: vec!["genomic".to_string(), "transcriptomic".to_string()],
+            partners: vec!["1234".to_string()],
+            ..Default::default()
+        }]
+    }
+
+    fn json_round_trip<T: Serialize + DeserializeOwned>(records: &[T]) -> Vec<T> {
+        let json = serde_json::to_string(records).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn csv_round_trip<T: Serialize + DeserializeOwned>(records: Vec<T>, columns: &[ColumnSchema]) -> Vec<T> {
+        let mut buffer = Vec::new();
+        let mut export = CsvExport::new(&mut buffer, columns).unwrap();
+        export.write_chunk(records).unwrap();
+        export.finish().unwrap();
+
+        let mut reader = csv::Reader::from_reader(buffer.as_slice());
+        reader.deserialize().collect::<Result<Vec<T>, csv::Error>>().unwrap()
+    }
+
+    #[test]
+    fn json_round_trips() {
+        assert_eq!(json_round_trip(&assemblies()), assemblies());
+        assert_eq!(json_round_trip(&collecting()), collecting());
+        assert_eq!(json_round_trip(&projects()), projects());
+    }
+
+    #[test]
+    fn csv_round_trips() {
+        assert_eq!(csv_round_trip(assemblies(), &Assembly::columns()), assemblies());
+        assert_eq!(csv_round_trip(collecting(), &Collecting::columns()), collecting());
+        assert_eq!(csv_round_trip(projects(), &Project::columns()), projects());
+    }
+
+    #[test]
+    fn csv_joins_lists_with_the_separator() {
+        let mut buffer = Vec::new();
+        let mut export = CsvExport::new(&mut buffer, &Collecting::columns()).unwrap();
+        export.write_chunk(collecting()).unwrap();
+        export.finish().unwrap();
+
+        let mut reader = csv::Reader::from_reader(buffer.as_slice());
+        let headers = reader.headers().unwrap().clone();
+        let column = headers.iter().position(|header| header == "collected_by").unwrap();
+        let cells: Vec<String> = reader
+            .records()
+            .map(|record| record.unwrap()[column].to_string())
+            .collect();
+        assert_eq!(cells, vec!["Jane Smith|John Citizen", "Jane Smith"]);
+    }
+
+    #[test]
+    fn streaming_export_matches_the_full_export() {
+        let dataset = synthetic_dataset(200_000, 2, 1).unwrap();
+
+        let mut full = Vec::new();
+        let mut streamed = Vec::new();
+        let written = write_csv(&dataset, &Model::Name, &mut full, usize::MAX).unwrap();
+        let streamed_written = write_csv(&dataset, &Model::Name, &mut streamed, 1_000).unwrap();
+
+        assert_eq!(written, 200_000);
+        assert_eq!(streamed_written, written);
+        assert!(full == streamed, "streamed export differs from the full export");
+    }
+
+    #[test]
+    fn chunked_models_are_resolved_in_entity_id_order() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = "fields:entity_id mapping:same col:id .
+            fields:scientific_name mapping:same col:name .";
+        let ids: Vec<String> = (0..50).rev().map(|idx| idx.to_string()).collect();
+        let rows: Vec<Vec<(&str, &str)>> = ids
+            .iter()
+            .map(|id| vec![("id", id.as_str()), ("name", "Felis catus")])
+            .collect();
+        let rows: Vec<&[(&str, &str)]> = rows.iter().map(|row| row.as_slice()).collect();
+        load_source(&mut dataset, &Model::Organism, "organisms", mappings, &rows).unwrap();
+
+        let mut chunks = Vec::new();
+        models::organism::get_chunked(&dataset, 7, |chunk| {
+            chunks.push(chunk.into_iter().map(|organism| organism.entity_id).collect::<Vec<_>>());
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(chunks.len(), 8);
+        assert!(chunks[..7].iter().all(|chunk| chunk.len() == 7));
+        let resolved: Vec<String> = chunks.into_iter().flatten().collect();
+        let expected: Vec<String> = (0..50).map(|idx| idx.to_string()).collect();
+        assert_eq!(resolved, expected);
+
+        let mut full = Vec::new();
+        let mut streamed = Vec::new();
+        write_csv(&dataset, &Model::Organism, &mut full, usize::MAX).unwrap();
+        write_csv(&dataset, &Model::Organism, &mut streamed, 7).unwrap();
+        assert!(full == streamed, "streamed export differs from the full export");
+    }
+
+    #[cfg(feature = "parquet")]
+    fn parquet_round_trip<T: Serialize + DeserializeOwned>(
+        records: Vec<T>,
+        columns: &[ColumnSchema],
+        name: &str,
+    ) -> Vec<T> {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::record::Field;
+
+        let path = std::env::temp_dir().join(format!("arga-transformer-{}-{name}.parquet", std::process::id()));
+        write_parquet(records, columns, &path).unwrap();
+
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut records = Vec::new();
+
+        for row in reader.get_row_iter(None).unwrap() {
+            let mut record = serde_json::Map::new();
+            for (name, field) in row.unwrap().get_column_iter() {
+                let value = match field {
+                    Field::Null => Value::Null,
+                    Field::Str(value) => Value::from(value.clone()),
+                    Field::ULong(value) => Value::from(*value),
+                    Field::Bool(value) => Value::from(*value),
+                    other => panic!("unexpected parquet field {other:?}"),
+                };
+                record.insert(name.clone(), value);
+            }
+            records.push(serde_json::from_value(Value::Object(record)).unwrap());
+        }
+
+        std::fs::remove_file(&path).unwrap();
+        records
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn parquet_round_trips() {
+        assert_eq!(parquet_round_trip(assemblies(), &Assembly::columns(), "assemblies"), assemblies());
+        assert_eq!(parquet_round_trip(collecting(), &Collecting::columns(), "collecting"), collecting());
+        assert_eq!(parquet_round_trip(projects(), &Project::columns(), "projects"), projects());
+    }
+}
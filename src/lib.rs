@@ -1,16 +1,27 @@
+pub mod coverage;
 pub mod dataset;
 pub mod errors;
+pub mod export;
+pub mod lint;
+pub mod matchers;
 pub mod models;
+pub mod names;
+pub mod preview;
+pub mod query;
 pub mod rdf;
 pub mod readers;
 pub mod resolver;
-
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
+pub mod utils;
+pub mod validation;
 
 use std::io::BufReader;
 
-use dataset::{Dataset, Triple};
+use dataset::{Dataset, Model, TransformStats, Triple};
 use errors::TransformError;
 use tracing::debug;
+pub use utils::parse_date_flexible;
 
 
 mod ttl {
@@ -19,7 +30,7 @@ mod ttl {
     // pub const BIOPLATFORMS: &[u8] = include_bytes!("../schemas/bioplatforms.ttl");
     pub const DNAZOO: &[u8] = include_bytes!("../schemas/dnazoo.ttl");
     pub const NCBI_TAXONOMY: &[u8] = include_bytes!("../schemas/ncbi_taxonomy.ttl");
-    // pub const NCBI_BIOSAMPLES: &[u8] = include_bytes!("../schemas/ncbi_biosamples.ttl");
+    pub const NCBI_BIOSAMPLES: &[u8] = include_bytes!("../schemas/ncbi_biosamples.ttl");
     pub const NCBI_GENBANK: &[u8] = include_bytes!("../schemas/ncbi_genbank.ttl");
     pub const NCBI_REPORTS: &[u8] = include_bytes!("../schemas/ncbi_reports.ttl");
 }
@@ -56,18 +67,52 @@ impl Transformer {
         // dataset.load_trig(BufReader::new(ttl::BIOPLATFORMS))?;
         dataset.load_trig(BufReader::new(ttl::DNAZOO))?;
         dataset.load_trig(BufReader::new(ttl::NCBI_TAXONOMY))?;
-        // dataset.load_trig(BufReader::new(ttl::NCBI_BIOSAMPLES))?;
+        dataset.load_trig(BufReader::new(ttl::NCBI_BIOSAMPLES))?;
         dataset.load_trig(BufReader::new(ttl::NCBI_GENBANK))?;
         dataset.load_trig(BufReader::new(ttl::NCBI_REPORTS))?;
 
         Ok(Transformer { dataset })
     }
 
+    /// Initialise the transformer with the mapping files in a directory instead of the embedded schemas.
+    ///
+    /// Every `.trig` and `.ttl` file in the directory is loaded in order of their file name.
+    /// Subdirectories are not searched.
+    pub fn new_from_directory(schema: &str, schema_dir: &std::path::Path) -> Result<Transformer, TransformError> {
+        let mut dataset = Dataset::new(schema)?;
+
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(schema_dir)? {
+            let path = entry?.path();
+            let extension = path.extension().and_then(|ext| ext.to_str());
+            if path.is_file() && matches!(extension, Some("trig") | Some("ttl")) {
+                files.push(path);
+            }
+        }
+        files.sort();
+
+        for path in files {
+            debug!(path = %path.display(), "loading schema");
+            let file = std::fs::File::open(&path)?;
+            dataset.load_trig(BufReader::new(file))?;
+        }
+
+        Ok(Transformer { dataset })
+    }
+
+    /// Load sources into graphs under the specified base IRI instead of the default.
+    ///
+    /// The source IRIs declared in the schemas with `transforms_into` must use the same
+    /// base otherwise the loaded data won't be in scope of any model.
+    pub fn set_source_base(&mut self, source_base: &str) -> Result<(), TransformError> {
+        self.dataset.set_source_base(source_base)
+    }
+
     /// Initialise the transformer and it's underlying RDF store.
     ///
     /// This will also load the mapping files defined in the `schemas` subrepo
     /// of which are included in the compiled binary.
-    pub fn load<I, E: std::fmt::Debug>(&mut self, triples: I, source: &str) -> Result<usize, TransformError>
+    pub fn load<I, E: std::fmt::Debug>(&mut self, triples: I, source: &str) -> Result<TransformStats, TransformError>
     where
         I: IntoIterator<Item = Result<Triple, E>>,
     {
@@ -75,66 +120,421 @@ impl Transformer {
         self.dataset.load(triples, source)
     }
 
+    /// Load a dataset along with extra provenance details about the source.
+    ///
+    /// See [`Dataset::load_with_meta`] for more details.
+    pub fn load_with_meta<I, E: std::fmt::Debug>(
+        &mut self,
+        triples: I,
+        source: &str,
+        meta: &dataset::LoadMeta,
+    ) -> Result<TransformStats, TransformError>
+    where
+        I: IntoIterator<Item = Result<Triple, E>>,
+    {
+        debug!(%self.dataset.schema, source, "loading dataset quads");
+        self.dataset.load_with_meta(triples, source, meta)
+    }
+
+    /// Load a dataset, collecting triples that fail to be read instead of stopping.
+    ///
+    /// See [`Dataset::load_tolerant`] for more details.
+    pub fn load_tolerant<I, E: std::fmt::Debug>(
+        &mut self,
+        triples: I,
+        source: &str,
+    ) -> Result<TransformStats, TransformError>
+    where
+        I: IntoIterator<Item = Result<Triple, E>>,
+    {
+        debug!(%self.dataset.schema, source, "loading dataset quads");
+        self.dataset.load_tolerant(triples, source)
+    }
+
+    /// Load a dataset with the record index namespaced by the prefix.
+    ///
+    /// See [`Dataset::load_with_prefix`] for more details.
+    pub fn load_with_prefix<I, E: std::fmt::Debug>(
+        &mut self,
+        triples: I,
+        source: &str,
+        entity_id_prefix: &str,
+    ) -> Result<TransformStats, TransformError>
+    where
+        I: IntoIterator<Item = Result<Triple, E>>,
+    {
+        debug!(%self.dataset.schema, source, entity_id_prefix, "loading dataset quads");
+        self.dataset.load_with_prefix(triples, source, Some(entity_id_prefix))
+    }
+
+    /// Load a dataset with the column IRIs created under a different namespace than the schema.
+    ///
+    /// See [`Dataset::load_with_namespace`] for more details.
+    pub fn load_with_namespace<I, E: std::fmt::Debug>(
+        &mut self,
+        triples: I,
+        source: &str,
+        namespace: &str,
+    ) -> Result<TransformStats, TransformError>
+    where
+        I: IntoIterator<Item = Result<Triple, E>>,
+    {
+        debug!(%self.dataset.schema, source, namespace, "loading dataset quads");
+        self.dataset.load_with_namespace(triples, source, namespace)
+    }
+
+    /// Load a dataset, replacing all data previously loaded from the source.
+    ///
+    /// See [`Dataset::load_replace`] for more details.
+    pub fn load_replace<I, E: std::fmt::Debug>(
+        &mut self,
+        triples: I,
+        source: &str,
+    ) -> Result<TransformStats, TransformError>
+    where
+        I: IntoIterator<Item = Result<Triple, E>>,
+    {
+        debug!(%self.dataset.schema, source, "replacing dataset quads");
+        self.dataset.load_replace(triples, source)
+    }
+
+    /// Load a dataset, replacing existing records that have the same value in the key column.
+    ///
+    /// See [`Dataset::load_upsert`] for more details.
+    pub fn load_upsert<I, E: std::fmt::Debug>(
+        &mut self,
+        triples: I,
+        source: &str,
+        key_header: &str,
+    ) -> Result<TransformStats, TransformError>
+    where
+        I: IntoIterator<Item = Result<Triple, E>>,
+    {
+        debug!(%self.dataset.schema, source, key_header, "upserting dataset quads");
+        self.dataset.load_upsert(triples, source, key_header)
+    }
+
+    /// Load a CSV file, decompressing it first if it has a `.gz` extension.
+    ///
+    /// Empty values are skipped in the same way as a reader made with the `CsvReaderBuilder`.
+    pub fn load_csv_path(&mut self, path: &std::path::Path, source: &str) -> Result<TransformStats, TransformError> {
+        let reader = readers::CsvReaderBuilder::new().build(readers::open_file(path)?)?;
+        debug!(%self.dataset.schema, source, path = %path.display(), "loading csv file");
+        self.dataset.load(reader, source)
+    }
+
+    /// Load a JSON file, decompressing it first if it has a `.gz` extension.
+    ///
+    /// See [`readers::JsonReader`] for the expected structure of the file.
+    pub fn load_json_path(&mut self, path: &std::path::Path, source: &str) -> Result<TransformStats, TransformError> {
+        let reader = readers::JsonReader::new(readers::open_file(path)?)?;
+        debug!(%self.dataset.schema, source, path = %path.display(), "loading json file");
+        self.dataset.load(reader, source)
+    }
+
+    pub fn agents(&self) -> Result<Vec<models::Agent>, TransformError> {
+        self.dataset
+            .check_scope(&[Model::Extraction, Model::Library, Model::Collecting, Model::DataProduct])?;
+        models::agent::get_all(&self.dataset)
+    }
+
+    /// Get all agents along with the merges performed when deriving their entity ids.
+    pub fn agents_with_report(&self) -> Result<(Vec<models::Agent>, models::AgentMergeReport), TransformError> {
+        self.dataset
+            .check_scope(&[Model::Extraction, Model::Library, Model::Collecting, Model::DataProduct])?;
+        models::agent::get_all_with_report(&self.dataset)
+    }
+
     pub fn annotations(&self) -> Result<Vec<models::Annotation>, TransformError> {
+        self.dataset.check_scope(&[Model::Annotation])?;
         models::annotation::get_all(&self.dataset)
     }
 
     pub fn assemblies(&self) -> Result<Vec<models::Assembly>, TransformError> {
+        self.dataset.check_scope(&[Model::Assembly])?;
         models::assembly::get_all(&self.dataset)
     }
 
     pub fn collecting(&self) -> Result<Vec<models::Collecting>, TransformError> {
+        self.dataset.check_scope(&[Model::Collecting])?;
         models::collecting::get_all(&self.dataset)
     }
 
     pub fn data_products(&self) -> Result<Vec<models::DataProduct>, TransformError> {
+        self.dataset.check_scope(&[Model::DataProduct])?;
         models::data_products::get_all(&self.dataset)
     }
 
     pub fn depositions(&self) -> Result<Vec<models::Deposition>, TransformError> {
+        self.dataset.check_scope(&[Model::Deposition])?;
         models::deposition::get_all(&self.dataset)
     }
 
     pub fn extractions(&self) -> Result<Vec<models::Extraction>, TransformError> {
+        self.dataset.check_scope(&[Model::Extraction])?;
         models::extraction::get_all(&self.dataset)
     }
 
     pub fn libraries(&self) -> Result<Vec<models::Library>, TransformError> {
+        self.dataset.check_scope(&[Model::Library])?;
         models::library::get_all(&self.dataset)
     }
 
     pub fn names(&self) -> Result<Vec<models::Name>, TransformError> {
+        self.dataset.check_scope(&[Model::Name])?;
         models::name::get_all(&self.dataset)
     }
 
+    /// Get all names along with the merges performed when deduplicating them.
+    pub fn names_with_report(&self) -> Result<(Vec<models::Name>, models::NameMergeReport), TransformError> {
+        self.dataset.check_scope(&[Model::Name])?;
+        models::name::get_all_with_report(&self.dataset)
+    }
+
     pub fn organisms(&self) -> Result<Vec<models::Organism>, TransformError> {
+        self.dataset.check_scope(&[Model::Organism])?;
         models::organism::get_all(&self.dataset)
     }
 
+    pub fn project_assemblies(&self) -> Result<Vec<models::ProjectAssembly>, TransformError> {
+        self.dataset.check_scope(&[Model::ProjectAssembly])?;
+        models::project_assemblies::get_all(&self.dataset)
+    }
+
     pub fn project_members(&self) -> Result<Vec<models::ProjectMember>, TransformError> {
+        self.dataset.check_scope(&[Model::ProjectMember])?;
         models::project_members::get_all(&self.dataset)
     }
 
     pub fn projects(&self) -> Result<Vec<models::Project>, TransformError> {
+        self.dataset.check_scope(&[Model::Project])?;
         models::projects::get_all(&self.dataset)
     }
 
     pub fn publications(&self) -> Result<Vec<models::Publication>, TransformError> {
+        self.dataset
+            .check_scope(&[Model::Publication, Model::Organism, Model::Extraction, Model::Assembly])?;
         models::publications::get_all(&self.dataset)
     }
 
     pub fn sequencing_runs(&self) -> Result<Vec<models::SequencingRun>, TransformError> {
+        self.dataset.check_scope(&[Model::SequencingRun])?;
         models::sequencing_run::get_all(&self.dataset)
     }
 
+    pub fn sequences(&self) -> Result<Vec<models::Sequence>, TransformError> {
+        self.dataset.check_scope(&[Model::Sequence])?;
+        models::sequence::get_all(&self.dataset)
+    }
+
+    /// Sequencing runs combined with the library and extraction they came from.
+    pub fn sequencing(&self) -> Result<Vec<models::Sequencing>, TransformError> {
+        self.dataset.check_scope(&[Model::SequencingRun])?;
+        models::sequencing::get_all(&self.dataset)
+    }
+
     pub fn subsamples(&self) -> Result<Vec<models::Subsample>, TransformError> {
+        self.dataset.check_scope(&[Model::Subsample])?;
         models::subsample::get_all(&self.dataset)
     }
 
+    pub fn taxa(&self) -> Result<Vec<models::Taxon>, TransformError> {
+        self.dataset.check_scope(&[Model::Taxon])?;
+        models::taxon::get_all(&self.dataset)
+    }
+
     pub fn tissues(&self) -> Result<Vec<models::Tissue>, TransformError> {
+        self.dataset.check_scope(&[Model::Tissue])?;
         models::tissue::get_all(&self.dataset)
     }
 
+    /// Report which canonical fields of every model have a mapping.
+    ///
+    /// The report is serialised to JSON.
+    pub fn coverage_report(&self) -> Result<String, TransformError> {
+        let report = coverage::report(&self.dataset)?;
+        Ok(serde_json::to_string_pretty(&report)?)
+    }
+
+    /// Find the references between models that point to a record that doesn't exist.
+    ///
+    /// See [`models::verify_references`] for the references that are checked.
+    pub fn verify(&self) -> Result<Vec<models::DanglingRef>, TransformError> {
+        models::verify_references(&self.dataset)
+    }
+
+    /// Report the references between models that point to a record that doesn't exist.
+    ///
+    /// The report is serialised to JSON.
+    pub fn verify_report(&self) -> Result<String, TransformError> {
+        let dangling = self.verify()?;
+        Ok(serde_json::to_string_pretty(&dangling)?)
+    }
+
+    /// Get a sample of the first `n` records of every model.
+    ///
+    /// See [`preview::preview`] for the structure of the result.
+    pub fn preview(&self, n: usize) -> Result<preview::PreviewResult, TransformError> {
+        preview::preview(&self.dataset, n)
+    }
+
+    /// Fail when getting the records of a model finds problems instead of logging a warning.
+    ///
+    /// See [`resolver::ResolveOptions::strict`] for the problems that are checked.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.dataset.set_strict(strict);
+    }
+
+    /// Return no records for models without any loaded data instead of failing.
+    ///
+    /// By default getting the records of a model fails with `TransformError::EmptyScope`
+    /// when none of its sources have data, see [`Dataset::check_scope`].
+    pub fn set_allow_empty(&mut self, allow_empty: bool) {
+        self.dataset.set_allow_empty(allow_empty);
+    }
+
+    /// Change the values that mean a value is missing, eg. `NA` or `not provided`.
+    ///
+    /// These aren't inserted when loading and are treated as absent by mappings that pick
+    /// between fields. See [`dataset::DEFAULT_NULL_TOKENS`] for the tokens used by default.
+    pub fn set_null_tokens(&mut self, null_tokens: dataset::NullTokens) {
+        self.dataset.set_null_tokens(null_tokens);
+    }
+
+    /// Derive the country codes of collecting events from misspelt country names as well.
+    ///
+    /// See [`validation::country::country_code_fuzzy`] for how names are matched.
+    pub fn set_fuzzy_countries(&mut self, fuzzy_countries: bool) {
+        self.dataset.set_fuzzy_countries(fuzzy_countries);
+    }
+
+    /// Find the entity ids that more than one record of the model with the specified name resolves to.
+    ///
+    /// See [`resolver::Resolver::check_unique_entities`] for more details.
+    pub fn entity_collisions(&self, model: &str) -> Result<Vec<resolver::EntityCollision>, TransformError> {
+        let model = dataset::Model::from_name(model)?;
+        let scope = self.dataset.scope_iris(&[model])?;
+        resolver::Resolver::new(&self.dataset).check_unique_entities(&scope)
+    }
+
+    /// Show the source data, mappings, and resolved values behind a record of the model.
+    ///
+    /// See [`resolver::Resolver::inspect`] for more details.
+    pub fn inspect_record(&self, model: &str, key: &str) -> Result<resolver::RecordInspection, TransformError> {
+        let model = dataset::Model::from_name(model)?;
+        let scope = self.dataset.scope_iris(&[model])?;
+        let key = rdf::Literal::String(key.to_string());
+        resolver::Resolver::new(&self.dataset).inspect(&key, &model.fields(), &scope)
+    }
+
+    /// Declare that a loaded source transforms into the model with the specified name.
+    ///
+    /// The model name is the one used in the model schema IRI, eg. `assembly`.
+    /// See [`Dataset::register_source`] for more details.
+    pub fn register_source(&mut self, source: &str, model: &str) -> Result<(), TransformError> {
+        let model = dataset::Model::from_name(model)?;
+        self.dataset.register_source(source, &model)
+    }
+
+    /// Load the mappings of a schema builder for a source.
+    ///
+    /// See [`Dataset::load_schema`] for more details.
+    pub fn load_schema(&mut self, source: &str, schema: &dataset::SchemaBuilder) -> Result<(), TransformError> {
+        self.dataset.load_schema(source, schema)
+    }
+
+    /// Get the provenance recorded when the source was last loaded.
+    pub fn source_metadata(&self, source: &str) -> Result<Option<dataset::SourceMetadata>, TransformError> {
+        self.dataset.source_metadata(source)
+    }
+
+    /// Get the sources that transform into the model with the specified name.
+    ///
+    /// This includes sources declared in the schemas and those registered with `register_source`.
+    pub fn sources_for(&self, model: &str) -> Result<Vec<iref::IriBuf>, TransformError> {
+        let model = dataset::Model::from_name(model)?;
+        self.dataset.get_source_from_model(&self.dataset.model_schema(&model))
+    }
+
+    /// Run a SPARQL query against the loaded schemas and datasets.
+    pub fn query(&self, query: &str) -> Result<Vec<rdf::SparqlRow>, TransformError> {
+        self.dataset.query(query)
+    }
+
+    /// Run a SPARQL query against the schemas and sources of the models with the specified names.
+    pub fn query_models(&self, query: &str, models: &[&str]) -> Result<Vec<rdf::SparqlRow>, TransformError> {
+        let models: Result<Vec<dataset::Model>, TransformError> =
+            models.iter().map(|model| dataset::Model::from_name(model)).collect();
+        let scope = self.dataset.scope_iris(&models?)?;
+        self.dataset.query_scoped(query, &scope)
+    }
+
+    /// Run a prepared SPARQL query after substituting its bound parameters.
+    pub fn query_prepared(&self, query: &query::Query) -> Result<Vec<rdf::SparqlRow>, TransformError> {
+        self.dataset.query(&query.render()?)
+    }
+
+    /// Get the distinct values of a field across all graphs.
+    ///
+    /// See [`Dataset::field_values`] for more details.
+    pub fn field_values(&self, field: &str) -> Result<Vec<rdf::Value>, TransformError> {
+        self.dataset.field_values(field)
+    }
+
+    /// Check the loaded mapping schemas for common mistakes.
+    ///
+    /// See [`lint::lint`] for the issues that are reported.
+    pub fn lint_schemas(&self) -> Result<Vec<lint::SchemaLint>, TransformError> {
+        lint::lint(&self.dataset)
+    }
+
+    /// Write every quad in the store to a file in the specified format.
+    ///
+    /// This is mostly useful for debugging the intermediate representation of the
+    /// transform or for handing it over to other RDF tooling.
+    pub fn export_store<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        format: dataset::ExportFormat,
+    ) -> Result<(), TransformError> {
+        let writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        match format {
+            dataset::ExportFormat::NQuads => self.dataset.write_nquads(writer, &dataset::ExportGraphs::All),
+            dataset::ExportFormat::TriG => self.dataset.write_trig(writer, &dataset::ExportGraphs::All),
+        }
+    }
+
+    /// Write the records of the model with the specified name to a CSV file.
+    pub fn export_csv<P: AsRef<std::path::Path>>(&self, model: &str, path: P) -> Result<usize, TransformError> {
+        self.export_csv_streaming(model, path, usize::MAX)
+    }
+
+    /// Write the records of the model with the specified name to a CSV file in chunks.
+    ///
+    /// See [`export::write_csv`] for the models that are built a chunk at a time. The header
+    /// comes from the model struct so every chunk is written with the same columns.
+    pub fn export_csv_streaming<P: AsRef<std::path::Path>>(
+        &self,
+        model: &str,
+        path: P,
+        chunk_size: usize,
+    ) -> Result<usize, TransformError> {
+        let model = dataset::Model::from_name(model)?;
+        let writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        export::write_csv(&self.dataset, &model, writer, chunk_size)
+    }
+
+    /// Write the records of the model with the specified name to a Parquet file.
+    ///
+    /// A row group is written for every `export::DEFAULT_ROW_GROUP_SIZE` records, and like
+    /// `export_csv_streaming` the assemblies are only built one row group at a time.
+    #[cfg(feature = "parquet")]
+    pub fn export_parquet<P: AsRef<std::path::Path>>(&self, model: &str, path: P) -> Result<usize, TransformError> {
+        let model = dataset::Model::from_name(model)?;
+        let writer = std::fs::File::create(path)?;
+        export::write_model_parquet(&self.dataset, &model, writer, export::DEFAULT_ROW_GROUP_SIZE)
+    }
+
     /// Get the triples loaded into the specified source graph.
     pub fn triples(&self, source: &str) -> Result<(), TransformError> {
         self.dataset.triples(source)
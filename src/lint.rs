@@ -0,0 +1,399 @@
+use std::collections::{HashMap, HashSet};
+
+use sophia::api::dataset::Dataset as DatasetTrait;
+use sophia::api::term::SimpleTerm;
+
+use crate::dataset::{Dataset, Model, stringify_term};
+use crate::errors::TransformError;
+use crate::rdf::{
+    ConvertCondition,
+    FromCondition,
+    Mapping,
+    MappingCondition,
+    PadCondition,
+    ReplaceCondition,
+    ScaleCondition,
+    Source,
+    ToIri,
+    TrimCondition,
+};
+use crate::resolver::Resolver;
+
+
+const MAPPING_NAMESPACE: &str = "http://arga.org.au/schemas/mapping/";
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub enum LintKind {
+    /// A predicate in the mapping namespace that isn't a known directive.
+    UnknownPredicate(String),
+    /// A mapping directive with an object that isn't the expected term kind.
+    InvalidObject { mapping: String, expected: &'static str },
+    /// A field referenced by a list mapping that has no `mapping:same` anywhere.
+    UndeclaredSourceField(String),
+    /// A graph with mappings that no source declares `transforms_into` for.
+    UnusedMappingGraph,
+    /// A model that no source `transforms_into`.
+    UnmappedModel,
+    /// More than one value producing mapping for the same field in the same graph.
+    ConflictingMappings(Vec<String>),
+}
+
+
+/// A single issue found in the loaded mapping schemas.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SchemaLint {
+    pub graph: Option<String>,
+    pub subject: String,
+    pub severity: Severity,
+    pub kind: LintKind,
+}
+
+
+/// Walk every mapping quad in the dataset and report problems with the schemas.
+///
+/// This is a static check of the mapping definitions and does not require any
+/// source data to be loaded. It's intended to catch typos and dangling references
+/// that would otherwise only show up as a model resolving to nothing.
+pub fn lint(dataset: &Dataset) -> Result<Vec<SchemaLint>, TransformError> {
+    let resolver = Resolver::new(dataset);
+    let mut lints = Vec::new();
+
+    // subjects of mapping:same in any graph. used to validate list references
+    let mut declared = HashSet::new();
+    // fields referenced by combines and hash_first lists
+    let mut referenced = Vec::new();
+    // the value producing mappings for a subject in a graph
+    let mut producers: HashMap<(Option<String>, String), Vec<String>> = HashMap::new();
+    // graphs that contain mapping directives
+    let mut mapping_graphs = HashSet::new();
+    // sources that transform into a model
+    let mut sources = HashSet::new();
+
+    for quad in dataset.source.quads() {
+        let (g, [s, p, o]) = quad?;
+
+        let SimpleTerm::Iri(predicate) = p
+        else {
+            continue;
+        };
+        if !predicate.as_str().starts_with(MAPPING_NAMESPACE) {
+            continue;
+        }
+
+        let graph = match g {
+            Some(SimpleTerm::Iri(iri)) => Some(iri.to_string()),
+            _ => None,
+        };
+        let subject = stringify_term(s);
+
+        // blank nodes hold the qualifiers for a mapping rather than being a mapping themselves
+        if let SimpleTerm::BlankNode(_) = s {
+            let known = ConvertCondition::try_from(p).is_ok()
+                || MappingCondition::try_from(p).is_ok()
+                || ReplaceCondition::try_from(p).is_ok()
+                || ScaleCondition::try_from(p).is_ok()
+                || PadCondition::try_from(p).is_ok()
+                || TrimCondition::try_from(p).is_ok()
+                || FromCondition::try_from(p).is_ok();
+            if !known {
+                lints.push(SchemaLint {
+                    graph,
+                    subject,
+                    severity: Severity::Error,
+                    kind: LintKind::UnknownPredicate(predicate.to_string()),
+                });
+            }
+            continue;
+        }
+
+        let mapping = match Mapping::try_from(p) {
+            Ok(mapping) => mapping,
+            Err(_) => {
+                let known = Source::try_from(p).is_ok()
+                    || MappingCondition::try_from(p).is_ok()
+                    || FromCondition::try_from(p).is_ok();
+
+                if !known {
+                    lints.push(SchemaLint {
+                        graph,
+                        subject,
+                        severity: Severity::Error,
+                        kind: LintKind::UnknownPredicate(predicate.to_string()),
+                    });
+                }
+                else if let (Ok(Source::TransformsInto), SimpleTerm::Iri(_)) = (Source::try_from(p), s) {
+                    sources.insert(subject);
+                }
+                continue;
+            }
+        };
+
+        if let Some(graph) = &graph {
+            mapping_graphs.insert(graph.clone());
+        }
+
+        let expected = match mapping {
+            Mapping::Same | Mapping::Hash | Mapping::Uppercase | Mapping::Lowercase | Mapping::Trim => {
+                ("an IRI", matches!(o, SimpleTerm::Iri(_)))
+            }
+            Mapping::HashFirst
+            | Mapping::Coalesce
+            | Mapping::Combines
+            | Mapping::Convert
+            | Mapping::Scale
+            | Mapping::Replace
+            | Mapping::PadLeft
+            | Mapping::PadRight
+            | Mapping::TrimChars => ("a blank node", matches!(o, SimpleTerm::BlankNode(_))),
+            Mapping::When | Mapping::From => {
+                ("a quoted triple or a blank node", matches!(o, SimpleTerm::Triple(_) | SimpleTerm::BlankNode(_)))
+            }
+        };
+
+        if !expected.1 {
+            lints.push(SchemaLint {
+                graph,
+                subject,
+                severity: Severity::Error,
+                kind: LintKind::InvalidObject {
+                    mapping: predicate.to_string(),
+                    expected: expected.0,
+                },
+            });
+            continue;
+        }
+
+        match mapping {
+            Mapping::Same => {
+                declared.insert(subject.clone());
+            }
+            Mapping::HashFirst | Mapping::Coalesce | Mapping::Combines => {
+                if let (SimpleTerm::BlankNode(node), Some(SimpleTerm::Iri(graph_iri))) = (o, g) {
+                    let mut iris = Vec::new();
+                    resolver.collect_iris(&mut iris, node, graph_iri.to_iri()?)?;
+                    for iri in iris {
+                        referenced.push((graph.clone(), subject.clone(), iri.to_string()));
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        // conditions filter values while replacements and case changes compose
+        // rather than produce new values so they can't conflict
+        if !matches!(mapping, Mapping::When | Mapping::Replace | Mapping::Uppercase | Mapping::Lowercase) {
+            producers.entry((graph, subject)).or_default().push(format!(
+                "{} {}",
+                predicate.as_str(),
+                stringify_term(o)
+            ));
+        }
+    }
+
+    for (graph, subject, iri) in referenced {
+        if !declared.contains(&iri) {
+            lints.push(SchemaLint {
+                graph,
+                subject,
+                severity: Severity::Error,
+                kind: LintKind::UndeclaredSourceField(iri),
+            });
+        }
+    }
+
+    let mut conflicts: Vec<_> = producers.into_iter().filter(|(_key, maps)| maps.len() > 1).collect();
+    conflicts.sort_by(|a, b| a.0.cmp(&b.0));
+    for ((graph, subject), maps) in conflicts {
+        lints.push(SchemaLint {
+            graph,
+            subject,
+            severity: Severity::Warning,
+            kind: LintKind::ConflictingMappings(maps),
+        });
+    }
+
+    let mut unused: Vec<String> = mapping_graphs
+        .into_iter()
+        .filter(|graph| !sources.contains(graph))
+        .collect();
+    unused.sort();
+    for graph in unused {
+        lints.push(SchemaLint {
+            subject: graph.clone(),
+            graph: Some(graph),
+            severity: Severity::Warning,
+            kind: LintKind::UnusedMappingGraph,
+        });
+    }
+
+    for model in Model::ALL {
+        let model_iri = dataset.model_schema(model);
+        if dataset.get_source_from_model(&model_iri)?.is_empty() {
+            lints.push(SchemaLint {
+                graph: None,
+                subject: model_iri.to_string(),
+                severity: Severity::Info,
+                kind: LintKind::UnmappedModel,
+            });
+        }
+    }
+
+    Ok(lints)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use super::*;
+    use crate::testing::{SOURCE_PREFIXES, SYNTHETIC_SCHEMA, load_source};
+
+    const BROKEN: &str = r#"
+        fields:entity_id mapping:sam col:id .
+        fields:scientific_name mapping:same "name" .
+        fields:canonical_name mapping:combines ( fields:genus fields:specific_epithet ) .
+        fields:scientific_name_authorship mapping:same col:authorship .
+        fields:scientific_name_authorship mapping:same col:author .
+        fields:size mapping:convert [ mapping:source col:size ; mapping:form_unit "kb" ] .
+    "#;
+
+    fn field(name: &str) -> String {
+        format!("http://arga.org.au/schemas/fields/{name}")
+    }
+
+    fn mapping(name: &str) -> String {
+        format!("{MAPPING_NAMESPACE}{name}")
+    }
+
+    fn broken_dataset() -> Dataset {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        load_source(&mut dataset, &Model::Name, "names", BROKEN, &[]).unwrap();
+
+        // mappings in a graph that isn't registered as a source
+        let orphan = dataset.source_iri("orphan").unwrap();
+        let trig = format!("{SOURCE_PREFIXES}<{orphan}> {{ fields:entity_id mapping:same col:id . }}");
+        dataset.load_trig(BufReader::new(trig.as_bytes())).unwrap();
+        dataset
+    }
+
+    fn kinds_of(lints: &[SchemaLint], subject: &str) -> Vec<(Severity, LintKind)> {
+        lints
+            .iter()
+            .filter(|lint| lint.subject == subject)
+            .map(|lint| (lint.severity, lint.kind.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn reports_unknown_predicates() {
+        let lints = lint(&broken_dataset()).unwrap();
+
+        let kinds = kinds_of(&lints, &field("entity_id"));
+        assert!(kinds.contains(&(Severity::Error, LintKind::UnknownPredicate(mapping("sam")))));
+
+        // qualifiers in a blank node are checked as well
+        let unknown_qualifier = LintKind::UnknownPredicate(mapping("form_unit"));
+        assert!(
+            lints
+                .iter()
+                .any(|lint| lint.kind == unknown_qualifier && lint.severity == Severity::Error)
+        );
+        assert!(
+            !lints
+                .iter()
+                .any(|lint| lint.kind == LintKind::UnknownPredicate(mapping("source")))
+        );
+    }
+
+    #[test]
+    fn reports_objects_of_the_wrong_kind() {
+        let lints = lint(&broken_dataset()).unwrap();
+        let invalid = LintKind::InvalidObject {
+            mapping: mapping("same"),
+            expected: "an IRI",
+        };
+        assert_eq!(kinds_of(&lints, &field("scientific_name")), vec![(Severity::Error, invalid)]);
+    }
+
+    #[test]
+    fn reports_undeclared_list_fields() {
+        let lints = lint(&broken_dataset()).unwrap();
+        assert_eq!(
+            kinds_of(&lints, &field("canonical_name")),
+            vec![
+                (Severity::Error, LintKind::UndeclaredSourceField(field("genus"))),
+                (Severity::Error, LintKind::UndeclaredSourceField(field("specific_epithet"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_conflicting_mappings() {
+        let lints = lint(&broken_dataset()).unwrap();
+        let kinds = kinds_of(&lints, &field("scientific_name_authorship"));
+
+        assert_eq!(kinds.len(), 1);
+        let (severity, LintKind::ConflictingMappings(maps)) = &kinds[0]
+        else {
+            panic!("expected conflicting mappings but found {kinds:?}");
+        };
+        assert_eq!(*severity, Severity::Warning);
+        assert_eq!(maps.len(), 2);
+    }
+
+    #[test]
+    fn reports_unused_graphs_and_unmapped_models() {
+        let dataset = broken_dataset();
+        let lints = lint(&dataset).unwrap();
+
+        let orphan = dataset.source_iri("orphan").unwrap().to_string();
+        let names = dataset.source_iri("names").unwrap().to_string();
+        assert_eq!(kinds_of(&lints, &orphan), vec![(Severity::Warning, LintKind::UnusedMappingGraph)]);
+        assert!(kinds_of(&lints, &names).is_empty());
+
+        let assembly = dataset.model_schema(&Model::Assembly).to_string();
+        let name = dataset.model_schema(&Model::Name).to_string();
+        assert_eq!(kinds_of(&lints, &assembly), vec![(Severity::Info, LintKind::UnmappedModel)]);
+        assert!(kinds_of(&lints, &name).is_empty());
+    }
+
+    #[test]
+    fn valid_mappings_have_no_errors() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:hash_first ( fields:canonical_name fields:scientific_name ) .
+            fields:canonical_name mapping:same col:canonical .
+            fields:scientific_name mapping:same col:name .
+        "#;
+        load_source(&mut dataset, &Model::Name, "names", mappings, &[]).unwrap();
+
+        let lints = lint(&dataset).unwrap();
+        assert!(lints.iter().all(|lint| lint.severity == Severity::Info), "{lints:?}");
+    }
+
+    #[test]
+    fn compound_conditions_have_no_findings() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:same col:id .
+            fields:quality mapping:same col:quality .
+            fields:quality mapping:when [
+                mapping:and ( << fields:quality mapping:greater_than 5 >> << fields:quality mapping:less_than 10 >> )
+            ] .
+        "#;
+        load_source(&mut dataset, &Model::Name, "names", mappings, &[]).unwrap();
+
+        let lints = lint(&dataset).unwrap();
+        assert!(lints.iter().all(|lint| lint.kind == LintKind::UnmappedModel), "{lints:?}");
+    }
+}
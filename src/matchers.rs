@@ -0,0 +1,177 @@
+use sophia::api::prelude::*;
+use sophia::api::term::matcher::GraphNameMatcher;
+use sophia::api::term::{GraphName, SimpleTerm};
+
+
+/// Match quads in any of the listed named graphs. The default graph is never matched.
+///
+/// Loaded data and the source mappings live in named graphs while declarations such as
+/// `mapping:transforms_into` live in the default graph, so this is the matcher to use
+/// when resolving data to avoid schema triples leaking into records.
+#[derive(Clone, Copy)]
+pub struct NamedGraphs<'a>(pub &'a [&'a str]);
+
+impl<'a> GraphNameMatcher for NamedGraphs<'a> {
+    type Term = SimpleTerm<'static>;
+
+    fn matches<T2: Term + ?Sized>(&self, graph_name: GraphName<&T2>) -> bool {
+        match graph_name {
+            Some(t) => match t.as_simple() {
+                SimpleTerm::Iri(iri) => self.0.contains(&iri.as_str()),
+                _ => false,
+            },
+            None => false,
+        }
+    }
+}
+
+
+/// Match quads in any of the listed named graphs as well as the default graph.
+#[derive(Clone, Copy)]
+pub struct NamedGraphsOrDefault<'a>(pub &'a [&'a str]);
+
+impl<'a> GraphNameMatcher for NamedGraphsOrDefault<'a> {
+    type Term = SimpleTerm<'static>;
+
+    fn matches<T2: Term + ?Sized>(&self, graph_name: GraphName<&T2>) -> bool {
+        match graph_name {
+            Some(t) => match t.as_simple() {
+                SimpleTerm::Iri(iri) => self.0.contains(&iri.as_str()),
+                _ => false,
+            },
+            None => true,
+        }
+    }
+}
+
+
+/// Match quads in a single named graph. The default graph is never matched.
+#[derive(Clone, Copy)]
+pub struct SingleGraph<'a>(pub &'a str);
+
+impl<'a> GraphNameMatcher for SingleGraph<'a> {
+    type Term = SimpleTerm<'static>;
+
+    fn matches<T2: Term + ?Sized>(&self, graph_name: GraphName<&T2>) -> bool {
+        match graph_name {
+            Some(t) => match t.as_simple() {
+                SimpleTerm::Iri(iri) => self.0 == iri.as_str(),
+                _ => false,
+            },
+            None => false,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use sophia::api::MownStr;
+    use sophia::api::term::BnodeId;
+    use sophia::inmem::dataset::FastDataset;
+
+    use super::*;
+    use crate::rdf::IntoIriTerm;
+
+    const NAMES: &str = "http://arga.org.au/schemas/maps/synthetic/names";
+    const OTHER: &str = "http://arga.org.au/schemas/maps/synthetic/other";
+    const FIELD: &str = "http://arga.org.au/schemas/maps/synthetic/name";
+
+    fn graph(iri: &str) -> iref::IriBuf {
+        iref::IriBuf::new(iri.to_string()).unwrap()
+    }
+
+    fn blank() -> SimpleTerm<'static> {
+        SimpleTerm::BlankNode(BnodeId::new_unchecked(MownStr::from("graph")))
+    }
+
+    #[test]
+    fn named_graphs_only_match_the_listed_graphs() {
+        let (names, other) = (graph(NAMES), graph(OTHER));
+        let matcher = NamedGraphs(&[NAMES]);
+
+        assert!(matcher.matches(Some(&names.into_iri_term().unwrap())));
+        assert!(!matcher.matches(Some(&other.into_iri_term().unwrap())));
+        assert!(!matcher.matches(Some(&blank())));
+        assert!(!matcher.matches(None::<&SimpleTerm>));
+        assert!(!NamedGraphs(&[]).matches(Some(&names.into_iri_term().unwrap())));
+    }
+
+    #[test]
+    fn named_graphs_or_default_also_match_the_default_graph() {
+        let (names, other) = (graph(NAMES), graph(OTHER));
+        let matcher = NamedGraphsOrDefault(&[NAMES]);
+
+        assert!(matcher.matches(Some(&names.into_iri_term().unwrap())));
+        assert!(!matcher.matches(Some(&other.into_iri_term().unwrap())));
+        assert!(!matcher.matches(Some(&blank())));
+        assert!(matcher.matches(None::<&SimpleTerm>));
+        assert!(NamedGraphsOrDefault(&[]).matches(None::<&SimpleTerm>));
+    }
+
+    #[test]
+    fn single_graph_only_matches_the_graph() {
+        let (names, other) = (graph(NAMES), graph(OTHER));
+        let matcher = SingleGraph(NAMES);
+
+        assert!(matcher.matches(Some(&names.into_iri_term().unwrap())));
+        assert!(!matcher.matches(Some(&other.into_iri_term().unwrap())));
+        assert!(!matcher.matches(Some(&blank())));
+        assert!(!matcher.matches(None::<&SimpleTerm>));
+    }
+
+    /// A dataset with a quad in each named graph and one in the default graph where the
+    /// subject of the quad is the name of the graph it is in.
+    fn dataset() -> FastDataset {
+        let (names, other, field) = (graph(NAMES), graph(OTHER), graph(FIELD));
+        let predicate = field.into_iri_term().unwrap();
+
+        let mut dataset = FastDataset::new();
+        dataset
+            .insert("names", &predicate, "value", Some(&names.into_iri_term().unwrap()))
+            .unwrap();
+        dataset
+            .insert("other", &predicate, "value", Some(&other.into_iri_term().unwrap()))
+            .unwrap();
+        dataset
+            .insert("default", &predicate, "value", None::<&SimpleTerm>)
+            .unwrap();
+        dataset
+    }
+
+    fn subjects<G: GraphNameMatcher>(dataset: &FastDataset, graphs: G) -> Vec<String> {
+        let mut subjects: Vec<String> = dataset
+            .quads_matching(Any, Any, Any, graphs)
+            .map(|quad| {
+                let (_g, [s, _p, _o]) = quad.unwrap();
+                match s {
+                    SimpleTerm::LiteralDatatype(value, _) => value.to_string(),
+                    _ => panic!("subject is not a literal"),
+                }
+            })
+            .collect();
+        subjects.sort();
+        subjects
+    }
+
+    #[test]
+    fn named_graphs_match_quads_in_the_listed_graphs() {
+        let dataset = dataset();
+        assert_eq!(subjects(&dataset, NamedGraphs(&[NAMES])), vec!["names"]);
+        assert_eq!(subjects(&dataset, NamedGraphs(&[NAMES, OTHER])), vec!["names", "other"]);
+        assert!(subjects(&dataset, NamedGraphs(&[])).is_empty());
+    }
+
+    #[test]
+    fn named_graphs_or_default_match_quads_in_the_default_graph() {
+        let dataset = dataset();
+        assert_eq!(subjects(&dataset, NamedGraphsOrDefault(&[NAMES])), vec!["default", "names"]);
+        assert_eq!(subjects(&dataset, NamedGraphsOrDefault(&[])), vec!["default"]);
+    }
+
+    #[test]
+    fn single_graph_matches_quads_in_the_graph() {
+        let dataset = dataset();
+        assert_eq!(subjects(&dataset, SingleGraph(OTHER)), vec!["other"]);
+    }
+}
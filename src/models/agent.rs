@@ -1,26 +1,251 @@
-use tracing::{info, instrument};
+use std::collections::{BTreeMap, BTreeSet};
+
+use tracing::{info, instrument, warn};
+use twox_hash::XxHash3_64;
 
 use crate::dataset::{Dataset, Model};
 use crate::errors::TransformError;
-use crate::rdf::{self, AnnotationField};
+use crate::rdf::{self, CollectingField, DataProductField, ExtractionField, LibraryField};
 use crate::resolver::{ResolvedRecords, Resolver};
+use crate::validation::orcid;
+
+
+/// The function an agent performed in the record they were derived from.
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentRole {
+    Custodian,
+    Extractor,
+    MaterialExtractor,
+    LibraryPreparer,
+    Collector,
+}
+
+impl AgentRole {
+    pub const ALL: &[AgentRole] = {
+        use AgentRole::*;
+        &[Custodian, Extractor, MaterialExtractor, LibraryPreparer, Collector]
+    };
+}
 
 
-#[derive(Debug, Default, serde::Serialize, Hash, Eq, PartialEq)]
-pub struct Agent {
+model_schema! {
+    #[derive(Debug, serde::Serialize, serde::Deserialize, Hash, Eq, PartialEq)]
+    pub struct Agent {
+        pub entity_id: String,
+        pub full_name: String,
+        pub orcid: Option<String>,
+        pub role: AgentRole,
+    }
+}
+
+impl Agent {
+    pub fn new(role: AgentRole) -> Agent {
+        Agent {
+            entity_id: String::new(),
+            full_name: String::new(),
+            orcid: None,
+            role,
+        }
+    }
+}
+
+
+/// An agent that more than one source entity id or name variant was merged into.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct AgentMerge {
     pub entity_id: String,
-    pub full_name: String,
-    pub orcid: Option<String>,
+    /// The entity ids from the source data that now refer to the agent
+    pub source_ids: Vec<String>,
+    /// The distinct full names the agent was recorded with
+    pub name_variants: Vec<String>,
 }
 
 
+/// The merges performed when deriving agent entity ids.
+///
+/// Other models reference agents by the entity id from the source data, so the remap
+/// table is used to point those references at the derived entity id instead.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct AgentMergeReport {
+    pub merges: Vec<AgentMerge>,
+    /// The derived entity id of every source entity id
+    pub remap: BTreeMap<String, String>,
+    /// Source entity ids that were used by more than one derived agent. These are left as
+    /// they are since there is no way to tell which agent a reference meant.
+    pub ambiguous: BTreeMap<String, Vec<String>>,
+    /// ORCIDs that failed validation and were dropped from the agent
+    pub invalid_orcids: BTreeSet<String>,
+}
+
+impl AgentMergeReport {
+    /// The derived entity id for a reference to an agent by its source entity id.
+    pub fn remap(&self, entity_id: String) -> String {
+        match self.remap.get(&entity_id) {
+            Some(derived) => derived.clone(),
+            None => entity_id,
+        }
+    }
+}
+
+
+/// Get all agents in every role.
 pub fn get_all(dataset: &Dataset) -> Result<Vec<Agent>, TransformError> {
-    let mut agents = get_custodian_agents(dataset)?;
-    agents.extend(get_extraction_agents(dataset)?);
-    agents.extend(get_material_extraction_agents(dataset)?);
-    agents.extend(get_prepared_agents(dataset)?);
-    agents.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
-    agents.dedup();
+    get_all_with_roles(dataset, AgentRole::ALL)
+}
+
+
+/// Get all agents in every role along with the merges performed to derive their entity ids.
+pub fn get_all_with_report(dataset: &Dataset) -> Result<(Vec<Agent>, AgentMergeReport), TransformError> {
+    let agents = get_source_agents(dataset, AgentRole::ALL)?;
+    Ok(merge_agents(agents))
+}
+
+
+/// Get all agents that performed one of the specified roles.
+///
+/// The same agent can appear more than once if they have more than one role, only
+/// agents with the same entity id and role are merged. See [`derive_entity_id`] for
+/// how the entity id is determined.
+#[instrument(skip_all)]
+pub fn get_all_with_roles(dataset: &Dataset, roles: &[AgentRole]) -> Result<Vec<Agent>, TransformError> {
+    let agents = get_source_agents(dataset, roles)?;
+    let (agents, _report) = merge_agents(agents);
+    Ok(agents)
+}
+
+
+/// Get the table to remap references to agents in the specified roles.
+///
+/// Derived entity ids only depend on the agent's own name and ORCID, so a model only
+/// needs to resolve the roles it references to get the same ids as the agent model.
+#[instrument(skip_all)]
+pub fn remap_table(dataset: &Dataset, roles: &[AgentRole]) -> Result<AgentMergeReport, TransformError> {
+    let agents = get_source_agents(dataset, roles)?;
+    let (_agents, report) = merge_agents(agents);
+    Ok(report)
+}
+
+
+/// Derive the entity id of an agent from their ORCID or otherwise their name.
+///
+/// An ORCID identifies a person regardless of how their name was written so it is
+/// preferred when it is valid. Without one the name is normalised to ignore differences
+/// in case and whitespace and then hashed with xxh3, so that personal names don't end up
+/// in the ids referenced by other models. The ids are prefixed by what they were derived
+/// from so an ORCID and a name can never collide. Returns `None` when there is nothing to
+/// derive from.
+pub fn derive_entity_id(full_name: &str, orcid: Option<&str>) -> Option<String> {
+    if let Some(Ok(orcid)) = orcid.map(orcid::normalise) {
+        return Some(format!("orcid:{orcid}"));
+    }
+
+    let name = normalise_name(full_name);
+    match name.is_empty() {
+        true => None,
+        false => Some(format!("name:{:016x}", XxHash3_64::oneshot(name.as_bytes()))),
+    }
+}
+
+
+/// Trim, collapse whitespace, and case fold a name.
+fn normalise_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<&str>>().join(" ").to_lowercase()
+}
+
+
+/// Replace the entity ids of the agents with the derived ones and merge the duplicates.
+fn merge_agents(mut agents: Vec<Agent>) -> (Vec<Agent>, AgentMergeReport) {
+    let mut derived_from: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut names: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+    let mut report = AgentMergeReport::default();
+
+    for agent in agents.iter_mut() {
+        agent.orcid = match agent.orcid.take() {
+            Some(value) => match orcid::normalise(&value) {
+                Ok(orcid) => Some(orcid),
+                Err(err) => {
+                    warn!(%err, "Dropping invalid agent ORCID");
+                    report.invalid_orcids.insert(value);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let Some(entity_id) = derive_entity_id(&agent.full_name, agent.orcid.as_deref())
+        else {
+            continue;
+        };
+
+        if !agent.entity_id.is_empty() {
+            derived_from
+                .entry(agent.entity_id.clone())
+                .or_default()
+                .insert(entity_id.clone());
+        }
+        let variants = names.entry(entity_id.clone()).or_default();
+        if !agent.full_name.trim().is_empty() {
+            variants.insert(agent.full_name.trim().to_string());
+        }
+        agent.entity_id = entity_id;
+    }
+
+    let mut source_ids: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (source_id, derived) in derived_from {
+        let derived: Vec<String> = derived.into_iter().collect();
+        match derived.as_slice() {
+            [entity_id] => {
+                source_ids.entry(entity_id.clone()).or_default().push(source_id.clone());
+                report.remap.insert(source_id, entity_id.clone());
+            }
+            _ => {
+                warn!(%source_id, ?derived, "Agent entity id is used by more than one agent");
+                report.ambiguous.insert(source_id, derived);
+            }
+        }
+    }
+
+    for (entity_id, variants) in names {
+        let ids = source_ids.remove(&entity_id).unwrap_or_default();
+        if ids.len() > 1 || variants.len() > 1 {
+            report.merges.push(AgentMerge {
+                entity_id,
+                source_ids: ids,
+                name_variants: variants.into_iter().collect(),
+            });
+        }
+    }
+
+    agents.sort_by(|a, b| (&a.entity_id, a.role).cmp(&(&b.entity_id, b.role)));
+    agents.dedup_by(|duplicate, agent| {
+        let same = duplicate.entity_id == agent.entity_id && duplicate.role == agent.role;
+        // keep the orcid if only the duplicate has it
+        if same && agent.orcid.is_none() {
+            agent.orcid = duplicate.orcid.take();
+        }
+        same
+    });
+
+    (agents, report)
+}
+
+
+/// Get the agents of the roles with the entity id from the source data.
+fn get_source_agents(dataset: &Dataset, roles: &[AgentRole]) -> Result<Vec<Agent>, TransformError> {
+    let mut agents = Vec::new();
+
+    for role in roles {
+        match role {
+            AgentRole::Custodian => agents.extend(get_custodian_agents(dataset)?),
+            AgentRole::Extractor => agents.extend(get_extraction_agents(dataset)?),
+            AgentRole::MaterialExtractor => agents.extend(get_material_extraction_agents(dataset)?),
+            AgentRole::LibraryPreparer => agents.extend(get_prepared_agents(dataset)?),
+            AgentRole::Collector => agents.extend(get_collector_agents(dataset)?),
+        }
+    }
+
     Ok(agents)
 }
 
@@ -28,16 +253,22 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<Agent>, TransformError> {
 pub fn get_custodian_agents(dataset: &Dataset) -> Result<Vec<Agent>, TransformError> {
     let resolver = Resolver::new(dataset);
 
-    let schemas = dataset.scope(&[Model::Agent]);
-    let schemas: Vec<&iref::Iri> = schemas.iter().map(|s| s.as_iri()).collect();
+    let schemas = dataset.scope_iris(&[Model::DataProduct])?;
 
-    info!("Resolving data");
-    let data: ResolvedRecords<AgentField> = resolver.resolve(rdf::Agent::ALL, &schemas)?;
+    info!("Resolving custodians");
+    let data: ResolvedRecords<DataProductField> = resolver.resolve(
+        &[
+            rdf::DataProduct::Custodian,
+            rdf::DataProduct::CustodianOrcid,
+            rdf::DataProduct::CustodianEntityId,
+        ],
+        &schemas,
+    )?;
 
 
     let mut agents = Vec::new();
     for (_idx, fields) in data {
-        let mut agent = Agent::default();
+        let mut agent = Agent::new(AgentRole::Custodian);
 
         for field in fields {
             match field {
@@ -56,26 +287,23 @@ pub fn get_custodian_agents(dataset: &Dataset) -> Result<Vec<Agent>, TransformEr
 
 
 pub fn get_extraction_agents(dataset: &Dataset) -> Result<Vec<Agent>, TransformError> {
-    let models = dataset.scope(&["extractions"]);
-    let mut scope = Vec::new();
-    for model in models.iter() {
-        scope.push(iref::Iri::new(model).unwrap());
-    }
-
     let resolver = Resolver::new(dataset);
 
-    let data: HashMap<Literal, Vec<ExtractionField>> = resolver.resolve(
+    let schemas = dataset.scope_iris(&[Model::Extraction])?;
+
+    info!("Resolving extractors");
+    let data: ResolvedRecords<ExtractionField> = resolver.resolve(
         &[
-            Extraction::ExtractedBy,
-            Extraction::ExtractedByOrcid,
-            Extraction::ExtractedByEntityId,
+            rdf::Extraction::ExtractedBy,
+            rdf::Extraction::ExtractedByOrcid,
+            rdf::Extraction::ExtractedByEntityId,
         ],
-        &scope,
+        &schemas,
     )?;
 
     let mut agents = Vec::new();
     for (_idx, fields) in data {
-        let mut agent = Agent::default();
+        let mut agent = Agent::new(AgentRole::Extractor);
 
         for field in fields {
             match field {
@@ -94,26 +322,23 @@ pub fn get_extraction_agents(dataset: &Dataset) -> Result<Vec<Agent>, TransformE
 
 
 pub fn get_material_extraction_agents(dataset: &Dataset) -> Result<Vec<Agent>, TransformError> {
-    let models = dataset.scope(&["extractions"]);
-    let mut scope = Vec::new();
-    for model in models.iter() {
-        scope.push(iref::Iri::new(model).unwrap());
-    }
-
     let resolver = Resolver::new(dataset);
 
-    let data: HashMap<Literal, Vec<ExtractionField>> = resolver.resolve(
+    let schemas = dataset.scope_iris(&[Model::Extraction])?;
+
+    info!("Resolving material extractors");
+    let data: ResolvedRecords<ExtractionField> = resolver.resolve(
         &[
-            Extraction::MaterialExtractedBy,
-            Extraction::MaterialExtractedByOrcid,
-            Extraction::MaterialExtractedByEntityId,
+            rdf::Extraction::MaterialExtractedBy,
+            rdf::Extraction::MaterialExtractedByOrcid,
+            rdf::Extraction::MaterialExtractedByEntityId,
         ],
-        &scope,
+        &schemas,
     )?;
 
     let mut agents = Vec::new();
     for (_idx, fields) in data {
-        let mut agent = Agent::default();
+        let mut agent = Agent::new(AgentRole::MaterialExtractor);
 
         for field in fields {
             match field {
@@ -132,20 +357,17 @@ pub fn get_material_extraction_agents(dataset: &Dataset) -> Result<Vec<Agent>, T
 
 
 pub fn get_prepared_agents(dataset: &Dataset) -> Result<Vec<Agent>, TransformError> {
-    let models = dataset.scope(&["library"]);
-    let mut scope = Vec::new();
-    for model in models.iter() {
-        scope.push(iref::Iri::new(model).unwrap());
-    }
-
     let resolver = Resolver::new(dataset);
 
-    let data: HashMap<Literal, Vec<LibraryField>> =
-        resolver.resolve(&[Library::PreparedBy, Library::PreparedByEntityId], &scope)?;
+    let schemas = dataset.scope_iris(&[Model::Library])?;
+
+    info!("Resolving library preparers");
+    let data: ResolvedRecords<LibraryField> =
+        resolver.resolve(&[rdf::Library::PreparedBy, rdf::Library::PreparedByEntityId], &schemas)?;
 
     let mut agents = Vec::new();
     for (_idx, fields) in data {
-        let mut agent = Agent::default();
+        let mut agent = Agent::new(AgentRole::LibraryPreparer);
 
         for field in fields {
             match field {
@@ -160,3 +382,144 @@ pub fn get_prepared_agents(dataset: &Dataset) -> Result<Vec<Agent>, TransformErr
 
     Ok(agents)
 }
+
+
+pub fn get_collector_agents(dataset: &Dataset) -> Result<Vec<Agent>, TransformError> {
+    let resolver = Resolver::new(dataset);
+
+    let schemas = dataset.scope_iris(&[Model::Collecting])?;
+
+    info!("Resolving collectors");
+    let data: ResolvedRecords<CollectingField> = resolver.resolve(
+        &[
+            rdf::Collecting::CollectedBy,
+            rdf::Collecting::CollectedByOrcid,
+            rdf::Collecting::CollectedByEntityId,
+        ],
+        &schemas,
+    )?;
+
+    let mut agents = Vec::new();
+    for (_idx, fields) in data {
+        let mut agent = Agent::new(AgentRole::Collector);
+
+        for field in fields {
+            match field {
+                CollectingField::CollectedBy(val) => agent.full_name = val,
+                CollectingField::CollectedByOrcid(val) => agent.orcid = Some(val),
+                CollectingField::CollectedByEntityId(val) => agent.entity_id = val,
+                _ => {}
+            }
+        }
+
+        agents.push(agent);
+    }
+
+    Ok(agents)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{SYNTHETIC_SCHEMA, load_source};
+
+    fn agent(entity_id: &str, full_name: &str, orcid: Option<&str>) -> Agent {
+        Agent {
+            entity_id: entity_id.to_string(),
+            full_name: full_name.to_string(),
+            orcid: orcid.map(String::from),
+            role: AgentRole::Collector,
+        }
+    }
+
+    fn derived(full_name: &str) -> String {
+        derive_entity_id(full_name, None).unwrap()
+    }
+
+    #[test]
+    fn derives_a_hash_of_the_normalised_name() {
+        assert_eq!(derived("Jane Smith"), "name:f3790e9861a71ae6");
+        assert_eq!(derived("  JANE   smith "), derived("Jane Smith"));
+        assert_ne!(derived("Jane Smith"), derived("John Citizen"));
+        assert_eq!(derive_entity_id("  ", None), None);
+    }
+
+    #[test]
+    fn derives_from_a_valid_orcid() {
+        let orcid = "https://orcid.org/0000-0002-1825-0097";
+        assert_eq!(derive_entity_id("Jane Smith", Some(orcid)), Some("orcid:0000-0002-1825-0097".to_string()));
+        assert_eq!(derive_entity_id("Jane Smith", Some("0000-0002-1825-0098")), Some(derived("Jane Smith")));
+    }
+
+    #[test]
+    fn merge_report_lists_merged_agents() {
+        let agents = vec![
+            agent("c1", "Jane Smith", None),
+            agent("c2", " jane  SMITH", None),
+            agent("c3", "John Citizen", None),
+            agent("c4", "Alice Jones", None),
+            agent("c4", "Bob Jones", None),
+            agent("c5", "Carol King", Some("not an orcid")),
+        ];
+
+        let (agents, report) = merge_agents(agents);
+        let jane = derived("Jane Smith");
+
+        assert_eq!(agents.len(), 5);
+        assert_eq!(
+            report.merges,
+            vec![AgentMerge {
+                entity_id: jane.clone(),
+                source_ids: vec!["c1".to_string(), "c2".to_string()],
+                name_variants: vec!["Jane Smith".to_string(), "jane  SMITH".to_string()],
+            }]
+        );
+
+        let mut ambiguous = vec![derived("Alice Jones"), derived("Bob Jones")];
+        ambiguous.sort();
+        assert_eq!(report.ambiguous.get("c4"), Some(&ambiguous));
+        assert_eq!(report.invalid_orcids, BTreeSet::from(["not an orcid".to_string()]));
+    }
+
+    #[test]
+    fn remap_table_points_references_at_the_derived_ids() {
+        let agents = vec![
+            agent("c1", "Jane Smith", None),
+            agent("c2", "jane smith", None),
+            agent("c3", "John Citizen", Some("0000-0002-1825-0097")),
+            agent("c4", "Alice Jones", None),
+            agent("c4", "Bob Jones", None),
+        ];
+
+        let (_agents, report) = merge_agents(agents);
+
+        assert_eq!(report.remap("c1".to_string()), derived("Jane Smith"));
+        assert_eq!(report.remap("c2".to_string()), derived("Jane Smith"));
+        assert_eq!(report.remap("c3".to_string()), "orcid:0000-0002-1825-0097");
+        // ambiguous and unknown references are left as they are
+        assert_eq!(report.remap("c4".to_string()), "c4");
+        assert_eq!(report.remap("unknown".to_string()), "unknown");
+    }
+
+    #[test]
+    fn remap_table_from_a_dataset() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:same col:id .
+            fields:extracted_by mapping:same col:extractor .
+            fields:extracted_by_entity_id mapping:same col:extractor_id .
+        "#;
+        let rows: &[&[(&str, &str)]] = &[
+            &[("id", "EX1"), ("extractor", "Jane Smith"), ("extractor_id", "agent-1")],
+            &[("id", "EX2"), ("extractor", "JANE SMITH"), ("extractor_id", "agent-2")],
+        ];
+        load_source(&mut dataset, &Model::Extraction, "extractions", mappings, rows).unwrap();
+
+        let report = remap_table(&dataset, &[AgentRole::Extractor]).unwrap();
+        assert_eq!(report.remap.len(), 2);
+        assert_eq!(report.remap("agent-1".to_string()), derived("Jane Smith"));
+        assert_eq!(report.remap("agent-2".to_string()), derived("Jane Smith"));
+        assert_eq!(report.merges.len(), 1);
+    }
+}
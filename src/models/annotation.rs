@@ -6,25 +6,27 @@ use crate::rdf::{self, AnnotationField};
 use crate::resolver::{ResolvedRecords, Resolver};
 
 
-#[derive(Debug, Default, serde::Serialize)]
-pub struct Annotation {
-    pub entity_id: String,
-    pub assembly_id: Option<String>,
+model_schema! {
+    #[derive(Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct Annotation {
+        pub entity_id: String,
+        pub assembly_id: Option<String>,
 
-    pub name: Option<String>,
-    pub provider: Option<String>,
-    pub method: Option<String>,
-    pub r#type: Option<String>,
-    pub version: Option<String>,
-    pub software: Option<String>,
-    pub software_version: Option<String>,
-    pub event_date: Option<String>,
+        pub name: Option<String>,
+        pub provider: Option<String>,
+        pub method: Option<String>,
+        pub r#type: Option<String>,
+        pub version: Option<String>,
+        pub software: Option<String>,
+        pub software_version: Option<String>,
+        pub event_date: Option<String>,
 
-    pub number_of_genes: Option<u64>,
-    pub number_of_coding_proteins: Option<u64>,
-    pub number_of_non_coding_proteins: Option<u64>,
-    pub number_of_pseudogenes: Option<u64>,
-    pub number_of_other_genes: Option<u64>,
+        pub number_of_genes: Option<u64>,
+        pub number_of_coding_proteins: Option<u64>,
+        pub number_of_non_coding_proteins: Option<u64>,
+        pub number_of_pseudogenes: Option<u64>,
+        pub number_of_other_genes: Option<u64>,
+    }
 }
 
 
@@ -32,8 +34,7 @@ pub struct Annotation {
 pub fn get_all(dataset: &Dataset) -> Result<Vec<Annotation>, TransformError> {
     let resolver = Resolver::new(dataset);
 
-    let schemas = dataset.scope(&[Model::Annotation]);
-    let schemas: Vec<&iref::Iri> = schemas.iter().map(|s| s.as_iri()).collect();
+    let schemas = dataset.scope_iris(&[Model::Annotation])?;
 
     info!("Resolving data");
     let data: ResolvedRecords<AnnotationField> = resolver.resolve(rdf::Annotation::ALL, &schemas)?;
@@ -1,139 +1,407 @@
+use std::collections::HashMap;
+
 use tracing::{info, instrument};
 
 use crate::dataset::{Dataset, Model};
 use crate::errors::TransformError;
-use crate::rdf::{self, AssemblyField};
+use crate::models::deposition::{self, Deposition};
+use crate::models::taxon::Taxon;
+use crate::models::{FromFields, publications};
+use crate::rdf::{self, AssemblyField, Condition, Literal, TaxonField};
 use crate::resolver::{ResolvedRecords, Resolver};
 
 
-#[derive(Debug, Default, serde::Serialize)]
-pub struct Assembly {
-    pub entity_id: String,
-    pub library_id: Option<String>,
-    pub assembly_id: Option<String>,
-    pub scientific_name: Option<String>,
-    pub event_date: Option<String>,
-    pub name: Option<String>,
-    pub r#type: Option<String>,
-    pub method: Option<String>,
-    pub method_version: Option<String>,
-    pub method_link: Option<String>,
-    pub size: Option<u64>,
-    pub size_ungapped: Option<u64>,
-    pub minimum_gap_length: Option<String>,
-    pub completeness: Option<String>,
-    pub completeness_method: Option<String>,
-    pub source_molecule: Option<String>,
-    pub reference_genome_used: Option<String>,
-    pub reference_genome_link: Option<String>,
-    pub number_of_scaffolds: Option<u64>,
-    pub number_of_contigs: Option<u64>,
-    pub number_of_chromosomes: Option<u64>,
-    pub number_of_component_sequences: Option<u64>,
-    pub number_of_organelles: Option<u64>,
-    pub number_of_gaps_between_scaffolds: Option<u64>,
-    pub number_of_atgc: Option<u64>,
-    pub number_of_guanine_cytosine: Option<u64>,
-    pub guanine_cytosine_percent: Option<u64>,
-    pub genome_coverage: Option<String>,
-    pub hybrid: Option<String>,
-    pub hybrid_information: Option<String>,
-    pub polishing_or_scaffolding_method: Option<String>,
-    pub polishing_or_scaffolding_data: Option<String>,
-    pub computational_infrastructure: Option<String>,
-    pub system_used: Option<String>,
-    pub level: Option<String>,
-    pub representation: Option<String>,
-    pub assembly_n50: Option<String>,
-    pub contig_n50: Option<u64>,
-    pub contig_l50: Option<u64>,
-    pub scaffold_n50: Option<u64>,
-    pub scaffold_l50: Option<u64>,
-    pub longest_contig: Option<u64>,
-    pub longest_scaffold: Option<u64>,
-    pub total_contig_size: Option<u64>,
-    pub total_scaffold_size: Option<u64>,
+model_schema! {
+    #[derive(Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct Assembly {
+        pub entity_id: String,
+        pub library_id: Option<String>,
+        pub assembly_id: Option<String>,
+        pub scientific_name: Option<String>,
+        pub canonical_name: Option<String>,
+        pub scientific_name_authorship: Option<String>,
+        pub taxon_id: Option<String>,
+        pub event_date: Option<String>,
+        pub name: Option<String>,
+        pub r#type: Option<String>,
+        pub method: Option<String>,
+        pub method_version: Option<String>,
+        pub method_link: Option<String>,
+        pub size: Option<u64>,
+        pub size_ungapped: Option<u64>,
+        pub minimum_gap_length: Option<String>,
+        pub completeness: Option<String>,
+        pub completeness_method: Option<String>,
+        pub source_molecule: Option<String>,
+        pub reference_genome_used: Option<String>,
+        pub reference_genome_link: Option<String>,
+        pub number_of_scaffolds: Option<u64>,
+        pub number_of_contigs: Option<u64>,
+        pub number_of_chromosomes: Option<u64>,
+        pub number_of_component_sequences: Option<u64>,
+        pub number_of_organelles: Option<u64>,
+        pub number_of_gaps_between_scaffolds: Option<u64>,
+        pub number_of_atgc: Option<u64>,
+        pub number_of_guanine_cytosine: Option<u64>,
+        pub guanine_cytosine_percent: Option<u64>,
+        pub genome_coverage: Option<String>,
+        pub hybrid: Option<String>,
+        pub hybrid_information: Option<String>,
+        pub polishing_or_scaffolding_method: Option<String>,
+        pub polishing_or_scaffolding_data: Option<String>,
+        pub computational_infrastructure: Option<String>,
+        pub system_used: Option<String>,
+        pub level: Option<String>,
+        pub representation: Option<String>,
+        pub assembly_n50: Option<String>,
+        pub contig_n50: Option<u64>,
+        pub contig_l50: Option<u64>,
+        pub scaffold_n50: Option<u64>,
+        pub scaffold_l50: Option<u64>,
+        pub longest_contig: Option<u64>,
+        pub longest_scaffold: Option<u64>,
+        pub total_contig_size: Option<u64>,
+        pub total_scaffold_size: Option<u64>,
+
+        pub doi: Option<String>,
+        pub citation: Option<String>,
+        pub publication_entity_id: Option<String>,
+
+        pub deposition_url: Option<String>,
+        pub deposition_institution: Option<String>,
+        pub deposition_date: Option<String>,
+    }
+}
+
+
+impl_model! {
+    Assembly => AssemblyField {
+        EntityId => entity_id required,
+        LibraryId => library_id,
+        AssemblyId => assembly_id,
+        ScientificName => scientific_name,
+        EventDate => event_date,
+        Name => name,
+        Type => r#type,
+        Method => method,
+        MethodVersion => method_version,
+        MethodLink => method_link,
+        Size => size,
+        SizeUngapped => size_ungapped,
+        MinimumGapLength => minimum_gap_length,
+        Completeness => completeness,
+        CompletenessMethod => completeness_method,
+        SourceMolecule => source_molecule,
+        ReferenceGenomeUsed => reference_genome_used,
+        ReferenceGenomeLink => reference_genome_link,
+        NumberOfScaffolds => number_of_scaffolds,
+        NumberOfContigs => number_of_contigs,
+        NumberOfChromosomes => number_of_chromosomes,
+        NumberOfComponentSequences => number_of_component_sequences,
+        NumberOfOrganelles => number_of_organelles,
+        NumberOfGapsBetweenScaffolds => number_of_gaps_between_scaffolds,
+        NumberOfATGC => number_of_atgc,
+        NumberOfGuanineCytosine => number_of_guanine_cytosine,
+        GuanineCytosinePercent => guanine_cytosine_percent,
+        GenomeCoverage => genome_coverage,
+        Hybrid => hybrid,
+        HybridInformation => hybrid_information,
+        PolishingOrScaffoldingMethod => polishing_or_scaffolding_method,
+        PolishingOrScaffoldingData => polishing_or_scaffolding_data,
+        ComputationalInfrastructure => computational_infrastructure,
+        SystemUsed => system_used,
+        Level => level,
+        Representation => representation,
+        AssemblyN50 => assembly_n50,
+        ContigN50 => contig_n50,
+        ContigL50 => contig_l50,
+        ScaffoldN50 => scaffold_n50,
+        ScaffoldL50 => scaffold_l50,
+        LongestContig => longest_contig,
+        LongestScaffold => longest_scaffold,
+        TotalContigSize => total_contig_size,
+        TotalScaffoldSize => total_scaffold_size,
+        CanonicalName => canonical_name,
+        ScientificNameAuthorship => scientific_name_authorship,
+        TaxonId => taxon_id,
+        Doi => doi,
+        Citation => citation,
+        PublicationEntityId => publication_entity_id,
+    }
 }
 
 
+/// Get all assemblies along with where they were deposited.
+///
+/// Depositions share field names with assemblies (eg. `event_date`) so rather than
+/// mapping them into the assembly model they are resolved separately and joined
+/// on the `assembly_id`. Missing names are filled from the taxonomy with `backfill_names`.
 #[instrument(skip_all)]
 pub fn get_all(dataset: &Dataset) -> Result<Vec<Assembly>, TransformError> {
     let resolver = Resolver::new(dataset);
 
-    let schemas = dataset.scope(&[Model::Assembly]);
-    let schemas: Vec<&iref::Iri> = schemas.iter().map(|s| s.as_iri()).collect();
+    let schemas = dataset.scope_iris(&[Model::Assembly])?;
 
     info!("Resolving data");
     let data: ResolvedRecords<AssemblyField> = resolver.resolve(rdf::Assembly::ALL, &schemas)?;
+    from_resolved(dataset, data)
+}
+
 
+/// Get all assemblies with the scientific name.
+///
+/// The name must match the resolved `scientific_name` exactly.
+#[instrument(skip_all)]
+pub fn get_by_scientific_name(dataset: &Dataset, name: &str) -> Result<Vec<Assembly>, TransformError> {
+    let resolver = Resolver::new(dataset);
+
+    let schemas = dataset.scope_iris(&[Model::Assembly])?;
+    let conditions = [(rdf::Assembly::ScientificName.into(), Condition::Is(Literal::String(name.to_string())))];
+
+    info!(name, "Resolving data");
+    let data: ResolvedRecords<AssemblyField> =
+        resolver.resolve_with_conditions(rdf::Assembly::ALL, &schemas, &conditions)?;
+
+    // records without a scientific name pass the condition as there is nothing to check
+    let mut records = from_resolved(dataset, data)?;
+    records.retain(|record| record.scientific_name.as_deref() == Some(name));
+    Ok(records)
+}
+
+
+fn from_resolved(dataset: &Dataset, data: ResolvedRecords<AssemblyField>) -> Result<Vec<Assembly>, TransformError> {
+    let depositions = depositions_by_assembly(dataset)?;
+    let mut assemblies: Vec<Assembly> = data.into_values().map(|fields| build(fields, &depositions)).collect();
 
-    let mut assemblies = Vec::new();
-
-    for (_idx, fields) in data {
-        let mut assembly = Assembly::default();
-
-        for field in fields {
-            match field {
-                AssemblyField::EntityId(val) => assembly.entity_id = val,
-                AssemblyField::LibraryId(val) => assembly.library_id = Some(val),
-                AssemblyField::AssemblyId(val) => assembly.assembly_id = Some(val),
-                AssemblyField::ScientificName(val) => assembly.scientific_name = Some(val),
-                AssemblyField::EventDate(val) => assembly.event_date = Some(val),
-                AssemblyField::Name(val) => assembly.name = Some(val),
-                AssemblyField::Type(val) => assembly.r#type = Some(val),
-                AssemblyField::Method(val) => assembly.method = Some(val),
-                AssemblyField::MethodVersion(val) => assembly.method_version = Some(val),
-                AssemblyField::MethodLink(val) => assembly.method_link = Some(val),
-                AssemblyField::Size(val) => assembly.size = Some(val),
-                AssemblyField::SizeUngapped(val) => assembly.size_ungapped = Some(val),
-                AssemblyField::MinimumGapLength(val) => assembly.minimum_gap_length = Some(val),
-                AssemblyField::Completeness(val) => assembly.completeness = Some(val),
-                AssemblyField::CompletenessMethod(val) => assembly.completeness_method = Some(val),
-                AssemblyField::SourceMolecule(val) => assembly.source_molecule = Some(val),
-                AssemblyField::ReferenceGenomeUsed(val) => assembly.reference_genome_used = Some(val),
-                AssemblyField::ReferenceGenomeLink(val) => assembly.reference_genome_link = Some(val),
-                AssemblyField::NumberOfScaffolds(val) => assembly.number_of_scaffolds = Some(val),
-                AssemblyField::NumberOfContigs(val) => assembly.number_of_contigs = Some(val),
-                AssemblyField::NumberOfChromosomes(val) => assembly.number_of_chromosomes = Some(val),
-                AssemblyField::NumberOfComponentSequences(val) => assembly.number_of_component_sequences = Some(val),
-                AssemblyField::NumberOfOrganelles(val) => assembly.number_of_organelles = Some(val),
-                AssemblyField::NumberOfGapsBetweenScaffolds(val) => {
-                    assembly.number_of_gaps_between_scaffolds = Some(val)
-                }
-                AssemblyField::NumberOfATGC(val) => assembly.number_of_atgc = Some(val),
-                AssemblyField::NumberOfGuanineCytosine(val) => assembly.number_of_guanine_cytosine = Some(val),
-                AssemblyField::GuanineCytosinePercent(val) => assembly.guanine_cytosine_percent = Some(val),
-                AssemblyField::GenomeCoverage(val) => assembly.genome_coverage = Some(val),
-                AssemblyField::Hybrid(val) => assembly.hybrid = Some(val),
-                AssemblyField::HybridInformation(val) => assembly.hybrid_information = Some(val),
-                AssemblyField::PolishingOrScaffoldingMethod(val) => {
-                    assembly.polishing_or_scaffolding_method = Some(val)
-                }
-                AssemblyField::PolishingOrScaffoldingData(val) => assembly.polishing_or_scaffolding_data = Some(val),
-                AssemblyField::ComputationalInfrastructure(val) => assembly.computational_infrastructure = Some(val),
-                AssemblyField::SystemUsed(val) => assembly.system_used = Some(val),
-                AssemblyField::Level(val) => assembly.level = Some(val),
-                AssemblyField::Representation(val) => assembly.representation = Some(val),
-
-                AssemblyField::AssemblyN50(val) => assembly.assembly_n50 = Some(val),
-                AssemblyField::ContigN50(val) => assembly.contig_n50 = Some(val),
-                AssemblyField::ContigL50(val) => assembly.contig_l50 = Some(val),
-                AssemblyField::ScaffoldN50(val) => assembly.scaffold_n50 = Some(val),
-                AssemblyField::ScaffoldL50(val) => assembly.scaffold_l50 = Some(val),
-
-                AssemblyField::LongestContig(val) => assembly.longest_contig = Some(val),
-                AssemblyField::LongestScaffold(val) => assembly.longest_scaffold = Some(val),
-                AssemblyField::TotalContigSize(val) => assembly.total_contig_size = Some(val),
-                AssemblyField::TotalScaffoldSize(val) => assembly.total_scaffold_size = Some(val),
-
-                AssemblyField::CanonicalName(_) => {}
-                AssemblyField::ScientificNameAuthorship(_) => {}
-                AssemblyField::TaxonId(_) => {}
-            }
+    backfill_names(dataset, &mut assemblies)?;
+    Ok(assemblies)
+}
+
+
+/// Get all assemblies in entity id order and pass them to `f` in chunks of `chunk_size`.
+///
+/// The assemblies are the same as `get_all` but only one chunk of them is resolved and built
+/// at a time with [`Resolver::resolve_chunked`], which keeps the memory used by exports of large
+/// sources like GenBank proportional to the chunk size rather than the number of assemblies.
+#[instrument(skip_all)]
+pub fn get_chunked<F>(dataset: &Dataset, chunk_size: usize, mut f: F) -> Result<(), TransformError>
+where
+    F: FnMut(Vec<Assembly>) -> Result<(), TransformError>,
+{
+    let resolver = Resolver::new(dataset);
+
+    let schemas = dataset.scope_iris(&[Model::Assembly])?;
+    let depositions = depositions_by_assembly(dataset)?;
+    let taxa = taxon_names(dataset)?;
+
+    info!("Resolving data");
+    resolver.resolve_chunked(rdf::Assembly::ALL, &schemas, chunk_size, |data: Vec<(_, Vec<AssemblyField>)>| {
+        let mut chunk: Vec<Assembly> = data
+            .into_iter()
+            .map(|(_idx, fields)| build(fields, &depositions))
+            .collect();
+        apply_names(&mut chunk, &taxa);
+        f(chunk)
+    })
+}
+
+
+fn depositions_by_assembly(dataset: &Dataset) -> Result<HashMap<String, Deposition>, TransformError> {
+    info!("Resolving depositions");
+    Ok(deposition::get_all(dataset)?
+        .into_iter()
+        .filter_map(|deposition| deposition.assembly_id.clone().map(|id| (id, deposition)))
+        .collect())
+}
+
+
+fn build(fields: Vec<AssemblyField>, depositions: &HashMap<String, Deposition>) -> Assembly {
+    let mut assembly = Assembly::from_fields(fields);
+
+    if assembly.publication_entity_id.is_none() {
+        assembly.publication_entity_id =
+            publications::derive_entity_id(assembly.doi.as_deref(), assembly.citation.as_deref(), None);
+    }
+
+    if let Some(deposition) = assembly.assembly_id.as_ref().and_then(|id| depositions.get(id)) {
+        assembly.deposition_url = deposition.url.clone();
+        assembly.deposition_institution = deposition.institution.clone();
+        assembly.deposition_date = deposition.event_date.clone();
+    }
+
+    assembly
+}
+
+
+/// Fill in the names of assemblies that don't have one from the loaded taxonomy.
+///
+/// Assemblies are joined to taxa on the `taxon_id` and only names missing from the
+/// assembly are filled so that a name provided by the source always takes precedence.
+#[instrument(skip_all)]
+pub fn backfill_names(dataset: &Dataset, assemblies: &mut [Assembly]) -> Result<(), TransformError> {
+    let taxa = taxon_names(dataset)?;
+    apply_names(assemblies, &taxa);
+    Ok(())
+}
+
+
+/// Get the names of every taxon in the loaded taxonomy keyed by the `taxon_id`.
+fn taxon_names(dataset: &Dataset) -> Result<HashMap<String, Taxon>, TransformError> {
+    // avoid warning about unmapped fields when there is no taxonomy to join with
+    if dataset
+        .get_source_from_model(&dataset.model_schema(&Model::Taxon))?
+        .is_empty()
+    {
+        return Ok(HashMap::new());
+    }
+
+    let resolver = Resolver::new(dataset);
+    let schemas = dataset.scope_iris(&[Model::Taxon])?;
+
+    info!("Resolving taxon names");
+    let data: ResolvedRecords<TaxonField> = resolver.resolve(
+        &[
+            rdf::Taxon::TaxonId,
+            rdf::Taxon::ScientificName,
+            rdf::Taxon::CanonicalName,
+        ],
+        &schemas,
+    )?;
+
+    Ok(data
+        .into_values()
+        .map(Taxon::from_fields)
+        .filter_map(|taxon| taxon.taxon_id.clone().map(|id| (id, taxon)))
+        .collect())
+}
+
+
+fn apply_names(assemblies: &mut [Assembly], taxa: &HashMap<String, Taxon>) {
+    for assembly in assemblies.iter_mut() {
+        let Some(taxon) = assembly.taxon_id.as_ref().and_then(|id| taxa.get(id))
+        else {
+            continue;
+        };
+
+        // an empty value in the source is as good as no name at all
+        if assembly.scientific_name.as_ref().is_none_or(|name| name.is_empty()) {
+            assembly.scientific_name = taxon.scientific_name.clone();
         }
+        if assembly.canonical_name.as_ref().is_none_or(|name| name.is_empty()) {
+            assembly.canonical_name = taxon.canonical_name.clone();
+        }
+    }
+}
+
 
-        assemblies.push(assembly);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{SYNTHETIC_SCHEMA, load_source};
+
+    const MAPPINGS: &str = r#"
+        fields:entity_id mapping:same col:id .
+        fields:size mapping:convert [
+            mapping:source col:size_mb ;
+            mapping:from_unit "Mb" ;
+            mapping:to_unit "bp"
+        ] .
+        fields:size mapping:same col:size_bp .
+    "#;
+
+    #[test]
+    fn converts_size_into_base_pairs() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let rows: &[&[(&str, &str)]] = &[
+            &[("id", "GCA_1"), ("size_mb", "2.5")],
+            &[("id", "GCA_2"), ("size_mb", "1,024.0004")],
+            &[("id", "GCA_3"), ("size_bp", "3000")],
+        ];
+        load_source(&mut dataset, &Model::Assembly, "assemblies.csv", MAPPINGS, rows).unwrap();
+
+        let mut assemblies = get_all(&dataset).unwrap();
+        assemblies.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
+
+        let sizes: Vec<Option<u64>> = assemblies.iter().map(|assembly| assembly.size).collect();
+        assert_eq!(sizes, vec![Some(2_500_000), Some(1_024_000_400), Some(3000)]);
     }
 
-    Ok(assemblies)
+    #[test]
+    fn drops_sizes_that_are_not_numbers() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let rows: &[&[(&str, &str)]] = &[&[("id", "GCA_1"), ("size_mb", "unknown")]];
+        load_source(&mut dataset, &Model::Assembly, "assemblies.csv", MAPPINGS, rows).unwrap();
+
+        let assemblies = get_all(&dataset).unwrap();
+        assert_eq!(assemblies.len(), 1);
+        assert_eq!(assemblies[0].size, None);
+    }
+
+    const NAMED: &str = r#"
+        fields:entity_id mapping:same col:id .
+        fields:taxon_id mapping:same col:taxid .
+        fields:scientific_name mapping:same col:organism_name .
+    "#;
+
+    const TAXONOMY: &str = r#"
+        fields:entity_id mapping:hash col:tax_id .
+        fields:taxon_id mapping:same col:tax_id .
+        fields:scientific_name mapping:same col:name .
+        fields:canonical_name mapping:same col:canonical .
+    "#;
+
+    fn names(assembly: &Assembly) -> (Option<&str>, Option<&str>) {
+        (assembly.scientific_name.as_deref(), assembly.canonical_name.as_deref())
+    }
+
+    #[test]
+    fn backfills_missing_names_from_the_taxonomy() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let rows: &[&[(&str, &str)]] = &[
+            &[
+                ("id", "GCA_1"),
+                ("taxid", "9685"),
+                ("organism_name", "Felis catus (domestic cat)"),
+            ],
+            &[("id", "GCA_2"), ("taxid", "9615")],
+            &[("id", "GCA_3"), ("taxid", "9685")],
+            &[("id", "GCA_4"), ("taxid", "1")],
+        ];
+        load_source(&mut dataset, &Model::Assembly, "assemblies.csv", NAMED, rows).unwrap();
+
+        let taxa: &[&[(&str, &str)]] = &[
+            &[
+                ("tax_id", "9685"),
+                ("name", "Felis catus Linnaeus, 1758"),
+                ("canonical", "Felis catus"),
+            ],
+            &[
+                ("tax_id", "9615"),
+                ("name", "Canis lupus familiaris"),
+                ("canonical", "Canis lupus familiaris"),
+            ],
+        ];
+        load_source(&mut dataset, &Model::Taxon, "taxonomy", TAXONOMY, taxa).unwrap();
+
+        let mut assemblies = get_all(&dataset).unwrap();
+        assemblies.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
+
+        // the name from the source is kept while the missing canonical name is filled
+        assert_eq!(names(&assemblies[0]), (Some("Felis catus (domestic cat)"), Some("Felis catus")));
+        assert_eq!(names(&assemblies[1]), (Some("Canis lupus familiaris"), Some("Canis lupus familiaris")));
+        assert_eq!(names(&assemblies[2]), (Some("Felis catus Linnaeus, 1758"), Some("Felis catus")));
+        // taxa missing from the taxonomy are left alone
+        assert_eq!(names(&assemblies[3]), (None, None));
+    }
+
+    #[test]
+    fn backfill_without_a_taxonomy_leaves_names_alone() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let rows: &[&[(&str, &str)]] = &[&[("id", "GCA_1"), ("taxid", "9685")]];
+        load_source(&mut dataset, &Model::Assembly, "assemblies.csv", NAMED, rows).unwrap();
+
+        let assemblies = get_all(&dataset).unwrap();
+        assert_eq!(assemblies[0].taxon_id.as_deref(), Some("9685"));
+        assert_eq!(names(&assemblies[0]), (None, None));
+    }
 }
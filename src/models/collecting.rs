@@ -1,65 +1,135 @@
-use tracing::{info, instrument};
+use tracing::{info, instrument, warn};
 
 use crate::dataset::{Dataset, Model};
 use crate::errors::TransformError;
-use crate::rdf::{self, CollectingField};
+use crate::models::agent::{self, AgentRole};
+use crate::rdf::{self, CollectingField, Condition, Literal};
 use crate::resolver::{ResolvedRecords, Resolver};
+use crate::utils::parse_distance;
+use crate::validation::country;
 
 
-#[derive(Debug, Default, serde::Serialize)]
-pub struct Collecting {
+model_schema! {
+    #[derive(Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct Collecting {
+        pub entity_id: String,
+        pub organism_id: Option<String>,
+        pub specimen_id: Option<String>,
+
+        pub field_collecting_id: Option<String>,
+        pub scientific_name: Option<String>,
+        #[serde(default, deserialize_with = "crate::models::deserialize_list")]
+        pub collected_by: Vec<String>,
+        pub collected_by_entity_id: Option<String>,
+        pub collection_date: Option<String>,
+        pub remarks: Option<String>,
+        pub preparation: Option<String>,
+        pub habitat: Option<String>,
+        pub specific_host: Option<String>,
+        pub individual_count: Option<String>,
+        pub strain: Option<String>,
+        pub isolate: Option<String>,
+        #[serde(default, deserialize_with = "crate::models::deserialize_list")]
+        pub permit: Vec<String>,
+        pub sampling_protocol: Option<String>,
+        pub organism_killed: Option<String>,
+        pub organism_kill_method: Option<String>,
+        pub field_sample_disposition: Option<String>,
+        pub field_notes: Option<String>,
+        pub environment_broad_scale: Option<String>,
+        pub environment_local_scale: Option<String>,
+        pub environment_medium: Option<String>,
+        pub locality: Option<String>,
+        pub country: Option<String>,
+        pub country_code: Option<String>,
+        pub state_province: Option<String>,
+        pub county: Option<String>,
+        pub municipality: Option<String>,
+        pub latitude: Option<String>,
+        pub longitude: Option<String>,
+        pub location_generalisation: Option<String>,
+        pub location_source: Option<String>,
+        pub elevation: Option<String>,
+        pub elevation_accuracy: Option<String>,
+        pub depth: Option<String>,
+        pub depth_accuracy: Option<String>,
+    }
+}
+
+
+/// The values that couldn't be normalised when resolving collecting events.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct CollectingReport {
+    /// Elevations that couldn't be parsed as a distance and were left as they are
+    pub unparsed_elevations: Vec<String>,
+    /// Depths that couldn't be parsed as a distance and were left as they are
+    pub unparsed_depths: Vec<String>,
+    /// Countries that didn't match a known country so a code couldn't be derived
+    pub unknown_countries: Vec<String>,
+    /// Records with a country code that doesn't match the code of the country
+    pub country_conflicts: Vec<CountryConflict>,
+}
+
+
+/// A collecting event where the country and country code are for different countries.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct CountryConflict {
     pub entity_id: String,
-    pub organism_id: Option<String>,
-    pub specimen_id: Option<String>,
-
-    pub field_collecting_id: Option<String>,
-    pub scientific_name: Option<String>,
-    pub collected_by: Option<String>,
-    pub collection_date: Option<String>,
-    pub remarks: Option<String>,
-    pub preparation: Option<String>,
-    pub habitat: Option<String>,
-    pub specific_host: Option<String>,
-    pub individual_count: Option<String>,
-    pub strain: Option<String>,
-    pub isolate: Option<String>,
-    pub permit: Option<String>,
-    pub sampling_protocol: Option<String>,
-    pub organism_killed: Option<String>,
-    pub organism_kill_method: Option<String>,
-    pub field_sample_disposition: Option<String>,
-    pub field_notes: Option<String>,
-    pub environment_broad_scale: Option<String>,
-    pub environment_local_scale: Option<String>,
-    pub environment_medium: Option<String>,
-    pub locality: Option<String>,
-    pub country: Option<String>,
-    pub country_code: Option<String>,
-    pub state_province: Option<String>,
-    pub county: Option<String>,
-    pub municipality: Option<String>,
-    pub latitude: Option<String>,
-    pub longitude: Option<String>,
-    pub location_generalisation: Option<String>,
-    pub location_source: Option<String>,
-    pub elevation: Option<String>,
-    pub elevation_accuracy: Option<String>,
-    pub depth: Option<String>,
-    pub depth_accuracy: Option<String>,
+    pub country: String,
+    pub country_code: String,
+    /// The code of the country as derived from its name
+    pub derived_code: String,
 }
 
 
 #[instrument(skip_all)]
 pub fn get_all(dataset: &Dataset) -> Result<Vec<Collecting>, TransformError> {
+    let (records, _report) = get_all_with_report(dataset)?;
+    Ok(records)
+}
+
+
+/// Get all collecting events along with the values that couldn't be normalised.
+#[instrument(skip_all)]
+pub fn get_all_with_report(dataset: &Dataset) -> Result<(Vec<Collecting>, CollectingReport), TransformError> {
     let resolver = Resolver::new(dataset);
 
-    let schemas = dataset.scope(&[Model::Collecting]);
-    let schemas: Vec<&iref::Iri> = schemas.iter().map(|s| s.as_iri()).collect();
+    let schemas = dataset.scope_iris(&[Model::Collecting])?;
 
     info!("Resolving data");
     let data: ResolvedRecords<CollectingField> = resolver.resolve(rdf::Collecting::ALL, &schemas)?;
+    let mut records = from_resolved(dataset, data)?;
+    let mut report = normalise_distances(&mut records);
+    reconcile_countries(&mut records, dataset.fuzzy_countries, &mut report);
+    Ok((records, report))
+}
+
+
+/// Get all collecting events with the scientific name.
+///
+/// The name must match the resolved `scientific_name` exactly.
+#[instrument(skip_all)]
+pub fn get_by_scientific_name(dataset: &Dataset, name: &str) -> Result<Vec<Collecting>, TransformError> {
+    let resolver = Resolver::new(dataset);
+
+    let schemas = dataset.scope_iris(&[Model::Collecting])?;
+    let conditions = [(rdf::Collecting::ScientificName.into(), Condition::Is(Literal::String(name.to_string())))];
+
+    info!(name, "Resolving data");
+    let data: ResolvedRecords<CollectingField> =
+        resolver.resolve_with_conditions(rdf::Collecting::ALL, &schemas, &conditions)?;
+
+    // records without a scientific name pass the condition as there is nothing to check
+    let mut records = from_resolved(dataset, data)?;
+    records.retain(|record| record.scientific_name.as_deref() == Some(name));
+    let mut report = normalise_distances(&mut records);
+    reconcile_countries(&mut records, dataset.fuzzy_countries, &mut report);
+    Ok(records)
+}
 
 
+fn from_resolved(dataset: &Dataset, data: ResolvedRecords<CollectingField>) -> Result<Vec<Collecting>, TransformError> {
+    let agents = agent::remap_table(dataset, &[AgentRole::Collector])?;
     let mut records = Vec::new();
 
     for (_idx, fields) in data {
@@ -72,7 +142,8 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<Collecting>, TransformError> {
                 CollectingField::MaterialSampleId(val) => record.specimen_id = Some(val),
                 CollectingField::FieldCollectingId(val) => record.field_collecting_id = Some(val),
                 CollectingField::ScientificName(val) => record.scientific_name = Some(val),
-                CollectingField::CollectedBy(val) => record.collected_by = Some(val),
+                CollectingField::CollectedBy(val) => record.collected_by.push(val),
+                CollectingField::CollectedByEntityId(val) => record.collected_by_entity_id = Some(agents.remap(val)),
                 CollectingField::CollectionDate(val) => record.collection_date = Some(val),
                 CollectingField::Remarks(val) => record.remarks = Some(val),
                 CollectingField::Preparation(val) => record.preparation = Some(val),
@@ -81,7 +152,7 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<Collecting>, TransformError> {
                 CollectingField::IndividualCount(val) => record.habitat = Some(val),
                 CollectingField::Strain(val) => record.strain = Some(val),
                 CollectingField::Isolate(val) => record.isolate = Some(val),
-                CollectingField::Permit(val) => record.permit = Some(val),
+                CollectingField::Permit(val) => record.permit.push(val),
                 CollectingField::SamplingProtocol(val) => record.sampling_protocol = Some(val),
                 CollectingField::OrganismKilled(val) => record.organism_killed = Some(val),
                 CollectingField::OrganismKillMethod(val) => record.organism_kill_method = Some(val),
@@ -105,6 +176,7 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<Collecting>, TransformError> {
                 CollectingField::Depth(val) => record.depth = Some(val),
                 CollectingField::DepthAccuracy(val) => record.depth_accuracy = Some(val),
 
+                CollectingField::CollectedByOrcid(_) => {}
                 CollectingField::CanonicalName(_) => {}
                 CollectingField::ScientificNameAuthorship(_) => {}
             }
@@ -117,46 +189,273 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<Collecting>, TransformError> {
 }
 
 
-// /// Get all scientific names.
-// #[instrument(skip_all)]
-// pub fn get_scientific_names(dataset: &Dataset) -> Result<HashMap<String, String>, Error> {
-//     let models = dataset.scope(&["collecting"]);
-//     let mut scope = Vec::new();
-//     for model in models.iter() {
-//         scope.push(iref::Iri::new(model).unwrap());
-//     }
-
-//     let resolver = Resolver::new(dataset);
-
-
-//     let mut names = HashMap::new();
-
-//     let data: HashMap<Literal, Vec<CollectingField>> = resolver.resolve(
-//         &[
-//             rdf::Collecting::EntityId,
-//             rdf::Collecting::ScientificName,
-//             rdf::Collecting::CanonicalName,
-//             rdf::Collecting::ScientificNameAuthorship,
-//         ],
-//         &scope,
-//     )?;
-
-//     for (_idx, fields) in data.into_iter() {
-//         let mut entity_id = None;
-//         let mut scientific_name = None;
-
-//         for field in fields {
-//             match field {
-//                 CollectingField::EntityId(val) => entity_id = Some(val),
-//                 CollectingField::ScientificName(val) => scientific_name = Some(val),
-//                 _ => {}
-//             }
-//         }
-
-//         if let (Some(entity_id), Some(scientific_name)) = (entity_id, scientific_name) {
-//             names.insert(entity_id, scientific_name);
-//         }
-//     }
-
-//     Ok(names)
-// }
+/// Normalise the elevation and depth of the records into metres with one decimal place.
+///
+/// Units are stripped and feet are converted into metres. An accuracy following the value,
+/// eg. `120m ±5m`, is used for the accuracy field when the record doesn't already have one.
+fn normalise_distances(records: &mut [Collecting]) -> CollectingReport {
+    let mut report = CollectingReport::default();
+
+    for record in records.iter_mut() {
+        normalise_distance(&mut record.elevation, &mut record.elevation_accuracy, &mut report.unparsed_elevations);
+        normalise_distance(&mut record.depth, &mut record.depth_accuracy, &mut report.unparsed_depths);
+    }
+
+    let unparsed = report.unparsed_elevations.len() + report.unparsed_depths.len();
+    if unparsed > 0 {
+        warn!(unparsed, "Elevations and depths that couldn't be normalised were left as they are");
+    }
+
+    report
+}
+
+
+fn normalise_distance(value: &mut Option<String>, accuracy: &mut Option<String>, unparsed: &mut Vec<String>) {
+    if let Some(distance) = accuracy.as_deref().and_then(parse_distance) {
+        *accuracy = Some(format!("{:.1}", distance.metres.abs()));
+    }
+
+    let Some(raw) = value.clone()
+    else {
+        return;
+    };
+
+    match parse_distance(&raw) {
+        Some(distance) => {
+            *value = Some(format!("{:.1}", distance.metres));
+            if accuracy.is_none()
+                && let Some(metres) = distance.accuracy
+            {
+                *accuracy = Some(format!("{metres:.1}"));
+            }
+        }
+        None => unparsed.push(raw),
+    }
+}
+
+
+/// Make sure every record with a country has an ISO 3166-1 alpha-2 country code.
+///
+/// Known codes are upper cased and a missing code is derived from the country name. When
+/// the record has both and they are for different countries the code from the source is
+/// kept and the conflict is reported, as is a country that doesn't match any known name.
+fn reconcile_countries(records: &mut [Collecting], fuzzy: bool, report: &mut CollectingReport) {
+    let lookup = match fuzzy {
+        true => country::country_code_fuzzy,
+        false => country::country_code,
+    };
+
+    for record in records.iter_mut() {
+        if let Some(code) = record.country_code.as_deref().and_then(country::normalise_code) {
+            record.country_code = Some(code.to_string());
+        }
+
+        let Some(name) = record.country.as_deref().filter(|name| !name.trim().is_empty())
+        else {
+            continue;
+        };
+
+        let Some(derived) = lookup(name)
+        else {
+            report.unknown_countries.push(name.to_string());
+            continue;
+        };
+
+        match &record.country_code {
+            None => record.country_code = Some(derived.to_string()),
+            Some(code) if code != derived => report.country_conflicts.push(CountryConflict {
+                entity_id: record.entity_id.clone(),
+                country: name.to_string(),
+                country_code: code.clone(),
+                derived_code: derived.to_string(),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    if !report.unknown_countries.is_empty() || !report.country_conflicts.is_empty() {
+        warn!(
+            unknown = report.unknown_countries.len(),
+            conflicts = report.country_conflicts.len(),
+            "Countries that couldn't be reconciled with a country code"
+        );
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collecting(entity_id: &str, country: Option<&str>, country_code: Option<&str>) -> Collecting {
+        Collecting {
+            entity_id: entity_id.to_string(),
+            country: country.map(String::from),
+            country_code: country_code.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    fn codes(records: &[Collecting]) -> Vec<Option<&str>> {
+        records.iter().map(|record| record.country_code.as_deref()).collect()
+    }
+
+    #[test]
+    fn reconcile_countries_derives_missing_codes() {
+        let mut records = vec![
+            collecting("1", Some("Australia"), None),
+            collecting("2", Some("USA"), None),
+            collecting("3", Some("Viet Nam"), None),
+            collecting("4", None, Some("nz")),
+            collecting("5", None, None),
+        ];
+        let mut report = CollectingReport::default();
+        reconcile_countries(&mut records, false, &mut report);
+
+        assert_eq!(codes(&records), vec![Some("AU"), Some("US"), Some("VN"), Some("NZ"), None]);
+        assert_eq!(report, CollectingReport::default());
+    }
+
+    #[test]
+    fn reconcile_countries_reports_conflicts() {
+        let mut records = vec![
+            collecting("1", Some("Australia"), Some("nz")),
+            collecting("2", Some("United Kingdom"), Some("GB")),
+        ];
+        let mut report = CollectingReport::default();
+        reconcile_countries(&mut records, false, &mut report);
+
+        // the code from the source is kept when it conflicts
+        assert_eq!(codes(&records), vec![Some("NZ"), Some("GB")]);
+        assert_eq!(
+            report.country_conflicts,
+            vec![CountryConflict {
+                entity_id: "1".to_string(),
+                country: "Australia".to_string(),
+                country_code: "NZ".to_string(),
+                derived_code: "AU".to_string(),
+            }]
+        );
+        assert!(report.unknown_countries.is_empty());
+    }
+
+    #[test]
+    fn reconcile_countries_reports_unknown_countries() {
+        let mut records = vec![
+            collecting("1", Some("Narnia"), None),
+            collecting("2", Some("Astralia"), None),
+            collecting("3", Some(" "), None),
+        ];
+        let mut report = CollectingReport::default();
+        reconcile_countries(&mut records, false, &mut report);
+
+        assert_eq!(codes(&records), vec![None, None, None]);
+        assert_eq!(report.unknown_countries, vec!["Narnia".to_string(), "Astralia".to_string()]);
+        assert!(report.country_conflicts.is_empty());
+    }
+
+    #[test]
+    fn reconcile_countries_matches_misspellings_when_fuzzy() {
+        let mut records = vec![
+            collecting("1", Some("Narnia"), None),
+            collecting("2", Some("Astralia"), None),
+        ];
+        let mut report = CollectingReport::default();
+        reconcile_countries(&mut records, true, &mut report);
+
+        assert_eq!(codes(&records), vec![None, Some("AU")]);
+        assert_eq!(report.unknown_countries, vec!["Narnia".to_string()]);
+    }
+
+    fn distances(elevation: &str, elevation_accuracy: Option<&str>) -> Collecting {
+        Collecting {
+            entity_id: elevation.to_string(),
+            elevation: Some(elevation.to_string()),
+            elevation_accuracy: elevation_accuracy.map(String::from),
+            depth: Some(elevation.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn normalise_distances_strips_metres() {
+        let cases = [
+            ("120", "120.0"),
+            ("120 m", "120.0"),
+            ("120m", "120.0"),
+            ("120 metres", "120.0"),
+            ("-15", "-15.0"),
+            ("1,200.46 m", "1200.5"),
+            ("250 cm", "2.5"),
+        ];
+
+        for (value, expected) in cases {
+            let mut records = vec![distances(value, None)];
+            let report = normalise_distances(&mut records);
+
+            assert_eq!(records[0].elevation.as_deref(), Some(expected), "{value}");
+            assert_eq!(records[0].depth.as_deref(), Some(expected), "{value}");
+            assert_eq!(records[0].elevation_accuracy, None, "{value}");
+            assert_eq!(report, CollectingReport::default(), "{value}");
+        }
+    }
+
+    #[test]
+    fn normalise_distances_converts_feet() {
+        let cases = [
+            ("15 ft", "4.6"),
+            ("15ft", "4.6"),
+            ("1000 feet", "304.8"),
+            ("-10 foot", "-3.0"),
+        ];
+
+        for (value, expected) in cases {
+            let mut records = vec![distances(value, None)];
+            normalise_distances(&mut records);
+            assert_eq!(records[0].elevation.as_deref(), Some(expected), "{value}");
+        }
+    }
+
+    #[test]
+    fn normalise_distances_takes_the_accuracy_from_the_value() {
+        let cases = [
+            ("120m ±5m", None, "120.0", Some("5.0")),
+            ("120 ± 5", None, "120.0", Some("5.0")),
+            ("100 ft +/- 10", None, "30.5", Some("3.0")),
+            ("100 ft ±2 m", None, "30.5", Some("2.0")),
+            // an accuracy from the source is kept over the one in the value
+            ("120m ±5m", Some("10 ft"), "120.0", Some("3.0")),
+            ("120m", Some("-4"), "120.0", Some("4.0")),
+        ];
+
+        for (value, accuracy, expected, expected_accuracy) in cases {
+            let mut records = vec![distances(value, accuracy)];
+            normalise_distances(&mut records);
+
+            assert_eq!(records[0].elevation.as_deref(), Some(expected), "{value}");
+            assert_eq!(records[0].elevation_accuracy.as_deref(), expected_accuracy, "{value}");
+            // the depth has no accuracy of its own so it gets one from the value as well
+            assert_eq!(records[0].depth.as_deref(), Some(expected), "{value}");
+        }
+    }
+
+    #[test]
+    fn normalise_distances_reports_garbage() {
+        let garbage = ["about 120 m", "120 km", "12 kb", "±5m", "120m ±", "unknown"];
+        let mut records: Vec<Collecting> = garbage.iter().map(|value| distances(value, None)).collect();
+        records.push(Collecting {
+            entity_id: "empty".to_string(),
+            ..Default::default()
+        });
+
+        let report = normalise_distances(&mut records);
+
+        // values that can't be parsed are left as they are
+        for (record, value) in records.iter().zip(garbage) {
+            assert_eq!(record.elevation.as_deref(), Some(value));
+            assert_eq!(record.elevation_accuracy, None);
+        }
+        assert_eq!(report.unparsed_elevations, garbage.to_vec());
+        assert_eq!(report.unparsed_depths, garbage.to_vec());
+    }
+}
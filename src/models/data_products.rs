@@ -2,28 +2,32 @@ use tracing::{info, instrument};
 
 use crate::dataset::{Dataset, Model};
 use crate::errors::TransformError;
+use crate::models::agent::{self, AgentRole};
+use crate::models::publications;
 use crate::rdf::{self, DataProductField};
 use crate::resolver::{ResolvedRecords, Resolver};
 
 
-#[derive(Debug, Default, serde::Serialize)]
-pub struct DataProduct {
-    pub entity_id: String,
-    pub organism_id: Option<String>,
-    pub extract_id: Option<String>,
-    pub sequence_run_id: Option<String>,
-    pub publication_id: Option<String>,
-    pub custodian: Option<String>,
+model_schema! {
+    #[derive(Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct DataProduct {
+        pub entity_id: String,
+        pub organism_id: Option<String>,
+        pub extract_id: Option<String>,
+        pub sequence_run_id: Option<String>,
+        pub publication_id: Option<String>,
+        pub custodian: Option<String>,
 
-    pub sequence_sample_id: Option<String>,
-    pub sequence_analysis_id: Option<String>,
-    pub notes: Option<String>,
-    pub context: Option<String>,
-    pub r#type: Option<String>,
-    pub file_type: Option<String>,
-    pub url: Option<String>,
-    pub licence: Option<String>,
-    pub access: Option<String>,
+        pub sequence_sample_id: Option<String>,
+        pub sequence_analysis_id: Option<String>,
+        pub notes: Option<String>,
+        pub context: Option<String>,
+        pub r#type: Option<String>,
+        pub file_type: Option<String>,
+        pub url: Option<String>,
+        pub licence: Option<String>,
+        pub access: Option<String>,
+    }
 }
 
 
@@ -31,16 +35,18 @@ pub struct DataProduct {
 pub fn get_all(dataset: &Dataset) -> Result<Vec<DataProduct>, TransformError> {
     let resolver = Resolver::new(dataset);
 
-    let schemas = dataset.scope(&[Model::DataProduct]);
-    let schemas: Vec<&iref::Iri> = schemas.iter().map(|s| s.as_iri()).collect();
+    let schemas = dataset.scope_iris(&[Model::DataProduct])?;
 
     info!("Resolving data");
     let data: ResolvedRecords<DataProductField> = resolver.resolve(rdf::DataProduct::ALL, &schemas)?;
+    let agents = agent::remap_table(dataset, &[AgentRole::Custodian])?;
 
     let mut products = Vec::new();
 
     for (_idx, fields) in data {
         let mut product = DataProduct::default();
+        let mut citation = None;
+        let mut source_url = None;
 
         for field in fields {
             match field {
@@ -57,16 +63,21 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<DataProduct>, TransformError> {
                 DataProductField::Url(val) => product.url = Some(val),
                 DataProductField::Licence(val) => product.licence = Some(val),
                 DataProductField::Access(val) => product.access = Some(val),
-                DataProductField::CustodianEntityId(val) => product.custodian = Some(val),
+                DataProductField::CustodianEntityId(val) => product.custodian = Some(agents.remap(val)),
                 DataProductField::PublicationEntityId(val) => product.publication_id = Some(val),
 
                 DataProductField::Custodian(_val) => {}
                 DataProductField::CustodianOrcid(_val) => {}
-                DataProductField::Citation(_val) => {}
-                DataProductField::SourceUrl(_val) => {}
+                // used to derive the publication id when it isn't mapped
+                DataProductField::Citation(val) => citation = Some(val),
+                DataProductField::SourceUrl(val) => source_url = Some(val),
             }
         }
 
+        if product.publication_id.is_none() {
+            product.publication_id = publications::derive_entity_id(None, citation.as_deref(), source_url.as_deref());
+        }
+
         products.push(product);
     }
 
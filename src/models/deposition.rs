@@ -2,18 +2,32 @@ use tracing::{info, instrument};
 
 use crate::dataset::{Dataset, Model};
 use crate::errors::TransformError;
+use crate::models::FromFields;
 use crate::rdf::{self, DepositionField};
 use crate::resolver::{ResolvedRecords, Resolver};
 
 
-#[derive(Debug, Default, serde::Serialize)]
-pub struct Deposition {
-    pub entity_id: String,
-    pub assembly_id: Option<String>,
+model_schema! {
+    #[derive(Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct Deposition {
+        pub entity_id: String,
+        pub assembly_id: Option<String>,
 
-    pub event_date: Option<String>,
-    pub url: Option<String>,
-    pub institution: Option<String>,
+        pub event_date: Option<String>,
+        pub url: Option<String>,
+        pub institution: Option<String>,
+    }
+}
+
+
+impl_model! {
+    Deposition => DepositionField {
+        EntityId => entity_id required,
+        AssemblyId => assembly_id,
+        EventDate => event_date,
+        Url => url,
+        Institution => institution,
+    }
 }
 
 
@@ -21,30 +35,34 @@ pub struct Deposition {
 pub fn get_all(dataset: &Dataset) -> Result<Vec<Deposition>, TransformError> {
     let resolver = Resolver::new(dataset);
 
-    let schemas = dataset.scope(&[Model::Deposition]);
-    let schemas: Vec<&iref::Iri> = schemas.iter().map(|s| s.as_iri()).collect();
+    let schemas = dataset.scope_iris(&[Model::Deposition])?;
 
     info!("Resolving data");
     let data: ResolvedRecords<DepositionField> = resolver.resolve(rdf::Deposition::ALL, &schemas)?;
+    let depositions = data.into_values().map(Deposition::from_fields).collect();
 
+    Ok(depositions)
+}
 
-    let mut depositions = Vec::new();
-
-    for (_idx, fields) in data {
-        let mut deposition = Deposition::default();
 
-        for field in fields {
-            match field {
-                DepositionField::EntityId(val) => deposition.entity_id = val,
-                DepositionField::AssemblyId(val) => deposition.assembly_id = Some(val),
-                DepositionField::EventDate(val) => deposition.event_date = Some(val),
-                DepositionField::Url(val) => deposition.url = Some(val),
-                DepositionField::Institution(val) => deposition.institution = Some(val),
-            }
-        }
+/// Get all depositions in entity id order and pass them to `f` in chunks of `chunk_size`.
+///
+/// Only one chunk of depositions is resolved and built at a time, see [`Resolver::resolve_chunked`].
+#[instrument(skip_all)]
+pub fn get_chunked<F>(dataset: &Dataset, chunk_size: usize, mut f: F) -> Result<(), TransformError>
+where
+    F: FnMut(Vec<Deposition>) -> Result<(), TransformError>,
+{
+    let resolver = Resolver::new(dataset);
 
-        depositions.push(deposition);
-    }
+    let schemas = dataset.scope_iris(&[Model::Deposition])?;
 
-    Ok(depositions)
+    info!("Resolving data");
+    resolver.resolve_chunked(rdf::Deposition::ALL, &schemas, chunk_size, |data: Vec<(_, Vec<DepositionField>)>| {
+        let depositions = data
+            .into_iter()
+            .map(|(_idx, fields)| Deposition::from_fields(fields))
+            .collect();
+        f(depositions)
+    })
 }
@@ -2,32 +2,36 @@ use tracing::{info, instrument};
 
 use crate::dataset::{Dataset, Model};
 use crate::errors::TransformError;
-use crate::rdf::{self, ExtractionField};
+use crate::models::agent::{self, AgentRole};
+use crate::models::publications;
+use crate::rdf::{self, ExtractionField, Unit};
 use crate::resolver::{ResolvedRecords, Resolver};
 
 
-#[derive(Debug, Default, serde::Serialize)]
-pub struct Extraction {
-    pub entity_id: String,
-    pub subsample_id: Option<String>,
-    pub publication_id: Option<String>,
-    pub extract_id: Option<String>,
-    pub extracted_by: Option<String>,
-    pub material_extracted_by: Option<String>,
-    pub scientific_name: Option<String>,
-    pub extraction_date: Option<String>,
-    pub nucleic_acid_type: Option<String>,
-    pub nucleic_acid_conformation: Option<String>,
-    pub nucleic_acid_preservation_method: Option<String>,
-    pub nucleic_acid_concentration: Option<String>,
-    pub nucleic_acid_quantification: Option<String>,
-    pub concentration_unit: Option<String>,
-    pub absorbance_260_230_ratio: Option<String>,
-    pub absorbance_260_280_ratio: Option<String>,
-    pub cell_lysis_method: Option<String>,
-    pub action_extracted: Option<String>,
-    pub extraction_method: Option<String>,
-    pub number_of_extracts_pooled: Option<String>,
+model_schema! {
+    #[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct Extraction {
+        pub entity_id: String,
+        pub subsample_id: Option<String>,
+        pub publication_id: Option<String>,
+        pub extract_id: Option<String>,
+        pub extracted_by: Option<String>,
+        pub material_extracted_by: Option<String>,
+        pub scientific_name: Option<String>,
+        pub extraction_date: Option<String>,
+        pub nucleic_acid_type: Option<String>,
+        pub nucleic_acid_conformation: Option<String>,
+        pub nucleic_acid_preservation_method: Option<String>,
+        pub nucleic_acid_concentration: Option<String>,
+        pub nucleic_acid_quantification: Option<String>,
+        pub concentration_unit: Option<String>,
+        pub absorbance_260_230_ratio: Option<String>,
+        pub absorbance_260_280_ratio: Option<String>,
+        pub cell_lysis_method: Option<String>,
+        pub action_extracted: Option<String>,
+        pub extraction_method: Option<String>,
+        pub number_of_extracts_pooled: Option<String>,
+    }
 }
 
 
@@ -35,17 +39,18 @@ pub struct Extraction {
 pub fn get_all(dataset: &Dataset) -> Result<Vec<Extraction>, TransformError> {
     let resolver = Resolver::new(dataset);
 
-    let schemas = dataset.scope(&[Model::Extraction]);
-    let schemas: Vec<&iref::Iri> = schemas.iter().map(|s| s.as_iri()).collect();
+    let schemas = dataset.scope_iris(&[Model::Extraction])?;
 
     info!("Resolving data");
     let data: ResolvedRecords<ExtractionField> = resolver.resolve(rdf::Extraction::ALL, &schemas)?;
-
+    let agents = agent::remap_table(dataset, &[AgentRole::Extractor, AgentRole::MaterialExtractor])?;
 
     let mut extractions = Vec::new();
 
     for (_idx, fields) in data {
         let mut extraction = Extraction::default();
+        let mut doi = None;
+        let mut citation = None;
 
         for field in fields {
             match field {
@@ -58,9 +63,9 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<Extraction>, TransformError> {
                 ExtractionField::NucleicAcidPreservationMethod(val) => {
                     extraction.nucleic_acid_preservation_method = Some(val)
                 }
-                ExtractionField::NucleicAcidConcentration(val) => extraction.nucleic_acid_conformation = Some(val),
+                ExtractionField::NucleicAcidConcentration(val) => extraction.nucleic_acid_concentration = Some(val),
                 ExtractionField::NucleicAcidQuantification(val) => extraction.nucleic_acid_quantification = Some(val),
-                ExtractionField::ConcentrationUnit(val) => extraction.concentration_unit = Some(val),
+                ExtractionField::ConcentrationUnit(val) => extraction.concentration_unit = Some(normalise_unit(val)),
                 ExtractionField::Absorbance260230Ratio(val) => extraction.absorbance_260_230_ratio = Some(val),
                 ExtractionField::Absorbance260280Ratio(val) => extraction.absorbance_260_280_ratio = Some(val),
                 ExtractionField::CellLysisMethod(val) => extraction.cell_lysis_method = Some(val),
@@ -69,8 +74,10 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<Extraction>, TransformError> {
                 ExtractionField::NumberOfExtractsPooled(val) => extraction.number_of_extracts_pooled = Some(val),
 
                 // only include the entity id for agents as they will be referenced instead
-                ExtractionField::ExtractedByEntityId(val) => extraction.extracted_by = Some(val),
-                ExtractionField::MaterialExtractedByEntityId(val) => extraction.material_extracted_by = Some(val),
+                ExtractionField::ExtractedByEntityId(val) => extraction.extracted_by = Some(agents.remap(val)),
+                ExtractionField::MaterialExtractedByEntityId(val) => {
+                    extraction.material_extracted_by = Some(agents.remap(val))
+                }
                 ExtractionField::PublicationEntityId(val) => extraction.publication_id = Some(val),
 
                 // fields we don't need to action as it's used in the production of the reference entity id
@@ -78,11 +85,17 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<Extraction>, TransformError> {
                 ExtractionField::ExtractedByOrcid(_) => {}
                 ExtractionField::MaterialExtractedBy(_) => {}
                 ExtractionField::MaterialExtractedByOrcid(_) => {}
-                ExtractionField::Doi(_) => {}
-                ExtractionField::Citation(_) => {}
+
+                // used to derive the publication id when it isn't mapped
+                ExtractionField::Doi(val) => doi = Some(val),
+                ExtractionField::Citation(val) => citation = Some(val),
             }
         }
 
+        if extraction.publication_id.is_none() {
+            extraction.publication_id = publications::derive_entity_id(doi.as_deref(), citation.as_deref(), None);
+        }
+
         extractions.push(extraction);
     }
 
@@ -98,6 +111,15 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<Extraction>, TransformError> {
 }
 
 
+/// Use the label of a known unit so that the same unit isn't spelt differently between sources.
+fn normalise_unit(unit: String) -> String {
+    match unit.parse::<Unit>() {
+        Ok(known) => known.label().to_string(),
+        Err(_) => unit,
+    }
+}
+
+
 // /// Get scientific names from subsamples.
 // ///
 // /// This will go through all subsamples and retrieve the name associated with the
@@ -140,3 +162,68 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<Extraction>, TransformError> {
 
 //     Ok(extractions)
 // }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{SYNTHETIC_SCHEMA, load_source};
+
+    const CONVERTED: &str = r#"
+        fields:entity_id mapping:same col:id .
+        fields:concentration_unit mapping:same col:unit .
+        fields:nucleic_acid_concentration mapping:convert [
+            mapping:source col:concentration ;
+            mapping:from_unit "ug/mL" ;
+            mapping:to_unit "ng/uL"
+        ] .
+    "#;
+
+    const COPIED: &str = r#"
+        fields:entity_id mapping:same col:id .
+        fields:nucleic_acid_concentration mapping:same col:dna_concentration .
+    "#;
+
+    fn extractions(dataset: &Dataset) -> Vec<Extraction> {
+        let mut extractions = get_all(dataset).unwrap();
+        extractions.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
+        extractions
+    }
+
+    #[test]
+    fn converts_concentration_and_normalises_unit() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let rows: &[&[(&str, &str)]] = &[&[("id", "EX1"), ("concentration", " 1,250.5 "), ("unit", "NG/UL")]];
+        load_source(&mut dataset, &Model::Extraction, "extractions.csv", CONVERTED, rows).unwrap();
+
+        let extractions = extractions(&dataset);
+        assert_eq!(extractions.len(), 1);
+        assert_eq!(extractions[0].nucleic_acid_concentration.as_deref(), Some("1250.5"));
+        assert_eq!(extractions[0].concentration_unit.as_deref(), Some("ng/µL"));
+        assert_eq!(extractions[0].nucleic_acid_conformation, None);
+    }
+
+    #[test]
+    fn keeps_unknown_units() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let rows: &[&[(&str, &str)]] = &[&[("id", "EX1"), ("concentration", "12"), ("unit", "nM")]];
+        load_source(&mut dataset, &Model::Extraction, "extractions.csv", CONVERTED, rows).unwrap();
+
+        let extractions = extractions(&dataset);
+        assert_eq!(extractions[0].concentration_unit.as_deref(), Some("nM"));
+    }
+
+    #[test]
+    fn only_converts_the_converted_source() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let converted: &[&[(&str, &str)]] = &[&[("id", "EX1"), ("concentration", "2,000")]];
+        let copied: &[&[(&str, &str)]] = &[&[("id", "EX2"), ("dna_concentration", "3,000")]];
+        load_source(&mut dataset, &Model::Extraction, "converted.csv", CONVERTED, converted).unwrap();
+        load_source(&mut dataset, &Model::Extraction, "copied.csv", COPIED, copied).unwrap();
+
+        let extractions = extractions(&dataset);
+        assert_eq!(extractions.len(), 2);
+        assert_eq!(extractions[0].nucleic_acid_concentration.as_deref(), Some("2000"));
+        assert_eq!(extractions[1].nucleic_acid_concentration.as_deref(), Some("3,000"));
+    }
+}
@@ -2,40 +2,43 @@ use tracing::{info, instrument};
 
 use crate::dataset::{Dataset, Model};
 use crate::errors::TransformError;
-use crate::rdf::{self, LibraryField};
+use crate::models::agent::{self, AgentRole};
+use crate::rdf::{self, Condition, LibraryField, Literal};
 use crate::resolver::{ResolvedRecords, Resolver};
 
 
-#[derive(Debug, Default, serde::Serialize)]
-pub struct Library {
-    pub entity_id: String,
-    pub extract_id: Option<String>,
-    pub library_id: Option<String>,
-    pub scientific_name: Option<String>,
-
-    pub event_date: Option<String>,
-    pub concentration: Option<String>,
-    pub concentration_unit: Option<String>,
-    pub pcr_cycles: Option<String>,
-    pub layout: Option<String>,
-    pub prepared_by: Option<String>,
-    pub selection: Option<String>,
-    pub bait_set_name: Option<String>,
-    pub bait_set_reference: Option<String>,
-    pub construction_protocol: Option<String>,
-    pub source: Option<String>,
-    pub insert_size: Option<String>,
-    pub design_description: Option<String>,
-    pub strategy: Option<String>,
-    pub index_tag: Option<String>,
-    pub index_dual_tag: Option<String>,
-    pub index_oligo: Option<String>,
-    pub index_dual_oligo: Option<String>,
-    pub location: Option<String>,
-    pub remarks: Option<String>,
-    pub dna_treatment: Option<String>,
-    pub number_of_libraries_pooled: Option<String>,
-    pub pcr_replicates: Option<String>,
+model_schema! {
+    #[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct Library {
+        pub entity_id: String,
+        pub extract_id: Option<String>,
+        pub library_id: Option<String>,
+        pub scientific_name: Option<String>,
+
+        pub event_date: Option<String>,
+        pub concentration: Option<String>,
+        pub concentration_unit: Option<String>,
+        pub pcr_cycles: Option<String>,
+        pub layout: Option<String>,
+        pub prepared_by: Option<String>,
+        pub selection: Option<String>,
+        pub bait_set_name: Option<String>,
+        pub bait_set_reference: Option<String>,
+        pub construction_protocol: Option<String>,
+        pub source: Option<String>,
+        pub insert_size: Option<String>,
+        pub design_description: Option<String>,
+        pub strategy: Option<String>,
+        pub index_tag: Option<String>,
+        pub index_dual_tag: Option<String>,
+        pub index_oligo: Option<String>,
+        pub index_dual_oligo: Option<String>,
+        pub location: Option<String>,
+        pub remarks: Option<String>,
+        pub dna_treatment: Option<String>,
+        pub number_of_libraries_pooled: Option<String>,
+        pub pcr_replicates: Option<String>,
+    }
 }
 
 
@@ -43,13 +46,37 @@ pub struct Library {
 pub fn get_all(dataset: &Dataset) -> Result<Vec<Library>, TransformError> {
     let resolver = Resolver::new(dataset);
 
-    let schemas = dataset.scope(&[Model::Library]);
-    let schemas: Vec<&iref::Iri> = schemas.iter().map(|s| s.as_iri()).collect();
+    let schemas = dataset.scope_iris(&[Model::Library])?;
 
     info!("Resolving data");
     let data: ResolvedRecords<LibraryField> = resolver.resolve(rdf::Library::ALL, &schemas)?;
+    from_resolved(dataset, data)
+}
+
+
+/// Get all libraries with the scientific name.
+///
+/// The name must match the resolved `scientific_name` exactly.
+#[instrument(skip_all)]
+pub fn get_by_scientific_name(dataset: &Dataset, name: &str) -> Result<Vec<Library>, TransformError> {
+    let resolver = Resolver::new(dataset);
+
+    let schemas = dataset.scope_iris(&[Model::Library])?;
+    let conditions = [(rdf::Library::ScientificName.into(), Condition::Is(Literal::String(name.to_string())))];
+
+    info!(name, "Resolving data");
+    let data: ResolvedRecords<LibraryField> =
+        resolver.resolve_with_conditions(rdf::Library::ALL, &schemas, &conditions)?;
+
+    // records without a scientific name pass the condition as there is nothing to check
+    let mut records = from_resolved(dataset, data)?;
+    records.retain(|record| record.scientific_name.as_deref() == Some(name));
+    Ok(records)
+}
 
 
+fn from_resolved(dataset: &Dataset, data: ResolvedRecords<LibraryField>) -> Result<Vec<Library>, TransformError> {
+    let agents = agent::remap_table(dataset, &[AgentRole::LibraryPreparer])?;
     let mut libraries = Vec::new();
 
     for (_idx, fields) in data {
@@ -66,7 +93,7 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<Library>, TransformError> {
                 LibraryField::ConcentrationUnit(val) => library.concentration_unit = Some(val),
                 LibraryField::PcrCycles(val) => library.pcr_cycles = Some(val),
                 LibraryField::Layout(val) => library.layout = Some(val),
-                LibraryField::PreparedByEntityId(val) => library.prepared_by = Some(val),
+                LibraryField::PreparedByEntityId(val) => library.prepared_by = Some(agents.remap(val)),
                 LibraryField::Selection(val) => library.selection = Some(val),
                 LibraryField::BaitSetName(val) => library.bait_set_name = Some(val),
                 LibraryField::BaitSetReference(val) => library.bait_set_reference = Some(val),
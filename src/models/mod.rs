@@ -1,4 +1,182 @@
-// pub mod agent;
+use crate::dataset::Model;
+
+
+/// The separator used when a column has more than one value, eg. the `collected_by` agents.
+pub const LIST_SEPARATOR: &str = "|";
+
+
+/// The description of a column when a model is serialized.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ColumnSchema {
+    pub name: String,
+    /// The Rust type of the value without the `Option` if it is optional.
+    pub rust_type: String,
+    pub optional: bool,
+}
+
+impl ColumnSchema {
+    fn new(name: &str, rust_type: &str) -> ColumnSchema {
+        // raw identifiers like `r#type` are serialized without the prefix
+        let name = name.trim_start_matches("r#").to_string();
+        let rust_type: String = rust_type.chars().filter(|c| !c.is_whitespace()).collect();
+
+        match rust_type.strip_prefix("Option<").and_then(|ty| ty.strip_suffix('>')) {
+            Some(inner) => ColumnSchema {
+                name,
+                rust_type: inner.to_string(),
+                optional: true,
+            },
+            None => ColumnSchema {
+                name,
+                rust_type,
+                optional: false,
+            },
+        }
+    }
+}
+
+
+/// The description of the columns of a model.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ModelSchema {
+    pub model: String,
+    pub columns: Vec<ColumnSchema>,
+}
+
+
+/// Define a model struct along with a `columns` function describing its members.
+///
+/// The columns are in the same order as the struct so they match the order of the
+/// values when a record is serialized.
+macro_rules! model_schema {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident {
+            $($(#[$field_meta:meta])* pub $field:ident: $ty:ty,)*
+        }
+    ) => {
+        $(#[$meta])*
+        pub struct $name {
+            $($(#[$field_meta])* pub $field: $ty,)*
+        }
+
+        impl $name {
+            /// The columns of the model when it is serialized.
+            pub fn columns() -> Vec<$crate::models::ColumnSchema> {
+                vec![$($crate::models::ColumnSchema::new(stringify!($field), stringify!($ty))),*]
+            }
+        }
+    };
+}
+
+
+/// Deserialize a list column from either a sequence or values joined with `LIST_SEPARATOR`.
+///
+/// Lists are a sequence in JSON but are joined into a single cell when exported as CSV, so
+/// both are accepted to be able to read either export back. CSV infers the type of a cell
+/// which means a single value that looks like a number is read in its canonical form, eg. `007`
+/// becomes `7`.
+pub fn deserialize_list<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    deserializer.deserialize_any(ListVisitor)
+}
+
+struct ListVisitor;
+
+impl<'de> serde::de::Visitor<'de> for ListVisitor {
+    type Value = Vec<String>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a sequence of strings or a string of values separated by '{LIST_SEPARATOR}'")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        match value.is_empty() {
+            true => Ok(Vec::new()),
+            false => Ok(value.split(LIST_SEPARATOR).map(String::from).collect()),
+        }
+    }
+
+    fn visit_bool<E: serde::de::Error>(self, value: bool) -> Result<Self::Value, E> {
+        Ok(vec![value.to_string()])
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(vec![value.to_string()])
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(vec![value.to_string()])
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(vec![value.to_string()])
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Vec::new())
+    }
+
+    fn visit_none<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Vec::new())
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element::<String>()? {
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+
+/// Build a model from the resolved values of its fields.
+pub trait FromFields<F>: Default {
+    /// Assign the value of a resolved field to the model.
+    fn apply(&mut self, field: F);
+
+    /// Create a model from all the resolved fields of a record.
+    fn from_fields(fields: Vec<F>) -> Self {
+        let mut model = Self::default();
+        for field in fields {
+            model.apply(field);
+        }
+        model
+    }
+}
+
+
+/// Implement `FromFields` for a model by assigning each field to a struct member.
+///
+/// Every variant of the field enum is listed with the struct member its value is assigned
+/// to. Members are wrapped in `Some` unless they are marked as `required`, and because the
+/// generated match has no catch all arm a new field variant must be added here to compile.
+/// See the deposition model for an example.
+macro_rules! impl_model {
+    (
+        $model:ident => $field:ident {
+            $($variant:ident => $member:ident $($required:ident)?),* $(,)?
+        }
+    ) => {
+        impl $crate::models::FromFields<$field> for $model {
+            fn apply(&mut self, field: $field) {
+                match field {
+                    $($field::$variant(val) => impl_model!(@assign self.$member, val $(, $required)?),)*
+                }
+            }
+        }
+    };
+
+    (@assign $member:expr, $val:expr) => {
+        $member = Some($val)
+    };
+    (@assign $member:expr, $val:expr, required) => {
+        $member = $val
+    };
+}
+
+
+pub mod agent;
 pub mod annotation;
 pub mod assembly;
 pub mod collecting;
@@ -8,15 +186,20 @@ pub mod extraction;
 pub mod library;
 pub mod name;
 pub mod organism;
+pub mod project_assemblies;
 pub mod project_members;
 pub mod projects;
 pub mod publications;
+pub mod references;
+pub mod sequence;
+pub mod sequencing;
 pub mod sequencing_run;
 pub mod subsample;
+pub mod taxon;
 pub mod tissue;
 
 
-// pub use agent::Agent;
+pub use agent::{Agent, AgentMergeReport, AgentRole};
 pub use annotation::Annotation;
 pub use assembly::Assembly;
 pub use collecting::Collecting;
@@ -24,11 +207,57 @@ pub use data_products::DataProduct;
 pub use deposition::Deposition;
 pub use extraction::Extraction;
 pub use library::Library;
-pub use name::Name;
+pub use name::{Name, NameMergeReport};
 pub use organism::Organism;
+pub use project_assemblies::ProjectAssembly;
 pub use project_members::ProjectMember;
 pub use projects::Project;
 pub use publications::Publication;
+pub use references::{DanglingRef, verify_references};
+pub use sequence::Sequence;
+pub use sequencing::Sequencing;
 pub use sequencing_run::SequencingRun;
 pub use subsample::Subsample;
+pub use taxon::Taxon;
 pub use tissue::Tissue;
+
+
+/// Describe the columns of every model.
+///
+/// This is intended for tooling that reads the exported records and needs to know the
+/// name, type, and optionality of each column without hardcoding the models.
+pub fn schema() -> Vec<ModelSchema> {
+    Model::ALL
+        .iter()
+        .map(|model| ModelSchema {
+            model: model.name().to_string(),
+            columns: columns(model),
+        })
+        .collect()
+}
+
+
+/// The columns of a model in the same order as its struct members.
+pub(crate) fn columns(model: &Model) -> Vec<ColumnSchema> {
+    match model {
+        Model::Agent => Agent::columns(),
+        Model::Annotation => Annotation::columns(),
+        Model::Assembly => Assembly::columns(),
+        Model::Collecting => Collecting::columns(),
+        Model::DataProduct => DataProduct::columns(),
+        Model::Deposition => Deposition::columns(),
+        Model::Extraction => Extraction::columns(),
+        Model::Library => Library::columns(),
+        Model::Name => Name::columns(),
+        Model::Organism => Organism::columns(),
+        Model::ProjectAssembly => ProjectAssembly::columns(),
+        Model::ProjectMember => ProjectMember::columns(),
+        Model::Project => Project::columns(),
+        Model::Publication => Publication::columns(),
+        Model::SequencingRun => SequencingRun::columns(),
+        Model::Sequence => Sequence::columns(),
+        Model::Subsample => Subsample::columns(),
+        Model::Taxon => Taxon::columns(),
+        Model::Tissue => Tissue::columns(),
+    }
+}
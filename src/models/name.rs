@@ -1,26 +1,58 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use tracing::{info, instrument};
 
 use crate::dataset::{Dataset, Model};
 use crate::errors::TransformError;
+use crate::names::{parse_scientific_name, tidy_name};
 use crate::rdf::{self, NameField};
 use crate::resolver::{ResolvedRecords, Resolver};
 
 
-#[derive(Debug, Default, serde::Serialize, Hash, Eq, PartialEq)]
-pub struct Name {
+model_schema! {
+    #[derive(Debug, Default, serde::Serialize, serde::Deserialize, Hash, Eq, PartialEq)]
+    pub struct Name {
+        pub entity_id: String,
+        pub canonical_name: String,
+        pub scientific_name: String,
+        pub scientific_name_authorship: Option<String>,
+    }
+}
+
+
+/// A name that other records with the same normalised name were merged into.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct NameMerge {
     pub entity_id: String,
-    pub canonical_name: String,
-    pub scientific_name: String,
-    pub scientific_name_authorship: Option<String>,
+    /// The entity ids of the other records that now refer to the name
+    pub merged_ids: Vec<String>,
+    /// The distinct scientific names the records were written with
+    pub name_variants: Vec<String>,
 }
 
 
-#[instrument(skip_all)]
+/// The merges performed when deduplicating names.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct NameMergeReport {
+    pub merges: Vec<NameMerge>,
+    /// The entity id of the kept name for every merged entity id
+    pub remap: BTreeMap<String, String>,
+}
+
+
+/// Get all names with the duplicates merged. See [`merge_names`] for how duplicates are found.
 pub fn get_all(dataset: &Dataset) -> Result<Vec<Name>, TransformError> {
+    let (names, _report) = get_all_with_report(dataset)?;
+    Ok(names)
+}
+
+
+/// Get all names along with the merges performed when deduplicating them.
+#[instrument(skip_all)]
+pub fn get_all_with_report(dataset: &Dataset) -> Result<(Vec<Name>, NameMergeReport), TransformError> {
     let resolver = Resolver::new(dataset);
 
-    let schemas = dataset.scope(&[Model::Name]);
-    let schemas: Vec<&iref::Iri> = schemas.iter().map(|s| s.as_iri()).collect();
+    let schemas = dataset.scope_iris(&[Model::Name])?;
 
     info!("Resolving data");
     let data: ResolvedRecords<NameField> = resolver.resolve(rdf::Name::ALL, &schemas)?;
@@ -40,11 +72,259 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<Name>, TransformError> {
             }
         }
 
+        backfill_name_parts(&mut name);
         names.push(name);
     }
 
-    names.sort_by(|a, b| a.scientific_name.cmp(&b.scientific_name));
-    names.dedup();
+    Ok(merge_names(names))
+}
 
-    Ok(names)
+
+/// Merge the names that are the same once differences in formatting are ignored.
+///
+/// Names are tidied with [`tidy_name`] and then compared by their case folded canonical name
+/// and authorship. Only the letters, digits and parentheses of an authorship are compared so
+/// `Linnaeus, 1758` and `Linnaeus 1758` are the same while a recombination in parentheses isn't.
+/// A name without an authorship is merged into the one with the same canonical name that has an
+/// authorship, unless there is more than one of them to choose from.
+///
+/// The name that is kept is the first with an authorship when ordered by scientific name and
+/// then entity id, so the result doesn't depend on the order the records were resolved in.
+fn merge_names(names: Vec<Name>) -> (Vec<Name>, NameMergeReport) {
+    let mut merged = Vec::new();
+    let mut report = NameMergeReport::default();
+
+    // canonical name -> authorship -> names
+    let mut groups: BTreeMap<String, BTreeMap<String, Vec<Name>>> = BTreeMap::new();
+
+    for mut name in names {
+        name.scientific_name = tidy_name(&name.scientific_name);
+        name.canonical_name = tidy_name(&name.canonical_name);
+        name.scientific_name_authorship = name
+            .scientific_name_authorship
+            .map(|authorship| tidy_name(&authorship))
+            .filter(|authorship| !authorship.is_empty());
+
+        // there is nothing to compare a name without a canonical name by
+        if name.canonical_name.is_empty() {
+            merged.push(name);
+            continue;
+        }
+
+        let canonical = name.canonical_name.to_lowercase();
+        let authorship = name
+            .scientific_name_authorship
+            .as_deref()
+            .map(authorship_key)
+            .unwrap_or_default();
+        groups
+            .entry(canonical)
+            .or_default()
+            .entry(authorship)
+            .or_default()
+            .push(name);
+    }
+
+    for (_canonical, mut authorships) in groups {
+        if authorships.len() == 2
+            && let Some(unauthored) = authorships.remove("")
+            && let Some(authored) = authorships.values_mut().next()
+        {
+            authored.extend(unauthored);
+        }
+
+        for (_authorship, group) in authorships {
+            merged.extend(merge_group(group, &mut report));
+        }
+    }
+
+    merged.sort_by(|a, b| {
+        a.scientific_name
+            .cmp(&b.scientific_name)
+            .then_with(|| a.entity_id.cmp(&b.entity_id))
+    });
+    (merged, report)
+}
+
+
+/// Merge a group of duplicate names into the preferred one and record the merge.
+fn merge_group(mut group: Vec<Name>, report: &mut NameMergeReport) -> Option<Name> {
+    group.sort_by(|a, b| {
+        b.scientific_name_authorship
+            .is_some()
+            .cmp(&a.scientific_name_authorship.is_some())
+            .then_with(|| a.scientific_name.cmp(&b.scientific_name))
+            .then_with(|| a.entity_id.cmp(&b.entity_id))
+    });
+
+    let mut group = group.into_iter();
+    let kept = group.next()?;
+
+    let mut merged_ids = BTreeSet::new();
+    let mut variants = BTreeSet::from([kept.scientific_name.clone()]);
+
+    for name in group {
+        if name.entity_id != kept.entity_id {
+            report.remap.insert(name.entity_id.clone(), kept.entity_id.clone());
+            merged_ids.insert(name.entity_id);
+        }
+        variants.insert(name.scientific_name);
+    }
+
+    // records that were exact copies of each other aren't worth reporting
+    if !merged_ids.is_empty() || variants.len() > 1 {
+        report.merges.push(NameMerge {
+            entity_id: kept.entity_id.clone(),
+            merged_ids: merged_ids.into_iter().collect(),
+            name_variants: variants.into_iter().collect(),
+        });
+    }
+
+    Some(kept)
+}
+
+
+/// The parts of an authorship that are compared when looking for duplicates.
+fn authorship_key(authorship: &str) -> String {
+    authorship
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '(' || *c == ')')
+        .collect()
+}
+
+
+/// Fill in the canonical name and authorship from the full scientific name when they weren't provided.
+fn backfill_name_parts(name: &mut Name) {
+    if !name.canonical_name.is_empty() && name.scientific_name_authorship.is_some() {
+        return;
+    }
+
+    let parsed = parse_scientific_name(&name.scientific_name);
+
+    if name.canonical_name.is_empty() {
+        name.canonical_name = parsed.canonical;
+    }
+    if name.scientific_name_authorship.is_none() {
+        name.scientific_name_authorship = parsed.authorship;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(entity_id: &str, scientific_name: &str, canonical_name: &str, authorship: Option<&str>) -> Name {
+        Name {
+            entity_id: entity_id.to_string(),
+            canonical_name: canonical_name.to_string(),
+            scientific_name: scientific_name.to_string(),
+            scientific_name_authorship: authorship.map(str::to_string),
+        }
+    }
+
+    fn ids(names: &[Name]) -> Vec<&str> {
+        names.iter().map(|name| name.entity_id.as_str()).collect()
+    }
+
+    fn remap(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect()
+    }
+
+    fn names() -> Vec<Name> {
+        vec![
+            name("felis-1", "Felis catus Linnaeus, 1758", "Felis catus", Some("Linnaeus, 1758")),
+            name("felis-2", "Felis catus Linnaeus 1758", "Felis catus", Some("Linnaeus 1758")),
+            name("canis-1", "Canis lupus", "Canis lupus", None),
+            name("canis-2", "Canis lupus Linnaeus, 1758", "Canis lupus", Some("Linnaeus, 1758")),
+            name("mentha-1", "Mentha × piperita", "Mentha × piperita", None),
+            name("mentha-2", " Mentha  x piperita ", "Mentha x piperita", None),
+        ]
+    }
+
+    #[test]
+    fn merges_authorship_punctuation_variants() {
+        let (merged, report) = merge_names(names());
+
+        assert_eq!(ids(&merged), vec!["canis-2", "felis-2", "mentha-1"]);
+        assert_eq!(
+            report.merges[1],
+            NameMerge {
+                entity_id: "felis-2".to_string(),
+                merged_ids: vec!["felis-1".to_string()],
+                name_variants: vec![
+                    "Felis catus Linnaeus 1758".to_string(),
+                    "Felis catus Linnaeus, 1758".to_string()
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn merges_whitespace_and_hybrid_sign_variants() {
+        let (merged, report) = merge_names(names());
+
+        let mentha = merged.iter().find(|name| name.entity_id == "mentha-1").unwrap();
+        assert_eq!(mentha.scientific_name, "Mentha ×piperita");
+        assert_eq!(
+            report.merges[2],
+            NameMerge {
+                entity_id: "mentha-1".to_string(),
+                merged_ids: vec!["mentha-2".to_string()],
+                name_variants: vec!["Mentha ×piperita".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn keeps_recombinations_apart() {
+        let names = vec![
+            name("a", "Felis catus Linnaeus, 1758", "Felis catus", Some("Linnaeus, 1758")),
+            name("b", "Felis catus (Linnaeus, 1758)", "Felis catus", Some("(Linnaeus, 1758)")),
+        ];
+        let (merged, report) = merge_names(names);
+
+        assert_eq!(ids(&merged), vec!["b", "a"]);
+        assert!(report.merges.is_empty());
+        assert!(report.remap.is_empty());
+    }
+
+    #[test]
+    fn folds_unauthored_names_into_a_single_authored_name() {
+        let (merged, report) = merge_names(names());
+        assert!(!ids(&merged).contains(&"canis-1"));
+        assert_eq!(report.merges[0].entity_id, "canis-2");
+
+        // with two authorships to choose from the unauthored name is left on its own
+        let names = vec![
+            name("a", "Felis catus", "Felis catus", None),
+            name("b", "Felis catus Linnaeus, 1758", "Felis catus", Some("Linnaeus, 1758")),
+            name("c", "Felis catus (Linnaeus, 1758)", "Felis catus", Some("(Linnaeus, 1758)")),
+        ];
+        let (merged, report) = merge_names(names);
+        assert_eq!(ids(&merged), vec!["a", "c", "b"]);
+        assert!(report.remap.is_empty());
+    }
+
+    #[test]
+    fn reports_the_kept_name_for_every_merged_name() {
+        let (_merged, report) = merge_names(names());
+        assert_eq!(report.remap, remap(&[("canis-1", "canis-2"), ("felis-1", "felis-2"), ("mentha-2", "mentha-1")]));
+    }
+
+    #[test]
+    fn merging_does_not_depend_on_the_order_of_the_names() {
+        let (merged, report) = merge_names(names());
+
+        let mut reversed = names();
+        reversed.reverse();
+        let (reversed_merged, reversed_report) = merge_names(reversed);
+
+        assert_eq!(merged, reversed_merged);
+        assert_eq!(report, reversed_report);
+    }
 }
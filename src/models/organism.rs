@@ -2,48 +2,52 @@ use tracing::{info, instrument};
 
 use crate::dataset::{Dataset, Model};
 use crate::errors::TransformError;
-use crate::rdf::{self, OrganismField};
+use crate::models::publications;
+use crate::rdf::{self, Condition, Literal, OrganismField};
 use crate::resolver::{ResolvedRecords, Resolver};
-
-
-#[derive(Debug, Default, serde::Serialize)]
-pub struct Organism {
-    pub entity_id: String,
-    pub organism_id: Option<String>,
-    pub publication_id: Option<String>,
-    pub curator_id: Option<String>,
-
-    pub scientific_name: Option<String>,
-    pub sex: Option<String>,
-    pub genotypic_sex: Option<String>,
-    pub phenotypic_sex: Option<String>,
-    pub life_stage: Option<String>,
-    pub reproductive_condition: Option<String>,
-    pub behavior: Option<String>,
-    pub live_state: Option<String>,
-    pub remarks: Option<String>,
-
-    pub identified_by: Option<String>,
-    pub identification_date: Option<String>,
-    pub disposition: Option<String>,
-    pub first_observed_at: Option<String>,
-    pub last_known_alive_at: Option<String>,
-
-    pub biome: Option<String>,
-    pub habitat: Option<String>,
-    pub bioregion: Option<String>,
-    pub ibra_imcra: Option<String>,
-
-    pub latitude: Option<String>,
-    pub longitude: Option<String>,
-    pub coordinate_system: Option<String>,
-    pub location_source: Option<String>,
-    pub holding: Option<String>,
-    pub holding_id: Option<String>,
-    pub holding_permit: Option<String>,
-
-    pub created_at: Option<String>,
-    pub updated_at: Option<String>,
+use crate::validation::orcid;
+
+
+model_schema! {
+    #[derive(Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct Organism {
+        pub entity_id: String,
+        pub organism_id: Option<String>,
+        pub publication_id: Option<String>,
+        pub curator_id: Option<String>,
+
+        pub scientific_name: Option<String>,
+        pub sex: Option<String>,
+        pub genotypic_sex: Option<String>,
+        pub phenotypic_sex: Option<String>,
+        pub life_stage: Option<String>,
+        pub reproductive_condition: Option<String>,
+        pub behavior: Option<String>,
+        pub live_state: Option<String>,
+        pub remarks: Option<String>,
+
+        pub identified_by: Option<String>,
+        pub identification_date: Option<String>,
+        pub disposition: Option<String>,
+        pub first_observed_at: Option<String>,
+        pub last_known_alive_at: Option<String>,
+
+        pub biome: Option<String>,
+        pub habitat: Option<String>,
+        pub bioregion: Option<String>,
+        pub ibra_imcra: Option<String>,
+
+        pub latitude: Option<String>,
+        pub longitude: Option<String>,
+        pub coordinate_system: Option<String>,
+        pub location_source: Option<String>,
+        pub holding: Option<String>,
+        pub holding_id: Option<String>,
+        pub holding_permit: Option<String>,
+
+        pub created_at: Option<String>,
+        pub updated_at: Option<String>,
+    }
 }
 
 
@@ -51,17 +55,64 @@ pub struct Organism {
 pub fn get_all(dataset: &Dataset) -> Result<Vec<Organism>, TransformError> {
     let resolver = Resolver::new(dataset);
 
-    let schemas = dataset.scope(&[Model::Organism]);
-    let schemas: Vec<&iref::Iri> = schemas.iter().map(|s| s.as_iri()).collect();
+    let schemas = dataset.scope_iris(&[Model::Organism])?;
 
     info!("Resolving data");
     let data: ResolvedRecords<OrganismField> = resolver.resolve(rdf::Organism::ALL, &schemas)?;
+    from_resolved(data)
+}
+
+
+/// Get all organisms in entity id order and pass them to `f` in chunks of `chunk_size`.
+///
+/// Only one chunk of organisms is resolved and built at a time, see [`Resolver::resolve_chunked`].
+#[instrument(skip_all)]
+pub fn get_chunked<F>(dataset: &Dataset, chunk_size: usize, mut f: F) -> Result<(), TransformError>
+where
+    F: FnMut(Vec<Organism>) -> Result<(), TransformError>,
+{
+    let resolver = Resolver::new(dataset);
+
+    let schemas = dataset.scope_iris(&[Model::Organism])?;
+
+    info!("Resolving data");
+    resolver.resolve_chunked(rdf::Organism::ALL, &schemas, chunk_size, |data: Vec<(_, Vec<OrganismField>)>| {
+        f(from_resolved(data)?)
+    })
+}
 
 
+/// Get all organisms with the scientific name.
+///
+/// The name must match the resolved `scientific_name` exactly.
+#[instrument(skip_all)]
+pub fn get_by_scientific_name(dataset: &Dataset, name: &str) -> Result<Vec<Organism>, TransformError> {
+    let resolver = Resolver::new(dataset);
+
+    let schemas = dataset.scope_iris(&[Model::Organism])?;
+    let conditions = [(rdf::Organism::ScientificName.into(), Condition::Is(Literal::String(name.to_string())))];
+
+    info!(name, "Resolving data");
+    let data: ResolvedRecords<OrganismField> =
+        resolver.resolve_with_conditions(rdf::Organism::ALL, &schemas, &conditions)?;
+
+    // records without a scientific name pass the condition as there is nothing to check
+    let mut records = from_resolved(data)?;
+    records.retain(|record| record.scientific_name.as_deref() == Some(name));
+    Ok(records)
+}
+
+
+fn from_resolved<I>(data: I) -> Result<Vec<Organism>, TransformError>
+where
+    I: IntoIterator<Item = (Literal, Vec<OrganismField>)>,
+{
     let mut records = Vec::new();
 
     for (_idx, fields) in data {
         let mut record = Organism::default();
+        let mut doi = None;
+        let mut citation = None;
 
         for field in fields {
             match field {
@@ -96,16 +147,22 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<Organism>, TransformError> {
                 OrganismField::UpdatedAt(val) => record.updated_at = Some(val),
 
                 OrganismField::PublicationEntityId(val) => record.publication_id = Some(val),
+                OrganismField::CuratorOrcid(val) => record.curator_id = orcid::normalise_or_warn(val),
+
+                // used to derive the publication id when it isn't mapped
+                OrganismField::Doi(val) => doi = Some(val),
+                OrganismField::Citation(val) => citation = Some(val),
 
-                OrganismField::Doi(_) => {}
-                OrganismField::Citation(_) => {}
                 OrganismField::Curator(_) => {}
-                OrganismField::CuratorOrcid(_) => {}
                 OrganismField::CanonicalName(_) => {}
                 OrganismField::ScientificNameAuthorship(_) => {}
             }
         }
 
+        if record.publication_id.is_none() {
+            record.publication_id = publications::derive_entity_id(doi.as_deref(), citation.as_deref(), None);
+        }
+
         records.push(record);
     }
 
@@ -0,0 +1,121 @@
+use std::collections::{HashMap, HashSet};
+
+use tracing::{info, instrument};
+
+use crate::dataset::{Dataset, Model};
+use crate::errors::TransformError;
+use crate::rdf::{self, AssemblyField, ProjectAssemblyField, ProjectField};
+use crate::resolver::{ResolvedRecords, Resolver};
+
+
+model_schema! {
+    /// A link between a project and an assembly that belongs to it.
+    #[derive(Debug, Default, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+    pub struct ProjectAssembly {
+        pub project_id: String,
+        pub assembly_entity_id: String,
+    }
+}
+
+
+/// Get all assemblies belonging to a project.
+///
+/// Links declared explicitly by a source that transforms into the project assemblies model
+/// are included as is. Projects and assemblies are also joined on their scientific name
+/// since projects that target a species will produce assemblies for that species.
+#[instrument(skip_all)]
+pub fn get_all(dataset: &Dataset) -> Result<Vec<ProjectAssembly>, TransformError> {
+    let resolver = Resolver::new(dataset);
+
+    let mut links = Vec::new();
+    let mut seen = HashSet::new();
+
+
+    let schemas = dataset.scope_iris(&[Model::ProjectAssembly])?;
+
+    info!("Resolving declared links");
+    let data: ResolvedRecords<ProjectAssemblyField> = resolver.resolve(rdf::ProjectAssembly::ALL, &schemas)?;
+
+    for (_idx, fields) in data {
+        let mut project_id = None;
+        let mut assembly_entity_id = None;
+
+        for field in fields {
+            match field {
+                ProjectAssemblyField::ProjectId(val) => project_id = Some(val),
+                ProjectAssemblyField::AssemblyEntityId(val) => assembly_entity_id = Some(val),
+                ProjectAssemblyField::EntityId(_) => {}
+            }
+        }
+
+        if let (Some(project_id), Some(assembly_entity_id)) = (project_id, assembly_entity_id) {
+            let link = ProjectAssembly {
+                project_id,
+                assembly_entity_id,
+            };
+            if seen.insert(link.clone()) {
+                links.push(link);
+            }
+        }
+    }
+
+
+    let schemas = dataset.scope_iris(&[Model::Project])?;
+
+    info!("Resolving project names");
+    let data: ResolvedRecords<ProjectField> =
+        resolver.resolve(&[rdf::Project::ProjectId, rdf::Project::ScientificName], &schemas)?;
+
+    let mut projects: HashMap<String, Vec<String>> = HashMap::new();
+    for (_idx, fields) in data {
+        let mut project_id = None;
+        let mut scientific_name = None;
+
+        for field in fields {
+            match field {
+                ProjectField::ProjectId(val) => project_id = Some(val),
+                ProjectField::ScientificName(val) => scientific_name = Some(val),
+                _ => {}
+            }
+        }
+
+        if let (Some(project_id), Some(scientific_name)) = (project_id, scientific_name) {
+            projects.entry(scientific_name).or_default().push(project_id);
+        }
+    }
+
+
+    let schemas = dataset.scope_iris(&[Model::Assembly])?;
+
+    info!("Resolving assembly names");
+    let data: ResolvedRecords<AssemblyField> =
+        resolver.resolve(&[rdf::Assembly::EntityId, rdf::Assembly::ScientificName], &schemas)?;
+
+    for (_idx, fields) in data {
+        let mut entity_id = None;
+        let mut scientific_name = None;
+
+        for field in fields {
+            match field {
+                AssemblyField::EntityId(val) => entity_id = Some(val),
+                AssemblyField::ScientificName(val) => scientific_name = Some(val),
+                _ => {}
+            }
+        }
+
+        let project_ids = scientific_name.as_ref().and_then(|name| projects.get(name));
+        if let (Some(entity_id), Some(project_ids)) = (entity_id, project_ids) {
+            for project_id in project_ids {
+                let link = ProjectAssembly {
+                    project_id: project_id.clone(),
+                    assembly_entity_id: entity_id.clone(),
+                };
+                if seen.insert(link.clone()) {
+                    links.push(link);
+                }
+            }
+        }
+    }
+
+    Ok(links)
+}
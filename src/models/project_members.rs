@@ -4,15 +4,18 @@ use crate::dataset::{Dataset, Model};
 use crate::errors::TransformError;
 use crate::rdf::{self, ProjectMemberField};
 use crate::resolver::{ResolvedRecords, Resolver};
+use crate::validation::orcid;
 
 
-#[derive(Debug, Default, serde::Serialize)]
-pub struct ProjectMember {
-    pub entity_id: String,
-    pub project_id: Option<String>,
-    pub name: Option<String>,
-    pub orcid: Option<String>,
-    pub organisation: Option<String>,
+model_schema! {
+    #[derive(Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct ProjectMember {
+        pub entity_id: String,
+        pub project_id: Option<String>,
+        pub name: Option<String>,
+        pub orcid: Option<String>,
+        pub organisation: Option<String>,
+    }
 }
 
 
@@ -20,8 +23,7 @@ pub struct ProjectMember {
 pub fn get_all(dataset: &Dataset) -> Result<Vec<ProjectMember>, TransformError> {
     let resolver = Resolver::new(dataset);
 
-    let schemas = dataset.scope(&[Model::ProjectMember]);
-    let schemas: Vec<&iref::Iri> = schemas.iter().map(|s| s.as_iri()).collect();
+    let schemas = dataset.scope_iris(&[Model::ProjectMember])?;
 
     info!("Resolving data");
     let data: ResolvedRecords<ProjectMemberField> = resolver.resolve(rdf::ProjectMember::ALL, &schemas)?;
@@ -37,7 +39,7 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<ProjectMember>, TransformError>
                 ProjectMemberField::EntityId(val) => member.entity_id = val,
                 ProjectMemberField::ProjectId(val) => member.project_id = Some(val),
                 ProjectMemberField::Name(val) => member.name = Some(val),
-                ProjectMemberField::Orcid(val) => member.orcid = Some(val),
+                ProjectMemberField::Orcid(val) => member.orcid = orcid::normalise_or_warn(val),
                 ProjectMemberField::Organisation(val) => member.organisation = Some(val),
             }
         }
@@ -4,25 +4,30 @@ use crate::dataset::{Dataset, Model};
 use crate::errors::TransformError;
 use crate::rdf::{self, ProjectField};
 use crate::resolver::{ResolvedRecords, Resolver};
+use crate::validation::orcid;
 
 
-#[derive(Debug, Default, serde::Serialize)]
-pub struct Project {
-    pub entity_id: String,
-    pub project_id: Option<String>,
+model_schema! {
+    #[derive(Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct Project {
+        pub entity_id: String,
+        pub project_id: Option<String>,
 
-    pub scientific_name: Option<String>,
-    pub initiative: Option<String>,
-    pub initiative_theme: Option<String>,
-    pub title: Option<String>,
-    pub description: Option<String>,
-    pub data_context: Option<String>,
-    pub data_types: Option<String>,
-    pub data_assay_types: Option<String>,
-    pub partners: Option<String>,
+        pub scientific_name: Option<String>,
+        pub initiative: Option<String>,
+        pub initiative_theme: Option<String>,
+        pub title: Option<String>,
+        pub description: Option<String>,
+        pub data_context: Option<String>,
+        #[serde(default, deserialize_with = "crate::models::deserialize_list")]
+        pub data_types: Vec<String>,
+        pub data_assay_types: Option<String>,
+        #[serde(default, deserialize_with = "crate::models::deserialize_list")]
+        pub partners: Vec<String>,
 
-    pub curator: Option<String>,
-    pub curator_orcid: Option<String>,
+        pub curator: Option<String>,
+        pub curator_orcid: Option<String>,
+    }
 }
 
 
@@ -30,8 +35,7 @@ pub struct Project {
 pub fn get_all(dataset: &Dataset) -> Result<Vec<Project>, TransformError> {
     let resolver = Resolver::new(dataset);
 
-    let schemas = dataset.scope(&[Model::Project]);
-    let schemas: Vec<&iref::Iri> = schemas.iter().map(|s| s.as_iri()).collect();
+    let schemas = dataset.scope_iris(&[Model::Project])?;
 
     info!("Resolving data");
     let data: ResolvedRecords<ProjectField> = resolver.resolve(rdf::Project::ALL, &schemas)?;
@@ -52,11 +56,11 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<Project>, TransformError> {
                 ProjectField::Title(val) => project.title = Some(val),
                 ProjectField::Description(val) => project.description = Some(val),
                 ProjectField::DataContext(val) => project.data_context = Some(val),
-                ProjectField::DataTypes(val) => project.data_types = Some(val),
+                ProjectField::DataTypes(val) => project.data_types.push(val),
                 ProjectField::DataAssayTypes(val) => project.data_assay_types = Some(val),
-                ProjectField::Partners(val) => project.partners = Some(val),
+                ProjectField::Partners(val) => project.partners.push(val),
                 ProjectField::Curator(val) => project.curator = Some(val),
-                ProjectField::CuratorOrcid(val) => project.curator_orcid = Some(val),
+                ProjectField::CuratorOrcid(val) => project.curator_orcid = orcid::normalise_or_warn(val),
             }
         }
 
@@ -1,34 +1,167 @@
-use tracing::{info, instrument};
+use std::collections::HashMap;
+
+use tracing::{info, instrument, warn};
 
 use crate::dataset::{Dataset, Model};
 use crate::errors::TransformError;
-use crate::rdf::{self, PublicationField};
+use crate::rdf::{self, AssemblyField, DataProductField, ExtractionField, OrganismField, PublicationField};
 use crate::resolver::{ResolvedRecords, Resolver};
+use crate::validation::doi;
+
+
+model_schema! {
+    #[derive(Debug, Default, serde::Serialize, serde::Deserialize, Hash, Eq, PartialEq)]
+    pub struct Publication {
+        pub entity_id: Option<String>,
+        pub title: Option<String>,
+        pub authors: Option<String>,
+        pub published_year: Option<String>,
+        pub published_date: Option<String>,
+        pub language: Option<String>,
+        pub publisher: Option<String>,
+        pub doi: Option<String>,
+        pub publication_type: Option<String>,
+
+        pub citation: Option<String>,
+        pub source_url: Option<String>,
+    }
+}
+
+
+impl Publication {
+    /// Fill any missing fields with the values from another record of the same publication.
+    fn merge(&mut self, other: Publication) {
+        self.entity_id = self.entity_id.take().or(other.entity_id);
+        self.title = self.title.take().or(other.title);
+        self.authors = self.authors.take().or(other.authors);
+        self.published_year = self.published_year.take().or(other.published_year);
+        self.published_date = self.published_date.take().or(other.published_date);
+        self.language = self.language.take().or(other.language);
+        self.publisher = self.publisher.take().or(other.publisher);
+        self.doi = self.doi.take().or(other.doi);
+        self.publication_type = self.publication_type.take().or(other.publication_type);
+        self.citation = self.citation.take().or(other.citation);
+        self.source_url = self.source_url.take().or(other.source_url);
+    }
+
+    /// Normalise the DOI so that the same DOI written differently is equal.
+    ///
+    /// An invalid DOI is removed, and used as the citation if there isn't one so the value
+    /// is still available.
+    fn normalise_doi(&mut self) {
+        let Some(value) = self.doi.take()
+        else {
+            return;
+        };
+
+        match doi::normalise(&value) {
+            Ok(doi) => self.doi = Some(doi),
+            Err(err) => {
+                warn!(entity_id = ?self.entity_id, %err, "Dropping invalid publication DOI");
+                self.citation.get_or_insert(value);
+            }
+        }
+    }
+
+    /// Use the derived entity id if the publication doesn't have one. See [`derive_entity_id`].
+    fn fill_entity_id(&mut self) {
+        if self.entity_id.as_ref().is_some_and(|id| !id.trim().is_empty()) {
+            return;
+        }
+        self.entity_id = derive_entity_id(self.doi.as_deref(), self.citation.as_deref(), self.source_url.as_deref());
+    }
+}
 
 
-#[derive(Debug, Default, serde::Serialize, Hash, Eq, PartialEq)]
-pub struct Publication {
-    pub entity_id: Option<String>,
-    pub title: Option<String>,
-    pub authors: Option<String>,
-    pub published_year: Option<String>,
-    pub published_date: Option<String>,
-    pub language: Option<String>,
-    pub publisher: Option<String>,
-    pub doi: Option<String>,
-    pub publication_type: Option<String>,
-
-    pub citation: Option<String>,
-    pub source_url: Option<String>,
+/// Derive the entity id of a publication from the first of its DOI, citation, or source URL.
+///
+/// This is used for publications that are cited or loaded without an entity id. Every model
+/// that references a publication derives it the same way so the reference and the publication
+/// agree. The DOI is normalised first and an invalid DOI is used as the citation if there isn't
+/// one, which is the same as the publication model. The ids are prefixed by what they were
+/// derived from so a citation can never collide with a DOI.
+pub fn derive_entity_id(doi: Option<&str>, citation: Option<&str>, source_url: Option<&str>) -> Option<String> {
+    let non_empty = |value: Option<&str>| value.map(str::trim).filter(|value| !value.is_empty());
+
+    let mut doi = non_empty(doi).map(String::from);
+    let mut citation = non_empty(citation).map(String::from);
+    if let Some(value) = doi.take() {
+        match doi::normalise(&value) {
+            Ok(normalised) => doi = Some(normalised),
+            Err(_) => citation = citation.or(Some(value)),
+        }
+    }
+
+    if let Some(doi) = doi {
+        return Some(format!("doi:{doi}"));
+    }
+    if let Some(citation) = citation {
+        return Some(format!("citation:{citation}"));
+    }
+    non_empty(source_url).map(|url| format!("url:{url}"))
 }
 
 
+/// Get all publications from the publication sources and the models that cite them.
+///
+/// Organisms, extractions, and assemblies can record the publication they came from. The
+/// same publication is often cited by many records so they are merged with the full
+/// publication records by `entity_id`, keeping the first value found for each field.
+///
+/// DOIs are normalised first and publications with the same DOI are then merged as well,
+/// since a DOI written differently in each source otherwise leads to different entity ids.
+/// Publications without an entity id get one from [`derive_entity_id`].
 #[instrument(skip_all)]
 pub fn get_all(dataset: &Dataset) -> Result<Vec<Publication>, TransformError> {
+    let mut publications = get_from_publications(dataset)?;
+    publications.extend(get_from_organisms(dataset)?);
+    publications.extend(get_from_extractions(dataset)?);
+    publications.extend(get_from_assemblies(dataset)?);
+    publications.extend(get_from_data_products(dataset)?);
+
+    for publication in publications.iter_mut() {
+        publication.normalise_doi();
+    }
+
+    // the sort is stable so the publication sources stay ahead of the citing models
+    publications.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
+    publications.dedup_by(|duplicate, kept| match (&duplicate.entity_id, &kept.entity_id) {
+        (Some(a), Some(b)) if a == b => {
+            kept.merge(std::mem::take(duplicate));
+            true
+        }
+        _ => duplicate == kept,
+    });
+
+    let mut merged: Vec<Publication> = Vec::with_capacity(publications.len());
+    let mut by_doi: HashMap<String, usize> = HashMap::new();
+    for publication in publications {
+        let same_doi = publication.doi.as_ref().and_then(|doi| by_doi.get(doi)).copied();
+
+        match same_doi {
+            Some(idx) => {
+                let kept = &mut merged[idx];
+                warn!(kept = ?kept.entity_id, merged = ?publication.entity_id, doi = ?kept.doi, "Merging same DOI");
+                kept.merge(publication);
+            }
+            None => {
+                if let Some(doi) = &publication.doi {
+                    by_doi.insert(doi.clone(), merged.len());
+                }
+                merged.push(publication);
+            }
+        }
+    }
+
+    Ok(merged)
+}
+
+
+#[instrument(skip_all)]
+pub fn get_from_publications(dataset: &Dataset) -> Result<Vec<Publication>, TransformError> {
     let resolver = Resolver::new(dataset);
 
-    let schemas = dataset.scope(&[Model::Publication]);
-    let schemas: Vec<&iref::Iri> = schemas.iter().map(|s| s.as_iri()).collect();
+    let schemas = dataset.scope_iris(&[Model::Publication])?;
 
     info!("Resolving data");
     let data: ResolvedRecords<PublicationField> = resolver.resolve(rdf::Publication::ALL, &schemas)?;
@@ -54,11 +187,246 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<Publication>, TransformError> {
             }
         }
 
+        publication.fill_entity_id();
         publications.push(publication);
     }
 
-    publications.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
-    publications.dedup();
+    Ok(publications)
+}
+
+
+/// Get the publications cited by organisms.
+#[instrument(skip_all)]
+pub fn get_from_organisms(dataset: &Dataset) -> Result<Vec<Publication>, TransformError> {
+    let resolver = Resolver::new(dataset);
+
+    let schemas = dataset.scope_iris(&[Model::Organism])?;
+    let fields = &[
+        rdf::Organism::PublicationEntityId,
+        rdf::Organism::Doi,
+        rdf::Organism::Citation,
+    ];
+
+    info!("Resolving organism publications");
+    let data: ResolvedRecords<OrganismField> = resolver.resolve(fields, &schemas)?;
+
+    let mut publications = Vec::new();
+    for (_entity_id, fields) in data {
+        let mut publication = Publication::default();
+
+        for field in fields {
+            match field {
+                OrganismField::PublicationEntityId(val) => publication.entity_id = Some(val),
+                OrganismField::Doi(val) => publication.doi = Some(val),
+                OrganismField::Citation(val) => publication.citation = Some(val),
+                _ => {}
+            }
+        }
+
+        publication.fill_entity_id();
+        if publication.entity_id.is_some() {
+            publications.push(publication);
+        }
+    }
 
     Ok(publications)
 }
+
+
+/// Get the publications cited by extractions.
+#[instrument(skip_all)]
+pub fn get_from_extractions(dataset: &Dataset) -> Result<Vec<Publication>, TransformError> {
+    let resolver = Resolver::new(dataset);
+
+    let schemas = dataset.scope_iris(&[Model::Extraction])?;
+    let fields = &[
+        rdf::Extraction::PublicationEntityId,
+        rdf::Extraction::Doi,
+        rdf::Extraction::Citation,
+    ];
+
+    info!("Resolving extraction publications");
+    let data: ResolvedRecords<ExtractionField> = resolver.resolve(fields, &schemas)?;
+
+    let mut publications = Vec::new();
+    for (_entity_id, fields) in data {
+        let mut publication = Publication::default();
+
+        for field in fields {
+            match field {
+                ExtractionField::PublicationEntityId(val) => publication.entity_id = Some(val),
+                ExtractionField::Doi(val) => publication.doi = Some(val),
+                ExtractionField::Citation(val) => publication.citation = Some(val),
+                _ => {}
+            }
+        }
+
+        publication.fill_entity_id();
+        if publication.entity_id.is_some() {
+            publications.push(publication);
+        }
+    }
+
+    Ok(publications)
+}
+
+
+/// Get the publications cited by assemblies.
+#[instrument(skip_all)]
+pub fn get_from_assemblies(dataset: &Dataset) -> Result<Vec<Publication>, TransformError> {
+    let resolver = Resolver::new(dataset);
+
+    let schemas = dataset.scope_iris(&[Model::Assembly])?;
+    let fields = &[
+        rdf::Assembly::PublicationEntityId,
+        rdf::Assembly::Doi,
+        rdf::Assembly::Citation,
+    ];
+
+    info!("Resolving assembly publications");
+    let data: ResolvedRecords<AssemblyField> = resolver.resolve(fields, &schemas)?;
+
+    let mut publications = Vec::new();
+    for (_entity_id, fields) in data {
+        let mut publication = Publication::default();
+
+        for field in fields {
+            match field {
+                AssemblyField::PublicationEntityId(val) => publication.entity_id = Some(val),
+                AssemblyField::Doi(val) => publication.doi = Some(val),
+                AssemblyField::Citation(val) => publication.citation = Some(val),
+                _ => {}
+            }
+        }
+
+        publication.fill_entity_id();
+        if publication.entity_id.is_some() {
+            publications.push(publication);
+        }
+    }
+
+    Ok(publications)
+}
+
+
+/// Get the publications cited by data products.
+#[instrument(skip_all)]
+pub fn get_from_data_products(dataset: &Dataset) -> Result<Vec<Publication>, TransformError> {
+    let resolver = Resolver::new(dataset);
+
+    let schemas = dataset.scope_iris(&[Model::DataProduct])?;
+    let fields = &[
+        rdf::DataProduct::PublicationEntityId,
+        rdf::DataProduct::Citation,
+        rdf::DataProduct::SourceUrl,
+    ];
+
+    info!("Resolving data product publications");
+    let data: ResolvedRecords<DataProductField> = resolver.resolve(fields, &schemas)?;
+
+    let mut publications = Vec::new();
+    for (_entity_id, fields) in data {
+        let mut publication = Publication::default();
+
+        for field in fields {
+            match field {
+                DataProductField::PublicationEntityId(val) => publication.entity_id = Some(val),
+                DataProductField::Citation(val) => publication.citation = Some(val),
+                DataProductField::SourceUrl(val) => publication.source_url = Some(val),
+                _ => {}
+            }
+        }
+
+        publication.fill_entity_id();
+        if publication.entity_id.is_some() {
+            publications.push(publication);
+        }
+    }
+
+    Ok(publications)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{SYNTHETIC_SCHEMA, load_source};
+
+    const MAPPINGS: &str = r#"
+        fields:doi mapping:same col:doi .
+        fields:title mapping:same col:title .
+        fields:publisher mapping:same col:publisher .
+    "#;
+
+    #[test]
+    fn variants_of_a_doi_collapse_into_one_publication() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let rows: &[&[(&str, &str)]] = &[
+            &[("doi", "doi:10.1000/XYZ123"), ("title", "A genome of the domestic cat")],
+            &[("doi", "https://doi.org/10.1000/xyz123.")],
+            &[("doi", "10.1000/xyz123"), ("publisher", "CSIRO Publishing")],
+        ];
+        load_source(&mut dataset, &Model::Publication, "publications.csv", MAPPINGS, rows).unwrap();
+
+        let publications = get_all(&dataset).unwrap();
+        assert_eq!(publications.len(), 1);
+
+        let publication = &publications[0];
+        assert_eq!(publication.entity_id.as_deref(), Some("doi:10.1000/xyz123"));
+        assert_eq!(publication.doi.as_deref(), Some("10.1000/xyz123"));
+        assert_eq!(publication.title.as_deref(), Some("A genome of the domestic cat"));
+        assert_eq!(publication.publisher.as_deref(), Some("CSIRO Publishing"));
+    }
+
+    #[test]
+    fn invalid_dois_are_kept_as_the_citation() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let rows: &[&[(&str, &str)]] = &[&[("doi", "see supplementary material")]];
+        load_source(&mut dataset, &Model::Publication, "publications.csv", MAPPINGS, rows).unwrap();
+
+        let publications = get_all(&dataset).unwrap();
+        assert_eq!(publications.len(), 1);
+        assert_eq!(publications[0].doi, None);
+        assert_eq!(publications[0].citation.as_deref(), Some("see supplementary material"));
+        assert_eq!(publications[0].entity_id.as_deref(), Some("citation:see supplementary material"));
+    }
+
+    #[test]
+    fn derive_entity_id_prefers_the_normalised_doi() {
+        let citation = Some("Smith (2020)");
+        assert_eq!(derive_entity_id(Some("DOI:10.1000/ABC"), citation, None).as_deref(), Some("doi:10.1000/abc"));
+        assert_eq!(derive_entity_id(Some("bad doi"), citation, None).as_deref(), Some("citation:Smith (2020)"));
+        assert_eq!(derive_entity_id(Some("bad doi"), None, None).as_deref(), Some("citation:bad doi"));
+        let url = Some("https://example.org");
+        assert_eq!(derive_entity_id(Some(" "), None, url).as_deref(), Some("url:https://example.org"));
+        assert_eq!(derive_entity_id(None, None, None), None);
+    }
+
+    #[test]
+    fn organism_publication_ids_match_the_publication_derived_from_a_doi() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:same col:id .
+            fields:doi mapping:same col:doi .
+        "#;
+        let rows: &[&[(&str, &str)]] = &[&[("id", "o1"), ("doi", "https://doi.org/10.1000/XYZ123")]];
+        load_source(&mut dataset, &Model::Organism, "organisms.csv", mappings, rows).unwrap();
+
+        let organisms = crate::models::organism::get_all(&dataset).unwrap();
+        let publications = get_all(&dataset).unwrap();
+
+        assert_eq!(publications.len(), 1);
+        assert_eq!(publications[0].doi.as_deref(), Some("10.1000/xyz123"));
+        assert_eq!(organisms[0].publication_id, publications[0].entity_id);
+        assert!(organisms[0].publication_id.is_some());
+
+        // the full publication record with another form of the doi is the same entity
+        let rows: &[&[(&str, &str)]] = &[&[("doi", "doi:10.1000/xyz123"), ("title", "A genome of the domestic cat")]];
+        load_source(&mut dataset, &Model::Publication, "publications.csv", MAPPINGS, rows).unwrap();
+
+        let publications = get_all(&dataset).unwrap();
+        assert_eq!(publications.len(), 1);
+        assert_eq!(organisms[0].publication_id, publications[0].entity_id);
+        assert_eq!(publications[0].title.as_deref(), Some("A genome of the domestic cat"));
+    }
+}
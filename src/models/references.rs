@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+
+use tracing::{info, instrument};
+
+use crate::dataset::{Dataset, Model};
+use crate::errors::TransformError;
+use crate::models::{
+    agent,
+    annotation,
+    assembly,
+    collecting,
+    data_products,
+    deposition,
+    extraction,
+    library,
+    organism,
+    publications,
+    sequencing_run,
+    subsample,
+    tissue,
+};
+
+
+/// A reference from a record to a record of another model that doesn't exist.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct DanglingRef {
+    /// The model of the record with the reference
+    pub model: String,
+    pub entity_id: String,
+    pub field: String,
+    /// The model that doesn't have a record for the value
+    pub target: String,
+    pub value: String,
+}
+
+
+/// The ids of the records in a model that references can point to.
+struct Targets {
+    model: Model,
+    ids: HashSet<String>,
+}
+
+impl Targets {
+    /// Collect the entity id and the model's own identifier of every record.
+    ///
+    /// References are usually made with the identifier from the source, eg. the `library_id`,
+    /// but can also be the derived entity id so both are accepted.
+    fn new<'a>(model: Model, records: impl IntoIterator<Item = (&'a String, Option<&'a String>)>) -> Targets {
+        let mut ids = HashSet::new();
+        for (entity_id, id) in records {
+            ids.insert(entity_id.clone());
+            ids.extend(id.cloned());
+        }
+        Targets { model, ids }
+    }
+}
+
+
+/// Collects the references that don't have a target record.
+#[derive(Default)]
+struct Verifier {
+    dangling: Vec<DanglingRef>,
+}
+
+impl Verifier {
+    fn check(&mut self, model: Model, entity_id: &str, field: &str, value: Option<&String>, targets: &Targets) {
+        let Some(value) = value.filter(|value| !value.trim().is_empty())
+        else {
+            return;
+        };
+
+        if !targets.ids.contains(value) {
+            self.dangling.push(DanglingRef {
+                model: model.name().to_string(),
+                entity_id: entity_id.to_string(),
+                field: field.to_string(),
+                target: targets.model.name().to_string(),
+                value: value.clone(),
+            });
+        }
+    }
+}
+
+
+/// Find the references between models that point to a record that doesn't exist.
+///
+/// Every model is transformed and the fields that link to another model are checked
+/// against the ids of that model's records. Subsamples link to tissues, extractions
+/// to subsamples, libraries to extractions, sequencing runs and assemblies to libraries,
+/// annotations and depositions to assemblies, and the agent and publication ids of any
+/// model to the agents and publications.
+///
+/// The references are ordered by the referencing model and then by the entity id of the
+/// referencing record, with the fields of a record in the order above, so the report is
+/// stable for the same data.
+#[instrument(skip_all)]
+pub fn verify_references(dataset: &Dataset) -> Result<Vec<DanglingRef>, TransformError> {
+    info!("Transforming models");
+    let tissues = tissue::get_all(dataset)?;
+    let subsamples = subsample::get_all(dataset)?;
+    let extractions = extraction::get_all(dataset)?;
+    let libraries = library::get_all(dataset)?;
+    let runs = sequencing_run::get_all(dataset)?;
+    let assemblies = assembly::get_all(dataset)?;
+    let annotations = annotation::get_all(dataset)?;
+    let depositions = deposition::get_all(dataset)?;
+    let organisms = organism::get_all(dataset)?;
+    let collecting = collecting::get_all(dataset)?;
+    let products = data_products::get_all(dataset)?;
+    let agents = agent::get_all(dataset)?;
+    let publications = publications::get_all(dataset)?;
+
+    info!("Checking references");
+    let tissue_ids = Targets::new(Model::Tissue, tissues.iter().map(|r| (&r.entity_id, r.tissue_id.as_ref())));
+    let subsample_ids =
+        Targets::new(Model::Subsample, subsamples.iter().map(|r| (&r.entity_id, r.subsample_id.as_ref())));
+    let extraction_ids =
+        Targets::new(Model::Extraction, extractions.iter().map(|r| (&r.entity_id, r.extract_id.as_ref())));
+    let library_ids = Targets::new(Model::Library, libraries.iter().map(|r| (&r.entity_id, r.library_id.as_ref())));
+    let assembly_ids = Targets::new(Model::Assembly, assemblies.iter().map(|r| (&r.entity_id, r.assembly_id.as_ref())));
+    let agent_ids = Targets::new(Model::Agent, agents.iter().map(|r| (&r.entity_id, None)));
+    let publication_ids = Targets {
+        model: Model::Publication,
+        ids: publications.iter().filter_map(|r| r.entity_id.clone()).collect(),
+    };
+
+    let mut verifier = Verifier::default();
+
+    for r in subsamples.iter() {
+        verifier.check(Model::Subsample, &r.entity_id, "tissue_id", r.tissue_id.as_ref(), &tissue_ids);
+    }
+
+    for r in extractions.iter() {
+        let model = Model::Extraction;
+        verifier.check(model, &r.entity_id, "subsample_id", r.subsample_id.as_ref(), &subsample_ids);
+        verifier.check(model, &r.entity_id, "extracted_by", r.extracted_by.as_ref(), &agent_ids);
+        verifier.check(model, &r.entity_id, "material_extracted_by", r.material_extracted_by.as_ref(), &agent_ids);
+        verifier.check(model, &r.entity_id, "publication_id", r.publication_id.as_ref(), &publication_ids);
+    }
+
+    for r in libraries.iter() {
+        verifier.check(Model::Library, &r.entity_id, "extract_id", r.extract_id.as_ref(), &extraction_ids);
+        verifier.check(Model::Library, &r.entity_id, "prepared_by", r.prepared_by.as_ref(), &agent_ids);
+    }
+
+    for r in runs.iter() {
+        verifier.check(Model::SequencingRun, &r.entity_id, "library_id", r.library_id.as_ref(), &library_ids);
+    }
+
+    for r in assemblies.iter() {
+        let model = Model::Assembly;
+        verifier.check(model, &r.entity_id, "library_id", r.library_id.as_ref(), &library_ids);
+        verifier.check(
+            model,
+            &r.entity_id,
+            "publication_entity_id",
+            r.publication_entity_id.as_ref(),
+            &publication_ids,
+        );
+    }
+
+    for r in annotations.iter() {
+        verifier.check(Model::Annotation, &r.entity_id, "assembly_id", r.assembly_id.as_ref(), &assembly_ids);
+    }
+
+    for r in depositions.iter() {
+        verifier.check(Model::Deposition, &r.entity_id, "assembly_id", r.assembly_id.as_ref(), &assembly_ids);
+    }
+
+    for r in organisms.iter() {
+        let model = Model::Organism;
+        verifier.check(model, &r.entity_id, "publication_id", r.publication_id.as_ref(), &publication_ids);
+    }
+
+    for r in collecting.iter() {
+        let model = Model::Collecting;
+        verifier.check(model, &r.entity_id, "collected_by_entity_id", r.collected_by_entity_id.as_ref(), &agent_ids);
+    }
+
+    for r in products.iter() {
+        let model = Model::DataProduct;
+        verifier.check(model, &r.entity_id, "custodian", r.custodian.as_ref(), &agent_ids);
+        verifier.check(model, &r.entity_id, "publication_id", r.publication_id.as_ref(), &publication_ids);
+    }
+
+    // records are resolved in no particular order. the sort is stable so the fields of a record
+    // stay in the order they were checked
+    for references in verifier.dangling.chunk_by_mut(|a, b| a.model == b.model) {
+        references.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
+    }
+
+    Ok(verifier.dangling)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{SYNTHETIC_SCHEMA, load_source};
+
+    const MAPPINGS: &str = "fields:entity_id mapping:same col:id .
+        fields:subsample_id mapping:same col:subsample_id .";
+
+    #[test]
+    fn reports_references_without_a_target() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let subsamples: &[&[(&str, &str)]] = &[&[("id", "SS1"), ("subsample_id", "sub-1")]];
+        load_source(&mut dataset, &Model::Subsample, "subsamples", MAPPINGS, subsamples).unwrap();
+
+        // a subsample can be referenced by either its subsample id or entity id
+        let extractions: &[&[(&str, &str)]] = &[
+            &[("id", "EX4"), ("subsample_id", "sub-4")],
+            &[("id", "EX1"), ("subsample_id", "sub-1")],
+            &[("id", "EX2"), ("subsample_id", "sub-2")],
+            &[("id", "EX3"), ("subsample_id", "SS1")],
+        ];
+        load_source(&mut dataset, &Model::Extraction, "extractions", MAPPINGS, extractions).unwrap();
+
+        let dangling = |entity_id: &str, value: &str| DanglingRef {
+            model: Model::Extraction.name().to_string(),
+            entity_id: entity_id.to_string(),
+            field: "subsample_id".to_string(),
+            target: Model::Subsample.name().to_string(),
+            value: value.to_string(),
+        };
+        assert_eq!(verify_references(&dataset).unwrap(), vec![dangling("EX2", "sub-2"), dangling("EX4", "sub-4")]);
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+
+use tracing::{info, instrument};
+
+use crate::dataset::{Dataset, Model};
+use crate::errors::TransformError;
+use crate::models::{FromFields, library};
+use crate::rdf::{self, SequenceField, SequencingRunField};
+use crate::resolver::{ResolvedRecords, Resolver};
+
+
+model_schema! {
+    /// A sequence of a marker, such as a COI or ITS barcode, produced by a sequencing run.
+    #[derive(Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct Sequence {
+        pub entity_id: String,
+        pub sequence_run_id: Option<String>,
+        pub scientific_name: Option<String>,
+
+        pub accession: Option<String>,
+        pub genbank_accession: Option<String>,
+        pub target_gene: Option<String>,
+        pub primer_forward: Option<String>,
+        pub primer_reverse: Option<String>,
+        pub sequence_length: Option<u64>,
+        pub ambiguities: Option<u64>,
+        pub basepair_quality: Option<String>,
+    }
+}
+
+
+impl_model! {
+    Sequence => SequenceField {
+        EntityId => entity_id required,
+        SequenceRunId => sequence_run_id,
+        Accession => accession,
+        GenbankAccession => genbank_accession,
+        TargetGene => target_gene,
+        PrimerForward => primer_forward,
+        PrimerReverse => primer_reverse,
+        SequenceLength => sequence_length,
+        Ambiguities => ambiguities,
+        BasepairQuality => basepair_quality,
+    }
+}
+
+
+/// Get all sequences along with the scientific name of the sequenced library.
+///
+/// Sequences don't have a name of their own so it is taken from the library of the
+/// sequencing run that produced the sequence.
+#[instrument(skip_all)]
+pub fn get_all(dataset: &Dataset) -> Result<Vec<Sequence>, TransformError> {
+    let resolver = Resolver::new(dataset);
+
+    let schemas = dataset.scope_iris(&[Model::Sequence])?;
+
+    info!("Resolving data");
+    let data: ResolvedRecords<SequenceField> = resolver.resolve(rdf::Sequence::ALL, &schemas)?;
+
+    let names = get_scientific_names(dataset)?;
+
+    let sequences = data.into_values().map(|fields| build(fields, &names)).collect();
+    Ok(sequences)
+}
+
+
+/// Get all sequences in entity id order and pass them to `f` in chunks of `chunk_size`.
+///
+/// Only one chunk of sequences is resolved and built at a time, see [`Resolver::resolve_chunked`].
+/// The names of the sequencing runs are resolved for all of them up front.
+#[instrument(skip_all)]
+pub fn get_chunked<F>(dataset: &Dataset, chunk_size: usize, mut f: F) -> Result<(), TransformError>
+where
+    F: FnMut(Vec<Sequence>) -> Result<(), TransformError>,
+{
+    let resolver = Resolver::new(dataset);
+
+    let schemas = dataset.scope_iris(&[Model::Sequence])?;
+    let names = get_scientific_names(dataset)?;
+
+    info!("Resolving data");
+    resolver.resolve_chunked(rdf::Sequence::ALL, &schemas, chunk_size, |data: Vec<(_, Vec<SequenceField>)>| {
+        f(data.into_iter().map(|(_idx, fields)| build(fields, &names)).collect())
+    })
+}
+
+
+fn build(fields: Vec<SequenceField>, names: &HashMap<String, String>) -> Sequence {
+    let mut sequence = Sequence::from_fields(fields);
+
+    if let Some(name) = sequence.sequence_run_id.as_ref().and_then(|id| names.get(id)) {
+        sequence.scientific_name = Some(name.clone());
+    }
+
+    sequence
+}
+
+
+/// Get the scientific names of sequencing runs keyed by the entity id of the run.
+///
+/// The name comes from the library that was sequenced in the run.
+#[instrument(skip_all)]
+pub fn get_scientific_names(dataset: &Dataset) -> Result<HashMap<String, String>, TransformError> {
+    let resolver = Resolver::new(dataset);
+
+    info!("Resolving library names");
+    let libraries: HashMap<String, String> = library::get_all(dataset)?
+        .into_iter()
+        .filter_map(|library| library.library_id.zip(library.scientific_name))
+        .collect();
+
+    let schemas = dataset.scope_iris(&[Model::SequencingRun])?;
+
+    info!("Resolving sequencing runs");
+    let data: ResolvedRecords<SequencingRunField> =
+        resolver.resolve(&[rdf::SequencingRun::EntityId, rdf::SequencingRun::LibraryId], &schemas)?;
+
+    let mut names = HashMap::new();
+    for (_idx, fields) in data {
+        let mut entity_id = None;
+        let mut library_id = None;
+
+        for field in fields {
+            match field {
+                SequencingRunField::EntityId(val) => entity_id = Some(val),
+                SequencingRunField::LibraryId(val) => library_id = Some(val),
+                _ => {}
+            }
+        }
+
+        if let (Some(entity_id), Some(name)) = (entity_id, library_id.and_then(|id| libraries.get(&id))) {
+            names.insert(entity_id, name.clone());
+        }
+    }
+
+    Ok(names)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use super::*;
+    use crate::readers::CsvReader;
+    use crate::testing::{SOURCE_PREFIXES, SYNTHETIC_SCHEMA};
+
+    const SEQUENCES: &str = r#"
+        fields:entity_id mapping:same col:sequence_id .
+        fields:sequence_run_id mapping:same col:run_id .
+        fields:accession mapping:same col:process_id .
+        fields:genbank_accession mapping:same col:genbank .
+        fields:target_gene mapping:same col:marker .
+        fields:primer_forward mapping:same col:primer_f .
+        fields:primer_reverse mapping:same col:primer_r .
+        fields:sequence_length mapping:same col:length .
+        fields:ambiguities mapping:same col:ns .
+    "#;
+
+    const RUNS: &str = r#"
+        fields:entity_id mapping:same col:run_id .
+        fields:library_id mapping:same col:library_id .
+    "#;
+
+    const LIBRARIES: &str = r#"
+        fields:entity_id mapping:same col:library_id .
+        fields:library_id mapping:same col:library_id .
+        fields:scientific_name mapping:same col:name .
+    "#;
+
+    fn load_csv(dataset: &mut Dataset, model: &Model, source: &str, mappings: &str, csv: &str) {
+        let graph = dataset.source_iri(source).unwrap();
+        let trig = format!("{SOURCE_PREFIXES}<{graph}> {{ {mappings} }}");
+        dataset.load_trig(BufReader::new(trig.as_bytes())).unwrap();
+        dataset.register_source(source, model).unwrap();
+        dataset.load(CsvReader::new(csv.as_bytes()).unwrap(), source).unwrap();
+    }
+
+    fn barcodes() -> Dataset {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let sequences = "sequence_id,run_id,process_id,genbank,marker,primer_f,primer_r,length,ns\n\
+            SEQ1,RUN1,BOLD-1,MN000001,COI-5P,LCO1490,HCO2198,658,0\n\
+            SEQ2,RUN2,BOLD-2,,ITS,,,412,3\n";
+        load_csv(&mut dataset, &Model::Sequence, "barcodes.csv", SEQUENCES, sequences);
+
+        let runs = "run_id,library_id\nRUN1,LIB1\n";
+        load_csv(&mut dataset, &Model::SequencingRun, "runs.csv", RUNS, runs);
+
+        let libraries = "library_id,name\nLIB1,Felis catus\n";
+        load_csv(&mut dataset, &Model::Library, "libraries.csv", LIBRARIES, libraries);
+        dataset
+    }
+
+    #[test]
+    fn resolves_sequences_from_trig_mappings() {
+        let mut sequences = get_all(&barcodes()).unwrap();
+        sequences.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
+
+        assert_eq!(
+            sequences[0],
+            Sequence {
+                entity_id: "SEQ1".to_string(),
+                sequence_run_id: Some("RUN1".to_string()),
+                scientific_name: Some("Felis catus".to_string()),
+                accession: Some("BOLD-1".to_string()),
+                genbank_accession: Some("MN000001".to_string()),
+                target_gene: Some("COI-5P".to_string()),
+                primer_forward: Some("LCO1490".to_string()),
+                primer_reverse: Some("HCO2198".to_string()),
+                sequence_length: Some(658),
+                ambiguities: Some(0),
+                basepair_quality: None,
+            }
+        );
+        assert_eq!(sequences.len(), 2);
+    }
+
+    #[test]
+    fn sequences_without_a_named_run_have_no_scientific_name() {
+        let sequences = get_all(&barcodes()).unwrap();
+        let sequence = sequences.iter().find(|sequence| sequence.entity_id == "SEQ2").unwrap();
+
+        assert_eq!(sequence.sequence_run_id.as_deref(), Some("RUN2"));
+        assert_eq!(sequence.scientific_name, None);
+        assert_eq!(sequence.target_gene.as_deref(), Some("ITS"));
+        assert_eq!(sequence.sequence_length, Some(412));
+        assert_eq!(sequence.primer_forward, None);
+    }
+}
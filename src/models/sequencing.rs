@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+use tracing::{info, instrument};
+
+use crate::dataset::Dataset;
+use crate::errors::TransformError;
+use crate::models::{Extraction, Library, SequencingRun, extraction, library, sequencing_run};
+
+
+/// A sequencing run together with the library it sequenced and the extraction the
+/// library was prepared from.
+///
+/// The run, library and extraction share a lot of field IRIs such as `fields:entity_id`
+/// so they can't be resolved into one field enum. Instead each model is resolved once
+/// and the records are joined on `library_id` and `extract_id`.
+#[derive(Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Sequencing {
+    pub entity_id: String,
+    pub run: SequencingRun,
+    pub library: Option<Library>,
+    pub extraction: Option<Extraction>,
+}
+
+
+/// Get all sequencing runs combined with their library and extraction.
+///
+/// A run without a matching library, or a library without a matching extraction, is
+/// still returned with the missing parts set to `None`.
+#[instrument(skip_all)]
+pub fn get_all(dataset: &Dataset) -> Result<Vec<Sequencing>, TransformError> {
+    info!("Resolving extractions");
+    let extractions: HashMap<String, Extraction> = extraction::get_all(dataset)?
+        .into_iter()
+        .filter_map(|extraction| extraction.extract_id.clone().map(|id| (id, extraction)))
+        .collect();
+
+    info!("Resolving libraries");
+    let libraries: HashMap<String, Library> = library::get_all(dataset)?
+        .into_iter()
+        .filter_map(|library| library.library_id.clone().map(|id| (id, library)))
+        .collect();
+
+    info!("Resolving sequencing runs");
+    let mut records = Vec::new();
+    for run in sequencing_run::get_all(dataset)? {
+        let library = run.library_id.as_ref().and_then(|id| libraries.get(id)).cloned();
+        let extraction = library
+            .as_ref()
+            .and_then(|library| library.extract_id.as_ref())
+            .and_then(|id| extractions.get(id))
+            .cloned();
+
+        records.push(Sequencing {
+            entity_id: run.entity_id.clone(),
+            run,
+            library,
+            extraction,
+        });
+    }
+
+    Ok(records)
+}
@@ -1,45 +1,82 @@
+use std::collections::HashMap;
+
 use tracing::{info, instrument};
 
 use crate::dataset::{Dataset, Model};
 use crate::errors::TransformError;
-use crate::rdf::{self, SequencingRunField};
+use crate::rdf::{self, Condition, DataProductField, Literal, SequencingRunField};
 use crate::resolver::{ResolvedRecords, Resolver};
 
 
-#[derive(Debug, Default, serde::Serialize)]
-pub struct SequencingRun {
-    pub entity_id: String,
-    pub library_id: Option<String>,
-    pub sequence_id: Option<String>,
-    pub scientific_name: Option<String>,
-    pub event_date: Option<String>,
-    pub facility: Option<String>,
-    pub instrument_or_method: Option<String>,
-    pub sra_run_accession: Option<String>,
-    pub platform: Option<String>,
-    pub dataset_file_format: Option<String>,
-    pub kit_chemistry: Option<String>,
-    pub flowcell_type: Option<String>,
-    pub cell_movie_length: Option<String>,
-    pub base_caller_model: Option<String>,
-    pub fast5_compression: Option<String>,
-    pub analysis_software: Option<String>,
-    pub analysis_software_version: Option<String>,
-    pub target_gene: Option<String>,
+model_schema! {
+    #[derive(Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct SequencingRun {
+        pub entity_id: String,
+        pub library_id: Option<String>,
+        pub sequence_id: Option<String>,
+        pub scientific_name: Option<String>,
+        pub event_date: Option<String>,
+        pub facility: Option<String>,
+        pub instrument_or_method: Option<String>,
+        pub sra_run_accession: Option<String>,
+        pub platform: Option<String>,
+        pub dataset_file_format: Option<String>,
+        pub kit_chemistry: Option<String>,
+        pub flowcell_type: Option<String>,
+        pub cell_movie_length: Option<String>,
+        pub base_caller_model: Option<String>,
+        pub fast5_compression: Option<String>,
+        pub analysis_software: Option<String>,
+        pub analysis_software_version: Option<String>,
+        pub target_gene: Option<String>,
+
+        pub data_product_ids: Vec<String>,
+    }
 }
 
 
+/// Get all sequencing runs along with the data products produced by them.
+///
+/// Data products reference the run with a `sequence_run_id` so they are resolved
+/// separately and joined on the entity id of the run.
 #[instrument(skip_all)]
 pub fn get_all(dataset: &Dataset) -> Result<Vec<SequencingRun>, TransformError> {
     let resolver = Resolver::new(dataset);
 
-    let schemas = dataset.scope(&[Model::SequencingRun]);
-    let schemas: Vec<&iref::Iri> = schemas.iter().map(|s| s.as_iri()).collect();
+    let schemas = dataset.scope_iris(&[Model::SequencingRun])?;
 
     info!("Resolving data");
     let data: ResolvedRecords<SequencingRunField> = resolver.resolve(rdf::SequencingRun::ALL, &schemas)?;
+    from_resolved(dataset, data)
+}
+
+
+/// Get all sequencing runs with the scientific name.
+///
+/// The name must match the resolved `scientific_name` exactly.
+#[instrument(skip_all)]
+pub fn get_by_scientific_name(dataset: &Dataset, name: &str) -> Result<Vec<SequencingRun>, TransformError> {
+    let resolver = Resolver::new(dataset);
+
+    let schemas = dataset.scope_iris(&[Model::SequencingRun])?;
+    let conditions = [(rdf::SequencingRun::ScientificName.into(), Condition::Is(Literal::String(name.to_string())))];
 
+    info!(name, "Resolving data");
+    let data: ResolvedRecords<SequencingRunField> =
+        resolver.resolve_with_conditions(rdf::SequencingRun::ALL, &schemas, &conditions)?;
+
+    // records without a scientific name pass the condition as there is nothing to check
+    let mut records = from_resolved(dataset, data)?;
+    records.retain(|record| record.scientific_name.as_deref() == Some(name));
+    Ok(records)
+}
 
+
+fn from_resolved(
+    dataset: &Dataset,
+    data: ResolvedRecords<SequencingRunField>,
+) -> Result<Vec<SequencingRun>, TransformError> {
+    let products = get_data_product_ids(dataset)?;
     let mut sequences = Vec::new();
 
     for (_idx, fields) in data {
@@ -50,6 +87,7 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<SequencingRun>, TransformError>
                 SequencingRunField::EntityId(val) => sequencing_run.entity_id = val,
                 SequencingRunField::LibraryId(val) => sequencing_run.library_id = Some(val),
                 SequencingRunField::SequenceId(val) => sequencing_run.sequence_id = Some(val),
+                SequencingRunField::ScientificName(val) => sequencing_run.scientific_name = Some(val),
                 SequencingRunField::Facility(val) => sequencing_run.facility = Some(val),
                 SequencingRunField::EventDate(val) => sequencing_run.event_date = Some(val),
                 SequencingRunField::InstrumentOrMethod(val) => sequencing_run.instrument_or_method = Some(val),
@@ -69,6 +107,10 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<SequencingRun>, TransformError>
             }
         }
 
+        if let Some(ids) = products.get(&sequencing_run.entity_id) {
+            sequencing_run.data_product_ids = ids.clone();
+        }
+
         sequences.push(sequencing_run);
     }
 
@@ -83,6 +125,44 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<SequencingRun>, TransformError>
 }
 
 
+/// Get the entity ids of data products keyed by the sequencing run they belong to.
+#[instrument(skip_all)]
+pub fn get_data_product_ids(dataset: &Dataset) -> Result<HashMap<String, Vec<String>>, TransformError> {
+    let resolver = Resolver::new(dataset);
+
+    let schemas = dataset.scope_iris(&[Model::DataProduct])?;
+
+    info!("Resolving data products");
+    let data: ResolvedRecords<DataProductField> =
+        resolver.resolve(&[rdf::DataProduct::EntityId, rdf::DataProduct::SequenceRunId], &schemas)?;
+
+    let mut products: HashMap<String, Vec<String>> = HashMap::new();
+    for (_idx, fields) in data {
+        let mut entity_id = None;
+        let mut sequence_run_id = None;
+
+        for field in fields {
+            match field {
+                DataProductField::EntityId(val) => entity_id = Some(val),
+                DataProductField::SequenceRunId(val) => sequence_run_id = Some(val),
+                _ => {}
+            }
+        }
+
+        if let (Some(entity_id), Some(sequence_run_id)) = (entity_id, sequence_run_id) {
+            products.entry(sequence_run_id).or_default().push(entity_id);
+        }
+    }
+
+    // keep the ids in a stable order as the resolved records are unordered
+    for ids in products.values_mut() {
+        ids.sort();
+    }
+
+    Ok(products)
+}
+
+
 // /// Get scientific names associated with libraries.
 // ///
 // /// This will go through all libraries and retrieve the name associated with it.
@@ -124,3 +204,27 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<SequencingRun>, TransformError>
 
 //     Ok(sequences)
 // }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{SYNTHETIC_SCHEMA, load_source};
+
+    #[test]
+    fn filters_runs_by_scientific_name() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = "fields:entity_id mapping:same col:id .
+            fields:scientific_name mapping:same col:name .";
+        let rows: &[&[(&str, &str)]] = &[
+            &[("id", "SRR1"), ("name", "Felis catus")],
+            &[("id", "SRR2"), ("name", "Canis lupus")],
+            &[("id", "SRR3")],
+        ];
+        load_source(&mut dataset, &Model::SequencingRun, "runs", mappings, rows).unwrap();
+
+        let runs = get_by_scientific_name(&dataset, "Felis catus").unwrap();
+        let ids: Vec<&str> = runs.iter().map(|run| run.entity_id.as_str()).collect();
+        assert_eq!(ids, vec!["SRR1"]);
+    }
+}
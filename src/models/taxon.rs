@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use tracing::{info, instrument};
+
+use crate::dataset::{Dataset, Model};
+use crate::errors::TransformError;
+use crate::models::FromFields;
+use crate::rdf::{self, TaxonField};
+use crate::resolver::{ResolvedRecords, Resolver};
+
+
+model_schema! {
+    /// A taxon from a taxonomy such as the NCBI taxonomy.
+    ///
+    /// The entity id is usually a `mapping:hash` of the `taxon_id` so that the same taxon
+    /// loaded from different sources is the same entity.
+    #[derive(Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct Taxon {
+        pub entity_id: String,
+        pub taxon_id: Option<String>,
+        pub parent_taxon_id: Option<String>,
+
+        pub scientific_name: Option<String>,
+        pub canonical_name: Option<String>,
+        pub scientific_name_authorship: Option<String>,
+        pub rank: Option<String>,
+        pub status: Option<String>,
+        pub nomenclatural_code: Option<String>,
+    }
+}
+
+
+impl_model! {
+    Taxon => TaxonField {
+        EntityId => entity_id required,
+        TaxonId => taxon_id,
+        ParentTaxonId => parent_taxon_id,
+        ScientificName => scientific_name,
+        CanonicalName => canonical_name,
+        ScientificNameAuthorship => scientific_name_authorship,
+        Rank => rank,
+        Status => status,
+        NomenclaturalCode => nomenclatural_code,
+    }
+}
+
+
+#[instrument(skip_all)]
+pub fn get_all(dataset: &Dataset) -> Result<Vec<Taxon>, TransformError> {
+    let resolver = Resolver::new(dataset);
+
+    let schemas = dataset.scope_iris(&[Model::Taxon])?;
+
+    info!("Resolving data");
+    let data: ResolvedRecords<TaxonField> = resolver.resolve(rdf::Taxon::ALL, &schemas)?;
+    Ok(data.into_values().map(Taxon::from_fields).collect())
+}
+
+
+/// Get the parent of every taxon keyed by the taxon id.
+///
+/// A taxon that is its own parent, like the root of the NCBI taxonomy, is left out so
+/// that walking up the hierarchy always ends.
+#[instrument(skip_all)]
+pub fn get_hierarchy(dataset: &Dataset) -> Result<HashMap<String, String>, TransformError> {
+    let resolver = Resolver::new(dataset);
+
+    let schemas = dataset.scope_iris(&[Model::Taxon])?;
+
+    info!("Resolving taxon parents");
+    let data: ResolvedRecords<TaxonField> =
+        resolver.resolve(&[rdf::Taxon::TaxonId, rdf::Taxon::ParentTaxonId], &schemas)?;
+
+    let mut hierarchy = HashMap::new();
+    for (_idx, fields) in data {
+        let taxon = Taxon::from_fields(fields);
+
+        if let (Some(taxon_id), Some(parent_id)) = (taxon.taxon_id, taxon.parent_taxon_id)
+            && taxon_id != parent_id
+        {
+            hierarchy.insert(taxon_id, parent_id);
+        }
+    }
+
+    Ok(hierarchy)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{SYNTHETIC_SCHEMA, load_source};
+
+    const NODES: &str = r#"
+        fields:entity_id mapping:hash col:tax_id .
+        fields:taxon_id mapping:same col:tax_id .
+        fields:parent_taxon_id mapping:same col:parent_tax_id .
+        fields:scientific_name mapping:same col:name_txt .
+        fields:rank mapping:same col:rank .
+    "#;
+
+    const AUTHORITIES: &str = r#"
+        fields:entity_id mapping:hash col:tax_id .
+        fields:scientific_name_authorship mapping:same col:authority .
+    "#;
+
+    fn ncbi() -> Dataset {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let nodes: &[&[(&str, &str)]] = &[
+            &[
+                ("tax_id", "1"),
+                ("parent_tax_id", "1"),
+                ("rank", "no rank"),
+                ("name_txt", "root"),
+            ],
+            &[
+                ("tax_id", "9681"),
+                ("parent_tax_id", "1"),
+                ("rank", "family"),
+                ("name_txt", "Felidae"),
+            ],
+            &[
+                ("tax_id", "9682"),
+                ("parent_tax_id", "9681"),
+                ("rank", "genus"),
+                ("name_txt", "Felis"),
+            ],
+            &[
+                ("tax_id", "9685"),
+                ("parent_tax_id", "9682"),
+                ("rank", "species"),
+                ("name_txt", "Felis catus"),
+            ],
+        ];
+        load_source(&mut dataset, &Model::Taxon, "nodes.dmp", NODES, nodes).unwrap();
+        dataset
+    }
+
+    #[test]
+    fn get_all_resolves_taxa() {
+        let taxa = get_all(&ncbi()).unwrap();
+        assert_eq!(taxa.len(), 4);
+
+        let cat = taxa
+            .iter()
+            .find(|taxon| taxon.taxon_id.as_deref() == Some("9685"))
+            .unwrap();
+        assert_eq!(cat.parent_taxon_id.as_deref(), Some("9682"));
+        assert_eq!(cat.scientific_name.as_deref(), Some("Felis catus"));
+        assert_eq!(cat.rank.as_deref(), Some("species"));
+        assert_eq!(cat.status, None);
+    }
+
+    #[test]
+    fn entity_ids_are_hashed_from_the_taxon_id() {
+        let mut dataset = ncbi();
+        let authorities: &[&[(&str, &str)]] = &[&[("tax_id", "9685"), ("authority", "Linnaeus, 1758")]];
+        load_source(&mut dataset, &Model::Taxon, "names.dmp", AUTHORITIES, authorities).unwrap();
+
+        let taxa = get_all(&dataset).unwrap();
+        let cat = taxa
+            .iter()
+            .find(|taxon| taxon.taxon_id.as_deref() == Some("9685"))
+            .unwrap();
+        let authority = taxa
+            .iter()
+            .find(|taxon| taxon.scientific_name_authorship.is_some())
+            .unwrap();
+
+        // the same taxon id from another source derives the same entity id
+        assert_ne!(cat.entity_id, "9685");
+        assert_eq!(authority.entity_id, cat.entity_id);
+
+        let mut ids: Vec<&str> = taxa.iter().map(|taxon| taxon.entity_id.as_str()).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 4);
+    }
+
+    #[test]
+    fn get_hierarchy_leaves_out_the_root() {
+        let hierarchy = get_hierarchy(&ncbi()).unwrap();
+
+        let expected = HashMap::from([
+            ("9681".to_string(), "1".to_string()),
+            ("9682".to_string(), "9681".to_string()),
+            ("9685".to_string(), "9682".to_string()),
+        ]);
+        assert_eq!(hierarchy, expected);
+    }
+}
@@ -6,34 +6,36 @@ use crate::rdf::{self, TissueField};
 use crate::resolver::{ResolvedRecords, Resolver};
 
 
-#[derive(Debug, Default, serde::Serialize)]
-pub struct Tissue {
-    pub entity_id: String,
-    pub organism_id: Option<String>,
-    pub tissue_id: Option<String>,
-    pub material_sample_id: Option<String>,
-
-    pub scientific_name: Option<String>,
-    pub original_catalogue_name: Option<String>,
-    pub current_catalogue_name: Option<String>,
-    pub identification_verified: Option<String>,
-    pub reference_material: Option<String>,
-    pub registered_by: Option<String>,
-    pub registration_date: Option<String>,
-    pub custodian: Option<String>,
-    pub institution: Option<String>,
-    pub institution_code: Option<String>,
-    pub collection: Option<String>,
-    pub collection_code: Option<String>,
-    pub status: Option<String>,
-    pub current_status: Option<String>,
-    pub sampling_protocol: Option<String>,
-    pub tissue_type: Option<String>,
-    pub disposition: Option<String>,
-    pub fixation: Option<String>,
-    pub storage: Option<String>,
-    pub source: Option<String>,
-    pub source_url: Option<String>,
+model_schema! {
+    #[derive(Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct Tissue {
+        pub entity_id: String,
+        pub organism_id: Option<String>,
+        pub tissue_id: Option<String>,
+        pub material_sample_id: Option<String>,
+
+        pub scientific_name: Option<String>,
+        pub original_catalogue_name: Option<String>,
+        pub current_catalogue_name: Option<String>,
+        pub identification_verified: Option<String>,
+        pub reference_material: Option<String>,
+        pub registered_by: Option<String>,
+        pub registration_date: Option<String>,
+        pub custodian: Option<String>,
+        pub institution: Option<String>,
+        pub institution_code: Option<String>,
+        pub collection: Option<String>,
+        pub collection_code: Option<String>,
+        pub status: Option<String>,
+        pub current_status: Option<String>,
+        pub sampling_protocol: Option<String>,
+        pub tissue_type: Option<String>,
+        pub disposition: Option<String>,
+        pub fixation: Option<String>,
+        pub storage: Option<String>,
+        pub source: Option<String>,
+        pub source_url: Option<String>,
+    }
 }
 
 
@@ -41,8 +43,7 @@ pub struct Tissue {
 pub fn get_all(dataset: &Dataset) -> Result<Vec<Tissue>, TransformError> {
     let resolver = Resolver::new(dataset);
 
-    let schemas = dataset.scope(&[Model::Tissue]);
-    let schemas: Vec<&iref::Iri> = schemas.iter().map(|s| s.as_iri()).collect();
+    let schemas = dataset.scope_iris(&[Model::Tissue])?;
 
     info!("Resolving data");
     let data: ResolvedRecords<TissueField> = resolver.resolve(rdf::Tissue::ALL, &schemas)?;
@@ -59,6 +60,7 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<Tissue>, TransformError> {
                 TissueField::OrganismId(val) => tissue.organism_id = Some(val),
                 TissueField::TissueId(val) => tissue.tissue_id = Some(val),
                 TissueField::MaterialSampleId(val) => tissue.material_sample_id = Some(val),
+                TissueField::ScientificName(val) => tissue.scientific_name = Some(val),
                 TissueField::OriginalCatalogueName(val) => tissue.original_catalogue_name = Some(val),
                 TissueField::CurrentCatalogueName(val) => tissue.current_catalogue_name = Some(val),
                 TissueField::IdentificationVerified(val) => tissue.identification_verified = Some(val),
@@ -85,56 +87,5 @@ pub fn get_all(dataset: &Dataset) -> Result<Vec<Tissue>, TransformError> {
         tissues.push(tissue);
     }
 
-    // let names = get_scientific_names(dataset)?;
-    // for tissue in tissues.iter_mut() {
-    //     if let Some(scientific_name) = names.get(&tissue.entity_id) {
-    //         tissue.scientific_name = Some(scientific_name.clone());
-    //     }
-    // }
-
     Ok(tissues)
 }
-
-
-// /// Get scientific names associated with material samples.
-// ///
-// /// This will go through all material samples and retrieve the name associated with the
-// /// original collection event.
-// #[instrument(skip_all)]
-// pub fn get_scientific_names(dataset: &Dataset) -> Result<HashMap<String, String>, Error> {
-//     let models = dataset.scope(&["tissues"]);
-//     let mut scope = Vec::new();
-//     for model in models.iter() {
-//         scope.push(iref::Iri::new(model).unwrap());
-//     }
-
-//     let resolver = Resolver::new(dataset);
-
-
-//     let names = super::collecting::get_scientific_names(dataset)?;
-//     let mut tissues = HashMap::new();
-
-//     let data: HashMap<Literal, Vec<TissueField>> =
-//         resolver.resolve(&[rdf::Tissue::EntityId, rdf::Tissue::MaterialSampleId], &scope)?;
-
-//     for (_idx, fields) in data.into_iter() {
-//         let mut entity_id = None;
-//         let mut material_sample_id = None;
-
-//         for field in fields {
-//             match field {
-//                 TissueField::EntityId(val) => entity_id = Some(val),
-//                 TissueField::MaterialSampleId(val) => material_sample_id = Some(val),
-//                 _ => {}
-//             }
-//         }
-
-//         if let (Some(entity_id), Some(material_sample_id)) = (entity_id, material_sample_id) {
-//             if let Some(name) = names.get(&material_sample_id) {
-//                 tissues.insert(entity_id, name.clone());
-//             }
-//         }
-//     }
-
-//     Ok(tissues)
-// }
@@ -0,0 +1,309 @@
+use unicode_normalization::UnicodeNormalization;
+
+
+/// The rank of a name as implied by the shape of a parsed scientific name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankHint {
+    Genus,
+    Species,
+    Subspecies,
+    Variety,
+    Form,
+}
+
+
+/// A scientific name split into its canonical name and authorship.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedName {
+    /// The name without the authorship, eg. `Felis catus`
+    pub canonical: String,
+    /// The authorship following the name, eg. `Linnaeus, 1758`
+    pub authorship: Option<String>,
+    /// The rank implied by the parts of the name, if it could be parsed
+    pub rank_hint: Option<RankHint>,
+}
+
+
+/// The multiplication sign used to mark a hybrid name, eg. `×Cupressocyparis`
+const HYBRID_SIGN: &str = "×";
+
+/// Infraspecific rank markers and the normalised form that's used in the canonical name.
+const RANK_MARKERS: &[(&str, &str, RankHint)] = &[
+    ("subsp.", "subsp.", RankHint::Subspecies),
+    ("ssp.", "subsp.", RankHint::Subspecies),
+    ("var.", "var.", RankHint::Variety),
+    ("v.", "var.", RankHint::Variety),
+    ("f.", "f.", RankHint::Form),
+    ("fo.", "f.", RankHint::Form),
+    ("forma", "f.", RankHint::Form),
+];
+
+/// Markers of a name that hasn't been identified to species, eg. `Felis sp.`
+const INDETERMINATE_MARKERS: &[&str] = &["sp.", "spp.", "sp", "spp"];
+
+/// Lower case words that start an authorship rather than being an epithet, eg. `de Candolle`.
+const AUTHOR_PARTICLES: &[&str] = &[
+    "da", "de", "del", "della", "der", "di", "du", "la", "le", "ter", "van", "von",
+];
+
+
+/// Split a full scientific name string into the canonical name and its authorship.
+///
+/// This is a heuristic parser for the common shapes of names found in sources rather
+/// than a complete implementation of the nomenclatural codes. It understands
+/// uninomials (`Felis Linnaeus, 1758`), binomials (`Felis catus Linnaeus, 1758`),
+/// zoological trinomials (`Canis lupus dingo Meyer, 1793`), botanical infraspecific
+/// names with a rank marker (`Poa annua var. annua L.`), parenthesised authorships
+/// (`Pseudomys australis (Gray, 1832)`) and subgenera (`Aedes (Stegomyia) aegypti`).
+///
+/// The name is tidied with [`tidy_name`] first so hybrids like `Mentha × piperita L.` parse
+/// into the canonical name `Mentha ×piperita`.
+///
+/// Rank markers are kept in the canonical name in their normalised form, so
+/// `ssp.` becomes `subsp.`, and subgenera are dropped from it. Names that don't start
+/// with a capitalised genus are returned whole as the canonical name without a rank, and
+/// indeterminate names like `Felis sp.` are returned whole with a genus rank.
+pub fn parse_scientific_name(value: &str) -> ParsedName {
+    let value = tidy_name(value);
+    let tokens: Vec<&str> = value.split_whitespace().collect();
+
+    let unparsed = || ParsedName {
+        canonical: tokens.join(" "),
+        authorship: None,
+        rank_hint: None,
+    };
+
+    let Some((genus, mut rest)) = tokens.split_first()
+    else {
+        return unparsed();
+    };
+
+    if !is_genus(genus) {
+        return unparsed();
+    }
+
+    let mut canonical = vec![genus.to_string()];
+    let mut rank = RankHint::Genus;
+
+    // indeterminate names have no authorship so anything after the marker is a part of the name
+    if let [marker, ..] = rest
+        && INDETERMINATE_MARKERS.contains(marker)
+    {
+        canonical.extend(rest.iter().map(|token| token.to_string()));
+        return ParsedName {
+            canonical: canonical.join(" "),
+            authorship: None,
+            rank_hint: Some(rank),
+        };
+    }
+
+    // subgenera sit between the genus and the species epithet but aren't part of the canonical name
+    if let [subgenus, next, ..] = rest
+        && is_subgenus(subgenus)
+        && is_epithet(next, rest.get(2))
+    {
+        rest = &rest[1..];
+    }
+
+    if let [epithet, ..] = rest
+        && is_epithet(epithet, rest.get(1))
+    {
+        canonical.push(epithet.to_string());
+        rank = RankHint::Species;
+        rest = &rest[1..];
+
+        if let [marker, epithet, ..] = rest
+            && let Some((normalised, hint)) = rank_marker(marker)
+            && is_epithet(epithet, rest.get(2))
+        {
+            canonical.push(normalised.to_string());
+            canonical.push(epithet.to_string());
+            rank = hint;
+            rest = &rest[2..];
+        }
+        else if let [epithet, ..] = rest
+            && is_epithet(epithet, rest.get(1))
+        {
+            canonical.push(epithet.to_string());
+            rank = RankHint::Subspecies;
+            rest = &rest[1..];
+        }
+    }
+
+    let authorship = match rest.is_empty() {
+        true => None,
+        false => Some(rest.join(" ")),
+    };
+
+    ParsedName {
+        canonical: canonical.join(" "),
+        authorship,
+        rank_hint: Some(rank),
+    }
+}
+
+
+/// Tidy a name by composing its unicode characters and collapsing whitespace.
+///
+/// The same name can be written with decomposed accents or irregular spacing depending on
+/// where it came from, so this gives one form of it to compare and store. A hybrid sign is
+/// attached to the name part that follows it, eg. `Mentha × piperita` becomes `Mentha ×piperita`,
+/// and a lone `x` after the first name part is taken to be a hybrid sign.
+pub fn tidy_name(value: &str) -> String {
+    let value: String = value.nfc().collect();
+    let tokens: Vec<&str> = value.split_whitespace().collect();
+
+    let mut tidied = String::with_capacity(value.len());
+    for (idx, token) in tokens.iter().enumerate() {
+        let token = match *token == "x" && idx > 0 && idx + 1 < tokens.len() {
+            true => HYBRID_SIGN,
+            false => token,
+        };
+
+        if !tidied.is_empty() && !tidied.ends_with(HYBRID_SIGN) {
+            tidied.push(' ');
+        }
+        tidied.push_str(token);
+    }
+
+    tidied
+}
+
+
+/// A capitalised word made up of letters, optionally with a hyphen or a leading hybrid sign.
+fn is_genus(token: &str) -> bool {
+    let token = token.strip_prefix(HYBRID_SIGN).unwrap_or(token);
+    let mut chars = token.chars();
+    match chars.next() {
+        Some(first) if first.is_uppercase() => chars.all(|c| c.is_lowercase() || c == '-'),
+        _ => false,
+    }
+}
+
+/// A genus in parentheses, eg. `(Stegomyia)`.
+fn is_subgenus(token: &str) -> bool {
+    token
+        .strip_prefix('(')
+        .and_then(|token| token.strip_suffix(')'))
+        .is_some_and(is_genus)
+}
+
+/// A lower case word that isn't the start of an authorship, optionally with a leading hybrid sign.
+///
+/// Author particles like `de` are only treated as an authorship when followed by a
+/// capitalised word so that an epithet that happens to be spelt the same still parses.
+fn is_epithet(token: &str, next: Option<&&str>) -> bool {
+    let token = token.strip_prefix(HYBRID_SIGN).unwrap_or(token);
+    let is_word =
+        token.chars().next().is_some_and(|c| c.is_lowercase()) && token.chars().all(|c| c.is_lowercase() || c == '-');
+
+    let starts_authorship = AUTHOR_PARTICLES.contains(&token)
+        && next
+            .and_then(|next| next.chars().next())
+            .is_some_and(|c| c.is_uppercase());
+
+    is_word && !starts_authorship
+}
+
+fn rank_marker(token: &str) -> Option<(&'static str, RankHint)> {
+    let token = token.to_lowercase();
+    RANK_MARKERS
+        .iter()
+        .find(|(marker, _, _)| *marker == token)
+        .map(|(_, normalised, hint)| (*normalised, *hint))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(canonical: &str, authorship: Option<&str>, rank_hint: Option<RankHint>) -> ParsedName {
+        ParsedName {
+            canonical: canonical.to_string(),
+            authorship: authorship.map(String::from),
+            rank_hint,
+        }
+    }
+
+    #[test]
+    fn parse_scientific_name_cases() {
+        use RankHint::*;
+
+        let cases = [
+            // uninomials
+            ("Felis", parsed("Felis", None, Some(Genus))),
+            ("Felis Linnaeus, 1758", parsed("Felis", Some("Linnaeus, 1758"), Some(Genus))),
+            ("Eucalyptus L'Hér.", parsed("Eucalyptus", Some("L'Hér."), Some(Genus))),
+            // binomials
+            ("Felis catus", parsed("Felis catus", None, Some(Species))),
+            ("Felis catus Linnaeus, 1758", parsed("Felis catus", Some("Linnaeus, 1758"), Some(Species))),
+            ("Poa annua L.", parsed("Poa annua", Some("L."), Some(Species))),
+            ("Eucalyptus globulus Labill.", parsed("Eucalyptus globulus", Some("Labill."), Some(Species))),
+            ("Acacia dealbata Link", parsed("Acacia dealbata", Some("Link"), Some(Species))),
+            ("Felis  catus   Linnaeus,  1758", parsed("Felis catus", Some("Linnaeus, 1758"), Some(Species))),
+            ("Banksia novae-angliae", parsed("Banksia novae-angliae", None, Some(Species))),
+            // parenthesised authorships
+            (
+                "Pseudomys australis (Gray, 1832)",
+                parsed("Pseudomys australis", Some("(Gray, 1832)"), Some(Species)),
+            ),
+            (
+                "Macropus rufus (Desmarest, 1822) Smith",
+                parsed("Macropus rufus", Some("(Desmarest, 1822) Smith"), Some(Species)),
+            ),
+            // trinomials
+            ("Canis lupus dingo", parsed("Canis lupus dingo", None, Some(Subspecies))),
+            ("Canis lupus dingo Meyer, 1793", parsed("Canis lupus dingo", Some("Meyer, 1793"), Some(Subspecies))),
+            // infraspecific rank markers
+            ("Poa annua var. annua L.", parsed("Poa annua var. annua", Some("L."), Some(Variety))),
+            ("Poa annua v. annua", parsed("Poa annua var. annua", None, Some(Variety))),
+            (
+                "Eucalyptus globulus subsp. bicostata (Maiden et al.) J.B.Kirkp.",
+                parsed("Eucalyptus globulus subsp. bicostata", Some("(Maiden et al.) J.B.Kirkp."), Some(Subspecies)),
+            ),
+            (
+                "Eucalyptus globulus ssp. maidenii",
+                parsed("Eucalyptus globulus subsp. maidenii", None, Some(Subspecies)),
+            ),
+            ("Acacia saligna Subsp. lindleyi", parsed("Acacia saligna subsp. lindleyi", None, Some(Subspecies))),
+            ("Quercus robur f. fastigiata", parsed("Quercus robur f. fastigiata", None, Some(Form))),
+            ("Quercus robur forma fastigiata", parsed("Quercus robur f. fastigiata", None, Some(Form))),
+            // subgenera
+            ("Aedes (Stegomyia) aegypti", parsed("Aedes aegypti", None, Some(Species))),
+            (
+                "Aedes (Stegomyia) aegypti (Linnaeus, 1762)",
+                parsed("Aedes aegypti", Some("(Linnaeus, 1762)"), Some(Species)),
+            ),
+            // author particles
+            ("Rosa canina de Candolle", parsed("Rosa canina", Some("de Candolle"), Some(Species))),
+            ("Salix alba von Seemen", parsed("Salix alba", Some("von Seemen"), Some(Species))),
+            ("Galium verum de", parsed("Galium verum de", None, Some(Subspecies))),
+            // hybrids
+            ("Mentha x piperita L.", parsed("Mentha ×piperita", Some("L."), Some(Species))),
+            ("Mentha × piperita", parsed("Mentha ×piperita", None, Some(Species))),
+            ("×Cupressocyparis leylandii", parsed("×Cupressocyparis leylandii", None, Some(Species))),
+            // indeterminate names
+            ("Felis sp.", parsed("Felis sp.", None, Some(Genus))),
+            (
+                "Acacia sp. Graveside Gorge (K.R.Thiele 4290)",
+                parsed("Acacia sp. Graveside Gorge (K.R.Thiele 4290)", None, Some(Genus)),
+            ),
+            // names that can't be parsed are kept whole
+            ("felis catus", parsed("felis catus", None, None)),
+            ("", parsed("", None, None)),
+        ];
+
+        for (value, expected) in cases {
+            assert_eq!(parse_scientific_name(value), expected, "parsing {value:?}");
+        }
+    }
+
+    #[test]
+    fn tidy_name_composes_and_collapses_whitespace() {
+        assert_eq!(tidy_name("Eucalyptus  L'He\u{301}r."), "Eucalyptus L'Hér.");
+        assert_eq!(tidy_name(" Mentha x piperita "), "Mentha ×piperita");
+        assert_eq!(tidy_name("x Mentha"), "x Mentha");
+        assert_eq!(tidy_name("Mentha x"), "Mentha x");
+    }
+}
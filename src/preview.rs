@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+use crate::dataset::{Dataset, Model};
+use crate::errors::TransformError;
+use crate::rdf::Literal;
+use crate::resolver::{ResolveOptions, Resolver};
+
+
+/// A sample of resolved records keyed by the model name.
+pub type PreviewResult = HashMap<String, Value>;
+
+
+/// Get the first `n` records of every model.
+///
+/// Each model is a JSON array of records where every record is an object of the
+/// resolved field values. Fields with more than one value are an array. Only models
+/// that a source transforms into are included.
+///
+/// This is intended for quickly checking that a newly ingested dataset is being
+/// transformed as expected, so the records are resolved from the field mappings
+/// alone and not joined with other models like the model structs are.
+pub fn preview(dataset: &Dataset, n: usize) -> Result<PreviewResult, TransformError> {
+    let resolver = Resolver::new(dataset);
+    let options = ResolveOptions {
+        max_records: Some(n),
+        ..Default::default()
+    };
+
+    let mut result = PreviewResult::new();
+
+    for model in Model::ALL {
+        let fields = model.fields();
+        if fields.is_empty() || dataset.get_source_from_model(&dataset.model_schema(model))?.is_empty() {
+            continue;
+        }
+
+        let scope = dataset.scope_iris(&[*model])?;
+        let values = resolver.resolve_values(&fields, &scope, &options)?;
+
+        // keep the preview stable between calls
+        let mut values: Vec<_> = values.into_iter().collect();
+        values.sort_by(|(a, _), (b, _)| a.loose_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut records = Vec::new();
+        for (_idx, record) in values {
+            let mut object = Map::new();
+
+            for field in fields.iter() {
+                let Some(values) = record.get(*field)
+                else {
+                    continue;
+                };
+
+                let name = field.as_str().rsplit('/').next().unwrap_or(field.as_str());
+                let mut values: Vec<Value> = values.iter().map(to_json).collect();
+                let value = match values.len() {
+                    1 => values.remove(0),
+                    _ => Value::Array(values),
+                };
+                object.insert(name.to_string(), value);
+            }
+
+            records.push(Value::Object(object));
+        }
+
+        result.insert(model.name().to_string(), Value::Array(records));
+    }
+
+    Ok(result)
+}
+
+
+fn to_json(literal: &Literal) -> Value {
+    match literal {
+        Literal::String(value) => Value::String(value.clone()),
+        Literal::UInt64(value) => Value::from(*value),
+    }
+}
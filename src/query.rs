@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::errors::TransformError;
+use crate::rdf::{Literal, Value};
+
+
+/// A SPARQL query template with named parameters.
+///
+/// Parameters are written as `{{name}}` in the template and are substituted with a bound
+/// IRI or literal when the query is rendered. Values are validated and escaped when bound
+/// so that a field name from user config can never change the structure of the query.
+#[derive(Debug, Clone)]
+pub struct Query {
+    template: String,
+    bindings: HashMap<String, String>,
+}
+
+impl Query {
+    pub fn new(template: &str) -> Query {
+        Query {
+            template: template.to_string(),
+            bindings: HashMap::new(),
+        }
+    }
+
+    /// Bind an IRI or literal to the parameter.
+    ///
+    /// IRIs must be absolute and can't contain characters that aren't allowed in a SPARQL
+    /// IRI reference, such as spaces or quotes. Blank nodes can't be bound as they are only
+    /// meaningful within the query results they came from.
+    pub fn bind(mut self, name: &str, value: impl Into<Value>) -> Result<Query, TransformError> {
+        let term = match value.into() {
+            Value::Iri(iri) => sparql_iri(name, &iri)?,
+            Value::Literal(Literal::String(value)) => sparql_string(&value),
+            Value::Literal(Literal::UInt64(value)) => value.to_string(),
+            Value::Bnode(id) => {
+                return Err(TransformError::InvalidQueryBinding {
+                    name: name.to_string(),
+                    reason: format!("blank node _:{id} cannot be bound"),
+                });
+            }
+        };
+
+        self.bindings.insert(name.to_string(), term);
+        Ok(self)
+    }
+
+    /// Substitute the bound values into the template.
+    ///
+    /// Every parameter in the template must be bound otherwise an error is returned
+    /// with the name of the first unbound parameter.
+    pub fn render(&self) -> Result<String, TransformError> {
+        let mut query = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start..].find("}}")
+            else {
+                break;
+            };
+
+            let name = rest[start + 2..start + end].trim();
+            let term = self
+                .bindings
+                .get(name)
+                .ok_or_else(|| TransformError::UnboundQueryParameter(name.to_string()))?;
+
+            query.push_str(&rest[..start]);
+            query.push_str(term);
+            rest = &rest[start + end + 2..];
+        }
+
+        query.push_str(rest);
+        Ok(query)
+    }
+}
+
+
+/// A collection of named query templates.
+#[derive(Debug, Clone, Default)]
+pub struct QueryRegistry {
+    queries: HashMap<String, String>,
+}
+
+impl QueryRegistry {
+    pub fn new() -> QueryRegistry {
+        QueryRegistry::default()
+    }
+
+    /// Load every `.rq` file in the directory as a query named after the file stem.
+    pub fn from_dir<P: AsRef<Path>>(path: P) -> Result<QueryRegistry, TransformError> {
+        let mut registry = QueryRegistry::new();
+
+        for entry in std::fs::read_dir(path)? {
+            let path = entry?.path();
+            if !path.is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("rq") {
+                continue;
+            }
+
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                let template = std::fs::read_to_string(&path)?;
+                registry.insert(name, &template);
+            }
+        }
+
+        Ok(registry)
+    }
+
+    /// Add a query template, replacing any previous template with the same name.
+    pub fn insert(&mut self, name: &str, template: &str) {
+        self.queries.insert(name.to_string(), template.to_string());
+    }
+
+    /// Get the named query ready to have its parameters bound.
+    pub fn get(&self, name: &str) -> Result<Query, TransformError> {
+        match self.queries.get(name) {
+            Some(template) => Ok(Query::new(template)),
+            None => Err(TransformError::UnknownQuery(name.to_string())),
+        }
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.queries.keys().map(|name| name.as_str()).collect();
+        names.sort();
+        names
+    }
+}
+
+
+/// Format the IRI as a SPARQL IRI reference, rejecting anything that isn't a valid absolute IRI.
+fn sparql_iri(name: &str, iri: &str) -> Result<String, TransformError> {
+    // the IRI spec already disallows these but we check them explicitly as they are
+    // the characters that would let a value escape the IRI reference
+    let disallowed = |c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"' | '{' | '}' | '|' | '^' | '`' | '\\');
+
+    if iri.contains(disallowed) || iref::Iri::new(iri).is_err() {
+        return Err(TransformError::InvalidQueryBinding {
+            name: name.to_string(),
+            reason: format!("'{iri}' is not a valid IRI"),
+        });
+    }
+
+    Ok(format!("<{iri}>"))
+}
+
+
+/// Format the value as a quoted SPARQL string literal.
+fn sparql_string(value: &str) -> String {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIELD: &str = "http://arga.org.au/schemas/fields/scientific_name";
+
+    fn is_invalid_binding<T>(result: Result<T, TransformError>) -> bool {
+        matches!(result, Err(TransformError::InvalidQueryBinding { .. }))
+    }
+
+    #[test]
+    fn renders_bound_iris_and_literals() {
+        let query = Query::new("SELECT ?s WHERE { ?s {{ field }} {{value}} }")
+            .bind("field", Value::Iri(FIELD.to_string()))
+            .unwrap()
+            .bind("value", Literal::String("say \"hi\"\n".to_string()))
+            .unwrap();
+
+        assert_eq!(query.render().unwrap(), format!("SELECT ?s WHERE {{ ?s <{FIELD}> \"say \\\"hi\\\"\\n\" }}"));
+    }
+
+    #[test]
+    fn sparql_iri_rejects_quotes_and_spaces() {
+        assert!(sparql_iri("field", FIELD).is_ok());
+        assert!(is_invalid_binding(sparql_iri("field", "http://arga.org.au/schemas/fields/scientific name")));
+        assert!(is_invalid_binding(sparql_iri("field", "http://arga.org.au/\"name")));
+        assert!(is_invalid_binding(sparql_iri("field", "http://arga.org.au/name> ?p ?o . <x")));
+        assert!(is_invalid_binding(sparql_iri("field", "scientific_name")));
+    }
+
+    #[test]
+    fn bind_rejects_invalid_iris_and_blank_nodes() {
+        let query = Query::new("SELECT ?s WHERE { ?s {{field}} ?o }");
+        assert!(is_invalid_binding(query.clone().bind("field", Value::Iri("scientific name".to_string()))));
+        assert!(is_invalid_binding(query.bind("field", Value::Bnode("b0".to_string()))));
+    }
+
+    #[test]
+    fn render_fails_on_unbound_parameters() {
+        let query = Query::new("SELECT ?s WHERE { ?s {{field}} ?o }");
+        assert!(matches!(query.render(), Err(TransformError::UnboundQueryParameter(name)) if name == "field"));
+    }
+
+    #[test]
+    fn registry_gets_named_queries() {
+        let mut registry = QueryRegistry::new();
+        registry.insert("values", "SELECT ?o WHERE { ?s {{field}} ?o }");
+        registry.insert("count", "SELECT (COUNT(?s) AS ?n) WHERE { ?s ?p ?o }");
+
+        assert_eq!(registry.names(), vec!["count", "values"]);
+        assert!(registry.get("values").is_ok());
+        assert!(matches!(registry.get("missing"), Err(TransformError::UnknownQuery(_))));
+    }
+}
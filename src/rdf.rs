@@ -3,7 +3,31 @@ use std::borrow::Borrow;
 use iref_enum::IriEnum;
 use sophia::api::term::{SimpleTerm, Term};
 
-use crate::errors::TransformError;
+use crate::errors::{ResolveError, TransformError};
+
+
+/// Define the fields of a model along with an `ALL` constant listing every field.
+///
+/// The list is generated from the enum so resolving `ALL` can never miss a field that
+/// was added to the model.
+macro_rules! model_fields {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident,)*
+        }
+    ) => {
+        $(#[$meta])*
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+        }
+
+        impl $name {
+            /// Every field of the model in the order they are declared.
+            pub const ALL: &[$name] = &[$($name::$variant),*];
+        }
+    };
+}
 
 
 #[derive(Debug, IriEnum)]
@@ -24,14 +48,136 @@ pub enum DataTypes {
 pub enum Value {
     Iri(String),
     Literal(Literal),
+    Bnode(String),
+}
+
+impl From<&iref::Iri> for Value {
+    fn from(value: &iref::Iri) -> Self {
+        Value::Iri(value.to_string())
+    }
+}
+
+impl From<Literal> for Value {
+    fn from(value: Literal) -> Self {
+        Value::Literal(value)
+    }
 }
 
+/// A row of bound values from a SPARQL query in the order of the selected variables.
+pub type SparqlRow = Vec<Option<Value>>;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Literal {
     String(String),
     UInt64(u64),
 }
 
+impl Literal {
+    /// The value of the literal as a float if it is numeric.
+    ///
+    /// Strings are trimmed and have thousands separators removed before parsing.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Literal::String(val) => val.trim().replace(",", "").parse::<f64>().ok(),
+            Literal::UInt64(val) => Some(*val as f64),
+        }
+    }
+
+    /// The value if the literal is a string.
+    ///
+    /// ```
+    /// use transformer::rdf::Literal;
+    ///
+    /// assert_eq!(Literal::String("10".to_string()).try_as_string(), Some("10"));
+    /// assert_eq!(Literal::UInt64(10).try_as_string(), None);
+    /// ```
+    pub fn try_as_string(&self) -> Option<&str> {
+        match self {
+            Literal::String(val) => Some(val),
+            Literal::UInt64(_) => None,
+        }
+    }
+
+    /// The value if the literal is an unsigned integer.
+    ///
+    /// Strings are never parsed, even if they contain a number.
+    ///
+    /// ```
+    /// use transformer::rdf::Literal;
+    ///
+    /// assert_eq!(Literal::UInt64(10).try_as_u64(), Some(10));
+    /// assert_eq!(Literal::String("10".to_string()).try_as_u64(), None);
+    /// ```
+    pub fn try_as_u64(&self) -> Option<u64> {
+        match self {
+            Literal::String(_) => None,
+            Literal::UInt64(val) => Some(*val),
+        }
+    }
+
+    /// The value of the literal as a float.
+    ///
+    /// Strings are parsed the same way as [`Literal::as_f64`].
+    ///
+    /// ```
+    /// use transformer::rdf::Literal;
+    ///
+    /// assert_eq!(Literal::String(" 1,024.5 ".to_string()).try_as_f64(), Some(1024.5));
+    /// assert_eq!(Literal::UInt64(10).try_as_f64(), Some(10.0));
+    /// assert_eq!(Literal::String("ten".to_string()).try_as_f64(), None);
+    /// ```
+    pub fn try_as_f64(&self) -> Option<f64> {
+        self.as_f64()
+    }
+
+    /// The lexical form of the literal.
+    pub fn lexical(&self) -> String {
+        match self {
+            Literal::String(val) => val.clone(),
+            Literal::UInt64(val) => val.to_string(),
+        }
+    }
+
+    /// Compare two literals while coercing between types.
+    ///
+    /// The coercion rules are:
+    /// - if both literals are numeric they are compared as numbers. a string is numeric if
+    ///   it can be parsed as a float after trimming whitespace and removing thousands separators
+    /// - otherwise they are compared by their lexical form as strings
+    ///
+    /// This means `"10"`, `"10.0"`, and `UInt64(10)` are all equal while `"ten"` is only
+    /// equal to itself.
+    pub fn loose_cmp(&self, other: &Literal) -> Option<std::cmp::Ordering> {
+        match (self.as_f64(), other.as_f64()) {
+            (Some(left), Some(right)) => left.partial_cmp(&right),
+            _ => Some(self.lexical().cmp(&other.lexical())),
+        }
+    }
+
+    /// Check if two literals are equal using the coercion rules of `loose_cmp`.
+    pub fn loose_eq(&self, other: &Literal) -> bool {
+        self.loose_cmp(other) == Some(std::cmp::Ordering::Equal)
+    }
+}
+
+impl From<String> for Literal {
+    fn from(value: String) -> Self {
+        Literal::String(value)
+    }
+}
+
+impl From<&str> for Literal {
+    fn from(value: &str) -> Self {
+        Literal::String(value.to_string())
+    }
+}
+
+impl From<u64> for Literal {
+    fn from(value: u64) -> Self {
+        Literal::UInt64(value)
+    }
+}
+
 impl TryFrom<&SimpleTerm<'static>> for Literal {
     type Error = TransformError;
 
@@ -40,8 +186,8 @@ impl TryFrom<&SimpleTerm<'static>> for Literal {
             SimpleTerm::LiteralDatatype(val, type_iri) => match try_from_iri(type_iri)? {
                 DataTypes::String => Ok(Literal::String(val.to_string())),
                 DataTypes::Boolean => todo!(),
-                DataTypes::Decimal => todo!(),
-                DataTypes::Integer => todo!(),
+                DataTypes::Decimal => Ok(Literal::String(val.to_string())),
+                DataTypes::Integer => Ok(Literal::UInt64(str_to_u64(val)?)),
             },
             _ => Err(TransformError::MissingEntityId),
         }
@@ -61,6 +207,42 @@ pub enum Source {
     Format,
 }
 
+impl TryFrom<&SimpleTerm<'static>> for Source {
+    type Error = TransformError;
+
+    fn try_from(value: &SimpleTerm<'static>) -> Result<Self, Self::Error> {
+        let mapping = try_from_term(value)?;
+        Ok(mapping)
+    }
+}
+
+
+/// The details recorded about a source each time it is loaded.
+#[derive(Debug, IriEnum)]
+#[iri_prefix("provenance" = "http://arga.org.au/schemas/provenance/")]
+pub enum Provenance {
+    /// The time the source was loaded as an RFC 3339 timestamp.
+    #[iri("provenance:loaded_at")]
+    LoadedAt,
+
+    /// The amount of records loaded from the source.
+    #[iri("provenance:records")]
+    Records,
+
+    /// A checksum of the source file supplied by the caller.
+    #[iri("provenance:checksum")]
+    Checksum,
+}
+
+impl TryFrom<&SimpleTerm<'static>> for Provenance {
+    type Error = TransformError;
+
+    fn try_from(value: &SimpleTerm<'static>) -> Result<Self, Self::Error> {
+        let provenance = try_from_term(value)?;
+        Ok(provenance)
+    }
+}
+
 
 #[derive(Debug, IriEnum)]
 #[iri_prefix("mapping" = "http://arga.org.au/schemas/mapping/")]
@@ -86,11 +268,71 @@ pub enum Mapping {
     #[iri("mapping:hash_first")]
     HashFirst,
 
+    /// The subject is the value of the first IRI in the object list
+    /// that has a non-empty value. Unlike `mapping:hash_first` the value is used as is.
+    #[iri("mapping:coalesce")]
+    Coalesce,
+
     #[iri("mapping:when")]
     When,
 
+    /// The subject is loaded from the records of another model that share the value of
+    /// a linking field. The object is either a quoted triple of the model and the
+    /// `mapping:via` field, or a blank node with the `mapping:graph`, `mapping:via`, and
+    /// optional `mapping:when` qualifiers. The condition filters the linked records
+    /// before they are joined. In the blank node form `mapping:via` can also be a list
+    /// of fields which are joined on together as a composite key.
     #[iri("mapping:from")]
     From,
+
+    /// The subject is the numeric value of the source field converted from
+    /// one unit to another. The object is a blank node with the `mapping:source`,
+    /// `mapping:from_unit`, and `mapping:to_unit` qualifiers.
+    #[iri("mapping:convert")]
+    Convert,
+
+    /// The subject is the numeric value of the source field multiplied by a factor, eg.
+    /// to turn kilobases into base pairs. The object is a blank node with the `mapping:source`
+    /// and `mapping:factor` qualifiers.
+    #[iri("mapping:scale")]
+    Scale,
+
+    /// The subject is the value of the source field with all occurrences of a
+    /// string replaced with another. The object is a blank node with the `mapping:source`,
+    /// `mapping:from`, and `mapping:to` qualifiers. Multiple replacements for the same
+    /// subject are applied one after the other.
+    #[iri("mapping:replace")]
+    Replace,
+
+    /// The subject is the value of the object converted to upper case.
+    /// When used alongside `mapping:same` the values from it are converted instead.
+    #[iri("mapping:uppercase")]
+    Uppercase,
+
+    /// The subject is the value of the object converted to lower case.
+    /// When used alongside `mapping:same` the values from it are converted instead.
+    #[iri("mapping:lowercase")]
+    Lowercase,
+
+    /// The subject is the value of the source field padded on the left to a fixed width.
+    /// The object is a blank node with the `mapping:source`, `mapping:width`, and
+    /// optional `mapping:fill` qualifiers. The fill character defaults to `0`.
+    #[iri("mapping:pad_left")]
+    PadLeft,
+
+    /// The same as `mapping:pad_left` except the value is padded on the right.
+    #[iri("mapping:pad_right")]
+    PadRight,
+
+    /// The subject is the value of the object with leading and trailing whitespace removed.
+    #[iri("mapping:trim")]
+    Trim,
+
+    /// The subject is the value of the source field with any of the characters removed
+    /// from the start and end. The object is a blank node with the `mapping:source` and
+    /// `mapping:characters` qualifiers.
+    #[iri("mapping:trim_chars")]
+    TrimChars,
 }
 
 impl TryFrom<&SimpleTerm<'static>> for Mapping {
@@ -108,6 +350,18 @@ impl TryFrom<&SimpleTerm<'static>> for Mapping {
 pub enum MappingCondition {
     #[iri("mapping:is")]
     Is,
+    #[iri("mapping:greater_than")]
+    GreaterThan,
+    #[iri("mapping:less_than")]
+    LessThan,
+
+    /// The object is a list of conditions that must all be met.
+    #[iri("mapping:and")]
+    And,
+
+    /// The object is a list of conditions where at least one must be met.
+    #[iri("mapping:or")]
+    Or,
 }
 
 impl TryFrom<&SimpleTerm<'static>> for MappingCondition {
@@ -123,8 +377,12 @@ impl TryFrom<&SimpleTerm<'static>> for MappingCondition {
 #[derive(Debug, IriEnum)]
 #[iri_prefix("mapping" = "http://arga.org.au/schemas/mapping/")]
 pub enum FromCondition {
+    #[iri("mapping:graph")]
+    Graph,
     #[iri("mapping:via")]
     Via,
+    #[iri("mapping:when")]
+    When,
 }
 
 impl TryFrom<&SimpleTerm<'static>> for FromCondition {
@@ -137,26 +395,299 @@ impl TryFrom<&SimpleTerm<'static>> for FromCondition {
 }
 
 
+#[derive(Debug, IriEnum)]
+#[iri_prefix("mapping" = "http://arga.org.au/schemas/mapping/")]
+pub enum ConvertCondition {
+    #[iri("mapping:source")]
+    Source,
+    #[iri("mapping:from_unit")]
+    FromUnit,
+    #[iri("mapping:to_unit")]
+    ToUnit,
+}
+
+impl TryFrom<&SimpleTerm<'static>> for ConvertCondition {
+    type Error = TransformError;
+
+    fn try_from(value: &SimpleTerm<'static>) -> Result<Self, Self::Error> {
+        let mapping = try_from_term(value)?;
+        Ok(mapping)
+    }
+}
+
+
+#[derive(Debug, IriEnum)]
+#[iri_prefix("mapping" = "http://arga.org.au/schemas/mapping/")]
+pub enum ScaleCondition {
+    #[iri("mapping:source")]
+    Source,
+    #[iri("mapping:factor")]
+    Factor,
+}
+
+impl TryFrom<&SimpleTerm<'static>> for ScaleCondition {
+    type Error = TransformError;
+
+    fn try_from(value: &SimpleTerm<'static>) -> Result<Self, Self::Error> {
+        let mapping = try_from_term(value)?;
+        Ok(mapping)
+    }
+}
+
+
+#[derive(Debug, IriEnum)]
+#[iri_prefix("mapping" = "http://arga.org.au/schemas/mapping/")]
+pub enum ReplaceCondition {
+    #[iri("mapping:source")]
+    Source,
+    #[iri("mapping:from")]
+    From,
+    #[iri("mapping:to")]
+    To,
+}
+
+impl TryFrom<&SimpleTerm<'static>> for ReplaceCondition {
+    type Error = TransformError;
+
+    fn try_from(value: &SimpleTerm<'static>) -> Result<Self, Self::Error> {
+        let mapping = try_from_term(value)?;
+        Ok(mapping)
+    }
+}
+
+
+#[derive(Debug, IriEnum)]
+#[iri_prefix("mapping" = "http://arga.org.au/schemas/mapping/")]
+pub enum PadCondition {
+    #[iri("mapping:source")]
+    Source,
+    #[iri("mapping:width")]
+    Width,
+    #[iri("mapping:fill")]
+    Fill,
+}
+
+impl TryFrom<&SimpleTerm<'static>> for PadCondition {
+    type Error = TransformError;
+
+    fn try_from(value: &SimpleTerm<'static>) -> Result<Self, Self::Error> {
+        let mapping = try_from_term(value)?;
+        Ok(mapping)
+    }
+}
+
+
+#[derive(Debug, IriEnum)]
+#[iri_prefix("mapping" = "http://arga.org.au/schemas/mapping/")]
+pub enum TrimCondition {
+    #[iri("mapping:source")]
+    Source,
+    #[iri("mapping:characters")]
+    Characters,
+}
+
+impl TryFrom<&SimpleTerm<'static>> for TrimCondition {
+    type Error = TransformError;
+
+    fn try_from(value: &SimpleTerm<'static>) -> Result<Self, Self::Error> {
+        let mapping = try_from_term(value)?;
+        Ok(mapping)
+    }
+}
+
+
 #[derive(Debug, Clone)]
 pub enum Map {
     Same(iref::IriBuf),
     Combines(Vec<iref::IriBuf>),
     Hash(iref::IriBuf),
     HashFirst(Vec<iref::IriBuf>),
+    Coalesce(Vec<iref::IriBuf>),
     When(iref::IriBuf, Condition),
-    From { graph: iref::IriBuf, via: iref::IriBuf },
-}
-
-
+    From {
+        graph: iref::IriBuf,
+        via: Vec<iref::IriBuf>,
+        condition: Option<(iref::IriBuf, Condition)>,
+    },
+    Convert {
+        source: iref::IriBuf,
+        from: Unit,
+        to: Unit,
+    },
+    Scale {
+        source: iref::IriBuf,
+        scale: f64,
+    },
+    Replace {
+        source: iref::IriBuf,
+        from: String,
+        to: String,
+    },
+    Uppercase(iref::IriBuf),
+    Lowercase(iref::IriBuf),
+    PadLeft {
+        source: iref::IriBuf,
+        width: usize,
+        fill: char,
+    },
+    PadRight {
+        source: iref::IriBuf,
+        width: usize,
+        fill: char,
+    },
+    Trim(iref::IriBuf),
+    TrimChars {
+        source: iref::IriBuf,
+        chars: String,
+    },
+}
+
+
+/// A filter on the value of a field.
+///
+/// All conditions coerce between string and numeric literals as described in `Literal::loose_cmp`.
+/// Compound conditions are checked in order and stop as soon as the outcome is known.
 #[derive(Debug, Clone)]
 pub enum Condition {
     Is(Literal),
+    GreaterThan(Literal),
+    LessThan(Literal),
+    And(Vec<Condition>),
+    Or(Vec<Condition>),
 }
 
 impl Condition {
     pub fn check(&self, value: &Literal) -> bool {
+        use std::cmp::Ordering;
+
+        match self {
+            Condition::Is(literal) => value.loose_eq(literal),
+            Condition::GreaterThan(literal) => value.loose_cmp(literal) == Some(Ordering::Greater),
+            Condition::LessThan(literal) => value.loose_cmp(literal) == Some(Ordering::Less),
+            Condition::And(conditions) => conditions.iter().all(|condition| condition.check(value)),
+            Condition::Or(conditions) => conditions.iter().any(|condition| condition.check(value)),
+        }
+    }
+}
+
+
+/// The units supported by the `mapping:convert` operator.
+///
+/// Units are grouped by the quantity they measure and a conversion is only
+/// possible between units of the same quantity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    NanogramsPerMicrolitre,
+    MicrogramsPerMillilitre,
+    BasePairs,
+    Kilobases,
+    Megabases,
+    Gigabases,
+    Metres,
+    Centimetres,
+    Feet,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantity {
+    Concentration,
+    SequenceLength,
+    Distance,
+}
+
+impl Unit {
+    /// The normalised unit string used when outputting a converted value.
+    pub fn label(&self) -> &'static str {
         match self {
-            Condition::Is(literal) => value.eq(literal),
+            Unit::NanogramsPerMicrolitre => "ng/µL",
+            Unit::MicrogramsPerMillilitre => "µg/mL",
+            Unit::BasePairs => "bp",
+            Unit::Kilobases => "kb",
+            Unit::Megabases => "Mb",
+            Unit::Gigabases => "Gb",
+            Unit::Metres => "m",
+            Unit::Centimetres => "cm",
+            Unit::Feet => "ft",
+        }
+    }
+
+    pub fn quantity(&self) -> Quantity {
+        match self {
+            Unit::NanogramsPerMicrolitre | Unit::MicrogramsPerMillilitre => Quantity::Concentration,
+            Unit::BasePairs | Unit::Kilobases | Unit::Megabases | Unit::Gigabases => Quantity::SequenceLength,
+            Unit::Metres | Unit::Centimetres | Unit::Feet => Quantity::Distance,
+        }
+    }
+
+    // the multiplier to get to the base unit of the quantity.
+    // ng/µL and µg/mL are the same concentration so they share a factor
+    fn factor(&self) -> f64 {
+        match self {
+            Unit::NanogramsPerMicrolitre => 1.0,
+            Unit::MicrogramsPerMillilitre => 1.0,
+            Unit::BasePairs => 1.0,
+            Unit::Kilobases => 1_000.0,
+            Unit::Megabases => 1_000_000.0,
+            Unit::Gigabases => 1_000_000_000.0,
+            Unit::Metres => 1.0,
+            Unit::Centimetres => 0.01,
+            Unit::Feet => 0.3048,
+        }
+    }
+
+    /// Convert a value in this unit into the `to` unit.
+    ///
+    /// Returns `None` if the units measure different quantities. Base pairs
+    /// are always rounded to the nearest whole number.
+    pub fn convert(&self, value: f64, to: &Unit) -> Option<f64> {
+        if self.quantity() != to.quantity() {
+            return None;
+        }
+
+        let converted = value * self.factor() / to.factor();
+        match to {
+            Unit::BasePairs => Some(converted.round()),
+            _ => Some(converted),
+        }
+    }
+
+    /// Convert a literal value from this unit into the `to` unit.
+    ///
+    /// String values are parsed as a float after removing thousands separators.
+    /// The converted value is returned as a string literal so that it can be
+    /// coerced into the target field type like any other source value.
+    pub fn convert_literal(&self, value: &Literal, to: &Unit) -> Result<Literal, TransformError> {
+        let value = match value {
+            Literal::String(val) => val.trim().replace(",", "").parse::<f64>()?,
+            Literal::UInt64(val) => *val as f64,
+        };
+
+        match self.convert(value, to) {
+            Some(converted) => Ok(Literal::String(converted.to_string())),
+            None => Err(ResolveError::IncompatibleUnits(*self, *to).into()),
+        }
+    }
+}
+
+impl std::str::FromStr for Unit {
+    type Err = ResolveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // both the micro sign and the greek mu are used in the wild as well as
+        // the plain 'u' so we normalise them all before matching
+        let unit = s.trim().replace(['µ', 'μ'], "u").to_lowercase();
+
+        match unit.as_str() {
+            "ng/ul" => Ok(Unit::NanogramsPerMicrolitre),
+            "ug/ml" => Ok(Unit::MicrogramsPerMillilitre),
+            "bp" => Ok(Unit::BasePairs),
+            "kb" | "kbp" => Ok(Unit::Kilobases),
+            "mb" | "mbp" => Ok(Unit::Megabases),
+            "gb" | "gbp" => Ok(Unit::Gigabases),
+            "m" | "metre" | "metres" | "meter" | "meters" => Ok(Unit::Metres),
+            "cm" => Ok(Unit::Centimetres),
+            "ft" | "foot" | "feet" => Ok(Unit::Feet),
+            _ => Err(ResolveError::UnsupportedUnit(s.to_string())),
         }
     }
 }
@@ -183,24 +714,19 @@ impl TryFrom<&SimpleTerm<'static>> for Rdfs {
 }
 
 
-#[derive(Debug, IriEnum)]
-#[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
-pub enum Name {
-    #[iri("fields:entity_id")]
-    EntityId,
-    #[iri("fields:canonical_name")]
-    CanonicalName,
-    #[iri("fields:scientific_name")]
-    ScientificName,
-    #[iri("fields:scientific_name_authorship")]
-    ScientificNameAuthorship,
-}
-
-impl Name {
-    pub const ALL: &[Name] = {
-        use Name::*;
-        &[EntityId, CanonicalName, ScientificName, ScientificNameAuthorship]
-    };
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum Name {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:canonical_name")]
+        CanonicalName,
+        #[iri("fields:scientific_name")]
+        ScientificName,
+        #[iri("fields:scientific_name_authorship")]
+        ScientificNameAuthorship,
+    }
 }
 
 
@@ -219,56 +745,39 @@ impl From<(Name, Literal)> for NameField {
             (Name::CanonicalName, Literal::String(value)) => Self::CanonicalName(value),
             (Name::ScientificName, Literal::String(value)) => Self::ScientificName(value),
             (Name::ScientificNameAuthorship, Literal::String(value)) => Self::ScientificNameAuthorship(value),
-            _ => unimplemented!(),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
         }
     }
 }
 
 
-#[derive(Debug, IriEnum)]
-#[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
-pub enum Publication {
-    #[iri("fields:publication_entity_id")]
-    EntityId,
-    #[iri("fields:title")]
-    Title,
-    #[iri("fields:authors")]
-    Authors,
-    #[iri("fields:published_year")]
-    PublishedYear,
-    #[iri("fields:published_date")]
-    PublishedDate,
-    #[iri("fields:language")]
-    Language,
-    #[iri("fields:publisher")]
-    Publisher,
-    #[iri("fields:doi")]
-    Doi,
-    #[iri("fields:publication_type")]
-    PublicationType,
-    #[iri("fields:citation")]
-    Citation,
-    #[iri("fields:source_url")]
-    SourceUrl,
-}
-
-impl Publication {
-    pub const ALL: &[Publication] = {
-        use Publication::*;
-        &[
-            EntityId,
-            Title,
-            Authors,
-            PublishedYear,
-            PublishedDate,
-            Language,
-            Publisher,
-            Doi,
-            PublicationType,
-            Citation,
-            SourceUrl,
-        ]
-    };
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum Publication {
+        #[iri("fields:publication_entity_id")]
+        EntityId,
+        #[iri("fields:title")]
+        Title,
+        #[iri("fields:authors")]
+        Authors,
+        #[iri("fields:published_year")]
+        PublishedYear,
+        #[iri("fields:published_date")]
+        PublishedDate,
+        #[iri("fields:language")]
+        Language,
+        #[iri("fields:publisher")]
+        Publisher,
+        #[iri("fields:doi")]
+        Doi,
+        #[iri("fields:publication_type")]
+        PublicationType,
+        #[iri("fields:citation")]
+        Citation,
+        #[iri("fields:source_url")]
+        SourceUrl,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -300,95 +809,67 @@ impl From<(Publication, Literal)> for PublicationField {
             (Publication::PublicationType, Literal::String(value)) => Self::PublicationType(value),
             (Publication::Citation, Literal::String(value)) => Self::Citation(value),
             (Publication::SourceUrl, Literal::String(value)) => Self::SourceUrl(value),
-            _ => unimplemented!(),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
         }
     }
 }
 
 
-#[derive(Debug, IriEnum)]
-#[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
-pub enum Tissue {
-    #[iri("fields:entity_id")]
-    EntityId,
-    #[iri("fields:organism_id")]
-    OrganismId,
-    #[iri("fields:tissue_id")]
-    TissueId,
-    #[iri("fields:material_sample_id")]
-    MaterialSampleId,
-    #[iri("fields:original_catalogue_name")]
-    OriginalCatalogueName,
-    #[iri("fields:current_catalogue_name")]
-    CurrentCatalogueName,
-    #[iri("fields:identification_verified")]
-    IdentificationVerified,
-    #[iri("fields:reference_material")]
-    ReferenceMaterial,
-    #[iri("fields:registered_by")]
-    RegisteredBy,
-    #[iri("fields:registration_date")]
-    RegistrationDate,
-    #[iri("fields:custodian")]
-    Custodian,
-    #[iri("fields:institution")]
-    Institution,
-    #[iri("fields:institution_code")]
-    InstitutionCode,
-    #[iri("fields:collection")]
-    Collection,
-    #[iri("fields:collection_code")]
-    CollectionCode,
-    #[iri("fields:status")]
-    Status,
-    #[iri("fields:current_status")]
-    CurrentStatus,
-    #[iri("fields:sampling_protocol")]
-    SamplingProtocol,
-    #[iri("fields:tissue_type")]
-    TissueType,
-    #[iri("fields:disposition")]
-    Disposition,
-    #[iri("fields:fixation")]
-    Fixation,
-    #[iri("fields:storage")]
-    Storage,
-    #[iri("fields:citation")]
-    Citation,
-    #[iri("fields:source_url")]
-    SourceUrl,
-}
-
-impl Tissue {
-    pub const ALL: &[Tissue] = {
-        use Tissue::*;
-        &[
-            EntityId,
-            OrganismId,
-            TissueId,
-            MaterialSampleId,
-            OriginalCatalogueName,
-            CurrentCatalogueName,
-            IdentificationVerified,
-            ReferenceMaterial,
-            RegisteredBy,
-            RegistrationDate,
-            Custodian,
-            Institution,
-            InstitutionCode,
-            Collection,
-            CollectionCode,
-            Status,
-            CurrentStatus,
-            SamplingProtocol,
-            TissueType,
-            Disposition,
-            Fixation,
-            Storage,
-            Citation,
-            SourceUrl,
-        ]
-    };
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum Tissue {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:organism_id")]
+        OrganismId,
+        #[iri("fields:tissue_id")]
+        TissueId,
+        #[iri("fields:material_sample_id")]
+        MaterialSampleId,
+        #[iri("fields:scientific_name")]
+        ScientificName,
+        #[iri("fields:original_catalogue_name")]
+        OriginalCatalogueName,
+        #[iri("fields:current_catalogue_name")]
+        CurrentCatalogueName,
+        #[iri("fields:identification_verified")]
+        IdentificationVerified,
+        #[iri("fields:reference_material")]
+        ReferenceMaterial,
+        #[iri("fields:registered_by")]
+        RegisteredBy,
+        #[iri("fields:registration_date")]
+        RegistrationDate,
+        #[iri("fields:custodian")]
+        Custodian,
+        #[iri("fields:institution")]
+        Institution,
+        #[iri("fields:institution_code")]
+        InstitutionCode,
+        #[iri("fields:collection")]
+        Collection,
+        #[iri("fields:collection_code")]
+        CollectionCode,
+        #[iri("fields:status")]
+        Status,
+        #[iri("fields:current_status")]
+        CurrentStatus,
+        #[iri("fields:sampling_protocol")]
+        SamplingProtocol,
+        #[iri("fields:tissue_type")]
+        TissueType,
+        #[iri("fields:disposition")]
+        Disposition,
+        #[iri("fields:fixation")]
+        Fixation,
+        #[iri("fields:storage")]
+        Storage,
+        #[iri("fields:citation")]
+        Citation,
+        #[iri("fields:source_url")]
+        SourceUrl,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -397,6 +878,7 @@ pub enum TissueField {
     OrganismId(String),
     TissueId(String),
     MaterialSampleId(String),
+    ScientificName(String),
     OriginalCatalogueName(String),
     CurrentCatalogueName(String),
     IdentificationVerified(String),
@@ -427,6 +909,7 @@ impl From<(Tissue, Literal)> for TissueField {
             (Tissue::OrganismId, Literal::String(value)) => Self::OrganismId(value),
             (Tissue::TissueId, Literal::String(value)) => Self::TissueId(value),
             (Tissue::MaterialSampleId, Literal::String(value)) => Self::MaterialSampleId(value),
+            (Tissue::ScientificName, Literal::String(value)) => Self::ScientificName(value),
             (Tissue::OriginalCatalogueName, Literal::String(value)) => Self::OriginalCatalogueName(value),
             (Tissue::CurrentCatalogueName, Literal::String(value)) => Self::CurrentCatalogueName(value),
             (Tissue::IdentificationVerified, Literal::String(value)) => Self::IdentificationVerified(value),
@@ -447,146 +930,189 @@ impl From<(Tissue, Literal)> for TissueField {
             (Tissue::Storage, Literal::String(value)) => Self::Storage(value),
             (Tissue::Citation, Literal::String(value)) => Self::Citation(value),
             (Tissue::SourceUrl, Literal::String(value)) => Self::SourceUrl(value),
-            _ => unimplemented!(),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
         }
     }
 }
 
 
-#[derive(Debug, IriEnum)]
-#[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
-pub enum Collecting {
-    #[iri("fields:entity_id")]
-    EntityId,
-    #[iri("fields:material_sample_id")]
-    MaterialSampleId,
-    #[iri("fields:scientific_name")]
-    ScientificName,
-    #[iri("fields:organism_id")]
-    OrganismId,
-    #[iri("fields:field_collecting_id")]
-    FieldCollectingId,
-
-    #[iri("fields:collected_by")]
-    CollectedBy,
-    #[iri("fields:collection_date")]
-    CollectionDate,
-    #[iri("fields:remarks")]
-    Remarks,
-
-    #[iri("fields:preparation")]
-    Preparation,
-    #[iri("fields:habitat")]
-    Habitat,
-    #[iri("fields:specific_host")]
-    SpecificHost,
-    #[iri("fields:individual_count")]
-    IndividualCount,
-    #[iri("fields:strain")]
-    Strain,
-    #[iri("fields:isolate")]
-    Isolate,
-
-    #[iri("fields:permit")]
-    Permit,
-    #[iri("fields:sampling_protocol")]
-    SamplingProtocol,
-    #[iri("fields:organism_killed")]
-    OrganismKilled,
-    #[iri("fields:organism_kill_method")]
-    OrganismKillMethod,
-    #[iri("fields:field_sample_disposition")]
-    FieldSampleDisposition,
-    #[iri("fields:field_notes")]
-    FieldNotes,
-
-    #[iri("fields:environment_broad_scale")]
-    EnvironmentBroadScale,
-    #[iri("fields:environment_local_scale")]
-    EnvironmentLocalScale,
-    #[iri("fields:environment_medium")]
-    EnvironmentMedium,
-
-    #[iri("fields:locality")]
-    Locality,
-    #[iri("fields:country")]
-    Country,
-    #[iri("fields:country_code")]
-    CountryCode,
-    #[iri("fields:state_province")]
-    StateProvince,
-    #[iri("fields:county")]
-    County,
-    #[iri("fields:municipality")]
-    Municipality,
-    #[iri("fields:latitude")]
-    Latitude,
-    #[iri("fields:longitude")]
-    Longitude,
-    #[iri("fields:location_generalisation")]
-    LocationGeneralisation,
-    #[iri("fields:location_source")]
-    LocationSource,
-    #[iri("fields:elevation")]
-    Elevation,
-    #[iri("fields:elevation_accuracy")]
-    ElevationAccuracy,
-    #[iri("fields:depth")]
-    Depth,
-    #[iri("fields:depth_accuracy")]
-    DepthAccuracy,
-
-    #[iri("fields:canonical_name")]
-    CanonicalName,
-    #[iri("fields:scientific_name_authorship")]
-    ScientificNameAuthorship,
-}
-
-impl Collecting {
-    pub const ALL: &[Collecting] = {
-        use Collecting::*;
-        &[
-            EntityId,
-            MaterialSampleId,
-            ScientificName,
-            OrganismId,
-            FieldCollectingId,
-            CollectedBy,
-            CollectionDate,
-            Remarks,
-            Preparation,
-            Habitat,
-            SpecificHost,
-            IndividualCount,
-            Strain,
-            Isolate,
-            Permit,
-            SamplingProtocol,
-            OrganismKilled,
-            OrganismKillMethod,
-            FieldSampleDisposition,
-            FieldNotes,
-            EnvironmentBroadScale,
-            EnvironmentLocalScale,
-            EnvironmentMedium,
-            Locality,
-            Country,
-            CountryCode,
-            StateProvince,
-            County,
-            Municipality,
-            Latitude,
-            Longitude,
-            LocationGeneralisation,
-            LocationSource,
-            Elevation,
-            ElevationAccuracy,
-            Depth,
-            DepthAccuracy,
-            CanonicalName,
-            ScientificNameAuthorship,
-        ]
-    };
+model_fields! {
+    /// The physical organism or voucher specimen held in a collection.
+    ///
+    /// A tissue is a subsample of a specimen so the biobanking details of it belong
+    /// to the `Tissue` model instead.
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum Specimen {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:specimen_id")]
+        SpecimenId,
+        #[iri("fields:scientific_name")]
+        ScientificName,
+        #[iri("fields:institution")]
+        Institution,
+        #[iri("fields:institution_code")]
+        InstitutionCode,
+        #[iri("fields:collection")]
+        Collection,
+        #[iri("fields:collection_code")]
+        CollectionCode,
+        #[iri("fields:catalogue_number")]
+        CatalogueNumber,
+        #[iri("fields:type_status")]
+        TypeStatus,
+        #[iri("fields:preparation_type")]
+        PreparationType,
+        #[iri("fields:sex")]
+        Sex,
+        #[iri("fields:life_stage")]
+        LifeStage,
+        #[iri("fields:latitude")]
+        Latitude,
+        #[iri("fields:longitude")]
+        Longitude,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SpecimenField {
+    EntityId(String),
+    SpecimenId(String),
+    ScientificName(String),
+    Institution(String),
+    InstitutionCode(String),
+    Collection(String),
+    CollectionCode(String),
+    CatalogueNumber(String),
+    TypeStatus(String),
+    PreparationType(String),
+    Sex(String),
+    LifeStage(String),
+    Latitude(String),
+    Longitude(String),
+}
+
+
+impl From<(Specimen, Literal)> for SpecimenField {
+    fn from(source: (Specimen, Literal)) -> Self {
+        match source {
+            (Specimen::EntityId, Literal::String(value)) => Self::EntityId(value),
+            (Specimen::SpecimenId, Literal::String(value)) => Self::SpecimenId(value),
+            (Specimen::ScientificName, Literal::String(value)) => Self::ScientificName(value),
+            (Specimen::Institution, Literal::String(value)) => Self::Institution(value),
+            (Specimen::InstitutionCode, Literal::String(value)) => Self::InstitutionCode(value),
+            (Specimen::Collection, Literal::String(value)) => Self::Collection(value),
+            (Specimen::CollectionCode, Literal::String(value)) => Self::CollectionCode(value),
+            (Specimen::CatalogueNumber, Literal::String(value)) => Self::CatalogueNumber(value),
+            (Specimen::TypeStatus, Literal::String(value)) => Self::TypeStatus(value),
+            (Specimen::PreparationType, Literal::String(value)) => Self::PreparationType(value),
+            (Specimen::Sex, Literal::String(value)) => Self::Sex(value),
+            (Specimen::LifeStage, Literal::String(value)) => Self::LifeStage(value),
+            (Specimen::Latitude, Literal::String(value)) => Self::Latitude(value),
+            (Specimen::Longitude, Literal::String(value)) => Self::Longitude(value),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
+        }
+    }
+}
+
+
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum Collecting {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:material_sample_id")]
+        MaterialSampleId,
+        #[iri("fields:scientific_name")]
+        ScientificName,
+        #[iri("fields:organism_id")]
+        OrganismId,
+        #[iri("fields:field_collecting_id")]
+        FieldCollectingId,
+
+        #[iri("fields:collected_by")]
+        CollectedBy,
+        #[iri("fields:collected_by_orcid")]
+        CollectedByOrcid,
+        /// The entity id of the collector agent. This is usually a `mapping:hash_first`
+        /// of `fields:collected_by_orcid` and `fields:collected_by` so that a collector with
+        /// an ORCID is the same agent no matter how their name was written.
+        #[iri("fields:collected_by_entity_id")]
+        CollectedByEntityId,
+        #[iri("fields:collection_date")]
+        CollectionDate,
+        #[iri("fields:remarks")]
+        Remarks,
+
+        #[iri("fields:preparation")]
+        Preparation,
+        #[iri("fields:habitat")]
+        Habitat,
+        #[iri("fields:specific_host")]
+        SpecificHost,
+        #[iri("fields:individual_count")]
+        IndividualCount,
+        #[iri("fields:strain")]
+        Strain,
+        #[iri("fields:isolate")]
+        Isolate,
+
+        #[iri("fields:permit")]
+        Permit,
+        #[iri("fields:sampling_protocol")]
+        SamplingProtocol,
+        #[iri("fields:organism_killed")]
+        OrganismKilled,
+        #[iri("fields:organism_kill_method")]
+        OrganismKillMethod,
+        #[iri("fields:field_sample_disposition")]
+        FieldSampleDisposition,
+        #[iri("fields:field_notes")]
+        FieldNotes,
+
+        #[iri("fields:environment_broad_scale")]
+        EnvironmentBroadScale,
+        #[iri("fields:environment_local_scale")]
+        EnvironmentLocalScale,
+        #[iri("fields:environment_medium")]
+        EnvironmentMedium,
+
+        #[iri("fields:locality")]
+        Locality,
+        #[iri("fields:country")]
+        Country,
+        #[iri("fields:country_code")]
+        CountryCode,
+        #[iri("fields:state_province")]
+        StateProvince,
+        #[iri("fields:county")]
+        County,
+        #[iri("fields:municipality")]
+        Municipality,
+        #[iri("fields:latitude")]
+        Latitude,
+        #[iri("fields:longitude")]
+        Longitude,
+        #[iri("fields:location_generalisation")]
+        LocationGeneralisation,
+        #[iri("fields:location_source")]
+        LocationSource,
+        #[iri("fields:elevation")]
+        Elevation,
+        #[iri("fields:elevation_accuracy")]
+        ElevationAccuracy,
+        #[iri("fields:depth")]
+        Depth,
+        #[iri("fields:depth_accuracy")]
+        DepthAccuracy,
+
+        #[iri("fields:canonical_name")]
+        CanonicalName,
+        #[iri("fields:scientific_name_authorship")]
+        ScientificNameAuthorship,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -598,6 +1124,8 @@ pub enum CollectingField {
     ScientificName(String),
 
     CollectedBy(String),
+    CollectedByOrcid(String),
+    CollectedByEntityId(String),
     CollectionDate(String),
     Remarks(String),
 
@@ -648,7 +1176,12 @@ impl From<(Collecting, Literal)> for CollectingField {
             (Collecting::FieldCollectingId, Literal::String(value)) => Self::FieldCollectingId(value),
             (Collecting::ScientificName, Literal::String(value)) => Self::ScientificName(value),
             (Collecting::CollectedBy, Literal::String(value)) => Self::CollectedBy(value),
-            (Collecting::CollectionDate, Literal::String(value)) => Self::CollectionDate(value),
+            (Collecting::CollectedByOrcid, Literal::String(value)) => Self::CollectedByOrcid(value),
+            (Collecting::CollectedByEntityId, Literal::String(value)) => Self::CollectedByEntityId(value),
+            (Collecting::CollectionDate, Literal::String(value)) => {
+                // keep the original value when it isn't in a known format rather than losing it
+                Self::CollectionDate(crate::utils::parse_date_flexible(&value).unwrap_or(value))
+            }
             (Collecting::Remarks, Literal::String(value)) => Self::Remarks(value),
             (Collecting::Preparation, Literal::String(value)) => Self::Preparation(value),
             (Collecting::Habitat, Literal::String(value)) => Self::Habitat(value),
@@ -683,137 +1216,95 @@ impl From<(Collecting, Literal)> for CollectingField {
 
             (Collecting::CanonicalName, Literal::String(value)) => Self::CanonicalName(value),
             (Collecting::ScientificNameAuthorship, Literal::String(value)) => Self::ScientificNameAuthorship(value),
-            _ => unimplemented!(),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
         }
     }
 }
 
 
-#[derive(Debug, IriEnum)]
-#[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
-pub enum Organism {
-    #[iri("fields:entity_id")]
-    EntityId,
-    #[iri("fields:organism_id")]
-    OrganismId,
-    #[iri("fields:scientific_name")]
-    ScientificName,
-    #[iri("fields:sex")]
-    Sex,
-    #[iri("fields:genotypic_sex")]
-    GenotypicSex,
-    #[iri("fields:phenotypic_sex")]
-    PhenotypicSex,
-    #[iri("fields:life_stage")]
-    LifeStage,
-    #[iri("fields:reproductive_condition")]
-    ReproductiveCondition,
-    #[iri("fields:behavior")]
-    Behavior,
-    #[iri("fields:live_state")]
-    LiveState,
-    #[iri("fields:remarks")]
-    Remarks,
-
-    #[iri("fields:identified_by")]
-    IdentifiedBy,
-    #[iri("fields:identification_date")]
-    IdentificationDate,
-    #[iri("fields:disposition")]
-    Disposition,
-    #[iri("fields:first_observed_at")]
-    FirstObservedAt,
-    #[iri("fields:last_known_alive_at")]
-    LastKnownAliveAt,
-
-    #[iri("fields:biome")]
-    Biome,
-    #[iri("fields:habitat")]
-    Habitat,
-    #[iri("fields:bioregion")]
-    Bioregion,
-    #[iri("fields:ibra_imcra")]
-    IbraImcra,
-
-    #[iri("fields:latitude")]
-    Latitude,
-    #[iri("fields:longitude")]
-    Longitude,
-    #[iri("fields:coordinate_system")]
-    CoordinateSystem,
-    #[iri("fields:location_source")]
-    LocationSource,
-    #[iri("fields:holding")]
-    Holding,
-    #[iri("fields:holding_id")]
-    HoldingId,
-    #[iri("fields:holding_permit")]
-    HoldingPermit,
-
-    #[iri("fields:doi")]
-    Doi,
-    #[iri("fields:citation")]
-    Citation,
-
-    #[iri("fields:curator")]
-    Curator,
-    #[iri("fields:curator_orcid")]
-    CuratorOrcid,
-    #[iri("fields:created_at")]
-    CreatedAt,
-    #[iri("fields:updated_at")]
-    UpdatedAt,
-
-    #[iri("fields:publication_entity_id")]
-    PublicationEntityId,
-    #[iri("fields:canonical_name")]
-    CanonicalName,
-    #[iri("fields:scientific_name_authorship")]
-    ScientificNameAuthorship,
-}
-
-impl Organism {
-    pub const ALL: &[Organism] = {
-        use Organism::*;
-        &[
-            EntityId,
-            OrganismId,
-            ScientificName,
-            Sex,
-            GenotypicSex,
-            PhenotypicSex,
-            LifeStage,
-            ReproductiveCondition,
-            Behavior,
-            LiveState,
-            Remarks,
-            IdentifiedBy,
-            IdentificationDate,
-            Disposition,
-            FirstObservedAt,
-            LastKnownAliveAt,
-            Biome,
-            Habitat,
-            Bioregion,
-            IbraImcra,
-            Latitude,
-            Longitude,
-            CoordinateSystem,
-            LocationSource,
-            Holding,
-            HoldingId,
-            HoldingPermit,
-            Doi,
-            Citation,
-            Curator,
-            CuratorOrcid,
-            CreatedAt,
-            UpdatedAt,
-            PublicationEntityId,
-            CanonicalName,
-            ScientificNameAuthorship,
-        ]
-    };
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum Organism {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:organism_id")]
+        OrganismId,
+        #[iri("fields:scientific_name")]
+        ScientificName,
+        #[iri("fields:sex")]
+        Sex,
+        #[iri("fields:genotypic_sex")]
+        GenotypicSex,
+        #[iri("fields:phenotypic_sex")]
+        PhenotypicSex,
+        #[iri("fields:life_stage")]
+        LifeStage,
+        #[iri("fields:reproductive_condition")]
+        ReproductiveCondition,
+        #[iri("fields:behavior")]
+        Behavior,
+        #[iri("fields:live_state")]
+        LiveState,
+        #[iri("fields:remarks")]
+        Remarks,
+
+        #[iri("fields:identified_by")]
+        IdentifiedBy,
+        #[iri("fields:identification_date")]
+        IdentificationDate,
+        #[iri("fields:disposition")]
+        Disposition,
+        #[iri("fields:first_observed_at")]
+        FirstObservedAt,
+        #[iri("fields:last_known_alive_at")]
+        LastKnownAliveAt,
+
+        #[iri("fields:biome")]
+        Biome,
+        #[iri("fields:habitat")]
+        Habitat,
+        #[iri("fields:bioregion")]
+        Bioregion,
+        #[iri("fields:ibra_imcra")]
+        IbraImcra,
+
+        #[iri("fields:latitude")]
+        Latitude,
+        #[iri("fields:longitude")]
+        Longitude,
+        #[iri("fields:coordinate_system")]
+        CoordinateSystem,
+        #[iri("fields:location_source")]
+        LocationSource,
+        #[iri("fields:holding")]
+        Holding,
+        #[iri("fields:holding_id")]
+        HoldingId,
+        #[iri("fields:holding_permit")]
+        HoldingPermit,
+
+        #[iri("fields:doi")]
+        Doi,
+        #[iri("fields:citation")]
+        Citation,
+
+        #[iri("fields:curator")]
+        Curator,
+        #[iri("fields:curator_orcid")]
+        CuratorOrcid,
+        #[iri("fields:created_at")]
+        CreatedAt,
+        #[iri("fields:updated_at")]
+        UpdatedAt,
+
+        #[iri("fields:publication_entity_id")]
+        PublicationEntityId,
+        #[iri("fields:canonical_name")]
+        CanonicalName,
+        #[iri("fields:scientific_name_authorship")]
+        ScientificNameAuthorship,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -898,98 +1389,69 @@ impl From<(Organism, Literal)> for OrganismField {
             (Organism::PublicationEntityId, Literal::String(value)) => Self::PublicationEntityId(value),
             (Organism::CanonicalName, Literal::String(value)) => Self::CanonicalName(value),
             (Organism::ScientificNameAuthorship, Literal::String(value)) => Self::ScientificNameAuthorship(value),
-            _ => unimplemented!(),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
         }
     }
 }
 
 
-#[derive(Debug, IriEnum)]
-#[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
-pub enum Subsample {
-    #[iri("fields:entity_id")]
-    EntityId,
-    #[iri("fields:specimen_id")]
-    SpecimenId,
-    #[iri("fields:material_sample_id")]
-    MaterialSampleId,
-    #[iri("fields:tissue_id")]
-    TissueId,
-    #[iri("fields:subsample_id")]
-    SubsampleId,
-    #[iri("fields:sample_type")]
-    SampleType,
-    #[iri("fields:institution")]
-    Institution,
-    #[iri("fields:institution_code")]
-    InstitutionCode,
-    #[iri("fields:name")]
-    Name,
-    #[iri("fields:custodian")]
-    Custodian,
-    #[iri("fields:description")]
-    Description,
-    #[iri("fields:notes")]
-    Notes,
-    #[iri("fields:culture_method")]
-    CultureMethod,
-    #[iri("fields:culture_media")]
-    CultureMedia,
-    #[iri("fields:weight_or_vol")]
-    WeightOrVolume,
-    #[iri("fields:preservation_method")]
-    PreservationMethod,
-    #[iri("fields:preservation_temperature")]
-    PreservationTemperature,
-    #[iri("fields:preservation_duration")]
-    PreservationDuration,
-    #[iri("fields:quality")]
-    Quality,
-    #[iri("fields:cell_type")]
-    CellType,
-    #[iri("fields:cell_line")]
-    CellLine,
-    #[iri("fields:clone_name")]
-    CloneName,
-    #[iri("fields:lab_host")]
-    LabHost,
-    #[iri("fields:sample_processing")]
-    SampleProcessing,
-    #[iri("fields:sample_pooling")]
-    SamplePooling,
-}
-
-impl Subsample {
-    pub const ALL: &[Subsample] = {
-        use Subsample::*;
-        &[
-            EntityId,
-            SpecimenId,
-            MaterialSampleId,
-            TissueId,
-            SubsampleId,
-            SampleType,
-            Institution,
-            InstitutionCode,
-            Name,
-            Custodian,
-            Description,
-            Notes,
-            CultureMethod,
-            CultureMedia,
-            WeightOrVolume,
-            PreservationMethod,
-            PreservationTemperature,
-            PreservationDuration,
-            Quality,
-            CellType,
-            CellLine,
-            CloneName,
-            LabHost,
-            SampleProcessing,
-            SamplePooling,
-        ]
-    };
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum Subsample {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:specimen_id")]
+        SpecimenId,
+        #[iri("fields:material_sample_id")]
+        MaterialSampleId,
+        #[iri("fields:tissue_id")]
+        TissueId,
+        #[iri("fields:subsample_id")]
+        SubsampleId,
+        #[iri("fields:scientific_name")]
+        ScientificName,
+        #[iri("fields:sample_type")]
+        SampleType,
+        #[iri("fields:institution")]
+        Institution,
+        #[iri("fields:institution_code")]
+        InstitutionCode,
+        #[iri("fields:name")]
+        Name,
+        #[iri("fields:custodian")]
+        Custodian,
+        #[iri("fields:description")]
+        Description,
+        #[iri("fields:notes")]
+        Notes,
+        #[iri("fields:culture_method")]
+        CultureMethod,
+        #[iri("fields:culture_media")]
+        CultureMedia,
+        #[iri("fields:weight_or_vol")]
+        WeightOrVolume,
+        #[iri("fields:preservation_method")]
+        PreservationMethod,
+        #[iri("fields:preservation_temperature")]
+        PreservationTemperature,
+        #[iri("fields:preservation_duration")]
+        PreservationDuration,
+        #[iri("fields:quality")]
+        Quality,
+        #[iri("fields:cell_type")]
+        CellType,
+        #[iri("fields:cell_line")]
+        CellLine,
+        #[iri("fields:clone_name")]
+        CloneName,
+        #[iri("fields:lab_host")]
+        LabHost,
+        #[iri("fields:sample_processing")]
+        SampleProcessing,
+        #[iri("fields:sample_pooling")]
+        SamplePooling,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -999,6 +1461,7 @@ pub enum SubsampleField {
     MaterialSampleId(String),
     TissueId(String),
     SubsampleId(String),
+    ScientificName(String),
     SampleType(String),
     Institution(String),
     InstitutionCode(String),
@@ -1030,6 +1493,7 @@ impl From<(Subsample, Literal)> for SubsampleField {
             (Subsample::MaterialSampleId, Literal::String(value)) => Self::MaterialSampleId(value),
             (Subsample::TissueId, Literal::String(value)) => Self::TissueId(value),
             (Subsample::SubsampleId, Literal::String(value)) => Self::SubsampleId(value),
+            (Subsample::ScientificName, Literal::String(value)) => Self::ScientificName(value),
             (Subsample::SampleType, Literal::String(value)) => Self::SampleType(value),
             (Subsample::Institution, Literal::String(value)) => Self::Institution(value),
             (Subsample::InstitutionCode, Literal::String(value)) => Self::InstitutionCode(value),
@@ -1050,99 +1514,68 @@ impl From<(Subsample, Literal)> for SubsampleField {
             (Subsample::LabHost, Literal::String(value)) => Self::LabHost(value),
             (Subsample::SampleProcessing, Literal::String(value)) => Self::SampleProcessing(value),
             (Subsample::SamplePooling, Literal::String(value)) => Self::SamplePooling(value),
-            _ => unimplemented!(),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
         }
     }
 }
 
 
-#[derive(Debug, IriEnum)]
-#[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
-pub enum Extraction {
-    #[iri("fields:entity_id")]
-    EntityId,
-    #[iri("fields:subsample_id")]
-    SubsampleId,
-    #[iri("fields:extract_id")]
-    ExtractId,
-    #[iri("fields:extracted_by")]
-    ExtractedBy,
-    #[iri("fields:extracted_by_orcid")]
-    ExtractedByOrcid,
-    #[iri("fields:extraction_date")]
-    ExtractionDate,
-    #[iri("fields:nucleic_acid_type")]
-    NucleicAcidType,
-    #[iri("fields:nucleic_acid_conformation")]
-    NucleicAcidConformation,
-    #[iri("fields:nucleic_acid_preservation_method")]
-    NucleicAcidPreservationMethod,
-    #[iri("fields:nucleic_acid_concentration")]
-    NucleicAcidConcentration,
-    #[iri("fields:nucleic_acid_quantification")]
-    NucleicAcidQuantification,
-    #[iri("fields:concentration_unit")]
-    ConcentrationUnit,
-    #[iri("fields:absorbance_260_230_ratio")]
-    Absorbance260230Ratio,
-    #[iri("fields:absorbance_260_280_ratio")]
-    Absorbance260280Ratio,
-    #[iri("fields:cell_lysis_method")]
-    CellLysisMethod,
-    #[iri("fields:material_extracted_by")]
-    MaterialExtractedBy,
-    #[iri("fields:material_extracted_by_orcid")]
-    MaterialExtractedByOrcid,
-    #[iri("fields:action_extracted")]
-    ActionExtracted,
-    #[iri("fields:extraction_method")]
-    ExtractionMethod,
-    #[iri("fields:number_of_extracts_pooled")]
-    NumberOfExtractsPooled,
-    #[iri("fields:doi")]
-    Doi,
-    #[iri("fields:citation")]
-    Citation,
-
-    #[iri("fields:extracted_by_entity_id")]
-    ExtractedByEntityId,
-    #[iri("fields:material_extracted_by_entity_id")]
-    MaterialExtractedByEntityId,
-    #[iri("fields:publication_entity_id")]
-    PublicationEntityId,
-}
-
-impl Extraction {
-    pub const ALL: &[Extraction] = {
-        use Extraction::*;
-        &[
-            EntityId,
-            SubsampleId,
-            ExtractId,
-            ExtractedBy,
-            ExtractedByOrcid,
-            ExtractionDate,
-            NucleicAcidType,
-            NucleicAcidConformation,
-            NucleicAcidPreservationMethod,
-            NucleicAcidConcentration,
-            NucleicAcidQuantification,
-            ConcentrationUnit,
-            Absorbance260230Ratio,
-            Absorbance260280Ratio,
-            CellLysisMethod,
-            MaterialExtractedBy,
-            MaterialExtractedByOrcid,
-            ActionExtracted,
-            ExtractionMethod,
-            NumberOfExtractsPooled,
-            Doi,
-            Citation,
-            ExtractedByEntityId,
-            MaterialExtractedByEntityId,
-            PublicationEntityId,
-        ]
-    };
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum Extraction {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:subsample_id")]
+        SubsampleId,
+        #[iri("fields:extract_id")]
+        ExtractId,
+        #[iri("fields:extracted_by")]
+        ExtractedBy,
+        #[iri("fields:extracted_by_orcid")]
+        ExtractedByOrcid,
+        #[iri("fields:extraction_date")]
+        ExtractionDate,
+        #[iri("fields:nucleic_acid_type")]
+        NucleicAcidType,
+        #[iri("fields:nucleic_acid_conformation")]
+        NucleicAcidConformation,
+        #[iri("fields:nucleic_acid_preservation_method")]
+        NucleicAcidPreservationMethod,
+        #[iri("fields:nucleic_acid_concentration")]
+        NucleicAcidConcentration,
+        #[iri("fields:nucleic_acid_quantification")]
+        NucleicAcidQuantification,
+        #[iri("fields:concentration_unit")]
+        ConcentrationUnit,
+        #[iri("fields:absorbance_260_230_ratio")]
+        Absorbance260230Ratio,
+        #[iri("fields:absorbance_260_280_ratio")]
+        Absorbance260280Ratio,
+        #[iri("fields:cell_lysis_method")]
+        CellLysisMethod,
+        #[iri("fields:material_extracted_by")]
+        MaterialExtractedBy,
+        #[iri("fields:material_extracted_by_orcid")]
+        MaterialExtractedByOrcid,
+        #[iri("fields:action_extracted")]
+        ActionExtracted,
+        #[iri("fields:extraction_method")]
+        ExtractionMethod,
+        #[iri("fields:number_of_extracts_pooled")]
+        NumberOfExtractsPooled,
+        #[iri("fields:doi")]
+        Doi,
+        #[iri("fields:citation")]
+        Citation,
+
+        #[iri("fields:extracted_by_entity_id")]
+        ExtractedByEntityId,
+        #[iri("fields:material_extracted_by_entity_id")]
+        MaterialExtractedByEntityId,
+        #[iri("fields:publication_entity_id")]
+        PublicationEntityId,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1206,115 +1639,79 @@ impl From<(Extraction, Literal)> for ExtractionField {
             (ExtractedByEntityId, Literal::String(value)) => Self::ExtractedByEntityId(value),
             (MaterialExtractedByEntityId, Literal::String(value)) => Self::MaterialExtractedByEntityId(value),
             (PublicationEntityId, Literal::String(value)) => Self::PublicationEntityId(value),
-            _ => unimplemented!(),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
         }
     }
 }
 
 
-#[derive(Debug, IriEnum)]
-#[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
-pub enum Library {
-    #[iri("fields:entity_id")]
-    EntityId,
-    #[iri("fields:extract_id")]
-    ExtractId,
-    #[iri("fields:library_id")]
-    LibraryId,
-    #[iri("fields:scientific_name")]
-    ScientificName,
-
-    #[iri("fields:event_date")]
-    EventDate,
-    #[iri("fields:concentration")]
-    Concentration,
-    #[iri("fields:concentration_unit")]
-    ConcentrationUnit,
-    #[iri("fields:pcr_cycles")]
-    PcrCycles,
-    #[iri("fields:layout")]
-    Layout,
-    #[iri("fields:prepared_by")]
-    PreparedBy,
-    #[iri("fields:selection")]
-    Selection,
-    #[iri("fields:bait_set_name")]
-    BaitSetName,
-    #[iri("fields:bait_set_reference")]
-    BaitSetReference,
-    #[iri("fields:construction_protocol")]
-    ConstructionProtocol,
-    #[iri("fields:source")]
-    Source,
-    #[iri("fields:insert_size")]
-    InsertSize,
-    #[iri("fields:design_description")]
-    DesignDescription,
-    #[iri("fields:strategy")]
-    Strategy,
-    #[iri("fields:index_tag")]
-    IndexTag,
-    #[iri("fields:index_dual_tag")]
-    IndexDualTag,
-    #[iri("fields:index_oligo")]
-    IndexOligo,
-    #[iri("fields:index_dual_oligo")]
-    IndexDualOligo,
-    #[iri("fields:location")]
-    Location,
-    #[iri("fields:remarks")]
-    Remarks,
-    #[iri("fields:dna_treatment")]
-    DnaTreatment,
-    #[iri("fields:number_of_libraries_pooled")]
-    NumberOfLibrariesPooled,
-    #[iri("fields:pcr_replicates")]
-    PcrReplicates,
-
-    #[iri("fields:prepared_by_entity_id")]
-    PreparedByEntityId,
-    #[iri("fields:canonical_name")]
-    CanonicalName,
-    #[iri("fields:scientific_name_authorship")]
-    ScientificNameAuthorship,
-}
-
-impl Library {
-    pub const ALL: &[Library] = {
-        use Library::*;
-        &[
-            EntityId,
-            ExtractId,
-            LibraryId,
-            ScientificName,
-            EventDate,
-            Concentration,
-            ConcentrationUnit,
-            PcrCycles,
-            Layout,
-            PreparedBy,
-            Selection,
-            BaitSetName,
-            BaitSetReference,
-            ConstructionProtocol,
-            Source,
-            InsertSize,
-            DesignDescription,
-            Strategy,
-            IndexTag,
-            IndexDualTag,
-            IndexOligo,
-            IndexDualOligo,
-            Location,
-            Remarks,
-            DnaTreatment,
-            NumberOfLibrariesPooled,
-            PcrReplicates,
-            PreparedByEntityId,
-            CanonicalName,
-            ScientificNameAuthorship,
-        ]
-    };
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum Library {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:extract_id")]
+        ExtractId,
+        #[iri("fields:library_id")]
+        LibraryId,
+        #[iri("fields:scientific_name")]
+        ScientificName,
+
+        #[iri("fields:event_date")]
+        EventDate,
+        #[iri("fields:concentration")]
+        Concentration,
+        #[iri("fields:concentration_unit")]
+        ConcentrationUnit,
+        #[iri("fields:pcr_cycles")]
+        PcrCycles,
+        #[iri("fields:layout")]
+        Layout,
+        #[iri("fields:prepared_by")]
+        PreparedBy,
+        #[iri("fields:selection")]
+        Selection,
+        #[iri("fields:bait_set_name")]
+        BaitSetName,
+        #[iri("fields:bait_set_reference")]
+        BaitSetReference,
+        #[iri("fields:construction_protocol")]
+        ConstructionProtocol,
+        #[iri("fields:source")]
+        Source,
+        #[iri("fields:insert_size")]
+        InsertSize,
+        #[iri("fields:design_description")]
+        DesignDescription,
+        #[iri("fields:strategy")]
+        Strategy,
+        #[iri("fields:index_tag")]
+        IndexTag,
+        #[iri("fields:index_dual_tag")]
+        IndexDualTag,
+        #[iri("fields:index_oligo")]
+        IndexOligo,
+        #[iri("fields:index_dual_oligo")]
+        IndexDualOligo,
+        #[iri("fields:location")]
+        Location,
+        #[iri("fields:remarks")]
+        Remarks,
+        #[iri("fields:dna_treatment")]
+        DnaTreatment,
+        #[iri("fields:number_of_libraries_pooled")]
+        NumberOfLibrariesPooled,
+        #[iri("fields:pcr_replicates")]
+        PcrReplicates,
+
+        #[iri("fields:prepared_by_entity_id")]
+        PreparedByEntityId,
+        #[iri("fields:canonical_name")]
+        CanonicalName,
+        #[iri("fields:scientific_name_authorship")]
+        ScientificNameAuthorship,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1388,74 +1785,53 @@ impl From<(Library, Literal)> for LibraryField {
             (PreparedByEntityId, Literal::String(value)) => Self::PreparedByEntityId(value),
             (CanonicalName, Literal::String(value)) => Self::CanonicalName(value),
             (ScientificNameAuthorship, Literal::String(value)) => Self::ScientificNameAuthorship(value),
-            _ => unimplemented!(),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
         }
     }
 }
 
 
-#[derive(Debug, IriEnum)]
-#[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
-pub enum SequencingRun {
-    #[iri("fields:entity_id")]
-    EntityId,
-    #[iri("fields:library_id")]
-    LibraryId,
-    #[iri("fields:sequence_id")]
-    SequenceId,
-    #[iri("fields:facility")]
-    Facility,
-    #[iri("fields:event_date")]
-    EventDate,
-    #[iri("fields:instrument_or_method")]
-    InstrumentOrMethod,
-    #[iri("fields:sra_run_accession")]
-    SraRunAccession,
-    #[iri("fields:platform")]
-    Platform,
-    #[iri("fields:dataset_file_format")]
-    DatasetFileFormat,
-    #[iri("fields:kit_chemistry")]
-    KitChemistry,
-    #[iri("fields:flowcell_type")]
-    FlowcellType,
-    #[iri("fields:cell_movie_length")]
-    CellMovieLength,
-    #[iri("fields:base_caller_model")]
-    BaseCallerModel,
-    #[iri("fields:fast5_compression")]
-    Fast5Compression,
-    #[iri("fields:analysis_software")]
-    AnalysisSoftware,
-    #[iri("fields:analysis_software_version")]
-    AnalysisSoftwareVersion,
-    #[iri("fields:target_gene")]
-    TargetGene,
-}
-
-impl SequencingRun {
-    pub const ALL: &[SequencingRun] = {
-        use SequencingRun::*;
-        &[
-            EntityId,
-            LibraryId,
-            SequenceId,
-            Facility,
-            EventDate,
-            InstrumentOrMethod,
-            SraRunAccession,
-            Platform,
-            DatasetFileFormat,
-            KitChemistry,
-            FlowcellType,
-            CellMovieLength,
-            BaseCallerModel,
-            Fast5Compression,
-            AnalysisSoftware,
-            AnalysisSoftwareVersion,
-            TargetGene,
-        ]
-    };
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum SequencingRun {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:library_id")]
+        LibraryId,
+        #[iri("fields:sequence_id")]
+        SequenceId,
+        #[iri("fields:scientific_name")]
+        ScientificName,
+        #[iri("fields:facility")]
+        Facility,
+        #[iri("fields:event_date")]
+        EventDate,
+        #[iri("fields:instrument_or_method")]
+        InstrumentOrMethod,
+        #[iri("fields:sra_run_accession")]
+        SraRunAccession,
+        #[iri("fields:platform")]
+        Platform,
+        #[iri("fields:dataset_file_format")]
+        DatasetFileFormat,
+        #[iri("fields:kit_chemistry")]
+        KitChemistry,
+        #[iri("fields:flowcell_type")]
+        FlowcellType,
+        #[iri("fields:cell_movie_length")]
+        CellMovieLength,
+        #[iri("fields:base_caller_model")]
+        BaseCallerModel,
+        #[iri("fields:fast5_compression")]
+        Fast5Compression,
+        #[iri("fields:analysis_software")]
+        AnalysisSoftware,
+        #[iri("fields:analysis_software_version")]
+        AnalysisSoftwareVersion,
+        #[iri("fields:target_gene")]
+        TargetGene,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1463,6 +1839,7 @@ pub enum SequencingRunField {
     EntityId(String),
     LibraryId(String),
     SequenceId(String),
+    ScientificName(String),
     Facility(String),
     EventDate(String),
     InstrumentOrMethod(String),
@@ -1487,6 +1864,7 @@ impl From<(SequencingRun, Literal)> for SequencingRunField {
             (EntityId, Literal::String(value)) => Self::EntityId(value),
             (LibraryId, Literal::String(value)) => Self::LibraryId(value),
             (SequenceId, Literal::String(value)) => Self::SequenceId(value),
+            (ScientificName, Literal::String(value)) => Self::ScientificName(value),
             (Facility, Literal::String(value)) => Self::Facility(value),
             (EventDate, Literal::String(value)) => Self::EventDate(value),
             (InstrumentOrMethod, Literal::String(value)) => Self::InstrumentOrMethod(value),
@@ -1501,170 +1879,250 @@ impl From<(SequencingRun, Literal)> for SequencingRunField {
             (AnalysisSoftware, Literal::String(value)) => Self::AnalysisSoftware(value),
             (AnalysisSoftwareVersion, Literal::String(value)) => Self::AnalysisSoftwareVersion(value),
             (TargetGene, Literal::String(value)) => Self::TargetGene(value),
-            _ => unimplemented!(),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
         }
     }
 }
 
 
-#[derive(Debug, IriEnum)]
-#[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
-pub enum Assembly {
-    #[iri("fields:entity_id")]
-    EntityId,
-    #[iri("fields:library_id")]
-    LibraryId,
-    #[iri("fields:assembly_id")]
-    AssemblyId,
-    #[iri("fields:scientific_name")]
-    ScientificName,
-    #[iri("fields:event_date")]
-    EventDate,
-    #[iri("fields:name")]
-    Name,
-    #[iri("fields:type")]
-    Type,
-    #[iri("fields:method")]
-    Method,
-    #[iri("fields:method_version")]
-    MethodVersion,
-    #[iri("fields:method_link")]
-    MethodLink,
-    #[iri("fields:size")]
-    Size,
-    #[iri("fields:size_ungapped")]
-    SizeUngapped,
-    #[iri("fields:minimum_gap_length")]
-    MinimumGapLength,
-    #[iri("fields:completeness")]
-    Completeness,
-    #[iri("fields:completeness_method")]
-    CompletenessMethod,
-    #[iri("fields:source_molecule")]
-    SourceMolecule,
-    #[iri("fields:reference_genome_used")]
-    ReferenceGenomeUsed,
-    #[iri("fields:reference_genome_link")]
-    ReferenceGenomeLink,
-    #[iri("fields:number_of_scaffolds")]
-    NumberOfScaffolds,
-    #[iri("fields:number_of_contigs")]
-    NumberOfContigs,
-    #[iri("fields:number_of_chromosomes")]
-    NumberOfChromosomes,
-    #[iri("fields:number_of_component_sequences")]
-    NumberOfComponentSequences,
-    #[iri("fields:number_of_organelles")]
-    NumberOfOrganelles,
-    #[iri("fields:number_of_gaps_between_scaffolds")]
-    NumberOfGapsBetweenScaffolds,
-    #[iri("fields:number_of_atgc")]
-    NumberOfATGC,
-    #[iri("fields:number_of_guanine_cytosine")]
-    NumberOfGuanineCytosine,
-    #[iri("fields:guanine_cytosine_percent")]
-    GuanineCytosinePercent,
-    #[iri("fields:genome_coverage")]
-    GenomeCoverage,
-    #[iri("fields:hybrid")]
-    Hybrid,
-    #[iri("fields:hybrid_information")]
-    HybridInformation,
-    #[iri("fields:polishing_or_scaffolding_method")]
-    PolishingOrScaffoldingMethod,
-    #[iri("fields:polishing_or_scaffolding_data")]
-    PolishingOrScaffoldingData,
-    #[iri("fields:computational_infrastructure")]
-    ComputationalInfrastructure,
-    #[iri("fields:system_used")]
-    SystemUsed,
-
-    #[iri("fields:level")]
-    Level,
-    #[iri("fields:representation")]
-    Representation,
-
-    #[iri("fields:assembly_n50")]
-    AssemblyN50,
-    #[iri("fields:contig_n50")]
-    ContigN50,
-    #[iri("fields:contig_l50")]
-    ContigL50,
-    #[iri("fields:scaffold_n50")]
-    ScaffoldN50,
-    #[iri("fields:scaffold_l50")]
-    ScaffoldL50,
-
-    #[iri("fields:longest_contig")]
-    LongestContig,
-    #[iri("fields:longest_scaffold")]
-    LongestScaffold,
-    #[iri("fields:total_contig_size")]
-    TotalContigSize,
-    #[iri("fields:total_scaffold_size")]
-    TotalScaffoldSize,
-
-    #[iri("fields:canonical_name")]
-    CanonicalName,
-    #[iri("fields:scientific_name_authorship")]
-    ScientificNameAuthorship,
-
-    #[iri("fields:taxon_id")]
-    TaxonId,
-}
-
-impl Assembly {
-    pub const ALL: &[Assembly] = {
-        use Assembly::*;
-        &[
-            EntityId,
-            LibraryId,
-            AssemblyId,
-            ScientificName,
-            EventDate,
-            Name,
-            Type,
-            Method,
-            MethodVersion,
-            MethodLink,
-            Size,
-            SizeUngapped,
-            MinimumGapLength,
-            Completeness,
-            CompletenessMethod,
-            SourceMolecule,
-            ReferenceGenomeUsed,
-            ReferenceGenomeLink,
-            NumberOfScaffolds,
-            NumberOfContigs,
-            NumberOfChromosomes,
-            NumberOfComponentSequences,
-            NumberOfOrganelles,
-            NumberOfGapsBetweenScaffolds,
-            NumberOfATGC,
-            NumberOfGuanineCytosine,
-            GuanineCytosinePercent,
-            GenomeCoverage,
-            Hybrid,
-            HybridInformation,
-            PolishingOrScaffoldingMethod,
-            PolishingOrScaffoldingData,
-            ComputationalInfrastructure,
-            SystemUsed,
-            Level,
-            Representation,
-            AssemblyN50,
-            ContigL50,
-            ContigN50,
-            LongestContig,
-            LongestScaffold,
-            TotalContigSize,
-            TotalScaffoldSize,
-            CanonicalName,
-            ScientificNameAuthorship,
-            TaxonId,
-        ]
-    };
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum Sequence {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:sequence_run_id")]
+        SequenceRunId,
+        #[iri("fields:accession")]
+        Accession,
+        #[iri("fields:genbank_accession")]
+        GenbankAccession,
+        #[iri("fields:target_gene")]
+        TargetGene,
+        #[iri("fields:primer_forward")]
+        PrimerForward,
+        #[iri("fields:primer_reverse")]
+        PrimerReverse,
+        #[iri("fields:sequence_length")]
+        SequenceLength,
+        #[iri("fields:ambiguities")]
+        Ambiguities,
+        #[iri("fields:basepair_quality")]
+        BasepairQuality,
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub enum SequenceField {
+    EntityId(String),
+    SequenceRunId(String),
+    Accession(String),
+    GenbankAccession(String),
+    TargetGene(String),
+    PrimerForward(String),
+    PrimerReverse(String),
+    SequenceLength(u64),
+    Ambiguities(u64),
+    BasepairQuality(String),
+}
+
+
+impl From<(Sequence, Literal)> for SequenceField {
+    fn from(source: (Sequence, Literal)) -> Self {
+        use Sequence::*;
+        match source {
+            (EntityId, Literal::String(value)) => Self::EntityId(value),
+            (SequenceRunId, Literal::String(value)) => Self::SequenceRunId(value),
+            (Accession, Literal::String(value)) => Self::Accession(value),
+            (GenbankAccession, Literal::String(value)) => Self::GenbankAccession(value),
+            (TargetGene, Literal::String(value)) => Self::TargetGene(value),
+            (PrimerForward, Literal::String(value)) => Self::PrimerForward(value),
+            (PrimerReverse, Literal::String(value)) => Self::PrimerReverse(value),
+            (SequenceLength, Literal::UInt64(value)) => Self::SequenceLength(value),
+            (SequenceLength, Literal::String(value)) => Self::SequenceLength(str_to_u64(&value).unwrap()),
+            (Ambiguities, Literal::UInt64(value)) => Self::Ambiguities(value),
+            (Ambiguities, Literal::String(value)) => Self::Ambiguities(str_to_u64(&value).unwrap()),
+            (BasepairQuality, Literal::String(value)) => Self::BasepairQuality(value),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
+        }
+    }
+}
+
+
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum Taxon {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:taxon_id")]
+        TaxonId,
+        #[iri("fields:parent_taxon_id")]
+        ParentTaxonId,
+        #[iri("fields:scientific_name")]
+        ScientificName,
+        #[iri("fields:canonical_name")]
+        CanonicalName,
+        #[iri("fields:scientific_name_authorship")]
+        ScientificNameAuthorship,
+        #[iri("fields:rank")]
+        Rank,
+        #[iri("fields:status")]
+        Status,
+        #[iri("fields:nomenclatural_code")]
+        NomenclaturalCode,
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub enum TaxonField {
+    EntityId(String),
+    TaxonId(String),
+    ParentTaxonId(String),
+    ScientificName(String),
+    CanonicalName(String),
+    ScientificNameAuthorship(String),
+    Rank(String),
+    Status(String),
+    NomenclaturalCode(String),
+}
+
+
+impl From<(Taxon, Literal)> for TaxonField {
+    fn from(source: (Taxon, Literal)) -> Self {
+        use Taxon::*;
+        match source {
+            // taxonomy ids are usually numeric so they're kept as strings however they were read
+            (EntityId, value) => Self::EntityId(value.lexical()),
+            (TaxonId, value) => Self::TaxonId(value.lexical()),
+            (ParentTaxonId, value) => Self::ParentTaxonId(value.lexical()),
+            (ScientificName, Literal::String(value)) => Self::ScientificName(value),
+            (CanonicalName, Literal::String(value)) => Self::CanonicalName(value),
+            (ScientificNameAuthorship, Literal::String(value)) => Self::ScientificNameAuthorship(value),
+            (Rank, Literal::String(value)) => Self::Rank(value),
+            (Status, Literal::String(value)) => Self::Status(value),
+            (NomenclaturalCode, Literal::String(value)) => Self::NomenclaturalCode(value),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
+        }
+    }
+}
+
+
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum Assembly {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:library_id")]
+        LibraryId,
+        #[iri("fields:assembly_id")]
+        AssemblyId,
+        #[iri("fields:scientific_name")]
+        ScientificName,
+        #[iri("fields:event_date")]
+        EventDate,
+        #[iri("fields:name")]
+        Name,
+        #[iri("fields:type")]
+        Type,
+        #[iri("fields:method")]
+        Method,
+        #[iri("fields:method_version")]
+        MethodVersion,
+        #[iri("fields:method_link")]
+        MethodLink,
+        #[iri("fields:size")]
+        Size,
+        #[iri("fields:size_ungapped")]
+        SizeUngapped,
+        #[iri("fields:minimum_gap_length")]
+        MinimumGapLength,
+        #[iri("fields:completeness")]
+        Completeness,
+        #[iri("fields:completeness_method")]
+        CompletenessMethod,
+        #[iri("fields:source_molecule")]
+        SourceMolecule,
+        #[iri("fields:reference_genome_used")]
+        ReferenceGenomeUsed,
+        #[iri("fields:reference_genome_link")]
+        ReferenceGenomeLink,
+        #[iri("fields:number_of_scaffolds")]
+        NumberOfScaffolds,
+        #[iri("fields:number_of_contigs")]
+        NumberOfContigs,
+        #[iri("fields:number_of_chromosomes")]
+        NumberOfChromosomes,
+        #[iri("fields:number_of_component_sequences")]
+        NumberOfComponentSequences,
+        #[iri("fields:number_of_organelles")]
+        NumberOfOrganelles,
+        #[iri("fields:number_of_gaps_between_scaffolds")]
+        NumberOfGapsBetweenScaffolds,
+        #[iri("fields:number_of_atgc")]
+        NumberOfATGC,
+        #[iri("fields:number_of_guanine_cytosine")]
+        NumberOfGuanineCytosine,
+        #[iri("fields:guanine_cytosine_percent")]
+        GuanineCytosinePercent,
+        #[iri("fields:genome_coverage")]
+        GenomeCoverage,
+        #[iri("fields:hybrid")]
+        Hybrid,
+        #[iri("fields:hybrid_information")]
+        HybridInformation,
+        #[iri("fields:polishing_or_scaffolding_method")]
+        PolishingOrScaffoldingMethod,
+        #[iri("fields:polishing_or_scaffolding_data")]
+        PolishingOrScaffoldingData,
+        #[iri("fields:computational_infrastructure")]
+        ComputationalInfrastructure,
+        #[iri("fields:system_used")]
+        SystemUsed,
+
+        #[iri("fields:level")]
+        Level,
+        #[iri("fields:representation")]
+        Representation,
+
+        #[iri("fields:assembly_n50")]
+        AssemblyN50,
+        #[iri("fields:contig_n50")]
+        ContigN50,
+        #[iri("fields:contig_l50")]
+        ContigL50,
+        #[iri("fields:scaffold_n50")]
+        ScaffoldN50,
+        #[iri("fields:scaffold_l50")]
+        ScaffoldL50,
+
+        #[iri("fields:longest_contig")]
+        LongestContig,
+        #[iri("fields:longest_scaffold")]
+        LongestScaffold,
+        #[iri("fields:total_contig_size")]
+        TotalContigSize,
+        #[iri("fields:total_scaffold_size")]
+        TotalScaffoldSize,
+
+        #[iri("fields:canonical_name")]
+        CanonicalName,
+        #[iri("fields:scientific_name_authorship")]
+        ScientificNameAuthorship,
+
+        #[iri("fields:taxon_id")]
+        TaxonId,
+
+        #[iri("fields:doi")]
+        Doi,
+        #[iri("fields:citation")]
+        Citation,
+        #[iri("fields:publication_entity_id")]
+        PublicationEntityId,
+    }
 }
 
 
@@ -1721,6 +2179,10 @@ pub enum AssemblyField {
     CanonicalName(String),
     ScientificNameAuthorship(String),
     TaxonId(String),
+
+    Doi(String),
+    Citation(String),
+    PublicationEntityId(String),
 }
 
 
@@ -1807,85 +2269,60 @@ impl From<(Assembly, Literal)> for AssemblyField {
             (CanonicalName, Literal::String(value)) => Self::CanonicalName(value),
             (ScientificNameAuthorship, Literal::String(value)) => Self::ScientificNameAuthorship(value),
             (TaxonId, Literal::String(value)) => Self::TaxonId(value),
-            (field, val) => {
-                tracing::error!(?field, ?val, "unsupported field format");
-                unimplemented!()
-            }
+            (Doi, Literal::String(value)) => Self::Doi(value),
+            (Citation, Literal::String(value)) => Self::Citation(value),
+            (PublicationEntityId, Literal::String(value)) => Self::PublicationEntityId(value),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
         }
     }
 }
 
 
-#[derive(Debug, IriEnum)]
-#[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
-pub enum DataProduct {
-    #[iri("fields:entity_id")]
-    EntityId,
-    #[iri("fields:organism_id")]
-    OrganismId,
-    #[iri("fields:extract_id")]
-    ExtractId,
-    #[iri("fields:sequence_run_id")]
-    SequenceRunId,
-
-    #[iri("fields:sequence_sample_id")]
-    SequenceSampleId,
-    #[iri("fields:sequence_analysis_id")]
-    SequenceAnalysisId,
-    #[iri("fields:notes")]
-    Notes,
-    #[iri("fields:context")]
-    Context,
-    #[iri("fields:type")]
-    Type,
-    #[iri("fields:file_type")]
-    FileType,
-    #[iri("fields:url")]
-    Url,
-    #[iri("fields:licence")]
-    Licence,
-    #[iri("fields:access")]
-    Access,
-    #[iri("fields:custodian")]
-    Custodian,
-    #[iri("fields:custodian_orcid")]
-    CustodianOrcid,
-    #[iri("fields:citation")]
-    Citation,
-    #[iri("fields:source_url")]
-    SourceUrl,
-
-    #[iri("fields:custodian_entity_id")]
-    CustodianEntityId,
-    #[iri("fields:publication_entity_id")]
-    PublicationEntityId,
-}
-
-impl DataProduct {
-    pub const ALL: &[DataProduct] = {
-        use DataProduct::*;
-        &[
-            EntityId,
-            OrganismId,
-            ExtractId,
-            SequenceRunId,
-            SequenceSampleId,
-            SequenceAnalysisId,
-            Notes,
-            Context,
-            Type,
-            FileType,
-            Url,
-            Licence,
-            Access,
-            Custodian,
-            CustodianOrcid,
-            Citation,
-            SourceUrl,
-            CustodianEntityId,
-            PublicationEntityId,
-        ]
-    };
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum DataProduct {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:organism_id")]
+        OrganismId,
+        #[iri("fields:extract_id")]
+        ExtractId,
+        #[iri("fields:sequence_run_id")]
+        SequenceRunId,
+
+        #[iri("fields:sequence_sample_id")]
+        SequenceSampleId,
+        #[iri("fields:sequence_analysis_id")]
+        SequenceAnalysisId,
+        #[iri("fields:notes")]
+        Notes,
+        #[iri("fields:context")]
+        Context,
+        #[iri("fields:type")]
+        Type,
+        #[iri("fields:file_type")]
+        FileType,
+        #[iri("fields:url")]
+        Url,
+        #[iri("fields:licence")]
+        Licence,
+        #[iri("fields:access")]
+        Access,
+        #[iri("fields:custodian")]
+        Custodian,
+        #[iri("fields:custodian_orcid")]
+        CustodianOrcid,
+        #[iri("fields:citation")]
+        Citation,
+        #[iri("fields:source_url")]
+        SourceUrl,
+
+        #[iri("fields:custodian_entity_id")]
+        CustodianEntityId,
+        #[iri("fields:publication_entity_id")]
+        PublicationEntityId,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1937,70 +2374,49 @@ impl From<(DataProduct, Literal)> for DataProductField {
             (SourceUrl, Literal::String(value)) => Self::SourceUrl(value),
             (CustodianEntityId, Literal::String(value)) => Self::CustodianEntityId(value),
             (PublicationEntityId, Literal::String(value)) => Self::PublicationEntityId(value),
-            _ => unimplemented!(),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
         }
     }
 }
 
 
-#[derive(Debug, IriEnum)]
-#[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
-pub enum Annotation {
-    #[iri("fields:entity_id")]
-    EntityId,
-    #[iri("fields:assembly_id")]
-    AssemblyId,
-
-    #[iri("fields:name")]
-    Name,
-    #[iri("fields:provider")]
-    Provider,
-    #[iri("fields:method")]
-    Method,
-    #[iri("fields:type")]
-    Type,
-    #[iri("fields:version")]
-    Version,
-    #[iri("fields:software")]
-    Software,
-    #[iri("fields:software_version")]
-    SoftwareVersion,
-    #[iri("fields:event_date")]
-    EventDate,
-
-    #[iri("fields:number_of_genes")]
-    NumberOfGenes,
-    #[iri("fields:number_of_coding_proteins")]
-    NumberOfCodingProteins,
-    #[iri("fields:number_of_non_coding_proteins")]
-    NumberOfNonCodingProteins,
-    #[iri("fields:number_of_pseudogenes")]
-    NumberOfPseudogenes,
-    #[iri("fields:number_of_other_genes")]
-    NumberOfOtherGenes,
-}
-
-impl Annotation {
-    pub const ALL: &[Annotation] = {
-        use Annotation::*;
-        &[
-            EntityId,
-            AssemblyId,
-            Name,
-            Provider,
-            Method,
-            Type,
-            Version,
-            Software,
-            SoftwareVersion,
-            EventDate,
-            NumberOfGenes,
-            NumberOfCodingProteins,
-            NumberOfNonCodingProteins,
-            NumberOfPseudogenes,
-            NumberOfOtherGenes,
-        ]
-    };
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum Annotation {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:assembly_id")]
+        AssemblyId,
+
+        #[iri("fields:name")]
+        Name,
+        #[iri("fields:provider")]
+        Provider,
+        #[iri("fields:method")]
+        Method,
+        #[iri("fields:type")]
+        Type,
+        #[iri("fields:version")]
+        Version,
+        #[iri("fields:software")]
+        Software,
+        #[iri("fields:software_version")]
+        SoftwareVersion,
+        #[iri("fields:event_date")]
+        EventDate,
+
+        #[iri("fields:number_of_genes")]
+        NumberOfGenes,
+        #[iri("fields:number_of_coding_proteins")]
+        NumberOfCodingProteins,
+        #[iri("fields:number_of_non_coding_proteins")]
+        NumberOfNonCodingProteins,
+        #[iri("fields:number_of_pseudogenes")]
+        NumberOfPseudogenes,
+        #[iri("fields:number_of_other_genes")]
+        NumberOfOtherGenes,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -2057,33 +2473,28 @@ impl From<(Annotation, Literal)> for AnnotationField {
             (NumberOfOtherGenes, Literal::String(value)) => {
                 Self::NumberOfOtherGenes(str_to_u64(&value).unwrap_or_default())
             }
-            _ => unimplemented!(),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
         }
     }
 }
 
 
-#[derive(Debug, IriEnum)]
-#[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
-pub enum Deposition {
-    #[iri("fields:entity_id")]
-    EntityId,
-    #[iri("fields:assembly_id")]
-    AssemblyId,
-
-    #[iri("fields:event_date")]
-    EventDate,
-    #[iri("fields:url")]
-    Url,
-    #[iri("fields:institution")]
-    Institution,
-}
-
-impl Deposition {
-    pub const ALL: &[Deposition] = {
-        use Deposition::*;
-        &[EntityId, AssemblyId, EventDate, Url, Institution]
-    };
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum Deposition {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:assembly_id")]
+        AssemblyId,
+
+        #[iri("fields:event_date")]
+        EventDate,
+        #[iri("fields:url")]
+        Url,
+        #[iri("fields:institution")]
+        Institution,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -2106,63 +2517,45 @@ impl From<(Deposition, Literal)> for DepositionField {
             (EventDate, Literal::String(value)) => Self::EventDate(value),
             (Url, Literal::String(value)) => Self::Url(value),
             (Institution, Literal::String(value)) => Self::Institution(value),
-            _ => unimplemented!(),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
         }
     }
 }
 
 
-#[derive(Debug, IriEnum)]
-#[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
-pub enum Project {
-    #[iri("fields:entity_id")]
-    EntityId,
-    #[iri("fields:project_id")]
-    ProjectId,
-
-    #[iri("fields:scientific_name")]
-    ScientificName,
-    #[iri("fields:initiative")]
-    Initiative,
-    #[iri("fields:initiative_theme")]
-    InitiativeTheme,
-    #[iri("fields:title")]
-    Title,
-    #[iri("fields:description")]
-    Description,
-    #[iri("fields:data_context")]
-    DataContext,
-    #[iri("fields:data_types")]
-    DataTypes,
-    #[iri("fields:data_assay_types")]
-    DataAssayTypes,
-    #[iri("fields:partners")]
-    Partners,
-
-    #[iri("fields:curator")]
-    Curator,
-    #[iri("fields:curator_orcid")]
-    CuratorOrcid,
-}
-
-impl Project {
-    pub const ALL: &[Project] = {
-        use Project::*;
-        &[
-            ProjectId,
-            ScientificName,
-            Initiative,
-            InitiativeTheme,
-            Title,
-            Description,
-            DataContext,
-            DataTypes,
-            DataAssayTypes,
-            Partners,
-            Curator,
-            CuratorOrcid,
-        ]
-    };
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum Project {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:project_id")]
+        ProjectId,
+
+        #[iri("fields:scientific_name")]
+        ScientificName,
+        #[iri("fields:initiative")]
+        Initiative,
+        #[iri("fields:initiative_theme")]
+        InitiativeTheme,
+        #[iri("fields:title")]
+        Title,
+        #[iri("fields:description")]
+        Description,
+        #[iri("fields:data_context")]
+        DataContext,
+        #[iri("fields:data_types")]
+        DataTypes,
+        #[iri("fields:data_assay_types")]
+        DataAssayTypes,
+        #[iri("fields:partners")]
+        Partners,
+
+        #[iri("fields:curator")]
+        Curator,
+        #[iri("fields:curator_orcid")]
+        CuratorOrcid,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -2202,32 +2595,27 @@ impl From<(Project, Literal)> for ProjectField {
             (Partners, Literal::String(value)) => Self::Partners(value),
             (Curator, Literal::String(value)) => Self::Curator(value),
             (CuratorOrcid, Literal::String(value)) => Self::CuratorOrcid(value),
-            _ => unimplemented!(),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
         }
     }
 }
 
 
-#[derive(Debug, IriEnum)]
-#[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
-pub enum ProjectMember {
-    #[iri("fields:entity_id")]
-    EntityId,
-    #[iri("fields:project_id")]
-    ProjectId,
-    #[iri("fields:name")]
-    Name,
-    #[iri("fields:orcid")]
-    Orcid,
-    #[iri("fields:organisation")]
-    Organisation,
-}
-
-impl ProjectMember {
-    pub const ALL: &[ProjectMember] = {
-        use ProjectMember::*;
-        &[EntityId, ProjectId, Name, Orcid, Organisation]
-    };
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum ProjectMember {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:project_id")]
+        ProjectId,
+        #[iri("fields:name")]
+        Name,
+        #[iri("fields:orcid")]
+        Orcid,
+        #[iri("fields:organisation")]
+        Organisation,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -2249,7 +2637,41 @@ impl From<(ProjectMember, Literal)> for ProjectMemberField {
             (Name, Literal::String(value)) => Self::Name(value),
             (Orcid, Literal::String(value)) => Self::Orcid(value),
             (Organisation, Literal::String(value)) => Self::Organisation(value),
-            _ => unimplemented!(),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
+        }
+    }
+}
+
+
+model_fields! {
+    #[derive(Debug, IriEnum)]
+    #[iri_prefix("fields" = "http://arga.org.au/schemas/fields/")]
+    pub enum ProjectAssembly {
+        #[iri("fields:entity_id")]
+        EntityId,
+        #[iri("fields:project_id")]
+        ProjectId,
+        #[iri("fields:assembly_entity_id")]
+        AssemblyEntityId,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ProjectAssemblyField {
+    EntityId(String),
+    ProjectId(String),
+    AssemblyEntityId(String),
+}
+
+
+impl From<(ProjectAssembly, Literal)> for ProjectAssemblyField {
+    fn from(source: (ProjectAssembly, Literal)) -> Self {
+        use ProjectAssembly::*;
+        match source {
+            (EntityId, Literal::String(value)) => Self::EntityId(value),
+            (ProjectId, Literal::String(value)) => Self::ProjectId(value),
+            (AssemblyEntityId, Literal::String(value)) => Self::AssemblyEntityId(value),
+            (field, Literal::UInt64(value)) => Self::from((field, Literal::String(value.to_string()))),
         }
     }
 }
@@ -2354,3 +2776,71 @@ impl TryInto<sophia::iri::IriRef<sophia::api::MownStr<'static>>> for DataTypes {
         Ok(iri.as_sophia_iri_ref()?)
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loose_eq_coerces_numeric_literals() {
+        let cases: &[(Literal, Literal, bool)] = &[
+            (Literal::from("10"), Literal::UInt64(10), true),
+            (Literal::UInt64(10), Literal::from("10"), true),
+            (Literal::from("10.0"), Literal::UInt64(10), true),
+            (Literal::from(" 10 "), Literal::UInt64(10), true),
+            (Literal::from("1,000"), Literal::UInt64(1000), true),
+            (Literal::from("10"), Literal::from("10.0"), true),
+            (Literal::UInt64(10), Literal::UInt64(10), true),
+            (Literal::from("11"), Literal::UInt64(10), false),
+            (Literal::UInt64(10), Literal::UInt64(11), false),
+            (Literal::from("ten"), Literal::UInt64(10), false),
+            (Literal::from("ten"), Literal::from("ten"), true),
+            (Literal::from("ten"), Literal::from("Ten"), false),
+            (Literal::from("10a"), Literal::from("10"), false),
+            (Literal::from(""), Literal::UInt64(0), false),
+        ];
+
+        for (left, right, expected) in cases {
+            assert_eq!(left.loose_eq(right), *expected, "{left:?} == {right:?}");
+        }
+    }
+
+    #[test]
+    fn comparison_conditions_coerce_numeric_literals() {
+        let cases: &[(Condition, Literal, bool)] = &[
+            (Condition::GreaterThan(Literal::UInt64(10)), Literal::from("11"), true),
+            (Condition::GreaterThan(Literal::UInt64(10)), Literal::from("10"), false),
+            (Condition::GreaterThan(Literal::from("10")), Literal::UInt64(9), false),
+            // numeric rather than lexical ordering
+            (Condition::GreaterThan(Literal::from("9")), Literal::from("10"), true),
+            (Condition::LessThan(Literal::from("10")), Literal::UInt64(9), true),
+            (Condition::LessThan(Literal::UInt64(10)), Literal::from("10.0"), false),
+            // non-numeric strings fall back to lexical ordering
+            (Condition::LessThan(Literal::UInt64(10)), Literal::from("abc"), false),
+            (Condition::LessThan(Literal::from("b")), Literal::from("a"), true),
+            (Condition::Is(Literal::from("10")), Literal::UInt64(10), true),
+            (Condition::Is(Literal::UInt64(10)), Literal::from("ten"), false),
+        ];
+
+        for (condition, value, expected) in cases {
+            assert_eq!(condition.check(value), *expected, "{condition:?} on {value:?}");
+        }
+    }
+
+    #[test]
+    fn compound_conditions_stop_at_the_deciding_member() {
+        let is = |value: &str| Condition::Is(Literal::from(value));
+        let value = Literal::from("active");
+
+        // members after the first failure of an and, or the first pass of an or, can't change the outcome
+        assert!(!Condition::And(vec![is("inactive"), is("active")]).check(&value));
+        assert!(Condition::Or(vec![is("active"), is("inactive")]).check(&value));
+        assert!(Condition::And(vec![is("active"), Condition::Or(vec![is("low"), is("active")])]).check(&value));
+        assert!(!Condition::Or(vec![is("low"), Condition::And(vec![is("active"), is("low")])]).check(&value));
+
+        // an empty and has nothing to fail while an empty or has nothing to pass
+        assert!(Condition::And(vec![]).check(&value));
+        assert!(!Condition::Or(vec![]).check(&value));
+    }
+}
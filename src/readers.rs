@@ -1,16 +1,94 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::BufRead;
+
+use quick_xml::events::{BytesStart, Event};
+
 use crate::dataset::Triple;
 use crate::errors::ReaderError;
 use crate::rdf::Literal;
 
+mod multi_csv;
+#[cfg(feature = "xlsx")]
+mod xlsx;
+
+pub use multi_csv::MultiCsvReader;
+#[cfg(feature = "xlsx")]
+pub use xlsx::XlsxReader;
+
+
+/// The type to parse the values of a column into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    /// An unsigned integer. Thousands separators are allowed but negative and decimal values don't parse.
+    Integer,
+    /// A decimal number. Kept as a string after validating.
+    Decimal,
+    /// A boolean normalised into `true` or `false`.
+    Boolean,
+    /// The value as is.
+    String,
+    /// An ISO 8601 calendar date (`YYYY-MM-DD`). Kept as a string after validating.
+    Date,
+}
+
+impl ColumnType {
+    /// Parse the value into the literal for the type.
+    ///
+    /// Empty values are always treated as an empty string as blank cells are
+    /// common in every column type.
+    pub fn parse(&self, value: &str) -> Option<Literal> {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            return Some(Literal::String(String::new()));
+        }
+
+        match self {
+            ColumnType::Integer => trimmed.replace(",", "").parse::<u64>().ok().map(Literal::UInt64),
+            ColumnType::Decimal => match trimmed.replace(",", "").parse::<f64>() {
+                Ok(_) => Some(Literal::String(trimmed.to_string())),
+                Err(_) => None,
+            },
+            ColumnType::Boolean => match trimmed.to_lowercase().as_str() {
+                "true" | "t" | "yes" | "y" | "1" => Some(Literal::String("true".to_string())),
+                "false" | "f" | "no" | "n" | "0" => Some(Literal::String("false".to_string())),
+                _ => None,
+            },
+            ColumnType::String => Some(Literal::String(value.to_string())),
+            ColumnType::Date => match is_iso_date(trimmed) {
+                true => Some(Literal::String(trimmed.to_string())),
+                false => None,
+            },
+        }
+    }
+}
+
 
 /// A CSV triples reader.
 ///
 /// This reader is a convenience wrapper for any stream that implements std::io::Read.
 /// Specifically it implements an iterator that can yield `Triple`'s making it
 /// compatible with the `Transformer`.
+///
+/// By default every value is yielded as a `Literal::String`. Use `with_types` to parse
+/// the values of specific columns into a typed literal instead.
+///
+/// Blank cells are yielded as empty strings unless `skip_empty_values` is enabled. The
+/// `CsvReaderBuilder` enables it by default.
 pub struct CsvReader<R: std::io::Read> {
     headers: Vec<String>,
     records: csv::StringRecordsIntoIter<R>,
+    // records read ahead by `peek_first_n` that the iterator hasn't reached yet
+    peeked: VecDeque<csv::StringRecord>,
+    // the byte offset of the first record after the header row
+    data_start: u64,
+
+    // the types to parse column values into, keyed by header
+    types: HashMap<String, ColumnType>,
+    // yield the value as a string instead of an error when it can't be parsed
+    lenient: bool,
+    // don't yield triples for blank values or any of the null sentinels
+    skip_empty_values: bool,
+    null_values: HashSet<String>,
 
     // the current line being iterated on
     current_record: Option<csv::StringRecord>,
@@ -28,21 +106,105 @@ impl<R: std::io::Read> CsvReader<R> {
         let mut reader = csv::ReaderBuilder::new().from_reader(reader);
 
         let headers = reader.headers()?.iter().map(|h| h.to_string()).collect();
+        let data_start = reader.position().byte();
         let records = reader.into_records();
 
         Ok(CsvReader {
             headers,
             records,
+            peeked: VecDeque::new(),
+            data_start,
+            types: HashMap::new(),
+            lenient: false,
+            skip_empty_values: false,
+            null_values: HashSet::new(),
             next_row: 1,
             next_column: 1,
             current_record: None,
         })
     }
 
+    /// Parse the values of the columns into the specified types.
+    ///
+    /// Values that fail to parse will yield a `ReaderError::TypeMismatch` unless
+    /// the reader is lenient.
+    pub fn with_types(mut self, types: HashMap<String, ColumnType>) -> Self {
+        self.types = types;
+        self
+    }
+
+    /// Yield values that can't be parsed into their column type as strings instead of an error.
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Don't yield triples for blank values or values matching one of the null sentinels.
+    pub fn skip_empty_values(mut self, skip: bool) -> Self {
+        self.skip_empty_values = skip;
+        self
+    }
+
+    /// Treat these values as empty when skipping empty values, eg. `N/A` or `null`.
+    pub fn null_values<T: Into<String>>(mut self, values: impl IntoIterator<Item = T>) -> Self {
+        self.null_values = values.into_iter().map(|value| value.into()).collect();
+        self
+    }
+
+    /// The header row of the file.
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    /// The number of columns in the header row.
+    pub fn column_count(&self) -> usize {
+        self.headers.len()
+    }
+
+    /// Get the next rows as header and value pairs without advancing the iterator.
+    ///
+    /// Before iterating these are the first rows of the file. The values are as they
+    /// appear in the file without skipping empty values or parsing column types. Fewer
+    /// rows are returned if the file doesn't have enough.
+    pub fn peek_first_n(&mut self, n: usize) -> Result<Vec<Vec<(String, String)>>, ReaderError> {
+        self.fill_peeked(n)?;
+
+        let rows = self
+            .peeked
+            .iter()
+            .take(n)
+            .map(|record| {
+                self.headers
+                    .iter()
+                    .zip(record.iter())
+                    .map(|(header, value)| (header.clone(), value.to_string()))
+                    .collect()
+            })
+            .collect();
+
+        Ok(rows)
+    }
+
+    // read ahead until there are at least n peeked records or the file has ended
+    fn fill_peeked(&mut self, n: usize) -> Result<(), ReaderError> {
+        while self.peeked.len() < n {
+            match self.records.next() {
+                Some(record) => self.peeked.push_back(record?),
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    fn is_empty_value(&self, value: &str) -> bool {
+        let value = value.trim();
+        value.is_empty() || self.null_values.contains(value)
+    }
+
     // get the next column if it exists and increment the count.
     // if there aren't any columns left then reset the column state
     // and return none
-    fn next_triple(&mut self) -> Option<Triple> {
+    fn next_triple(&mut self) -> Option<Result<Triple, ReaderError>> {
         match &self.current_record {
             // no record or reached the end
             None => None,
@@ -53,9 +215,29 @@ impl<R: std::io::Read> CsvReader<R> {
                 let current_column = self.next_column - 1;
 
                 match record.get(current_column) {
+                    // skip over empty values by moving on to the next column
+                    Some(value) if self.skip_empty_values && self.is_empty_value(value) => {
+                        self.next_column += 1;
+                        self.next_triple()
+                    }
                     Some(value) => {
                         self.next_column += 1;
-                        Some((current_row, self.headers[current_column].clone(), Literal::String(value.to_string())))
+                        let header = self.headers[current_column].clone();
+
+                        let literal = match self.types.get(&header).map(|column_type| column_type.parse(value)) {
+                            None => Literal::String(value.to_string()),
+                            Some(Some(literal)) => literal,
+                            Some(None) if self.lenient => Literal::String(value.to_string()),
+                            Some(None) => {
+                                return Some(Err(ReaderError::TypeMismatch {
+                                    row: current_row,
+                                    column: header,
+                                    value: value.to_string(),
+                                }));
+                            }
+                        };
+
+                        Some(Ok((current_row, header, literal)))
                     }
                     // reached end of line
                     None => {
@@ -69,6 +251,38 @@ impl<R: std::io::Read> CsvReader<R> {
     }
 }
 
+impl<R: std::io::Read + std::io::Seek> CsvReader<R> {
+    /// Estimate the number of rows in the file from its size and the average size of a row.
+    ///
+    /// The average is taken from the rows read so far, reading ahead a sample of rows
+    /// without advancing the iterator if none have been read yet. Returns `None` if the
+    /// file has no rows or its size can't be determined.
+    pub fn estimated_rows(&mut self) -> Option<usize> {
+        const SAMPLE_ROWS: usize = 100;
+
+        if self.next_row == 1 && self.peeked.is_empty() {
+            self.fill_peeked(SAMPLE_ROWS).ok()?;
+        }
+
+        // the csv reader buffers ahead of its position so the underlying stream is put
+        // back where it was after finding the size of the file
+        let stream = self.records.reader_mut().get_mut();
+        let current = stream.stream_position().ok()?;
+        let size = stream.seek(std::io::SeekFrom::End(0)).ok()?;
+        stream.seek(std::io::SeekFrom::Start(current)).ok()?;
+
+        let rows_read = (self.next_row - 1 + self.peeked.len()) as u64;
+        let bytes_read = self.records.reader().position().byte().saturating_sub(self.data_start);
+        if rows_read == 0 || bytes_read == 0 {
+            return None;
+        }
+
+        let row_size = bytes_read as f64 / rows_read as f64;
+        let estimate = size.saturating_sub(self.data_start) as f64 / row_size;
+        Some(estimate.round() as usize)
+    }
+}
+
 /// The iterator for the CSV reader.
 ///
 /// Transformer readers need to return triples and for a CSV file a triple
@@ -82,34 +296,1292 @@ impl<R: std::io::Read> std::iter::Iterator for CsvReader<R> {
     type Item = Result<Triple, ReaderError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        // still have columns left, return the next triple
-        if let Some(triple) = self.next_triple() {
-            return Some(Ok(triple));
+        loop {
+            // still have columns left, return the next triple
+            if let Some(triple) = self.next_triple() {
+                return Some(triple);
+            }
+
+            // no columns left so go to the next line, starting with any peeked lines
+            let next = match self.peeked.pop_front() {
+                Some(record) => Some(Ok(record)),
+                None => self.records.next(),
+            };
+
+            match next {
+                // we've reached the end of the document
+                None => return None,
+
+                Some(result) => match result {
+                    // when an error occurs during parsing we want to return the error
+                    // and carry on to the next row.
+                    Err(err) => return Some(Err(err.into())),
+
+                    // we've got a new line so we set it as the current record and loop
+                    // around to return the first triple from it. if every value in the row
+                    // was skipped we'll end up moving on to the following line instead
+                    Ok(record) => {
+                        self.next_row += 1;
+                        self.current_record = Some(record);
+                    }
+                },
+            }
         }
+    }
+}
 
-        // no columns left so go to the next line
-        match self.records.next() {
-            // we've reached the end of the document
-            None => None,
 
-            Some(result) => match result {
-                // when an error occurs during parsing we want to return the error
-                // and carry on to the next row.
-                Err(err) => Some(Err(err.into())),
+/// A builder for a `CsvReader`.
+///
+/// Unlike `CsvReader::new` this skips empty values by default so that blank cells don't
+/// end up as valid field values that can override better data from another source.
+pub struct CsvReaderBuilder {
+    types: HashMap<String, ColumnType>,
+    lenient: bool,
+    skip_empty_values: bool,
+    null_values: Vec<String>,
+}
+
+impl Default for CsvReaderBuilder {
+    fn default() -> Self {
+        CsvReaderBuilder {
+            types: HashMap::new(),
+            lenient: false,
+            skip_empty_values: true,
+            null_values: Vec::new(),
+        }
+    }
+}
+
+impl CsvReaderBuilder {
+    pub fn new() -> CsvReaderBuilder {
+        CsvReaderBuilder::default()
+    }
+
+    /// Parse the values of the columns into the specified types.
+    pub fn with_types(mut self, types: HashMap<String, ColumnType>) -> Self {
+        self.types = types;
+        self
+    }
+
+    /// Yield values that can't be parsed into their column type as strings instead of an error.
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Don't yield triples for blank values or values matching one of the null sentinels.
+    pub fn skip_empty_values(mut self, skip: bool) -> Self {
+        self.skip_empty_values = skip;
+        self
+    }
+
+    /// Treat these values as empty when skipping empty values, eg. `N/A` or `null`.
+    pub fn null_values<T: Into<String>>(mut self, values: impl IntoIterator<Item = T>) -> Self {
+        self.null_values = values.into_iter().map(|value| value.into()).collect();
+        self
+    }
+
+    pub fn build<R: std::io::Read>(self, reader: R) -> Result<CsvReader<R>, ReaderError> {
+        Ok(CsvReader::new(reader)?
+            .with_types(self.types)
+            .lenient(self.lenient)
+            .skip_empty_values(self.skip_empty_values)
+            .null_values(self.null_values))
+    }
+}
+
+
+/// A JSON triples reader.
+///
+/// The document must be an array of flat objects where every object is a record and every
+/// key is a field. Strings are yielded as is, unsigned integers as a `Literal::UInt64` and
+/// all other numbers and booleans as a string. Null values are skipped while nested arrays
+/// and objects are yielded as their JSON string since they can't be represented as a literal.
+///
+/// The whole document is parsed when creating the reader.
+pub struct JsonReader {
+    records: std::vec::IntoIter<serde_json::Map<String, serde_json::Value>>,
+    current: Option<serde_json::map::IntoIter>,
+    row: usize,
+}
+
+impl JsonReader {
+    pub fn new<R: std::io::Read>(reader: R) -> Result<JsonReader, ReaderError> {
+        let records: Vec<serde_json::Map<String, serde_json::Value>> = serde_json::from_reader(reader)?;
+
+        Ok(JsonReader {
+            records: records.into_iter(),
+            current: None,
+            row: 0,
+        })
+    }
+}
+
+impl std::iter::Iterator for JsonReader {
+    type Item = Result<Triple, ReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(fields) = self.current.as_mut() {
+                for (key, value) in fields.by_ref() {
+                    let literal = match value {
+                        serde_json::Value::Null => continue,
+                        serde_json::Value::String(value) => Literal::String(value),
+                        serde_json::Value::Number(value) => match value.as_u64() {
+                            Some(value) => Literal::UInt64(value),
+                            None => Literal::String(value.to_string()),
+                        },
+                        serde_json::Value::Bool(value) => Literal::String(value.to_string()),
+                        value => Literal::String(value.to_string()),
+                    };
+
+                    return Some(Ok((self.row, key, literal)));
+                }
+            }
+
+            // no fields left so move on to the next record
+            let record = self.records.next()?;
+            self.current = Some(record.into_iter());
+            self.row += 1;
+        }
+    }
+}
+
+
+/// Open a file to pass to a reader, decompressing it if it has a `.gz` extension.
+pub fn open_file<P: AsRef<std::path::Path>>(path: P) -> Result<Box<dyn std::io::Read + Send>, ReaderError> {
+    let path = path.as_ref();
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+
+    match path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gz")) {
+        true => Ok(Box::new(flate2::read::MultiGzDecoder::new(file))),
+        false => Ok(Box::new(file)),
+    }
+}
 
-                // we've got a new line so we set it as the current record
-                // and return the first triple from it
-                Ok(record) => {
+
+/// An NCBI assembly report triples reader.
+///
+/// Assembly reports are made up of two sections. The first is a list of `# Key: value`
+/// comments describing the assembly which are yielded as triples on record index 0 with
+/// the key normalised into snake case. The second is a tab separated sequence table where
+/// the header is the last comment line before the table and every row is yielded as a
+/// separate record starting from index 1.
+pub struct AssemblyReportReader<R: std::io::Read> {
+    lines: std::io::Lines<std::io::BufReader<R>>,
+
+    // the normalised sequence table headers once the table has been reached
+    headers: Option<Vec<String>>,
+
+    // the last comment that looked like a table header. the sequence table doesn't
+    // mark the header in any way other than being the comment right before the data
+    last_comment: Option<String>,
+
+    // triples parsed from the current line that are yet to be yielded
+    pending: VecDeque<Triple>,
+    next_row: usize,
+}
+
+impl<R: std::io::Read> AssemblyReportReader<R> {
+    pub fn new(reader: R) -> AssemblyReportReader<R> {
+        AssemblyReportReader {
+            lines: std::io::BufReader::new(reader).lines(),
+            headers: None,
+            last_comment: None,
+            pending: VecDeque::new(),
+            next_row: 1,
+        }
+    }
+
+    fn parse_comment(&mut self, comment: &str) {
+        let comment = comment.trim_start_matches('#').trim();
+
+        if comment.contains('\t') {
+            self.last_comment = Some(comment.to_string());
+        }
+        else if let Some((key, value)) = comment.split_once(':') {
+            let key = to_snake_case(key);
+            let value = value.trim();
+
+            if !key.is_empty() && !value.is_empty() {
+                self.pending.push_back((0, key, Literal::String(value.to_string())));
+            }
+        }
+    }
+
+    fn parse_row(&mut self, line: &str) -> Result<(), ReaderError> {
+        if self.headers.is_none() {
+            match &self.last_comment {
+                Some(header) => self.headers = Some(header.split('\t').map(to_snake_case).collect()),
+                None => return Err(ReaderError::MissingTableHeader(self.next_row)),
+            }
+        }
+
+        if let Some(headers) = &self.headers {
+            for (header, value) in headers.iter().zip(line.split('\t')) {
+                self.pending
+                    .push_back((self.next_row, header.clone(), Literal::String(value.trim().to_string())));
+            }
+        }
+
+        self.next_row += 1;
+        Ok(())
+    }
+}
+
+impl<R: std::io::Read> std::iter::Iterator for AssemblyReportReader<R> {
+    type Item = Result<Triple, ReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(triple) = self.pending.pop_front() {
+                return Some(Ok(triple));
+            }
+
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err.into())),
+            };
+
+            // reports downloaded from the NCBI can have windows line endings
+            let line = line.trim_end_matches('\r');
+
+            if line.starts_with('#') {
+                self.parse_comment(line);
+            }
+            else if !line.trim().is_empty()
+                && let Err(err) = self.parse_row(line)
+            {
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+/// An NCBI BioSample XML triples reader.
+///
+/// Every `<BioSample>` element is yielded as a separate record starting from index 1. The
+/// accession, title, organism, taxonomy id and owner name are yielded with those names while
+/// every `<Attribute>` is yielded with the `attribute_name` as the field name. Other blocks
+/// such as the owner contacts, links and models are skipped.
+///
+/// The full BioSample dump is far too large to keep in memory so the document is streamed
+/// and only the triples of the current record are buffered. A malformed record yields a
+/// `ReaderError::InvalidRecord` and the reader carries on with the next record.
+pub struct BioSampleXmlReader<R: std::io::Read> {
+    reader: quick_xml::Reader<std::io::BufReader<R>>,
+    buf: Vec<u8>,
+
+    // triples from the last complete record that are yet to be yielded
+    pending: VecDeque<Triple>,
+    next_row: usize,
+
+    // the document position of the last error. used to bail out if the parser
+    // fails to make any progress past an error
+    last_error: Option<u64>,
+}
+
+impl<R: std::io::Read> BioSampleXmlReader<R> {
+    pub fn new(reader: R) -> BioSampleXmlReader<R> {
+        let mut reader = quick_xml::Reader::from_reader(std::io::BufReader::new(reader));
+        // we track the element depth ourselves so that a mismatched tag only affects
+        // the record it is in rather than the rest of the document
+        reader.config_mut().check_end_names = false;
+
+        BioSampleXmlReader {
+            reader,
+            buf: Vec::new(),
+            pending: VecDeque::new(),
+            next_row: 1,
+            last_error: None,
+        }
+    }
+
+    // skip to the next biosample and parse it into pending triples. the rest of a
+    // malformed record is skipped over the same way when looking for the next one.
+    // returns false when the end of the document is reached
+    fn next_record(&mut self) -> Result<bool, ReaderError> {
+        loop {
+            self.buf.clear();
+            match self.reader.read_event_into(&mut self.buf)? {
+                Event::Eof => return Ok(false),
+                Event::Start(start) if start.local_name().as_ref() == b"BioSample" => {
+                    let accession = attribute(&start, b"accession");
+                    let row = self.next_row;
                     self.next_row += 1;
-                    self.current_record = Some(record);
-                    match self.next_triple() {
-                        // this would only ever happen if there are no columns in the csv
-                        // file, in which case it'd be an empty file so just return EOF
-                        None => None,
-                        Some(triple) => Some(Ok(triple)),
+
+                    return match self.parse_record(row, accession) {
+                        Ok(triples) => {
+                            self.pending.extend(triples);
+                            Ok(true)
+                        }
+                        Err(err) => Err(ReaderError::InvalidRecord { row, reason: err }),
+                    };
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn parse_record(&mut self, row: usize, accession: Option<String>) -> Result<Vec<Triple>, String> {
+        let mut triples = Vec::new();
+        match accession {
+            Some(accession) => triples.push((row, "accession".to_string(), Literal::String(accession))),
+            None => return Err("missing accession".to_string()),
+        }
+
+        // the element names from the biosample down to the current element
+        let mut path: Vec<Vec<u8>> = Vec::new();
+        let mut attribute_name: Option<String> = None;
+        let mut text = String::new();
+
+        loop {
+            self.buf.clear();
+            let event = self
+                .reader
+                .read_event_into(&mut self.buf)
+                .map_err(|err| err.to_string())?;
+
+            match event {
+                Event::Start(start) => {
+                    let name = start.local_name().as_ref().to_vec();
+                    if name == b"BioSample" {
+                        return Err("nested BioSample element".to_string());
+                    }
+
+                    path.push(name);
+                    text.clear();
+
+                    match path_str(&path).as_slice() {
+                        ["Description", "Organism"] => organism_triples(row, &start, &mut triples),
+                        ["Attributes", "Attribute"] => match attribute(&start, b"attribute_name") {
+                            Some(name) => attribute_name = Some(name),
+                            None => return Err("attribute without an attribute_name".to_string()),
+                        },
+                        _ => {}
                     }
                 }
-            },
+                Event::Empty(start) => {
+                    path.push(start.local_name().as_ref().to_vec());
+                    if let ["Description", "Organism"] = path_str(&path).as_slice() {
+                        organism_triples(row, &start, &mut triples);
+                    }
+                    path.pop();
+                }
+                Event::Text(value) => text.push_str(&value.xml_content().map_err(|err| err.to_string())?),
+                Event::CData(value) => text.push_str(&value.decode().map_err(|err| err.to_string())?),
+                Event::GeneralRef(reference) => {
+                    let reference = format!("&{};", reference.decode().map_err(|err| err.to_string())?);
+                    let value = quick_xml::escape::unescape(&reference).map_err(|err| err.to_string())?;
+                    text.push_str(&value);
+                }
+                Event::End(_) if path.is_empty() => return Ok(triples),
+                Event::End(_) => {
+                    let value = text.trim().to_string();
+                    let field = match path_str(&path).as_slice() {
+                        ["Description", "Title"] => Some("title".to_string()),
+                        ["Description", "Organism", "OrganismName"] => Some("organism_name".to_string()),
+                        ["Owner", "Name"] => Some("owner_name".to_string()),
+                        ["Attributes", "Attribute"] => attribute_name.take(),
+                        _ => None,
+                    };
+
+                    if let Some(field) = field
+                        && !value.is_empty()
+                    {
+                        triples.push((row, field, Literal::String(value)));
+                    }
+
+                    path.pop();
+                    text.clear();
+                }
+                Event::Eof => return Err("unexpected end of document".to_string()),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<R: std::io::Read> std::iter::Iterator for BioSampleXmlReader<R> {
+    type Item = Result<Triple, ReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(triple) = self.pending.pop_front() {
+                return Some(Ok(triple));
+            }
+
+            match self.next_record() {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(err) => {
+                    // stop if we keep failing at the same spot otherwise we'd never finish
+                    let position = self.reader.buffer_position();
+                    if self.last_error == Some(position) {
+                        return None;
+                    }
+                    self.last_error = Some(position);
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+
+/// A Parquet triples reader.
+///
+/// Large pre-joined exports are often distributed as Parquet files rather than CSV.
+/// This reader streams the rows one row group at a time and yields every non-null
+/// column as a triple. Unsigned and non-negative integers become a `Literal::UInt64`
+/// and every other primitive value is yielded as a `Literal::String`.
+///
+/// Nested columns (groups, lists and maps) can't be represented as a single triple
+/// and will yield a `ReaderError::NestedColumn`.
+#[cfg(feature = "parquet")]
+pub struct ParquetReader {
+    rows: parquet::record::reader::RowIter<'static>,
+
+    // triples parsed from the current row that are yet to be yielded
+    pending: VecDeque<Triple>,
+    next_row: usize,
+}
+
+#[cfg(feature = "parquet")]
+impl ParquetReader {
+    pub fn new<R: parquet::file::reader::ChunkReader + 'static>(reader: R) -> Result<ParquetReader, ReaderError> {
+        let reader = parquet::file::reader::SerializedFileReader::new(reader)?;
+
+        Ok(ParquetReader {
+            rows: parquet::record::reader::RowIter::from_file_into(Box::new(reader)),
+            pending: VecDeque::new(),
+            next_row: 1,
+        })
+    }
+
+    fn parse_row(&mut self, row: &parquet::record::Row) -> Result<(), ReaderError> {
+        use parquet::record::Field;
+
+        for (column, field) in row.get_column_iter() {
+            let literal = match field {
+                Field::Null => continue,
+                Field::Byte(value) if *value >= 0 => Literal::UInt64(*value as u64),
+                Field::Short(value) if *value >= 0 => Literal::UInt64(*value as u64),
+                Field::Int(value) if *value >= 0 => Literal::UInt64(*value as u64),
+                Field::Long(value) if *value >= 0 => Literal::UInt64(*value as u64),
+                Field::UByte(value) => Literal::UInt64(*value as u64),
+                Field::UShort(value) => Literal::UInt64(*value as u64),
+                Field::UInt(value) => Literal::UInt64(*value as u64),
+                Field::ULong(value) => Literal::UInt64(*value),
+                // the display impl quotes strings so we use the value as is
+                Field::Str(value) => Literal::String(value.clone()),
+                Field::Group(_) | Field::ListInternal(_) | Field::MapInternal(_) => {
+                    return Err(ReaderError::NestedColumn(column.clone()));
+                }
+                other => Literal::String(other.to_string()),
+            };
+
+            self.pending.push_back((self.next_row, column.clone(), literal));
+        }
+
+        self.next_row += 1;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl std::iter::Iterator for ParquetReader {
+    type Item = Result<Triple, ReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(triple) = self.pending.pop_front() {
+                return Some(Ok(triple));
+            }
+
+            let row = match self.rows.next()? {
+                Ok(row) => row,
+                Err(err) => return Some(Err(err.into())),
+            };
+
+            if let Err(err) = self.parse_row(&row) {
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+/// The number of times `fetch` will retry a request that failed with a transient error.
+#[cfg(feature = "http")]
+pub const DEFAULT_RETRIES: usize = 3;
+
+
+/// Stream a remote file over HTTP so it can be passed to a reader without downloading it first.
+///
+/// The body is streamed as it's read and is never buffered in full. Compressed content is
+/// transparently decompressed, whether it's from a gzip `Content-Encoding` or the file itself
+/// being gzipped, such as the `.gz` dumps from the NCBI.
+///
+/// Transient failures like connection errors, timeouts and 5xx responses are retried up to
+/// `DEFAULT_RETRIES` times. Use `fetch_with_retries` to change the amount of retries.
+#[cfg(feature = "http")]
+pub fn fetch(url: &str) -> Result<Box<dyn std::io::Read + Send>, ReaderError> {
+    fetch_with_retries(url, DEFAULT_RETRIES)
+}
+
+
+/// Stream a remote file over HTTP, retrying transient failures the specified amount of times.
+#[cfg(feature = "http")]
+pub fn fetch_with_retries(url: &str, retries: usize) -> Result<Box<dyn std::io::Read + Send>, ReaderError> {
+    let mut attempt = 0;
+
+    let response = loop {
+        match ureq::get(url).call() {
+            Ok(response) => break response,
+            Err(err) if attempt < retries && is_transient(&err) => {
+                attempt += 1;
+                tracing::warn!(url, attempt, %err, "retrying request");
+                // back off exponentially to give an overloaded server some room
+                std::thread::sleep(std::time::Duration::from_millis(500 * 2u64.pow(attempt as u32 - 1)));
+            }
+            Err(ureq::Error::StatusCode(status)) => {
+                return Err(ReaderError::Http {
+                    status,
+                    url: url.to_string(),
+                });
+            }
+            Err(err) => return Err(err.into()),
+        }
+    };
+
+    // we check the magic bytes instead of the extension or content type as servers
+    // are inconsistent with how they describe gzipped files
+    let mut body = std::io::BufReader::new(response.into_body().into_reader());
+    let gzipped = body.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+
+    match gzipped {
+        true => Ok(Box::new(flate2::read::MultiGzDecoder::new(body))),
+        false => Ok(Box::new(body)),
+    }
+}
+
+
+/// Whether a failed request is worth retrying.
+#[cfg(feature = "http")]
+fn is_transient(err: &ureq::Error) -> bool {
+    match err {
+        ureq::Error::StatusCode(status) => *status >= 500 || *status == 408 || *status == 429,
+        ureq::Error::Io(_) | ureq::Error::Timeout(_) | ureq::Error::ConnectionFailed => true,
+        _ => false,
+    }
+}
+
+
+/// A triples reader adaptor that renames and drops fields.
+///
+/// Source files often have headers that don't match what the schema expects or have
+/// columns that should never be loaded. This adaptor wraps any triples reader and cleans
+/// up the field names before they reach the dataset, keeping per-file quirks out of the schema.
+///
+/// A field is processed in the following order:
+/// 1. if the field is in the rename map it is renamed to the mapped value
+/// 2. otherwise the field is passed through the header normaliser
+/// 3. if either the original or the resulting field is in the drop list the triple is dropped
+///
+/// The default normaliser lowercases the field and replaces spaces with underscores.
+/// To get the renamed and dropped counts after loading pass a mutable reference of the
+/// reader to the transformer rather than the reader itself.
+pub struct MappedReader<I> {
+    inner: I,
+    renames: HashMap<String, String>,
+    drops: HashSet<String>,
+    normaliser: Box<dyn Fn(&str) -> String>,
+
+    renamed: usize,
+    dropped: usize,
+}
+
+impl<I> MappedReader<I> {
+    pub fn new(inner: I) -> MappedReader<I> {
+        MappedReader {
+            inner,
+            renames: HashMap::new(),
+            drops: HashSet::new(),
+            normaliser: Box::new(|header| header.to_lowercase().replace(" ", "_")),
+            renamed: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Rename the field `from` into `to`. The renamed field skips normalisation.
+    pub fn rename(mut self, from: &str, to: &str) -> Self {
+        self.renames.insert(from.to_string(), to.to_string());
+        self
+    }
+
+    /// Rename all fields in the map.
+    pub fn renames(mut self, renames: HashMap<String, String>) -> Self {
+        self.renames.extend(renames);
+        self
+    }
+
+    /// Drop all triples for the field.
+    pub fn drop_field(mut self, field: &str) -> Self {
+        self.drops.insert(field.to_string());
+        self
+    }
+
+    /// Use a custom header normaliser instead of the default.
+    pub fn normaliser<F>(mut self, normaliser: F) -> Self
+    where
+        F: Fn(&str) -> String + 'static,
+    {
+        self.normaliser = Box::new(normaliser);
+        self
+    }
+
+    /// The amount of triples that had their field changed.
+    pub fn renamed(&self) -> usize {
+        self.renamed
+    }
+
+    /// The amount of triples that were dropped.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+}
+
+impl<I, E> std::iter::Iterator for MappedReader<I>
+where
+    I: Iterator<Item = Result<Triple, E>>,
+{
+    type Item = Result<Triple, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (idx, header, value) = match self.inner.next()? {
+                Ok(triple) => triple,
+                Err(err) => return Some(Err(err)),
+            };
+
+            let field = match self.renames.get(&header) {
+                Some(renamed) => renamed.clone(),
+                None => (self.normaliser)(&header),
+            };
+
+            if self.drops.contains(&header) || self.drops.contains(&field) {
+                self.dropped += 1;
+                continue;
+            }
+
+            if field != header {
+                self.renamed += 1;
+            }
+
+            return Some(Ok((idx, field, value)));
+        }
+    }
+}
+
+
+/// A triples reader adaptor that only yields records matching a predicate.
+///
+/// Filtering with `mapping:when` happens after the whole source is loaded into the dataset
+/// which can use a lot of memory for large sources when only a subset of it is needed. This
+/// adaptor instead buffers all triples for a record and only yields them if the predicate
+/// returns true for the record's field/value pairs, dropping the record before it is loaded.
+///
+/// A record boundary is detected when the index of the triple changes, which means the
+/// wrapped reader must yield all triples of a record together. All the readers in this
+/// module do so.
+pub struct FilteredReader<I, F> {
+    inner: I,
+    predicate: F,
+
+    // the record currently being buffered
+    current: Option<usize>,
+    buffer: Vec<(String, Literal)>,
+
+    // triples of a matching record that are yet to be yielded
+    pending: VecDeque<Triple>,
+    finished: bool,
+    filtered: usize,
+}
+
+impl<I, F> FilteredReader<I, F>
+where
+    F: Fn(&[(String, Literal)]) -> bool,
+{
+    pub fn new(inner: I, predicate: F) -> FilteredReader<I, F> {
+        FilteredReader {
+            inner,
+            predicate,
+            current: None,
+            buffer: Vec::new(),
+            pending: VecDeque::new(),
+            finished: false,
+            filtered: 0,
+        }
+    }
+
+    /// The amount of records that didn't match the predicate.
+    pub fn filtered(&self) -> usize {
+        self.filtered
+    }
+
+    // move the buffered record into the pending queue if it matches
+    fn flush(&mut self) {
+        let Some(idx) = self.current
+        else {
+            return;
+        };
+
+        if (self.predicate)(&self.buffer) {
+            self.pending
+                .extend(self.buffer.drain(..).map(|(header, value)| (idx, header, value)));
+        }
+        else {
+            self.buffer.clear();
+            self.filtered += 1;
+        }
+    }
+}
+
+impl<I, F, E> std::iter::Iterator for FilteredReader<I, F>
+where
+    I: Iterator<Item = Result<Triple, E>>,
+    F: Fn(&[(String, Literal)]) -> bool,
+{
+    type Item = Result<Triple, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(triple) = self.pending.pop_front() {
+                return Some(Ok(triple));
+            }
+
+            if self.finished {
+                return None;
+            }
+
+            match self.inner.next() {
+                // flush the final record at the end of the input
+                None => {
+                    self.flush();
+                    self.finished = true;
+                }
+                Some(Err(err)) => return Some(Err(err)),
+                Some(Ok((idx, header, value))) => {
+                    if self.current != Some(idx) {
+                        self.flush();
+                        self.current = Some(idx);
+                    }
+                    self.buffer.push((header, value));
+                }
+            }
+        }
+    }
+}
+
+
+/// Check if the value is an ISO 8601 calendar date in the form of `YYYY-MM-DD`.
+fn is_iso_date(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('-').collect();
+    let [year, month, day] = parts.as_slice()
+    else {
+        return false;
+    };
+
+    let digits = |part: &str, len: usize| part.len() == len && part.chars().all(|c| c.is_ascii_digit());
+    if !digits(year, 4) || !digits(month, 2) || !digits(day, 2) {
+        return false;
+    }
+
+    matches!(month.parse::<u8>(), Ok(1..=12)) && matches!(day.parse::<u8>(), Ok(1..=31))
+}
+
+
+/// Convert a header such as `Assembly name` or `Sequence-Name` into `assembly_name`
+/// and `sequence_name` so that it is a valid IRI segment.
+fn to_snake_case(value: &str) -> String {
+    let mut snake = String::with_capacity(value.len());
+    for ch in value.trim().chars() {
+        if ch.is_alphanumeric() {
+            snake.extend(ch.to_lowercase());
+        }
+        else if !snake.is_empty() && !snake.ends_with('_') {
+            snake.push('_');
+        }
+    }
+
+    snake.trim_end_matches('_').to_string()
+}
+
+
+/// Get the unescaped value of an XML element attribute, ignoring blank values.
+fn attribute(element: &BytesStart, name: &[u8]) -> Option<String> {
+    match element.try_get_attribute(name) {
+        Ok(Some(attr)) => attr.unescape_value().ok().map(|value| value.trim().to_string()),
+        _ => None,
+    }
+    .filter(|value| !value.is_empty())
+}
+
+
+/// Convert the element names of a path into strings for matching against.
+fn path_str(path: &[Vec<u8>]) -> Vec<&str> {
+    path.iter()
+        .map(|name| std::str::from_utf8(name).unwrap_or_default())
+        .collect()
+}
+
+
+/// Get the organism name and taxonomy id from the attributes of a BioSample organism element.
+fn organism_triples(row: usize, element: &BytesStart, triples: &mut Vec<Triple>) {
+    if let Some(name) = attribute(element, b"taxonomy_name") {
+        triples.push((row, "organism".to_string(), Literal::String(name)));
+    }
+    if let Some(id) = attribute(element, b"taxonomy_id") {
+        triples.push((row, "taxonomy_id".to_string(), Literal::String(id)));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::{Dataset, Model};
+    use crate::models::name;
+    use crate::testing::{SYNTHETIC_SCHEMA, load_source};
+
+    const FILLED: &str = "id,name,rank\n1,Felis catus,species\n2,Canis lupus,species\n3,Vulpes,genus\n";
+    const SPARSE: &str = "id,name,rank\n1,Felis catus,\n2, ,N/A\n3,-,-\n";
+
+    fn read(reader: CsvReader<&[u8]>) -> Vec<Triple> {
+        reader.collect::<Result<Vec<Triple>, ReaderError>>().unwrap()
+    }
+
+    #[test]
+    fn builder_skips_empty_values() {
+        let filled = read(CsvReaderBuilder::new().build(FILLED.as_bytes()).unwrap());
+        let sparse = read(CsvReaderBuilder::new().build(SPARSE.as_bytes()).unwrap());
+        assert_eq!(filled.len(), 9);
+        assert_eq!(sparse.len(), 7);
+        assert!(sparse.iter().all(|(_, _, value)| !value.lexical().trim().is_empty()));
+    }
+
+    #[test]
+    fn builder_skips_null_values() {
+        let reader = CsvReaderBuilder::new()
+            .null_values(["N/A", "-"])
+            .build(SPARSE.as_bytes())
+            .unwrap();
+        let triples = read(reader);
+
+        // rows with every value skipped still keep their index
+        let rows: Vec<(usize, &str)> = triples.iter().map(|(row, column, _)| (*row, column.as_str())).collect();
+        assert_eq!(rows, vec![(1, "id"), (1, "name"), (2, "id"), (3, "id")]);
+    }
+
+    #[test]
+    fn reader_keeps_empty_values_by_default() {
+        let triples = read(CsvReader::new(SPARSE.as_bytes()).unwrap());
+        assert_eq!(triples.len(), 9);
+    }
+
+    fn string(row: usize, field: &str, value: &str) -> Triple {
+        (row, field.to_string(), Literal::String(value.to_string()))
+    }
+
+    const ASSEMBLY_REPORT: &str = "# Assembly name:  ASM123v1\n\
+        # Organism name:  Felis catus (domestic cat)\n\
+        #\n\
+        # Sequence-Name\tSequence-Role\tGenBank-Accn\n\
+        chr1\tassembled-molecule\tCM000001.1\n\
+        chrUn\tunplaced-scaffold\tKZ000002.1\n";
+
+    fn assembly_report(report: &str) -> Vec<Triple> {
+        AssemblyReportReader::new(report.as_bytes())
+            .collect::<Result<Vec<Triple>, ReaderError>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn assembly_report_yields_the_comments_and_sequence_table() {
+        assert_eq!(
+            assembly_report(ASSEMBLY_REPORT),
+            vec![
+                string(0, "assembly_name", "ASM123v1"),
+                string(0, "organism_name", "Felis catus (domestic cat)"),
+                string(1, "sequence_name", "chr1"),
+                string(1, "sequence_role", "assembled-molecule"),
+                string(1, "genbank_accn", "CM000001.1"),
+                string(2, "sequence_name", "chrUn"),
+                string(2, "sequence_role", "unplaced-scaffold"),
+                string(2, "genbank_accn", "KZ000002.1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn assembly_report_handles_windows_line_endings() {
+        let report = ASSEMBLY_REPORT.replace('\n', "\r\n");
+        assert_eq!(assembly_report(&report), assembly_report(ASSEMBLY_REPORT));
+    }
+
+    #[test]
+    fn assembly_report_parses_comments_into_key_values() {
+        let report = "# RefSeq category: Representative Genome\n\
+            # Submitter:  Museum: Sydney\n\
+            # GenBank assembly accession: \n\
+            ## Assembly-Units:\n\
+            # Just a note\n";
+
+        // the value is everything after the first colon and keys without a value are skipped
+        assert_eq!(
+            assembly_report(report),
+            vec![
+                string(0, "refseq_category", "Representative Genome"),
+                string(0, "submitter", "Museum: Sydney"),
+            ]
+        );
+    }
+
+    #[test]
+    fn assembly_report_without_a_sequence_table() {
+        let report = "# Assembly name:  ASM123v1\n# Taxid:          9685\n\n";
+        assert_eq!(assembly_report(report), vec![string(0, "assembly_name", "ASM123v1"), string(0, "taxid", "9685")]);
+
+        // a table without a header can't be read
+        let mut reader = AssemblyReportReader::new("# Taxid: 9685\nchr1\tCM000001.1\n".as_bytes());
+        assert_eq!(reader.next().unwrap().unwrap(), string(0, "taxid", "9685"));
+        assert!(matches!(reader.next(), Some(Err(ReaderError::MissingTableHeader(1)))));
+    }
+
+    const BIOSAMPLES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<BioSampleSet>
+  <BioSample accession="SAMN00000001" id="1">
+    <Description>
+      <Title>Cat tissue</Title>
+      <Organism taxonomy_id="9685" taxonomy_name="Felis catus">
+        <OrganismName>Felis catus</OrganismName>
+      </Organism>
+    </Description>
+    <Owner>
+      <Name>Australian Museum</Name>
+      <Contacts><Contact email="curator@example.org"><Name><First>Jane</First></Name></Contact></Contacts>
+    </Owner>
+    <Attributes>
+      <Attribute attribute_name="collection_date" harmonized_name="collection_date">2020-01-01</Attribute>
+      <Attribute attribute_name="geo_loc_name">Australia: Sydney &amp; surrounds</Attribute>
+    </Attributes>
+  </BioSample>
+  <BioSample id="2">
+    <Description><Title>No accession</Title></Description>
+  </BioSample>
+  <BioSample accession="SAMN00000003">
+    <Attributes><Attribute>no name</Attribute></Attributes>
+  </BioSample>
+  <BioSample accession="SAMN00000004">
+    <Description><Organism taxonomy_id="9615" taxonomy_name="Canis lupus"/></Description>
+  </BioSample>
+</BioSampleSet>
+"#;
+
+    #[test]
+    fn biosample_yields_the_fields_of_every_record() {
+        let triples: Vec<Triple> = BioSampleXmlReader::new(BIOSAMPLES.as_bytes())
+            .filter_map(Result::ok)
+            .collect();
+
+        assert_eq!(
+            triples,
+            vec![
+                string(1, "accession", "SAMN00000001"),
+                string(1, "title", "Cat tissue"),
+                string(1, "organism", "Felis catus"),
+                string(1, "taxonomy_id", "9685"),
+                string(1, "organism_name", "Felis catus"),
+                string(1, "owner_name", "Australian Museum"),
+                string(1, "collection_date", "2020-01-01"),
+                string(1, "geo_loc_name", "Australia: Sydney & surrounds"),
+                string(4, "accession", "SAMN00000004"),
+                string(4, "organism", "Canis lupus"),
+                string(4, "taxonomy_id", "9615"),
+            ]
+        );
+    }
+
+    #[test]
+    fn biosample_skips_malformed_records() {
+        let errors: Vec<ReaderError> = BioSampleXmlReader::new(BIOSAMPLES.as_bytes())
+            .filter_map(Result::err)
+            .collect();
+
+        assert!(matches!(
+            errors.as_slice(),
+            [
+                ReaderError::InvalidRecord { row: 2, reason: missing },
+                ReaderError::InvalidRecord { row: 3, reason: unnamed },
+            ] if missing == "missing accession" && unnamed == "attribute without an attribute_name"
+        ));
+    }
+
+    const MAPPED: &str = "Taxon ID,Scientific Name,notes,internal\n9685,Felis catus,pet,x\n";
+
+    #[test]
+    fn mapped_reader_renames_and_passes_through_fields() {
+        let csv = CsvReader::new(MAPPED.as_bytes()).unwrap();
+        let mut reader = MappedReader::new(csv)
+            .rename("Taxon ID", "entity_id")
+            .drop_field("internal");
+        let triples = (&mut reader).collect::<Result<Vec<Triple>, ReaderError>>().unwrap();
+
+        // unmapped fields are normalised, which leaves already normalised fields as they are
+        assert_eq!(
+            triples,
+            vec![
+                string(1, "entity_id", "9685"),
+                string(1, "scientific_name", "Felis catus"),
+                string(1, "notes", "pet"),
+            ]
+        );
+        assert_eq!(reader.renamed(), 2);
+        assert_eq!(reader.dropped(), 1);
+    }
+
+    #[test]
+    fn mapped_reader_renames_skip_the_normaliser() {
+        let csv = CsvReader::new(MAPPED.as_bytes()).unwrap();
+        let reader = MappedReader::new(csv)
+            .renames(HashMap::from([("Taxon ID".to_string(), "TaxonID".to_string())]))
+            .normaliser(|header| header.to_uppercase())
+            .drop_field("Scientific Name")
+            .drop_field("INTERNAL");
+        let triples = reader.collect::<Result<Vec<Triple>, ReaderError>>().unwrap();
+
+        // fields can be dropped by either their original or normalised name
+        assert_eq!(triples, vec![string(1, "TaxonID", "9685"), string(1, "NOTES", "pet")]);
+    }
+
+    fn is_species(record: &[(String, Literal)]) -> bool {
+        record
+            .iter()
+            .any(|(header, value)| header == "rank" && value.lexical() == "species")
+    }
+
+    #[test]
+    fn filtered_reader_drops_records_not_matching_the_predicate() {
+        let csv = CsvReader::new(FILLED.as_bytes()).unwrap();
+        let mut reader = FilteredReader::new(csv, is_species);
+        let triples = (&mut reader).collect::<Result<Vec<Triple>, ReaderError>>().unwrap();
+
+        let rows: Vec<usize> = triples.iter().map(|(row, _, _)| *row).collect();
+        assert_eq!(rows, vec![1, 1, 1, 2, 2, 2]);
+        assert_eq!(triples[1], string(1, "name", "Felis catus"));
+        assert_eq!(reader.filtered(), 1);
+    }
+
+    #[test]
+    fn filtered_reader_passes_errors_through() {
+        let triples: Vec<Result<Triple, String>> = vec![
+            Ok(string(1, "rank", "species")),
+            Err("invalid row".to_string()),
+            Ok(string(1, "name", "Felis catus")),
+            Ok(string(2, "rank", "genus")),
+        ];
+        let reader = FilteredReader::new(triples.into_iter(), is_species);
+
+        // errors are yielded straight away rather than waiting for the record to finish
+        assert_eq!(
+            reader.collect::<Vec<_>>(),
+            vec![
+                Err("invalid row".to_string()),
+                Ok(string(1, "rank", "species")),
+                Ok(string(1, "name", "Felis catus")),
+            ]
+        );
+    }
+
+    #[test]
+    fn column_types_parse_values() {
+        let text = |value: &str| Some(Literal::String(value.to_text()));
+        let cases = [
+            (ColumnType::Integer, "42", Some(Literal::UInt64(42))),
+            (ColumnType::Integer, " 1,024 ", Some(Literal::UInt64(1024))),
+            (ColumnType::Integer, "-5", None),
+            (ColumnType::Integer, "1.5", None),
+            (ColumnType::Decimal, "1,024.5", text("1,024.5")),
+            (ColumnType::Decimal, "-2.5", text("-2.5")),
+            (ColumnType::Decimal, "two", None),
+            (ColumnType::Boolean, "Yes", text("true")),
+            (ColumnType::Boolean, "f", text("false")),
+            (ColumnType::Boolean, "maybe", None),
+            (ColumnType::String, " as is ", text(" as is ")),
+            (ColumnType::Date, "2020-01-31", text("2020-01-31")),
+            (ColumnType::Date, "2020-13-01", None),
+            (ColumnType::Date, "31/01/2020", None),
+        ];
+
+        for (column_type, value, expected) in cases {
+            assert_eq!(column_type.parse(value), expected, "{column_type:?} {value:?}");
+        }
+
+        // blank cells are allowed in every column type
+        for column_type in [
+            ColumnType::Integer,
+            ColumnType::Decimal,
+            ColumnType::Boolean,
+            ColumnType::Date,
+        ] {
+            assert_eq!(column_type.parse("  "), text(""));
+        }
+    }
+
+    #[test]
+    fn peeking_does_not_consume_rows() {
+        let mut reader = CsvReader::new(FILLED.as_bytes()).unwrap();
+
+        let peeked = reader.peek_first_n(2).unwrap();
+        let row = |values: [&str; 3]| -> Vec<(String, String)> {
+            ["id", "name", "rank"]
+                .into_iter()
+                .zip(values)
+                .map(|(header, value)| (header.to_string(), value.to_string()))
+                .collect()
+        };
+        assert_eq!(
+            peeked,
+            vec![
+                row(["1", "Felis catus", "species"]),
+                row(["2", "Canis lupus", "species"])
+            ]
+        );
+
+        // asking for more rows than there are returns what the file has
+        assert_eq!(reader.peek_first_n(10).unwrap().len(), 3);
+        assert_eq!(read(reader), read(CsvReader::new(FILLED.as_bytes()).unwrap()));
+    }
+
+    #[test]
+    fn estimated_rows_of_a_small_file() {
+        let mut reader = CsvReader::new(std::io::Cursor::new(FILLED.as_bytes())).unwrap();
+        assert_eq!(reader.estimated_rows(), Some(3));
+        assert_eq!(reader.count(), 9);
+
+        let mut reader = CsvReader::new(std::io::Cursor::new("id,name,rank\n".as_bytes())).unwrap();
+        assert_eq!(reader.estimated_rows(), None);
+    }
+
+    #[test]
+    fn json_numeric_ids_resolve_into_names() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = "fields:entity_id mapping:same col:id .
+            fields:scientific_name mapping:same col:name .";
+        load_source(&mut dataset, &Model::Name, "names", mappings, &[]).unwrap();
+
+        let json = r#"[{"id": 9685, "name": "Felis catus"}, {"id": "9615", "name": "Canis lupus"}]"#;
+        let reader = JsonReader::new(json.as_bytes()).unwrap();
+        dataset.load(reader, "names").unwrap();
+
+        let mut names: Vec<_> = name::get_all(&dataset)
+            .unwrap()
+            .into_iter()
+            .map(|name| (name.entity_id, name.scientific_name))
+            .collect();
+        names.sort();
+
+        assert_eq!(
+            names,
+            vec![
+                ("9615".to_string(), "Canis lupus".to_string()),
+                ("9685".to_string(), "Felis catus".to_string()),
+            ]
+        );
+    }
+
+    #[cfg(feature = "http")]
+    mod http {
+        use std::io::{BufReader, Write};
+
+        use super::super::*;
+
+        const TAXA: &str = "taxon_id,scientific_name,rank\n9685,Felis catus,species\n9612,Canis lupus,species\n";
+
+        fn triples<R: std::io::Read>(reader: CsvReader<R>) -> Vec<Triple> {
+            reader.collect::<Result<Vec<Triple>, ReaderError>>().unwrap()
+        }
+
+        /// Serve the responses on a local port, one for each connection in the order given.
+        fn serve(responses: Vec<Vec<u8>>) -> String {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/taxa.csv.gz", listener.local_addr().unwrap());
+
+            std::thread::spawn(move || {
+                for response in responses {
+                    let (mut stream, _) = listener.accept().unwrap();
+
+                    // the request has to be read before responding or the client may see a reset
+                    let mut request = BufReader::new(stream.try_clone().unwrap());
+                    let mut line = String::new();
+                    while request.read_line(&mut line).unwrap() > 2 {
+                        line.clear();
+                    }
+
+                    stream.write_all(&response).unwrap();
+                }
+            });
+
+            url
+        }
+
+        fn response(status: &str, body: &[u8]) -> Vec<u8> {
+            let head = format!("HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len());
+            let mut response = head.into_bytes();
+            response.extend_from_slice(body);
+            response
+        }
+
+        fn gzip(body: &str) -> Vec<u8> {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(body.as_bytes()).unwrap();
+            encoder.finish().unwrap()
+        }
+
+        #[test]
+        fn fetch_yields_the_same_triples_as_a_file() {
+            let url = serve(vec![response("200 OK", TAXA.as_bytes())]);
+            let fetched = CsvReader::new(fetch(&url).unwrap()).unwrap();
+            assert_eq!(triples(fetched), triples(CsvReader::new(TAXA.as_bytes()).unwrap()));
+        }
+
+        #[test]
+        fn fetch_decompresses_gzipped_files() {
+            let url = serve(vec![response("200 OK", &gzip(TAXA))]);
+            let fetched = CsvReader::new(fetch(&url).unwrap()).unwrap();
+            assert_eq!(triples(fetched), triples(CsvReader::new(TAXA.as_bytes()).unwrap()));
+        }
+
+        #[test]
+        fn fetch_retries_server_errors() {
+            let url = serve(vec![
+                response("503 Service Unavailable", b""),
+                response("200 OK", TAXA.as_bytes()),
+            ]);
+            let fetched = CsvReader::new(fetch_with_retries(&url, 1).unwrap()).unwrap();
+            assert_eq!(triples(fetched).len(), 6);
+        }
+
+        #[test]
+        fn fetch_reports_http_errors() {
+            let url = serve(vec![response("404 Not Found", b"")]);
+            let result = fetch(&url);
+            assert!(matches!(result, Err(ReaderError::Http { status: 404, url: failed }) if failed == url));
+
+            let url = serve(vec![response("503 Service Unavailable", b"")]);
+            let result = fetch_with_retries(&url, 0);
+            assert!(matches!(result, Err(ReaderError::Http { status: 503, .. })));
         }
     }
 }
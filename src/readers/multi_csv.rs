@@ -0,0 +1,317 @@
+use std::collections::VecDeque;
+
+use crate::dataset::Triple;
+use crate::errors::ReaderError;
+use crate::rdf::Literal;
+use crate::readers::CsvReader;
+
+
+/// A CSV triples reader over a directory of files that share a header.
+///
+/// Files are read in sorted order and the record indices are offset by the records
+/// of the previous files so that they never collide. Every record also gets a triple
+/// with the name of the file it came from, under `source_file` unless changed with
+/// `source_field`.
+///
+/// By default every file must have the same columns as the first file. Use
+/// `union_headers` to allow files with differing columns instead.
+///
+/// Streams that aren't files in a directory can be read with `from_readers` instead.
+pub struct MultiCsvReader {
+    inputs: VecDeque<MultiCsvInput>,
+    source_field: String,
+    // the headers every input must have when they can only be checked as each input is opened
+    expected_headers: Option<Vec<String>>,
+    check_headers: bool,
+
+    current: Option<(String, CsvReader<Box<dyn std::io::Read + Send>>)>,
+    // the record index offset for the current file and the last record yielded from it
+    offset: usize,
+    last_row: usize,
+    // the triple to yield after the source file triple for a new record
+    pending: Option<Triple>,
+}
+
+/// A file or stream to be read by the `MultiCsvReader` once the ones before it are done.
+enum MultiCsvInput {
+    File(std::path::PathBuf),
+    Reader(String, Box<dyn std::io::Read + Send>),
+}
+
+impl MultiCsvReader {
+    /// Read the streams in order as if they were one file.
+    ///
+    /// Every stream must have the same columns as the first one. Unlike `from_dir` the
+    /// headers can only be checked when a stream is reached, so a mismatch is yielded as
+    /// an error after the records of the streams before it. The source file triple of a
+    /// record is the position of its stream, eg. `reader_0`.
+    pub fn from_readers(readers: Vec<Box<dyn std::io::Read + Send>>) -> MultiCsvReader {
+        let inputs = readers
+            .into_iter()
+            .enumerate()
+            .map(|(idx, reader)| MultiCsvInput::Reader(format!("reader_{idx}"), reader))
+            .collect();
+
+        MultiCsvReader {
+            inputs,
+            source_field: "source_file".to_string(),
+            expected_headers: None,
+            check_headers: true,
+            current: None,
+            offset: 0,
+            last_row: 0,
+            pending: None,
+        }
+    }
+
+    /// Read all files in the directory with a name matching the glob pattern, eg. `*.csv`.
+    ///
+    /// The headers of all matching files are checked when creating the reader so that
+    /// a mismatch is found before any data is loaded.
+    pub fn from_dir<P: AsRef<std::path::Path>>(path: P, pattern: &str) -> Result<MultiCsvReader, ReaderError> {
+        Self::from_dir_with_options(path, pattern, false)
+    }
+
+    /// Read all files in the directory matching the glob pattern, allowing files to have differing columns.
+    pub fn from_dir_union<P: AsRef<std::path::Path>>(path: P, pattern: &str) -> Result<MultiCsvReader, ReaderError> {
+        Self::from_dir_with_options(path, pattern, true)
+    }
+
+    fn from_dir_with_options<P: AsRef<std::path::Path>>(
+        path: P,
+        pattern: &str,
+        union_headers: bool,
+    ) -> Result<MultiCsvReader, ReaderError> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(path)? {
+            let path = entry?.path();
+            let matches = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| glob_match(pattern, name));
+            if path.is_file() && matches == Some(true) {
+                files.push(path);
+            }
+        }
+        files.sort();
+
+        if !union_headers {
+            let mut expected: Option<Vec<String>> = None;
+            for file in files.iter() {
+                let mut reader = csv::Reader::from_path(file)?;
+                let headers: Vec<String> = reader.headers()?.iter().map(|h| h.to_string()).collect();
+
+                match &expected {
+                    None => expected = Some(headers),
+                    Some(expected) if *expected != headers => {
+                        return Err(ReaderError::HeaderMismatch {
+                            file: file.display().to_string(),
+                            missing: expected.iter().filter(|h| !headers.contains(h)).cloned().collect(),
+                            extra: headers.iter().filter(|h| !expected.contains(h)).cloned().collect(),
+                        });
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        Ok(MultiCsvReader {
+            inputs: files.into_iter().map(MultiCsvInput::File).collect(),
+            source_field: "source_file".to_string(),
+            // the headers of the files have already been checked
+            expected_headers: None,
+            check_headers: false,
+            current: None,
+            offset: 0,
+            last_row: 0,
+            pending: None,
+        })
+    }
+
+    /// The field name to use for the triple with the source file name of a record.
+    pub fn source_field(mut self, field: impl Into<String>) -> Self {
+        self.source_field = field.into();
+        self
+    }
+
+    // open the next input and move the offset past the records of the current one
+    fn next_file(&mut self) -> Option<Result<(), ReaderError>> {
+        let (name, stream): (String, Box<dyn std::io::Read + Send>) = match self.inputs.pop_front()? {
+            MultiCsvInput::Reader(name, reader) => (name, reader),
+            MultiCsvInput::File(path) => {
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+
+                match std::fs::File::open(&path) {
+                    Ok(file) => (name, Box::new(file)),
+                    Err(err) => return Some(Err(err.into())),
+                }
+            }
+        };
+
+        let reader = match CsvReader::new(stream) {
+            Ok(reader) => reader,
+            Err(err) => return Some(Err(err)),
+        };
+
+        if self.check_headers {
+            let headers = reader.headers();
+            match &self.expected_headers {
+                None => self.expected_headers = Some(headers.to_vec()),
+                Some(expected) if expected != headers => {
+                    return Some(Err(ReaderError::HeaderMismatch {
+                        file: name,
+                        missing: expected.iter().filter(|h| !headers.contains(h)).cloned().collect(),
+                        extra: headers.iter().filter(|h| !expected.contains(h)).cloned().collect(),
+                    }));
+                }
+                Some(_) => {}
+            }
+        }
+
+        self.offset = self.last_row;
+        self.current = Some((name, reader));
+        Some(Ok(()))
+    }
+}
+
+impl std::iter::Iterator for MultiCsvReader {
+    type Item = Result<Triple, ReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(triple) = self.pending.take() {
+            return Some(Ok(triple));
+        }
+
+        loop {
+            let Some((name, reader)) = &mut self.current
+            else {
+                if let Err(err) = self.next_file()? {
+                    return Some(Err(err));
+                }
+                continue;
+            };
+
+            match reader.next() {
+                None => self.current = None,
+                Some(Err(err)) => return Some(Err(err)),
+                Some(Ok((row, field, value))) => {
+                    let row = self.offset + row;
+
+                    // the first triple of a new record so we yield the source file first
+                    if row != self.last_row {
+                        self.last_row = row;
+                        self.pending = Some((row, field, value));
+                        return Some(Ok((row, self.source_field.clone(), Literal::String(name.clone()))));
+                    }
+
+                    return Some(Ok((row, field, value)));
+                }
+            }
+        }
+    }
+}
+
+
+/// Match a file name against a glob pattern where `*` matches any run of
+/// characters and `?` matches a single character.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    // the position of the last star and where in the name it started matching from
+    // so that we can backtrack and let the star consume another character
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some('?') => {
+                p += 1;
+                n += 1;
+            }
+            Some(ch) if *ch == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|ch| *ch == '*')
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::*;
+
+    fn stream(csv: &'static str) -> Box<dyn std::io::Read + Send> {
+        Box::new(csv.as_bytes())
+    }
+
+    #[test]
+    fn row_indices_are_unique_across_streams() {
+        let reader = MultiCsvReader::from_readers(vec![
+            stream("id,name\n1,Felis catus\n2,Canis lupus\n"),
+            stream("id,name\n3,Vombatus ursinus\n4,Phascolarctos cinereus\n5,Ornithorhynchus anatinus\n"),
+        ]);
+        let triples: Vec<Triple> = reader.collect::<Result<_, _>>().unwrap();
+
+        // the rows of each stream by its source file triple
+        let mut streams: BTreeMap<String, BTreeSet<usize>> = BTreeMap::new();
+        let mut ids: BTreeMap<usize, String> = BTreeMap::new();
+        for (row, field, value) in triples {
+            let Literal::String(value) = value
+            else {
+                panic!("expected a string but found {value:?}");
+            };
+
+            match field.as_str() {
+                "source_file" => {
+                    streams.entry(value).or_default().insert(row);
+                }
+                "id" => {
+                    ids.insert(row, value);
+                }
+                _ => {}
+            }
+        }
+
+        assert_eq!(streams["reader_0"].len(), 2);
+        assert_eq!(streams["reader_1"].len(), 3);
+        assert!(streams["reader_0"].is_disjoint(&streams["reader_1"]));
+        assert_eq!(ids.values().collect::<Vec<_>>(), vec!["1", "2", "3", "4", "5"]);
+    }
+
+    #[test]
+    fn mismatched_stream_headers_are_an_error() {
+        let reader = MultiCsvReader::from_readers(vec![stream("id,name\n1,a\n"), stream("id,rank\n2,species\n")]);
+        let result: Result<Vec<Triple>, ReaderError> = reader.collect();
+        assert!(matches!(result, Err(ReaderError::HeaderMismatch { file, .. }) if file == "reader_1"));
+    }
+
+    #[test]
+    fn glob_patterns() {
+        assert!(glob_match("*.csv", "taxa.csv"));
+        assert!(glob_match("taxa_?.csv", "taxa_1.csv"));
+        assert!(glob_match("*_part*.csv", "taxa_part_2.csv"));
+        assert!(!glob_match("*.csv", "taxa.tsv"));
+        assert!(!glob_match("taxa_?.csv", "taxa_10.csv"));
+    }
+}
@@ -0,0 +1,166 @@
+use std::path::Path;
+
+use calamine::{Data, Range, Reader};
+
+use crate::dataset::Triple;
+use crate::errors::ReaderError;
+use crate::rdf::Literal;
+
+
+/// Read the first sheet of an Excel workbook as triples.
+///
+/// The first row of the sheet is used as the headers and every other row is a record.
+/// A triple is yielded for each cell with a value, empty cells are skipped in the same
+/// way as the `skip_empty_values` option of the `CsvReader`.
+///
+/// Unlike the other readers this takes a path rather than a stream because the workbook
+/// needs to be seekable to be read.
+pub struct XlsxReader {
+    range: Range<Data>,
+    headers: Vec<String>,
+    row: usize,
+    column: usize,
+}
+
+impl XlsxReader {
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<XlsxReader, ReaderError> {
+        let mut workbook = calamine::open_workbook_auto(path).map_err(|err| ReaderError::Xlsx(err.to_string()))?;
+        let range = match workbook.worksheet_range_at(0) {
+            Some(range) => range.map_err(|err| ReaderError::Xlsx(err.to_string()))?,
+            None => return Err(ReaderError::Xlsx("the workbook has no sheets".to_string())),
+        };
+
+        let headers = range.headers().unwrap_or_default();
+
+        Ok(XlsxReader {
+            range,
+            headers,
+            row: 1,
+            column: 0,
+        })
+    }
+}
+
+impl std::iter::Iterator for XlsxReader {
+    type Item = Result<Triple, ReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (height, width) = self.range.get_size();
+
+        while self.row < height {
+            let (row, column) = (self.row, self.column);
+
+            self.column += 1;
+            if self.column >= width {
+                self.column = 0;
+                self.row += 1;
+            }
+
+            let Some(header) = self.headers.get(column).filter(|header| !header.trim().is_empty())
+            else {
+                continue;
+            };
+
+            let literal = match self.range.get((row, column)) {
+                Some(cell) => cell_literal(cell),
+                None => None,
+            };
+
+            if let Some(literal) = literal {
+                return Some(Ok((row, header.clone(), literal)));
+            }
+        }
+
+        None
+    }
+}
+
+
+/// Convert a cell into a literal, returning `None` if the cell doesn't have a value.
+///
+/// Whole numbers become a `Literal::UInt64` while dates become an ISO 8601 string. Cells
+/// with a formula error such as `#N/A` are treated as empty.
+fn cell_literal(cell: &Data) -> Option<Literal> {
+    let literal = match cell {
+        Data::Empty | Data::Error(_) => return None,
+        Data::Int(value) if *value >= 0 => Literal::UInt64(*value as u64),
+        Data::Int(value) => Literal::String(value.to_string()),
+        Data::Float(value) if value.fract() == 0.0 && *value >= 0.0 && *value <= u64::MAX as f64 => {
+            Literal::UInt64(*value as u64)
+        }
+        Data::Float(value) => Literal::String(value.to_string()),
+        Data::Bool(value) => Literal::String(value.to_string()),
+        Data::String(value) if value.trim().is_empty() => return None,
+        Data::String(value) => Literal::String(value.clone()),
+        Data::DateTimeIso(value) | Data::DurationIso(value) => Literal::String(value.clone()),
+        Data::DateTime(value) => match value.as_datetime() {
+            // dates without a time component are far more common so we omit it when
+            // there isn't one to match the dates found in other sources
+            Some(datetime) if datetime.time() == chrono::NaiveTime::MIN => {
+                Literal::String(datetime.date().format("%Y-%m-%d").to_string())
+            }
+            Some(datetime) => Literal::String(datetime.format("%Y-%m-%dT%H:%M:%S").to_string()),
+            None => Literal::String(value.to_string()),
+        },
+    };
+
+    Some(literal)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::{Dataset, Model};
+    use crate::models::name;
+    use crate::testing::{SYNTHETIC_SCHEMA, load_source};
+
+    fn reader(cells: &[&[Data]]) -> XlsxReader {
+        let mut range = Range::new((0, 0), (cells.len() as u32 - 1, cells[0].len() as u32 - 1));
+        for (row, values) in cells.iter().enumerate() {
+            for (column, value) in values.iter().enumerate() {
+                range.set_value((row as u32, column as u32), value.clone());
+            }
+        }
+
+        let headers = range.headers().unwrap_or_default();
+        XlsxReader {
+            range,
+            headers,
+            row: 1,
+            column: 0,
+        }
+    }
+
+    #[test]
+    fn numeric_cells_resolve_into_string_fields() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = "fields:entity_id mapping:same col:id .
+            fields:scientific_name mapping:same col:name .";
+        load_source(&mut dataset, &Model::Name, "names", mappings, &[]).unwrap();
+
+        let cells: &[&[Data]] = &[
+            &[Data::String("id".to_string()), Data::String("name".to_string())],
+            &[Data::Int(9685), Data::String("Felis catus".to_string())],
+            &[Data::Float(9615.0), Data::String("Canis lupus".to_string())],
+        ];
+        dataset
+            .load_with_prefix(reader(cells), "names", Some("names:"))
+            .unwrap();
+
+        let mut names: Vec<_> = name::get_all(&dataset)
+            .unwrap()
+            .into_iter()
+            .map(|name| (name.entity_id, name.scientific_name))
+            .collect();
+        names.sort();
+
+        assert_eq!(
+            names,
+            vec![
+                ("9615".to_string(), "Canis lupus".to_string()),
+                ("9685".to_string(), "Felis catus".to_string()),
+            ]
+        );
+    }
+}
@@ -1,451 +1,2403 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 
 use sophia::api::MownStr;
 use sophia::api::prelude::*;
 use sophia::api::term::matcher::GraphNameMatcher;
-use sophia::api::term::{BnodeId, GraphName, SimpleTerm};
+use sophia::api::term::{BnodeId, SimpleTerm};
 use tracing::{debug, info, trace, warn};
 
+use crate::dataset::NullTokens;
 use crate::errors::{ResolveError, TransformError};
+pub use crate::matchers::{NamedGraphs, NamedGraphsOrDefault, SingleGraph};
+use crate::models::FromFields;
 use crate::rdf::{
     Condition,
+    ConvertCondition,
     FromCondition,
     IntoIriTerm,
     Literal,
     Map,
     Mapping,
     MappingCondition,
+    PadCondition,
     Rdfs,
+    ReplaceCondition,
+    ScaleCondition,
     ToIri,
     ToIriOwned,
+    TrimCondition,
+    Unit,
     try_from_iri,
 };
 
 
 pub type FieldMap = HashMap<iref::IriBuf, Vec<Map>>;
 pub type ValueMap = HashMap<iref::IriBuf, Vec<Literal>>;
-pub type RecordMap = HashMap<Literal, ValueMap>;
+pub type ReverseMap = HashMap<iref::IriBuf, Vec<iref::IriBuf>>;
 
 pub type ResolvedRecords<R> = HashMap<Literal, Vec<R>>;
 
+
+/// The resolved values of records keyed by their entity id.
+///
+/// This derefs to the underlying map so it can be used like a `HashMap` while also
+/// providing helpers for iterating over records.
+#[derive(Debug, Clone, Default)]
+pub struct RecordMap(pub HashMap<Literal, ValueMap>);
+
+impl RecordMap {
+    pub fn new() -> RecordMap {
+        RecordMap::default()
+    }
+
+    /// Iterate over the entity id and values of every record.
+    pub fn iter_records(&self) -> impl Iterator<Item = (&Literal, &ValueMap)> {
+        self.0.iter()
+    }
+
+    /// Consume the map and iterate over the entity id and values of every record.
+    pub fn into_records(self) -> impl Iterator<Item = (Literal, ValueMap)> {
+        self.0.into_iter()
+    }
+
+    /// The values of the record with the entity id.
+    pub fn get_record(&self, entity_id: &Literal) -> Option<&ValueMap> {
+        self.0.get(entity_id)
+    }
+}
+
+impl std::ops::Deref for RecordMap {
+    type Target = HashMap<Literal, ValueMap>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for RecordMap {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl IntoIterator for RecordMap {
+    type IntoIter = std::collections::hash_map::IntoIter<Literal, ValueMap>;
+    type Item = (Literal, ValueMap);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl FromIterator<(Literal, ValueMap)> for RecordMap {
+    fn from_iter<T: IntoIterator<Item = (Literal, ValueMap)>>(iter: T) -> Self {
+        RecordMap(HashMap::from_iter(iter))
+    }
+}
+
+
+/// Accessors for fields in a `ValueMap` that are expected to have a single value.
+pub trait ValueMapExt {
+    /// The first value of the field if it is a string.
+    fn get_first_string(&self, iri: &iref::Iri) -> Option<&str>;
+
+    /// The first value of the field if it is an unsigned integer.
+    fn get_first_u64(&self, iri: &iref::Iri) -> Option<u64>;
+}
+
+impl ValueMapExt for ValueMap {
+    fn get_first_string(&self, iri: &iref::Iri) -> Option<&str> {
+        self.get(iri)?.first()?.try_as_string()
+    }
+
+    fn get_first_u64(&self, iri: &iref::Iri) -> Option<u64> {
+        self.get(iri)?.first()?.try_as_u64()
+    }
+}
+
+
+/// Whether a field has a mapping in any of the graphs within a scope.
+#[derive(Debug, Clone, serde::Serialize)]
+pub enum FieldCoverage {
+    /// The field is mapped by the listed graphs
+    Mapped(Vec<String>),
+    Unmapped,
+}
+
+/// Options to limit the work done when resolving records.
+#[derive(Debug, Clone, Default)]
+pub struct ResolveOptions {
+    /// Stop collecting records once this many have been found. The records kept
+    /// are the first ones found which is not necessarily in index order.
+    pub max_records: Option<usize>,
+    /// Fail with `ResolveError::ConflictingMapping` when a source field is copied into more
+    /// than one field, or with `ResolveError::DuplicateEntityId` when records share an entity id,
+    /// instead of only logging a warning.
+    pub strict: bool,
+    /// What to do with a source field that is matched but isn't mapped by the fields
+    /// being resolved. This can happen when two scopes map the same source field.
+    pub on_unknown_predicate: UnknownPredicate,
+    /// Also read records from the default graph rather than only the named graphs of the scope.
+    pub include_default: bool,
+    /// Only collect the records with these subjects rather than every record in the scope.
+    /// Records linked with `mapping:from` are always collected from the whole linked scope.
+    pub subjects: Option<Vec<Literal>>,
+}
+
+
+/// The handling of source fields that don't have a mapping when collecting records.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnknownPredicate {
+    /// Log the source field the first time it is found and leave it out of the records.
+    #[default]
+    Skip,
+    /// Fail with `ResolveError::IriNotFound`.
+    Error,
+}
+
+
+/// An entity id that more than one record in the source data resolves to.
+///
+/// This usually means the entity id is hashed from a source field that isn't unique, and
+/// unless it is caught the records are merged into one with the fields of both.
+#[derive(Debug, Clone)]
+pub struct EntityCollision {
+    pub entity_id: Literal,
+    /// The subjects of the records in the source data
+    pub subjects: Vec<Literal>,
+    /// The source values that the entity id was derived from for each subject
+    pub inputs: Vec<ValueMap>,
+}
+
+
+/// Everything that went into resolving the record of a single entity.
+///
+/// This is meant for tracking down why a record has an unexpected value, so everything
+/// is kept as strings that can be dumped as JSON.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct RecordInspection {
+    pub entity_id: String,
+    /// The subjects of the records in the source data that resolve to the entity id
+    pub subjects: Vec<String>,
+    /// The quads loaded for the subjects
+    pub raw: Vec<RawQuad>,
+    /// The mappings of the resolved fields that read from any of the loaded source fields
+    pub mappings: Vec<AppliedMapping>,
+    /// The resolved values of the fields, keyed by the field IRI
+    pub fields: std::collections::BTreeMap<String, Vec<String>>,
+}
+
+/// A quad loaded from the source data.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RawQuad {
+    pub subject: String,
+    pub predicate: String,
+    pub value: String,
+    pub graph: String,
+}
+
+/// A mapping that was used when resolving a field.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AppliedMapping {
+    pub field: String,
+    pub mapping: String,
+}
+
+
+/// The values of every record in a scope indexed by subject and then predicate.
+///
+/// Building the index scans the scope once so that resolving different fields from the
+/// same scope afterwards is a lookup rather than another scan of the dataset.
+#[derive(Debug, Default)]
+pub struct RecordIndex {
+    records: HashMap<Literal, ValueMap>,
+}
+
+impl RecordIndex {
+    /// Index the loaded data in the named graphs of the scope.
+    ///
+    /// Only quads with a literal subject and object are indexed as those are the records
+    /// loaded from a source. Mappings defined in the same graphs are skipped. The default
+    /// graph is only indexed as well when `include_default` is set.
+    pub fn build(
+        dataset: &super::dataset::Dataset,
+        scope: &[&iref::Iri],
+        include_default: bool,
+    ) -> Result<RecordIndex, TransformError> {
+        let scope: Vec<&str> = scope.iter().map(|s| s.as_str()).collect();
+        match include_default {
+            true => RecordIndex::build_matching(dataset, NamedGraphsOrDefault(&scope)),
+            false => RecordIndex::build_matching(dataset, NamedGraphs(&scope)),
+        }
+    }
+
+    fn build_matching<G: GraphNameMatcher>(
+        dataset: &super::dataset::Dataset,
+        graphs: G,
+    ) -> Result<RecordIndex, TransformError> {
+        let mut records: HashMap<Literal, ValueMap> = HashMap::new();
+
+        for quad in dataset
+            .source
+            .quads_matching(TermKind::Literal, Any, TermKind::Literal, graphs)
+        {
+            let (_g, [s, p, o]) = quad?;
+
+            let (
+                SimpleTerm::LiteralDatatype(subject, _),
+                SimpleTerm::Iri(predicate),
+                SimpleTerm::LiteralDatatype(value, _),
+            ) = (s, p, o)
+            else {
+                continue;
+            };
+
+            records
+                .entry(Literal::String(subject.to_string()))
+                .or_default()
+                .entry(predicate.to_iri_owned()?)
+                .or_default()
+                .push(Literal::String(value.to_string()));
+        }
+
+        Ok(RecordIndex { records })
+    }
+
+    /// The values of the record keyed by the source field IRI.
+    pub fn get(&self, subject: &Literal) -> Option<&ValueMap> {
+        self.records.get(subject)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Literal, &ValueMap)> {
+        self.records.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+
+/// Resolves the fields of records by applying the mappings defined in the source graphs.
+///
+/// The records of a scope are indexed the first time they are needed and reused for any
+/// later call with the same scope. Because the resolver borrows the dataset the index can
+/// never become stale, loading more data requires a new resolver.
 pub struct Resolver<'a> {
     dataset: &'a super::dataset::Dataset,
+    indexes: RefCell<HashMap<(Vec<String>, bool), Rc<RecordIndex>>>,
+    /// Unknown predicates that were already logged so they are only reported once
+    skipped_predicates: RefCell<HashSet<iref::IriBuf>>,
+    /// The options for the methods that don't take them instead of deriving them from the dataset
+    options: Option<ResolveOptions>,
 }
 
 impl Resolver<'_> {
     pub fn new(dataset: &super::dataset::Dataset) -> Resolver<'_> {
-        Resolver { dataset }
+        Resolver {
+            dataset,
+            indexes: RefCell::new(HashMap::new()),
+            skipped_predicates: RefCell::new(HashSet::new()),
+            options: None,
+        }
+    }
+
+    /// Use the options when resolving with any of the methods that don't take them.
+    ///
+    /// By default the options come from the `strict` setting of the dataset, which makes
+    /// unknown predicates an error only when the dataset is strict.
+    pub fn with_options(mut self, options: ResolveOptions) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    /// Show the source data, mappings, and resolved values behind the record of an entity.
+    ///
+    /// Entity ids are often hashed or combined from other fields so the key is compared with
+    /// the resolved entity id of every record rather than looked for in the source data. All
+    /// records that resolve to the key are included as they are merged into one entity.
+    #[tracing::instrument(skip_all)]
+    pub fn inspect<S: AsRef<iref::Iri>>(
+        &self,
+        entity_key: &Literal,
+        fields: &[&iref::Iri],
+        scope: &[S],
+    ) -> Result<RecordInspection, TransformError> {
+        let scope: Vec<&iref::Iri> = scope.iter().map(|s| s.as_ref()).collect();
+        let entity_id: &iref::Iri = crate::rdf::Name::EntityId.as_ref();
+        let key = entity_key.lexical();
+        let options = self.default_options();
+
+        let ids = self.resolve_values_where(&[entity_id], &scope, &[], &options)?;
+        let mut subjects: Vec<String> = ids
+            .iter_records()
+            .filter(|(_subject, record)| {
+                record
+                    .get(entity_id)
+                    .into_iter()
+                    .flatten()
+                    .any(|id| id.lexical() == key)
+            })
+            .map(|(subject, _record)| subject.lexical())
+            .collect();
+        subjects.sort();
+
+        // the index merges the graphs of a scope so the quads are read directly to keep the graph
+        let graphs: Vec<&str> = scope.iter().map(|iri| iri.as_str()).collect();
+        let mut raw = Vec::new();
+        for quad in self
+            .dataset
+            .source
+            .quads_matching(TermKind::Literal, Any, TermKind::Literal, NamedGraphs(&graphs))
+        {
+            let (g, [s, p, o]) = quad?;
+            let (
+                SimpleTerm::LiteralDatatype(subject, _),
+                SimpleTerm::Iri(predicate),
+                SimpleTerm::LiteralDatatype(value, _),
+            ) = (s, p, o)
+            else {
+                continue;
+            };
+
+            let subject = subject.to_string();
+            if subjects.contains(&subject) {
+                raw.push(RawQuad {
+                    subject,
+                    predicate: predicate.to_string(),
+                    value: value.to_string(),
+                    graph: g.map(crate::dataset::stringify_term).unwrap_or_default(),
+                });
+            }
+        }
+        raw.sort_by(|a, b| (&a.subject, &a.graph, &a.predicate).cmp(&(&b.subject, &b.graph, &b.predicate)));
+
+        let predicates: HashSet<&str> = raw.iter().map(|quad| quad.predicate.as_str()).collect();
+        let mut mappings = Vec::new();
+        for (field, maps) in self.field_map(fields, &scope)?.iter() {
+            for field_map in maps {
+                if map_sources(field_map)
+                    .iter()
+                    .any(|iri| predicates.contains(iri.as_str()))
+                {
+                    mappings.push(AppliedMapping {
+                        field: field.to_string(),
+                        mapping: format!("{field_map:?}"),
+                    });
+                }
+            }
+        }
+        mappings.sort_by(|a, b| (&a.field, &a.mapping).cmp(&(&b.field, &b.mapping)));
+
+        let records = self.resolve_values_where(fields, &scope, &[], &options)?;
+        let mut resolved = std::collections::BTreeMap::<String, Vec<String>>::new();
+        for (subject, record) in records.iter_records() {
+            if subjects.contains(&subject.lexical()) {
+                for (iri, values) in record {
+                    resolved
+                        .entry(iri.to_string())
+                        .or_default()
+                        .extend(values.iter().map(|value| value.lexical()));
+                }
+            }
+        }
+
+        Ok(RecordInspection {
+            entity_id: key,
+            subjects,
+            raw,
+            mappings,
+            fields: resolved,
+        })
+    }
+
+    /// Get the record index for the named graphs of the scope, building it if it hasn't been built yet.
+    pub fn index<S: AsRef<iref::Iri>>(&self, scope: &[S]) -> Result<Rc<RecordIndex>, TransformError> {
+        self.index_graphs(scope, false)
+    }
+
+    /// Get the record index for the scope along with the default graph if `include_default` is set.
+    fn index_graphs<S: AsRef<iref::Iri>>(
+        &self,
+        scope: &[S],
+        include_default: bool,
+    ) -> Result<Rc<RecordIndex>, TransformError> {
+        let scope: Vec<&iref::Iri> = scope.iter().map(|s| s.as_ref()).collect();
+
+        // the order of the scope doesn't change the records in it
+        let mut graphs: Vec<String> = scope.iter().map(|s| s.to_string()).collect();
+        graphs.sort();
+        graphs.dedup();
+        let key = (graphs, include_default);
+
+        if let Some(index) = self.indexes.borrow().get(&key) {
+            return Ok(index.clone());
+        }
+
+        debug!(?scope, include_default, "indexing records");
+        let index = Rc::new(RecordIndex::build(self.dataset, &scope, include_default)?);
+        self.indexes.borrow_mut().insert(key, index.clone());
+        Ok(index)
+    }
+
+    /// The options used when none are specified, either those set with `with_options` or
+    /// ones that follow the strictness of the dataset.
+    fn default_options(&self) -> ResolveOptions {
+        if let Some(options) = &self.options {
+            return options.clone();
+        }
+
+        ResolveOptions {
+            strict: self.dataset.strict,
+            on_unknown_predicate: match self.dataset.strict {
+                true => UnknownPredicate::Error,
+                false => UnknownPredicate::Skip,
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Find the entity ids that more than one record in the scope resolves to.
+    ///
+    /// Along with the colliding records this includes the source values that each entity id
+    /// was derived from, which is usually enough to find the field that isn't unique.
+    #[tracing::instrument(skip_all)]
+    pub fn check_unique_entities<S: AsRef<iref::Iri>>(
+        &self,
+        scope: &[S],
+    ) -> Result<Vec<EntityCollision>, TransformError> {
+        let scope: Vec<&iref::Iri> = scope.iter().map(|s| s.as_ref()).collect();
+        let entity_id: &iref::Iri = crate::rdf::Name::EntityId.as_ref();
+
+        let options = ResolveOptions::default();
+        let values = self.resolve_values_where(&[entity_id], &scope, &[], &options)?;
+
+        // the source fields that the entity id is derived from
+        let map = self.field_map(&[entity_id], &scope)?;
+        let terms = resolve_field_terms(&vec![entity_id], &map)?;
+        let sources: Vec<&str> = terms
+            .iter()
+            .filter_map(|term| match term {
+                SimpleTerm::Iri(iri) => Some(iri.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        let index = self.index(&scope)?;
+        let mut collisions = Vec::new();
+
+        for (entity_id, subjects) in shared_entity_ids(&values) {
+            let inputs = subjects
+                .iter()
+                .map(|subject| {
+                    index
+                        .get(subject)
+                        .map(|record| {
+                            record
+                                .iter()
+                                .filter(|(predicate, _values)| sources.contains(&predicate.as_str()))
+                                .map(|(predicate, values)| (predicate.clone(), values.clone()))
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                })
+                .collect();
+
+            collisions.push(EntityCollision {
+                entity_id,
+                subjects,
+                inputs,
+            });
+        }
+
+        Ok(collisions)
     }
 
     /// Load all records within the specified scope and resolve the specified fields
     #[tracing::instrument(skip_all)]
-    pub fn resolve<'a, T, R>(&self, fields: &'a [T], scope: &[&iref::Iri]) -> Result<ResolvedRecords<R>, TransformError>
+    pub fn resolve<'a, T, R, S>(&self, fields: &'a [T], scope: &[S]) -> Result<ResolvedRecords<R>, TransformError>
+    where
+        T: Into<&'a iref::Iri> + TryFrom<&'a iref::Iri> + std::fmt::Debug,
+        R: From<(T, Literal)> + Clone,
+        &'a iref::Iri: From<&'a T>,
+        S: AsRef<iref::Iri>,
+    {
+        self.resolve_with_options(fields, scope, &self.default_options())
+    }
+
+    /// Load records within the specified scope and resolve the specified fields, limited by the options.
+    #[tracing::instrument(skip_all)]
+    pub fn resolve_with_options<'a, T, R, S>(
+        &self,
+        fields: &'a [T],
+        scope: &[S],
+        options: &ResolveOptions,
+    ) -> Result<ResolvedRecords<R>, TransformError>
+    where
+        T: Into<&'a iref::Iri> + TryFrom<&'a iref::Iri> + std::fmt::Debug,
+        R: From<(T, Literal)> + Clone,
+        &'a iref::Iri: From<&'a T>,
+        S: AsRef<iref::Iri>,
+    {
+        self.resolve_typed(fields, scope, &[], options)
+    }
+
+    /// Load records within the specified scope that pass the conditions and resolve the specified fields.
+    ///
+    /// The conditions are checked against the resolved values of the records in the same way
+    /// as `records_with_conditions`, so a field used in a condition must be mapped in the scope.
+    #[tracing::instrument(skip_all)]
+    pub fn resolve_with_conditions<'a, T, R, S>(
+        &self,
+        fields: &'a [T],
+        scope: &[S],
+        conditions: &[(&iref::Iri, Condition)],
+    ) -> Result<ResolvedRecords<R>, TransformError>
+    where
+        T: Into<&'a iref::Iri> + TryFrom<&'a iref::Iri> + std::fmt::Debug,
+        R: From<(T, Literal)> + Clone,
+        &'a iref::Iri: From<&'a T>,
+        S: AsRef<iref::Iri>,
+    {
+        self.resolve_typed(fields, scope, conditions, &self.default_options())
+    }
+
+    fn resolve_typed<'a, T, R, S>(
+        &self,
+        fields: &'a [T],
+        scope: &[S],
+        conditions: &[(&iref::Iri, Condition)],
+        options: &ResolveOptions,
+    ) -> Result<ResolvedRecords<R>, TransformError>
     where
         T: Into<&'a iref::Iri> + TryFrom<&'a iref::Iri> + std::fmt::Debug,
         R: From<(T, Literal)> + Clone,
         &'a iref::Iri: From<&'a T>,
+        S: AsRef<iref::Iri>,
     {
+        let scope: Vec<&iref::Iri> = scope.iter().map(|s| s.as_ref()).collect();
         info!(?fields, ?scope, "Resolving fields");
 
         // get the iri for all fields to resolve
         let field_iris: Vec<&iref::Iri> = fields.iter().map(|f| f.into()).collect();
-        let map = self.field_map(&field_iris, scope)?;
+        let values = self.resolve_values_where(&field_iris, &scope, conditions, options)?;
+
+        let entity_id: &iref::Iri = crate::rdf::Name::EntityId.as_ref();
+        if field_iris.contains(&entity_id) {
+            for (entity_id, subjects) in shared_entity_ids(&values) {
+                match options.strict {
+                    true => {
+                        return Err(ResolveError::DuplicateEntityId {
+                            entity_id: entity_id.lexical(),
+                            subjects: subjects.iter().map(|subject| subject.lexical()).collect(),
+                        }
+                        .into());
+                    }
+                    false => warn!(entity_id = %entity_id.lexical(), ?subjects, "duplicate entity id"),
+                }
+            }
+        }
+
+        let mut data: ResolvedRecords<R> = HashMap::new();
+        for (entity_id, record) in values.into_records() {
+            let fields = data.entry(entity_id).or_default();
+
+            for field_iri in field_iris.iter() {
+                for value in record.get(*field_iri).into_iter().flatten() {
+                    let mapped_from =
+                        T::try_from(field_iri).map_err(|_| TransformError::InvalidMappingIri(field_iri.to_string()))?;
+                    fields.push((mapped_from, value.clone()).into());
+                }
+            }
+        }
+
+        Ok(data)
+    }
+
+    /// Load all records within each of the specified scopes and merge the resolved fields.
+    ///
+    /// Each scope is resolved on its own, so sources from different systems that map to the
+    /// same model don't have to share a scope. A record resolved in more than one scope is
+    /// logged as a collision and the fields from every scope are kept for it.
+    #[tracing::instrument(skip_all)]
+    pub fn resolve_union<'a, T, R, S>(
+        &self,
+        fields: &'a [T],
+        scopes: &[&[S]],
+    ) -> Result<ResolvedRecords<R>, TransformError>
+    where
+        T: Into<&'a iref::Iri> + TryFrom<&'a iref::Iri> + std::fmt::Debug,
+        R: From<(T, Literal)> + Clone,
+        &'a iref::Iri: From<&'a T>,
+        S: AsRef<iref::Iri>,
+    {
+        let mut data: ResolvedRecords<R> = HashMap::new();
+
+        for (idx, scope) in scopes.iter().enumerate() {
+            for (entity_id, fields) in self.resolve(fields, scope)? {
+                match data.get_mut(&entity_id) {
+                    Some(existing) => {
+                        warn!(entity_id = %entity_id.lexical(), scope = idx, "entity id resolved in multiple scopes");
+                        existing.extend(fields);
+                    }
+                    None => {
+                        data.insert(entity_id, fields);
+                    }
+                }
+            }
+        }
+
+        Ok(data)
+    }
+
+    /// Load all records within the specified scope and resolve the specified fields in a deterministic order.
+    ///
+    /// Records are sorted by their resolved entity id with `entity_order` so that repeated runs
+    /// over the same data always produce the same ordering. Records without an entity id are
+    /// sorted by their subject instead.
+    #[tracing::instrument(skip_all)]
+    pub fn resolve_sorted<'a, T, R, S>(
+        &self,
+        fields: &'a [T],
+        scope: &[S],
+    ) -> Result<Vec<(Literal, Vec<R>)>, TransformError>
+    where
+        T: Into<&'a iref::Iri> + TryFrom<&'a iref::Iri> + std::fmt::Debug,
+        R: From<(T, Literal)> + Clone,
+        &'a iref::Iri: From<&'a T>,
+        S: AsRef<iref::Iri>,
+    {
+        let options = self.default_options();
+        let graphs: Vec<&iref::Iri> = scope.iter().map(|s| s.as_ref()).collect();
+
+        let subjects = self.sorted_subjects(&graphs, &options)?;
+        let mut data: ResolvedRecords<R> = self.resolve_typed(fields, scope, &[], &options)?;
+        Ok(subjects
+            .iter()
+            .filter_map(|subject| data.remove_entry(subject))
+            .collect())
+    }
+
+    /// Load all records within the specified scope and pass them to `f` in chunks of `chunk_size`.
+    ///
+    /// The records are in the same order as `resolve_sorted` but only one chunk is resolved at a
+    /// time, so the resolved fields held in memory are proportional to the chunk size rather than
+    /// the number of records. The record index of the scope and the entity ids used to order the
+    /// records are still built for the whole scope up front. Because each chunk is resolved on
+    /// its own, records sharing an entity id are only reported when they land in the same chunk.
+    #[tracing::instrument(skip_all)]
+    pub fn resolve_chunked<'a, T, R, S, F>(
+        &self,
+        fields: &'a [T],
+        scope: &[S],
+        chunk_size: usize,
+        mut f: F,
+    ) -> Result<(), TransformError>
+    where
+        T: Into<&'a iref::Iri> + TryFrom<&'a iref::Iri> + std::fmt::Debug,
+        R: From<(T, Literal)> + Clone,
+        &'a iref::Iri: From<&'a T>,
+        S: AsRef<iref::Iri>,
+        F: FnMut(Vec<(Literal, Vec<R>)>) -> Result<(), TransformError>,
+    {
+        let options = self.default_options();
+        let graphs: Vec<&iref::Iri> = scope.iter().map(|s| s.as_ref()).collect();
+        let subjects = self.sorted_subjects(&graphs, &options)?;
+
+        for chunk in subjects.chunks(chunk_size.max(1)) {
+            let options = ResolveOptions {
+                subjects: Some(chunk.to_vec()),
+                ..options.clone()
+            };
+            let mut data: ResolvedRecords<R> = self.resolve_typed(fields, scope, &[], &options)?;
+            let records: Vec<(Literal, Vec<R>)> =
+                chunk.iter().filter_map(|subject| data.remove_entry(subject)).collect();
+
+            if !records.is_empty() {
+                f(records)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The subjects of every record in the scope ordered by their resolved entity id.
+    ///
+    /// Only the entity id and the fields it is combined or hashed from are resolved, so this is
+    /// much cheaper than resolving the records when they are going to be resolved in parts.
+    fn sorted_subjects(&self, scope: &[&iref::Iri], options: &ResolveOptions) -> Result<Vec<Literal>, TransformError> {
+        let entity_id: &iref::Iri = crate::rdf::Name::EntityId.as_ref();
+
+        // list mappings read from other fields which have to be resolved along with the entity id
+        let mut lookup = vec![entity_id.to_owned()];
+        for field_map in self.field_map(&[entity_id], scope)?.values().flatten() {
+            if let Map::HashFirst(iris) | Map::Coalesce(iris) | Map::Combines(iris) = field_map {
+                lookup.extend(iris.iter().cloned());
+            }
+        }
+        let lookup: Vec<&iref::Iri> = lookup.iter().map(|iri| iri.as_iri()).collect();
+
+        let ids = self.resolve_values_where(&lookup, scope, &[], options)?;
+        let index = self.index_graphs(scope, options.include_default)?;
+
+        let mut subjects: Vec<((bool, u64, String), Literal)> = index
+            .iter()
+            .map(|(subject, _values)| {
+                let id = ids
+                    .get_record(subject)
+                    .and_then(|record| record.get(entity_id))
+                    .and_then(|ids| ids.first())
+                    .unwrap_or(subject);
+                (entity_order(id), subject.clone())
+            })
+            .collect();
+
+        // subjects break ties between records that share an entity id
+        subjects.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.lexical().cmp(&b.1.lexical())));
+        Ok(subjects.into_iter().map(|(_id, subject)| subject).collect())
+    }
+
+    /// Load all records within the specified scope and serialize each one as a model.
+    ///
+    /// The resolved fields of a record are assigned to the model with `FromFields` so only
+    /// models with an `impl_model!` can be used. Records are in the same order as `resolve_sorted`.
+    #[tracing::instrument(skip_all)]
+    pub fn resolve_as_json<'a, T, R, M, S>(
+        &self,
+        fields: &'a [T],
+        scope: &[S],
+    ) -> Result<Vec<serde_json::Value>, TransformError>
+    where
+        T: Into<&'a iref::Iri> + TryFrom<&'a iref::Iri> + std::fmt::Debug,
+        R: From<(T, Literal)> + Clone,
+        &'a iref::Iri: From<&'a T>,
+        S: AsRef<iref::Iri>,
+        M: FromFields<R> + serde::Serialize,
+    {
+        let mut values = Vec::new();
+        for (_entity_id, fields) in self.resolve_sorted(fields, scope)? {
+            values.push(serde_json::to_value(M::from_fields(fields))?);
+        }
+        Ok(values)
+    }
+
+    /// Load the records within the specified scope with an entity id after the cursor and resolve the fields.
+    ///
+    /// Entity ids are compared in the same order as `resolve_sorted` so this only picks up the new
+    /// records of a source when its ids are assigned in increasing order. The caller keeps the greatest entity
+    /// id it has processed to use as the next cursor. Without a cursor every record is resolved,
+    /// otherwise records without an entity id are left out as they can't be placed after it.
+    #[tracing::instrument(skip_all)]
+    pub fn resolve_incremental<'a, T, R, S>(
+        &self,
+        fields: &'a [T],
+        scope: &[S],
+        after_entity_id: Option<&Literal>,
+    ) -> Result<ResolvedRecords<R>, TransformError>
+    where
+        T: Into<&'a iref::Iri> + TryFrom<&'a iref::Iri> + std::fmt::Debug,
+        R: From<(T, Literal)> + Clone,
+        &'a iref::Iri: From<&'a T>,
+        S: AsRef<iref::Iri>,
+    {
+        let scope: Vec<&iref::Iri> = scope.iter().map(|s| s.as_ref()).collect();
+        let cursor = after_entity_id.map(entity_order);
+        info!(?fields, ?scope, ?cursor, "Resolving fields after cursor");
+
+        // the entity id is always needed to compare with the cursor even if it wasn't asked for
+        let entity_id: &iref::Iri = crate::rdf::Name::EntityId.as_ref();
+        let field_iris: Vec<&iref::Iri> = fields.iter().map(|f| f.into()).collect();
+        let mut lookup = field_iris.clone();
+        if !lookup.contains(&entity_id) {
+            lookup.push(entity_id);
+        }
+
+        let values = self.resolve_values_where(&lookup, &scope, &[], &self.default_options())?;
+
+        let mut data: ResolvedRecords<R> = HashMap::new();
+        for (subject, record) in values.into_records() {
+            if let Some(cursor) = &cursor {
+                let after = record
+                    .get(entity_id)
+                    .into_iter()
+                    .flatten()
+                    .any(|id| entity_order(id) > *cursor);
+                if !after {
+                    continue;
+                }
+            }
+
+            let fields = data.entry(subject).or_default();
+            for field_iri in field_iris.iter() {
+                for value in record.get(*field_iri).into_iter().flatten() {
+                    let mapped_from =
+                        T::try_from(field_iri).map_err(|_| TransformError::InvalidMappingIri(field_iri.to_string()))?;
+                    fields.push((mapped_from, value.clone()).into());
+                }
+            }
+        }
+
+        Ok(data)
+    }
+
+    /// Load all records within the specified scope and build one model for each entity id.
+    ///
+    /// Records that share an entity id are merged one field at a time, with the values of
+    /// the first record that has the field winning. Records are merged in the order of their
+    /// subject so the same values win between runs. A record with different values for a field
+    /// that was already merged is logged as a conflict. Records without an entity id are keyed
+    /// by their subject instead.
+    #[tracing::instrument(skip_all)]
+    pub fn resolve_first<'a, T, R, M, S>(
+        &self,
+        fields: &'a [T],
+        scope: &[S],
+    ) -> Result<HashMap<Literal, M>, TransformError>
+    where
+        T: Into<&'a iref::Iri> + TryFrom<&'a iref::Iri> + std::fmt::Debug,
+        R: From<(T, Literal)> + Clone,
+        &'a iref::Iri: From<&'a T>,
+        S: AsRef<iref::Iri>,
+        M: FromFields<R>,
+    {
+        use std::collections::hash_map::Entry;
+
+        let scope: Vec<&iref::Iri> = scope.iter().map(|s| s.as_ref()).collect();
+        info!(?fields, ?scope, "Resolving first fields");
+
+        let field_iris: Vec<&iref::Iri> = fields.iter().map(|f| f.into()).collect();
+        let values = self.resolve_values_where(&field_iris, &scope, &[], &self.default_options())?;
+
+        let mut records: Vec<(Literal, ValueMap)> = values.into_records().collect();
+        records.sort_by_cached_key(|(subject, _record)| subject.lexical());
+
+        let entity_id_iri: &iref::Iri = crate::rdf::Name::EntityId.as_ref();
+        let mut merged: HashMap<Literal, ValueMap> = HashMap::new();
+
+        for (subject, record) in records {
+            let entity_id = record
+                .get(entity_id_iri)
+                .and_then(|ids| ids.first())
+                .cloned()
+                .unwrap_or(subject);
+
+            let entity = merged.entry(entity_id.clone()).or_default();
+            for (field_iri, values) in record {
+                match entity.entry(field_iri) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(values);
+                    }
+                    Entry::Occupied(entry) => {
+                        if entry.get() != &values {
+                            warn!(
+                                entity_id = %entity_id.lexical(),
+                                field = %entry.key(),
+                                kept = ?entry.get(),
+                                conflicting = ?values,
+                                "conflicting values for a duplicate entity id"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut data = HashMap::new();
+        for (entity_id, record) in merged {
+            let mut fields = Vec::new();
+            for field_iri in field_iris.iter() {
+                for value in record.get(*field_iri).into_iter().flatten() {
+                    let mapped_from =
+                        T::try_from(field_iri).map_err(|_| TransformError::InvalidMappingIri(field_iri.to_string()))?;
+                    fields.push((mapped_from, value.clone()).into());
+                }
+            }
+            data.insert(entity_id, M::from_fields(fields));
+        }
+
+        Ok(data)
+    }
+
+    /// Load records within the specified scope and resolve the values of the specified fields.
+    ///
+    /// This is the same as `resolve_with_options` except that the values are left keyed by
+    /// the field IRI rather than converted into a typed field.
+    #[tracing::instrument(skip_all)]
+    pub fn resolve_values<S: AsRef<iref::Iri>>(
+        &self,
+        field_iris: &[&iref::Iri],
+        scope: &[S],
+        options: &ResolveOptions,
+    ) -> Result<RecordMap, TransformError> {
+        let scope: Vec<&iref::Iri> = scope.iter().map(|s| s.as_ref()).collect();
+        self.resolve_values_where(field_iris, &scope, &[], options)
+    }
+
+    fn resolve_values_where(
+        &self,
+        field_iris: &[&iref::Iri],
+        scope: &[&iref::Iri],
+        conditions: &[(&iref::Iri, Condition)],
+        options: &ResolveOptions,
+    ) -> Result<RecordMap, TransformError> {
+        let map = self.field_map(field_iris, scope)?;
+
+        let records = self.records_inner(field_iris, scope, conditions, options, &mut HashSet::new())?;
+
+        // the records as they were loaded, keyed by the source field rather than the mapped field
+        let index = self.index_graphs(scope, options.include_default)?;
+
+        let mut data = RecordMap::new();
+
+        // get the transform plan for the field and add that to the final result
+        for &field_iri in field_iris {
+            let Some(mapping) = map.get(field_iri)
+            else {
+                warn!("Field mapping not found: {field_iri}");
+                continue;
+            };
+
+            let transformed = transformed_sources(mapping);
+
+            for (entity_id, fields) in records.iter_records() {
+                let loaded = index.get(entity_id);
+
+                // the values of a source that is only transformed into the field shouldn't
+                // also be copied into the field as is
+                let untransformed = match transformed.is_empty() {
+                    true => None,
+                    false => Some(untransformed_values(fields.get(field_iri), loaded, &transformed)),
+                };
+                let copied = untransformed.as_ref().or(fields.get(field_iri));
+
+                for field_map in mapping {
+                    let result = match field_map {
+                        Map::Same(_iri) => copied,
+                        Map::Hash(_iri) => copied,
+                        Map::HashFirst(iris) | Map::Coalesce(iris) => {
+                            Some(&coalesce_values(fields, iris, &self.dataset.null_tokens))
+                        }
+                        Map::Combines(iris) => {
+                            let mut to_combine: Vec<String> = Vec::new();
+                            for iri in iris {
+                                // a field can be mapped to multiple source fields so we
+                                // need to handle that scenario here. this can lead to pretty
+                                // strange bugs due to the order being random so if there is
+                                // more than one value we fail with an ambiguity error.
+                                //
+                                // the reason why this matter for Combines is because we can't
+                                // tell which value is from which graph leaving us no possible way
+                                // to combine values isolated within their graphs
+                                if let Some(values) = fields.get(iri) {
+                                    let present: Vec<String> = values
+                                        .iter()
+                                        .filter_map(|v| match v {
+                                            // only return strings with actual data
+                                            Literal::String(val) => match self.dataset.null_tokens.is_null(val) {
+                                                true => None,
+                                                false => Some(val.clone()),
+                                            },
+                                            Literal::UInt64(val) => Some(val.to_string()),
+                                        })
+                                        .collect();
+
+                                    let value = if present.len() > 1 {
+                                        Err(ResolveError::AmbiguousMapping(iri.clone(), values.clone()))
+                                    }
+                                    else {
+                                        Ok(present.first().cloned())
+                                    }?;
+
+                                    if let Some(val) = value {
+                                        to_combine.push(val);
+                                    }
+                                }
+                            }
+
+                            Some(&vec![Literal::String(to_combine.join(" "))])
+                        }
+                        Map::When(_iri, _condition) => None,
+                        Map::From { .. } => None,
+                        Map::Convert { source, from, to } => {
+                            Some(&convert_values(field_iri, source_values(loaded, source), from, to))
+                        }
+                        Map::Scale { source, scale } => {
+                            Some(&scale_values(field_iri, source_values(loaded, source), *scale))
+                        }
+                        Map::Replace { source, .. } => {
+                            // all replacements of a source are applied together to allow them to
+                            // be composed, so only the first replace mapping of a source produces values
+                            let first = mapping
+                                .iter()
+                                .find(|m| matches!(m, Map::Replace { source: replaced, .. } if replaced == source));
+                            match first.is_some_and(|m| std::ptr::eq(m, field_map)) {
+                                true => {
+                                    let values = source_values(loaded, source);
+                                    Some(&replace_values(values, mapping, source))
+                                }
+                                false => None,
+                            }
+                        }
+                        Map::PadLeft { source, width, fill } => {
+                            Some(&pad_values(source_values(loaded, source), *width, *fill, Padding::Left))
+                        }
+                        Map::PadRight { source, width, fill } => {
+                            Some(&pad_values(source_values(loaded, source), *width, *fill, Padding::Right))
+                        }
+                        Map::Trim(iri) => Some(&trim_values(source_values(loaded, iri), char::is_whitespace)),
+                        Map::TrimChars { source, chars } => {
+                            Some(&trim_values(source_values(loaded, source), |c: char| chars.contains(c)))
+                        }
+                        Map::Uppercase(_iri) | Map::Lowercase(_iri) => {
+                            // the case is applied to all values for the field below so if there
+                            // is already a same mapping we don't want to duplicate the values
+                            match mapping.iter().any(|m| matches!(m, Map::Same(_))) {
+                                true => None,
+                                false => copied,
+                            }
+                        }
+                    };
+
+
+                    // add all the fields even if there are multiple of the same.
+                    // uniqueness or disambiguation is a job outside this function
+                    if let Some(result) = result {
+                        for value in result {
+                            data.entry(entity_id.clone())
+                                .or_default()
+                                .entry(field_iri.to_owned())
+                                .or_default()
+                                .push(apply_case(value, mapping));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(data)
+    }
+
+    /// Get records containing the specified fields in the specified models.
+    ///
+    /// Records are read from the named graphs of the scope and from the default graph as well
+    /// when `include_default` is set. Loaded data always lands in the graph of its source so
+    /// the default graph is only needed for quads that were inserted into the dataset directly.
+    #[tracing::instrument(skip_all)]
+    pub fn records<S: AsRef<iref::Iri>>(
+        &self,
+        fields: &[&iref::Iri],
+        scope: &[S],
+        include_default: bool,
+    ) -> Result<RecordMap, TransformError> {
+        let scope: Vec<&iref::Iri> = scope.iter().map(|s| s.as_ref()).collect();
+        let options = ResolveOptions {
+            include_default,
+            ..self.default_options()
+        };
+        self.records_inner(fields, &scope, &[], &options, &mut HashSet::new())
+    }
+
+    /// Get records containing the specified fields in the specified models that also pass the conditions.
+    ///
+    /// The conditions are added to any conditions defined in the schema with `mapping:when` and
+    /// a record must pass all of them to be included.
+    #[tracing::instrument(skip_all)]
+    pub fn records_with_conditions<S: AsRef<iref::Iri>>(
+        &self,
+        fields: &[&iref::Iri],
+        scope: &[S],
+        conditions: &[(&iref::Iri, Condition)],
+    ) -> Result<RecordMap, TransformError> {
+        let scope: Vec<&iref::Iri> = scope.iter().map(|s| s.as_ref()).collect();
+        self.records_inner(fields, &scope, conditions, &self.default_options(), &mut HashSet::new())
+    }
+
+    /// Collect the records for the fields, following any `mapping:from` links into other models.
+    ///
+    /// The visited graphs are the models currently being linked further up the call stack. A
+    /// link back into one of them would recurse forever so it is reported as an error instead.
+    fn records_inner(
+        &self,
+        fields: &[&iref::Iri],
+        scope: &[&iref::Iri],
+        conditions: &[(&iref::Iri, Condition)],
+        options: &ResolveOptions,
+        visited_graphs: &mut HashSet<iref::IriBuf>,
+    ) -> Result<RecordMap, TransformError> {
+        let max_records = options.max_records;
+        let mut map = self.field_map(fields, scope)?;
+
+        // the via fields of a link are needed to join the linked records. when following a chain
+        // of links only the linked field is asked for so the via fields are resolved as well
+        let mut via_fields: Vec<iref::IriBuf> = Vec::new();
+        for field_map in map.values().flatten() {
+            if let Map::From { via, .. } = field_map {
+                for iri in via {
+                    if !fields.contains(&iri.as_iri()) && !via_fields.contains(iri) {
+                        via_fields.push(iri.clone());
+                    }
+                }
+            }
+        }
+
+        let mut fields = fields.to_vec();
+        if !via_fields.is_empty() {
+            fields.extend(via_fields.iter().map(|iri| iri.as_iri()));
+            map = self.field_map(&fields, scope)?;
+        }
+
+        let mut conditions: Vec<(&iref::Iri, &Condition)> =
+            conditions.iter().map(|(iri, condition)| (*iri, condition)).collect();
+        let mut linked: Vec<(&iref::Iri, &iref::Iri, &[iref::IriBuf], Option<&(iref::IriBuf, Condition)>)> = Vec::new();
+
+        // the field names in the matched triples will be the specific source model field which means
+        // we need to build a simple map to get the field type that it is mapped to
+        let reverse_map = reverse_map(&map);
+
+        for conflict in mapping_conflicts(&map) {
+            match options.strict {
+                true => {
+                    return Err(ResolveError::ConflictingMapping {
+                        source_iri: conflict.source_iri.to_string(),
+                        targets: conflict.targets.iter().map(|iri| iri.to_string()).collect(),
+                    }
+                    .into());
+                }
+                false => warn!(source = %conflict.source_iri, targets = ?conflict.targets, "conflicting mapping"),
+            }
+        }
+
+        for (key, maps) in map.iter() {
+            for field in maps {
+                if let Map::When(iri, condition) = field {
+                    conditions.push((iri.as_iri(), condition));
+                }
+
+                if let Map::From { graph, via, condition } = field {
+                    linked.push((key.as_iri(), graph.as_iri(), via.as_slice(), condition.as_ref()));
+                }
+            }
+        }
+
+
+        // get the predicate terms to find matching triples for. in our case the predicate
+        // is the mapped field name with the subject being the record entity_id and the object
+        // being the value of the field.
+        let terms = resolve_field_terms(&fields, &map)?;
+        let terms: std::collections::HashSet<&str> = terms
+            .iter()
+            .filter_map(|term| match term {
+                SimpleTerm::Iri(iri) => Some(iri.as_str()),
+                _ => None,
+            })
+            .collect();
+        debug!(?terms, "resolved terms");
+
+
+        // get the data and use the reverse map to associate the record with a list of fields
+        let mut records = RecordMap::new();
+
+        // records come from the named source graphs unless asked for. the default graph holds
+        // the schema declarations which would otherwise be picked up as records
+        let index = self.index_graphs(scope, options.include_default)?;
+        // only the selected records are collected when resolving part of the scope
+        let selected: Box<dyn Iterator<Item = (&Literal, &ValueMap)>> = match &options.subjects {
+            Some(subjects) => Box::new(
+                subjects
+                    .iter()
+                    .filter_map(|subject| Some((subject, index.get(subject)?))),
+            ),
+            None => Box::new(index.iter()),
+        };
+
+        for (subject, values) in selected {
+            // stop collecting new records once we have enough. conditions can filter out
+            // records afterwards so in that case we need all of them to pick from
+            if let Some(max) = max_records
+                && conditions.is_empty()
+                && records.len() >= max
+            {
+                break;
+            }
+
+            // the predicates are the source fields so only those used by the mappings of
+            // the requested fields are part of the record
+            for (predicate, values) in values.iter() {
+                if !terms.contains(predicate.as_str()) {
+                    continue;
+                }
+
+                let mapped_to_iri = match (reverse_map.get(predicate), options.on_unknown_predicate) {
+                    (Some(iris), _) => iris,
+                    (None, UnknownPredicate::Error) => {
+                        return Err(ResolveError::IriNotFound(predicate.to_string()).into());
+                    }
+                    (None, UnknownPredicate::Skip) => {
+                        if self.skipped_predicates.borrow_mut().insert(predicate.clone()) {
+                            warn!(%predicate, ?scope, "skipping source field without a mapping");
+                        }
+                        continue;
+                    }
+                };
+
+                // copy the resolved data to all iris that are mapped to it. its
+                // possible to map the same source iri to multiple model iris which
+                // means we have to clone the data into all of them
+                let record = records.entry(subject.clone()).or_default();
+                for iri in mapped_to_iri {
+                    for value in values {
+                        record.entry(iri.clone()).or_default().push(value.clone());
+                    }
+                }
+            }
+        }
+
+
+        for (key, graph, via, condition) in linked {
+            debug!(?key, ?via, ?graph, ?condition, "getting linked dataset matches");
+            let models = self.dataset.get_source_from_model(graph)?;
+            let mut models: Vec<&iref::Iri> = models.iter().map(|m| m.as_ref()).collect();
+            models.push(graph);
+
+            // a lookup that associates a link key with record rows that also have the
+            // same key. this lets us iterate through the linked data and extend all records
+            // associated with it in this map
+            let mut record_links: HashMap<Literal, Vec<Literal>> = HashMap::new();
+            for (idx, record) in records.iter() {
+                for link_key in link_keys(record, via)? {
+                    record_links.entry(link_key).or_default().push(idx.clone());
+                }
+            }
+
+            let mut link_fields = vec![key];
+            link_fields.extend(via.iter().map(|iri| iri.as_iri()));
+            if let Some((iri, _)) = condition {
+                link_fields.push(iri.as_iri());
+            }
+
+            if !visited_graphs.insert(graph.to_owned()) {
+                return Err(TransformError::CircularGraphLink(graph.to_string()));
+            }
+            // the linked records are all needed to find the ones that link up
+            let linked_options = ResolveOptions {
+                max_records: None,
+                subjects: None,
+                ..options.clone()
+            };
+            let linked_data = self.records_inner(&link_fields, &models, &[], &linked_options, visited_graphs);
+            visited_graphs.remove(graph);
+            let linked_data = linked_data?;
+
+            for (_k, mut values) in linked_data.into_records() {
+                // only join linked records that have the conditional field and pass it. the
+                // conditional field is only used for filtering so it doesn't get joined either
+                if let Some((iri, cond)) = condition {
+                    let passed = match values.get(iri) {
+                        Some(vals) => vals.iter().all(|val| cond.check(val)),
+                        None => false,
+                    };
+                    if !passed {
+                        continue;
+                    }
+                    if iri != key && !via.contains(iri) {
+                        values.remove(iri);
+                    }
+                }
+
+                // the via fields of a further link are only for joining and would otherwise
+                // overwrite the values of the same fields in the records being extended
+                values.retain(|iri, _values| iri.as_iri() == key || via.contains(iri));
+
+                // get the first key assigned to the through fields
+                if let Some(via_key) = link_keys(&values, via)?.first() {
+                    // look up rows that have matching values to the 'via' fields
+                    // and extend it with the values on the linked dataset.
+                    if let Some(rows) = record_links.get(via_key) {
+                        for idx in rows {
+                            records.entry(idx.clone()).or_default().extend(values.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+
+        // filter records that dont match the condition placed on it
+        let records = records
+            .into_iter()
+            .filter(|(_idx, record)| {
+                for (iri, cond) in &conditions {
+                    if let Some(values) = record.get(*iri) {
+                        for value in values {
+                            if !cond.check(value) {
+                                return false;
+                            }
+                        }
+                    }
+                }
+                true
+            })
+            .take(max_records.unwrap_or(usize::MAX))
+            .collect();
+
+        Ok(records)
+    }
+
+    /// Get the fields that each source field is mapped into for the specified fields.
+    ///
+    /// This is the lookup used when collecting records and is mostly useful for checking
+    /// which source columns feed which fields when writing a schema.
+    pub fn reverse_map<S: AsRef<iref::Iri>>(
+        &self,
+        fields: &[&iref::Iri],
+        scope: &[S],
+    ) -> Result<ReverseMap, TransformError> {
+        let map = self.field_map(fields, scope)?;
+        Ok(reverse_map(&map))
+    }
+
+    /// Get the field mapping for the specified fields
+    #[tracing::instrument(skip_all)]
+    pub fn field_map<S: AsRef<iref::Iri>>(
+        &self,
+        fields: &[&iref::Iri],
+        scope: &[S],
+    ) -> Result<FieldMap, TransformError> {
+        let scope: Vec<&iref::Iri> = scope.iter().map(|s| s.as_ref()).collect();
+        let mut resolved = FieldMap::new();
+
+        // convert the fields into a simple term for the iri
+        let mut terms: Vec<SimpleTerm> = Vec::new();
+        for iri in fields.iter() {
+            terms.push(iri.into_iri_term()?);
+        }
+
+        // convert the scope iri's in graph name matchers
+        let mut scope_terms = Vec::new();
+        for iri in scope.iter() {
+            scope_terms.push(Some(iri.into_iri_term()?));
+        }
+
+        trace!(?terms, ?scope, "Matching triples");
+        for quad in self
+            .dataset
+            .source
+            .quads_matching(terms.as_slice(), Any, Any, scope_terms.as_slice())
+        {
+            let (g, [s, p, o]) = quad?;
+            let graph = match g {
+                Some(SimpleTerm::Iri(iri_ref)) => iri_ref.to_iri()?,
+                _ => unimplemented!(),
+            };
+
+            // parse the predicate as a valid mapping term
+            let predicate: Mapping = p.try_into()?;
+
+            let map = match predicate {
+                // allows an iri to refer to a different iri. this is the most
+                // common option to map a common domain from one model to another
+                Mapping::Same => match o {
+                    SimpleTerm::Iri(iri_ref) => Map::Same(iri_ref.to_iri_owned()?),
+                    _ => unimplemented!(),
+                },
+                // the same as Same except that it indicates that it should hash
+                // the resolved value
+                Mapping::Hash => match o {
+                    SimpleTerm::Iri(iri_ref) => Map::Hash(iri_ref.to_iri_owned()?),
+                    _ => unimplemented!(),
+                },
+                // hash the first field that has a valid value
+                Mapping::HashFirst => match o {
+                    SimpleTerm::BlankNode(bnode_id) => {
+                        let mut iris = Vec::new();
+                        self.collect_iris(&mut iris, bnode_id, graph)?;
+                        Map::HashFirst(iris)
+                    }
+                    _ => unimplemented!(),
+                },
+                // use the first field that has a non-empty value
+                Mapping::Coalesce => match o {
+                    SimpleTerm::BlankNode(bnode_id) => {
+                        let mut iris = Vec::new();
+                        self.collect_iris(&mut iris, bnode_id, graph)?;
+                        Map::Coalesce(iris)
+                    }
+                    other => return Err(unexpected_term("mapping:coalesce", "a list of field IRIs", other)),
+                },
+                // combines all field values into one
+                Mapping::Combines => match o {
+                    SimpleTerm::BlankNode(bnode_id) => {
+                        let mut iris = Vec::new();
+                        self.collect_iris(&mut iris, bnode_id, graph)?;
+                        Map::Combines(iris)
+                    }
+                    _ => unimplemented!(),
+                },
+                // a filter condition to only return data if met
+                Mapping::When => match o {
+                    SimpleTerm::Triple(triple) => {
+                        let (subject, condition) = condition_from_triple(triple)?;
+                        Map::When(subject, condition)
+                    }
+                    SimpleTerm::BlankNode(bnode_id) => {
+                        let (subject, condition) = self.collect_condition(bnode_id, graph)?;
+                        Map::When(subject, condition)
+                    }
+                    _ => unimplemented!(),
+                },
+
+                // a directive to load the data from another graph
+                Mapping::From => match o {
+                    SimpleTerm::Triple(triple) => {
+                        let [cond_s, cond_p, cond_o] = triple.spo();
+
+                        let graph = match cond_s {
+                            SimpleTerm::Iri(iri_ref) => iri_ref.to_iri_owned()?,
+                            other => return Err(unexpected_term("mapping:from", "a model IRI", other)),
+                        };
+
+                        // a quoted link only has room for the via field, the other qualifiers
+                        // have to be in a blank node
+                        let via = match (FromCondition::try_from(cond_p)?, cond_o) {
+                            (FromCondition::Via, SimpleTerm::Iri(iri_ref)) => iri_ref.to_iri_owned()?,
+                            (FromCondition::Via, other) => {
+                                return Err(unexpected_term("mapping:via", "a field IRI", other));
+                            }
+                            (FromCondition::Graph | FromCondition::When, _) => {
+                                let msg = format!("a quoted link must use mapping:via but found {cond_p:?}");
+                                return Err(ResolveError::InvalidQualifier("mapping:from", msg).into());
+                            }
+                        };
+
+                        Map::From {
+                            graph,
+                            via: vec![via],
+                            condition: None,
+                        }
+                    }
+                    SimpleTerm::BlankNode(bnode_id) => self.collect_from(bnode_id, graph)?,
+                    other => return Err(unexpected_term("mapping:from", "a quoted triple or a blank node", other)),
+                },
+
+                // convert the numeric value of a field from one unit into another
+                Mapping::Convert => match o {
+                    SimpleTerm::BlankNode(bnode_id) => self.collect_convert(bnode_id, graph)?,
+                    other => return Err(unexpected_term("mapping:convert", "a blank node of qualifiers", other)),
+                },
+
+                // multiply the numeric value of a field by a factor
+                Mapping::Scale => match o {
+                    SimpleTerm::BlankNode(bnode_id) => self.collect_scale(bnode_id, graph)?,
+                    other => return Err(unexpected_term("mapping:scale", "a blank node of qualifiers", other)),
+                },
+
+                // change the case of the resolved value
+                Mapping::Uppercase => match o {
+                    SimpleTerm::Iri(iri_ref) => Map::Uppercase(iri_ref.to_iri_owned()?),
+                    other => return Err(unexpected_term("mapping:uppercase", "a source field IRI", other)),
+                },
+                Mapping::Lowercase => match o {
+                    SimpleTerm::Iri(iri_ref) => Map::Lowercase(iri_ref.to_iri_owned()?),
+                    other => return Err(unexpected_term("mapping:lowercase", "a source field IRI", other)),
+                },
+
+                // substitute a string in the value with another
+                Mapping::Replace => match o {
+                    SimpleTerm::BlankNode(bnode_id) => self.collect_replace(bnode_id, graph)?,
+                    other => return Err(unexpected_term("mapping:replace", "a blank node of qualifiers", other)),
+                },
+
+                // pad the value to a fixed width
+                Mapping::PadLeft => match o {
+                    SimpleTerm::BlankNode(bnode_id) => {
+                        let (source, width, fill) = self.collect_pad(bnode_id, graph)?;
+                        Map::PadLeft { source, width, fill }
+                    }
+                    other => return Err(unexpected_term("mapping:pad_left", "a blank node of qualifiers", other)),
+                },
+                Mapping::PadRight => match o {
+                    SimpleTerm::BlankNode(bnode_id) => {
+                        let (source, width, fill) = self.collect_pad(bnode_id, graph)?;
+                        Map::PadRight { source, width, fill }
+                    }
+                    other => return Err(unexpected_term("mapping:pad_right", "a blank node of qualifiers", other)),
+                },
+
+                // strip characters from the start and end of the value
+                Mapping::Trim => match o {
+                    SimpleTerm::Iri(iri_ref) => Map::Trim(iri_ref.to_iri_owned()?),
+                    other => return Err(unexpected_term("mapping:trim", "a source field IRI", other)),
+                },
+                Mapping::TrimChars => match o {
+                    SimpleTerm::BlankNode(bnode_id) => self.collect_trim_chars(bnode_id, graph)?,
+                    other => return Err(unexpected_term("mapping:trim_chars", "a blank node of qualifiers", other)),
+                },
+            };
+
+
+            // add the map to the common domain model
+            match s {
+                SimpleTerm::Iri(iri_ref) => resolved.entry(iri_ref.to_iri_owned()?).or_default().push(map),
+                _ => unimplemented!(),
+            };
+        }
+
+        Ok(resolved)
+    }
+
+    /// Determine which of the fields have a mapping within the scope.
+    ///
+    /// Like `list_mapped_fields` this only looks at the mapping definitions. Every field
+    /// specified is returned in the same order along with the graphs that map it.
+    #[tracing::instrument(skip_all)]
+    pub fn coverage<T, S>(
+        &self,
+        fields: &[T],
+        scope: &[S],
+    ) -> Result<Vec<(iref::IriBuf, FieldCoverage)>, TransformError>
+    where
+        T: AsRef<iref::Iri>,
+        S: AsRef<iref::Iri>,
+    {
+        let scope: Vec<&iref::Iri> = scope.iter().map(|s| s.as_ref()).collect();
+        let field_iris: Vec<&iref::Iri> = fields.iter().map(|f| f.as_ref()).collect();
+
+        let mut terms: Vec<SimpleTerm> = Vec::new();
+        for iri in field_iris.iter() {
+            terms.push(iri.into_iri_term()?);
+        }
+
+        let mut scope_terms = Vec::new();
+        for iri in scope.iter() {
+            scope_terms.push(Some(iri.into_iri_term()?));
+        }
+
+        let mut graphs: HashMap<iref::IriBuf, std::collections::BTreeSet<String>> = HashMap::new();
+        for quad in self
+            .dataset
+            .source
+            .quads_matching(terms.as_slice(), Any, Any, scope_terms.as_slice())
+        {
+            let (g, [s, p, _o]) = quad?;
+            if Mapping::try_from(p).is_err() {
+                continue;
+            }
+
+            if let (Some(SimpleTerm::Iri(graph)), SimpleTerm::Iri(subject)) = (g, s) {
+                graphs
+                    .entry(subject.to_iri_owned()?)
+                    .or_default()
+                    .insert(graph.to_string());
+            }
+        }
+
+        let mut coverage = Vec::with_capacity(field_iris.len());
+        for &iri in field_iris.iter() {
+            let field = match graphs.remove(iri) {
+                Some(graphs) => FieldCoverage::Mapped(graphs.into_iter().collect()),
+                None => FieldCoverage::Unmapped,
+            };
+            coverage.push((iri.to_owned(), field));
+        }
+
+        Ok(coverage)
+    }
+
+    /// List all the fields that can be produced by the schemas within the scope.
+    ///
+    /// This only looks at the mapping definitions so no data needs to be loaded. Any subject
+    /// of a mapping directive is considered a field that the schema can produce.
+    #[tracing::instrument(skip_all)]
+    pub fn list_mapped_fields<S: AsRef<iref::Iri>>(&self, scope: &[S]) -> Result<Vec<iref::IriBuf>, TransformError> {
+        let scope: Vec<&iref::Iri> = scope.iter().map(|s| s.as_ref()).collect();
+        let mut scope_terms = Vec::new();
+        for iri in scope.iter() {
+            scope_terms.push(Some(iri.into_iri_term()?));
+        }
+
+        let mut fields = std::collections::BTreeSet::new();
+        for quad in self
+            .dataset
+            .source
+            .quads_matching(Any, Any, Any, scope_terms.as_slice())
+        {
+            let (_g, [s, p, _o]) = quad?;
+
+            // only mapping directives define a field
+            if Mapping::try_from(p).is_err() {
+                continue;
+            }
+
+            if let SimpleTerm::Iri(iri_ref) = s {
+                fields.insert(iri_ref.to_iri_owned()?);
+            }
+        }
+
+        Ok(fields.into_iter().collect())
+    }
+
+    /// Collect all the IRIs in a linked list specified by rdfs
+    #[tracing::instrument(skip_all)]
+    pub fn collect_iris(
+        &self,
+        iris: &mut Vec<iref::IriBuf>,
+        node: &BnodeId<MownStr<'_>>,
+        graph: &iref::Iri,
+    ) -> Result<(), TransformError> {
+        for quad in self
+            .dataset
+            .source
+            .quads_matching([node], Any, Any, SingleGraph(graph.as_str()))
+        {
+            let (_g, [_s, p, o]) = quad?;
+            let pred: Rdfs = p.try_into()?;
+
+            match pred {
+                Rdfs::First => match o {
+                    SimpleTerm::Iri(iri_ref) => iris.push(iri_ref.to_iri_owned()?),
+                    _ => continue,
+                    // _ => unimplemented!(),
+                },
+
+                Rdfs::Rest => match o {
+                    SimpleTerm::BlankNode(bnode_id) => self.collect_iris(iris, bnode_id, graph)?,
+                    SimpleTerm::Iri(iri_ref) => match try_from_iri::<_, Rdfs>(iri_ref)? {
+                        Rdfs::Nil => return Ok(()),
+                        _ => unimplemented!(),
+                    },
+                    _ => unimplemented!(),
+                },
+
+                Rdfs::Nil => return Ok(()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Collect the qualifiers of a from mapping into a `Map::From`
+    #[tracing::instrument(skip_all)]
+    pub fn collect_from(&self, node: &BnodeId<MownStr<'_>>, graph: &iref::Iri) -> Result<Map, TransformError> {
+        let mut model = None;
+        let mut via = None;
+        let mut condition = None;
+
+        for quad in self
+            .dataset
+            .source
+            .quads_matching([node], Any, Any, SingleGraph(graph.as_str()))
+        {
+            let (_g, [_s, p, o]) = quad?;
+            let pred: FromCondition = p.try_into()?;
+
+            match (pred, o) {
+                (FromCondition::Graph, SimpleTerm::Iri(iri_ref)) => model = Some(iri_ref.to_iri_owned()?),
+                (FromCondition::Via, SimpleTerm::Iri(iri_ref)) => via = Some(vec![iri_ref.to_iri_owned()?]),
+                (FromCondition::Via, SimpleTerm::BlankNode(bnode_id)) => {
+                    let mut iris = Vec::new();
+                    self.collect_iris(&mut iris, bnode_id, graph)?;
+                    via = Some(iris);
+                }
+                (FromCondition::When, SimpleTerm::Triple(triple)) => condition = Some(condition_from_triple(triple)?),
+                (FromCondition::When, SimpleTerm::BlankNode(bnode_id)) => {
+                    condition = Some(self.collect_condition(bnode_id, graph)?)
+                }
+                _ => unimplemented!(),
+            }
+        }
+
+        let graph = model.ok_or(ResolveError::MissingQualifier("mapping:graph"))?;
+        let via = via.ok_or(ResolveError::MissingQualifier("mapping:via"))?;
+
+        Ok(Map::From { graph, via, condition })
+    }
+
+    /// Collect a compound condition from a blank node with a `mapping:and` or `mapping:or` list.
+    ///
+    /// The members of the list are quoted conditions or other compound conditions. Since a
+    /// condition is checked against the value of a single field every member must be on the
+    /// same field.
+    #[tracing::instrument(skip_all)]
+    pub fn collect_condition(
+        &self,
+        node: &BnodeId<MownStr<'_>>,
+        graph: &iref::Iri,
+    ) -> Result<(iref::IriBuf, Condition), TransformError> {
+        let mut compound = None;
 
-        let records = self.records(&field_iris, scope)?;
+        for quad in self
+            .dataset
+            .source
+            .quads_matching([node], Any, Any, SingleGraph(graph.as_str()))
+        {
+            let (_g, [_s, p, o]) = quad?;
+            let pred: MappingCondition = p.try_into()?;
 
-        let mut data: ResolvedRecords<R> = HashMap::new();
+            let (qualifier, list, compound_of): (_, _, fn(Vec<Condition>) -> Condition) = match (pred, o) {
+                (MappingCondition::And, SimpleTerm::BlankNode(list)) => ("mapping:and", list, Condition::And),
+                (MappingCondition::Or, SimpleTerm::BlankNode(list)) => ("mapping:or", list, Condition::Or),
+                _ => unimplemented!(),
+            };
 
-        // get the transform plan for the field and add that to the final result
-        for field_iri in field_iris {
-            let Some(mapping) = map.get(field_iri)
+            let mut members = Vec::new();
+            self.collect_conditions(&mut members, list, graph)?;
+
+            let Some((subject, _)) = members.first()
             else {
-                warn!("Field mapping not found: {field_iri}");
-                continue;
+                return Err(ResolveError::InvalidQualifier(qualifier, "the list has no conditions".to_string()).into());
             };
+            let subject = subject.clone();
 
-            for (entity_id, fields) in records.iter() {
-                for field_map in mapping {
-                    let result = match field_map {
-                        Map::Same(_iri) => fields.get(field_iri),
-                        Map::Hash(_iri) => fields.get(field_iri),
-                        Map::HashFirst(iris) => {
-                            let mut value = None;
-                            for iri in iris {
-                                if let Some(val) = fields.get(iri) {
-                                    value = Some(val);
-                                    break;
-                                }
-                            }
-                            value
-                        }
-                        Map::Combines(iris) => {
-                            let mut to_combine: Vec<String> = Vec::new();
-                            for iri in iris {
-                                // a field can be mapped to multiple source fields so we
-                                // need to handle that scenario here. this can lead to pretty
-                                // strange bugs due to the order being random so if there is
-                                // more than one value we fail with an ambiguity error.
-                                //
-                                // the reason why this matter for Combines is because we can't
-                                // tell which value is from which graph leaving us no possible way
-                                // to combine values isolated within their graphs
-                                if let Some(values) = fields.get(iri) {
-                                    let present: Vec<String> = values
-                                        .iter()
-                                        .filter_map(|v| match v {
-                                            // only return strings with actual data
-                                            Literal::String(val) => match val.is_empty() {
-                                                true => None,
-                                                false => Some(val.clone()),
-                                            },
-                                            Literal::UInt64(val) => Some(val.to_string()),
-                                        })
-                                        .collect();
+            let mut conditions = Vec::new();
+            for (field, condition) in members {
+                if field != subject {
+                    let msg = format!("the conditions are on different fields: {subject} and {field}");
+                    return Err(ResolveError::InvalidQualifier(qualifier, msg).into());
+                }
+                conditions.push(condition);
+            }
 
-                                    let value = if present.len() > 1 {
-                                        Err(ResolveError::AmbiguousMapping(iri.clone(), values.clone()))
-                                    }
-                                    else {
-                                        Ok(present.first().cloned())
-                                    }?;
+            compound = Some((subject, compound_of(conditions)));
+        }
 
-                                    if let Some(val) = value {
-                                        to_combine.push(val);
-                                    }
-                                }
-                            }
+        let compound = compound.ok_or(ResolveError::MissingQualifier("mapping:and"))?;
+        Ok(compound)
+    }
 
-                            Some(&vec![Literal::String(to_combine.join(" "))])
-                        }
-                        Map::When(_iri, _condition) => None,
-                        Map::From { .. } => None,
-                    };
+    /// Collect all the conditions in a linked list specified by rdfs
+    #[tracing::instrument(skip_all)]
+    pub fn collect_conditions(
+        &self,
+        conditions: &mut Vec<(iref::IriBuf, Condition)>,
+        node: &BnodeId<MownStr<'_>>,
+        graph: &iref::Iri,
+    ) -> Result<(), TransformError> {
+        for quad in self
+            .dataset
+            .source
+            .quads_matching([node], Any, Any, SingleGraph(graph.as_str()))
+        {
+            let (_g, [_s, p, o]) = quad?;
+            let pred: Rdfs = p.try_into()?;
 
+            match pred {
+                Rdfs::First => match o {
+                    SimpleTerm::Triple(triple) => conditions.push(condition_from_triple(triple)?),
+                    SimpleTerm::BlankNode(bnode_id) => conditions.push(self.collect_condition(bnode_id, graph)?),
+                    _ => unimplemented!(),
+                },
 
-                    // add all the fields even if there are multiple of the same.
-                    // uniqueness or disambiguation is a job outside this function
-                    if let Some(result) = result {
-                        for value in result {
-                            let mapped_from = T::try_from(field_iri)
-                                .map_err(|_| TransformError::InvalidMappingIri(field_iri.to_string()))?;
-                            let field: R = (mapped_from, value.clone()).into();
-                            data.entry(entity_id.clone()).or_default().push(field);
-                        }
-                    }
-                }
+                Rdfs::Rest => match o {
+                    SimpleTerm::BlankNode(bnode_id) => self.collect_conditions(conditions, bnode_id, graph)?,
+                    SimpleTerm::Iri(iri_ref) => match try_from_iri::<_, Rdfs>(iri_ref)? {
+                        Rdfs::Nil => return Ok(()),
+                        _ => unimplemented!(),
+                    },
+                    _ => unimplemented!(),
+                },
+
+                Rdfs::Nil => return Ok(()),
             }
         }
 
-        Ok(data)
+        Ok(())
     }
 
-    /// Get records container the specified fields in the specified models
+    /// Collect the qualifiers of a convert mapping into a `Map::Convert`
     #[tracing::instrument(skip_all)]
-    pub fn records(&self, fields: &[&iref::Iri], scope: &[&iref::Iri]) -> Result<RecordMap, TransformError> {
-        let map = self.field_map(fields, scope)?;
-
-        let mut conditions: Vec<(&iref::Iri, &Condition)> = Vec::new();
-        let mut linked: Vec<(&iref::Iri, &iref::Iri, &iref::Iri)> = Vec::new();
-        let mut linked_fields: Vec<&iref::IriBuf> = Vec::new();
+    pub fn collect_convert(&self, node: &BnodeId<MownStr<'_>>, graph: &iref::Iri) -> Result<Map, TransformError> {
+        let mut source = None;
+        let mut from = None;
+        let mut to = None;
 
-        // the field names in the matched triples will be the specific source model field which means
-        // we need to build a simple map to get the field type that it is mapped to
-        let mut reverse_map: HashMap<iref::IriBuf, Vec<iref::IriBuf>> = HashMap::new();
-        for (key, maps) in map.iter() {
-            for field in maps {
-                let iris = match field {
-                    Map::Same(iri) => vec![iri.clone()],
-                    Map::Combines(iris) => iris.clone(),
-                    Map::Hash(iri) => vec![iri.clone()],
-                    Map::HashFirst(iris) => iris.clone(),
-                    Map::When(_iri, _condition) => vec![],
-                    Map::From { .. } => vec![],
-                };
+        for quad in self
+            .dataset
+            .source
+            .quads_matching([node], Any, Any, SingleGraph(graph.as_str()))
+        {
+            let (_g, [_s, p, o]) = quad?;
+            let pred: ConvertCondition = p.try_into()?;
 
-                for mapped_from in iris {
-                    reverse_map.entry(mapped_from).or_default().push(key.clone());
+            match (pred, o) {
+                (ConvertCondition::Source, SimpleTerm::Iri(iri_ref)) => source = Some(iri_ref.to_iri_owned()?),
+                (ConvertCondition::FromUnit, SimpleTerm::LiteralDatatype(unit, _)) => {
+                    from = Some(unit.parse::<Unit>()?)
                 }
-
-                if let Map::When(iri, condition) = field {
-                    conditions.push((iri.as_iri(), condition));
+                (ConvertCondition::ToUnit, SimpleTerm::LiteralDatatype(unit, _)) => to = Some(unit.parse::<Unit>()?),
+                (ConvertCondition::Source, other) => {
+                    return Err(unexpected_term("mapping:source", "a source field IRI", other));
+                }
+                (ConvertCondition::FromUnit, other) => {
+                    return Err(unexpected_term("mapping:from_unit", "a literal", other));
                 }
+                (ConvertCondition::ToUnit, other) => {
+                    return Err(unexpected_term("mapping:to_unit", "a literal", other));
+                }
+            }
+        }
+
+        let source = source.ok_or(ResolveError::MissingQualifier("mapping:source"))?;
+        let from = from.ok_or(ResolveError::MissingQualifier("mapping:from_unit"))?;
+        let to = to.ok_or(ResolveError::MissingQualifier("mapping:to_unit"))?;
+
+        if from.quantity() != to.quantity() {
+            return Err(ResolveError::IncompatibleUnits(from, to).into());
+        }
+
+        Ok(Map::Convert { source, from, to })
+    }
+
+    /// Collect the qualifiers of a scale mapping into a `Map::Scale`
+    #[tracing::instrument(skip_all)]
+    pub fn collect_scale(&self, node: &BnodeId<MownStr<'_>>, graph: &iref::Iri) -> Result<Map, TransformError> {
+        let mut source = None;
+        let mut scale = None;
 
-                if let Map::From { graph, via } = field {
-                    linked.push((key.as_iri(), graph.as_iri(), via.as_iri()));
-                    linked_fields.push(via);
+        for quad in self
+            .dataset
+            .source
+            .quads_matching([node], Any, Any, SingleGraph(graph.as_str()))
+        {
+            let (_g, [_s, p, o]) = quad?;
+            let pred: ScaleCondition = p.try_into()?;
+
+            match (pred, o) {
+                (ScaleCondition::Source, SimpleTerm::Iri(iri_ref)) => source = Some(iri_ref.to_iri_owned()?),
+                (ScaleCondition::Factor, SimpleTerm::LiteralDatatype(val, _)) => scale = Some(val.parse::<f64>()?),
+                (ScaleCondition::Source, other) => {
+                    return Err(unexpected_term("mapping:source", "a source field IRI", other));
                 }
+                (ScaleCondition::Factor, other) => return Err(unexpected_term("mapping:factor", "a literal", other)),
             }
         }
 
+        let source = source.ok_or(ResolveError::MissingQualifier("mapping:source"))?;
+        let scale = scale.ok_or(ResolveError::MissingQualifier("mapping:factor"))?;
 
-        // get the predicate terms to find matching triples for. in our case the predicate
-        // is the mapped field name with the subject being the record entity_id and the object
-        // being the value of the field.
-        let terms = resolve_field_terms(&fields.to_vec(), &map)?;
-        let terms = Vec::from_iter(terms);
-        debug!(?terms, "resolved terms");
+        if !scale.is_finite() {
+            return Err(ResolveError::InvalidQualifier("mapping:factor", scale.to_string()).into());
+        }
 
+        Ok(Map::Scale { source, scale })
+    }
 
-        // get the data and use the reverse map to associate the record with a list of fields
-        let mut records = RecordMap::new();
+    /// Collect the qualifiers of a replace mapping into a `Map::Replace`
+    #[tracing::instrument(skip_all)]
+    pub fn collect_replace(&self, node: &BnodeId<MownStr<'_>>, graph: &iref::Iri) -> Result<Map, TransformError> {
+        let mut source = None;
+        let mut from = None;
+        let mut to = None;
 
-        // a lookup that associates a link value with record rows that also contain the
-        // same value. this lets us iterate through the linked data and extend all records
-        // associated with it in this map
-        let mut record_links: HashMap<&iref::Iri, HashMap<Literal, Vec<Literal>>> = HashMap::new();
+        for quad in self
+            .dataset
+            .source
+            .quads_matching([node], Any, Any, SingleGraph(graph.as_str()))
+        {
+            let (_g, [_s, p, o]) = quad?;
+            let pred: ReplaceCondition = p.try_into()?;
+
+            match (pred, o) {
+                (ReplaceCondition::Source, SimpleTerm::Iri(iri_ref)) => source = Some(iri_ref.to_iri_owned()?),
+                (ReplaceCondition::From, SimpleTerm::LiteralDatatype(val, _)) => from = Some(val.to_string()),
+                (ReplaceCondition::To, SimpleTerm::LiteralDatatype(val, _)) => to = Some(val.to_string()),
+                (ReplaceCondition::Source, other) => {
+                    return Err(unexpected_term("mapping:source", "a source field IRI", other));
+                }
+                (ReplaceCondition::From, other) => return Err(unexpected_term("mapping:from", "a literal", other)),
+                (ReplaceCondition::To, other) => return Err(unexpected_term("mapping:to", "a literal", other)),
+            }
+        }
 
+        let source = source.ok_or(ResolveError::MissingQualifier("mapping:source"))?;
+        let from = from.ok_or(ResolveError::MissingQualifier("mapping:from"))?;
+        let to = to.ok_or(ResolveError::MissingQualifier("mapping:to"))?;
 
-        let scope: Vec<&str> = scope.iter().map(|s| s.as_str()).collect();
+        Ok(Map::Replace { source, from, to })
+    }
+
+    /// Collect the qualifiers of a pad mapping into the source, width, and fill character
+    #[tracing::instrument(skip_all)]
+    pub fn collect_pad(
+        &self,
+        node: &BnodeId<MownStr<'_>>,
+        graph: &iref::Iri,
+    ) -> Result<(iref::IriBuf, usize, char), TransformError> {
+        let mut source = None;
+        let mut width = None;
+        let mut fill = '0';
 
         for quad in self
             .dataset
             .source
-            .quads_matching(Any, terms.as_slice(), Any, GraphIri(&scope))
+            .quads_matching([node], Any, Any, SingleGraph(graph.as_str()))
         {
-            let (_g, [s, p, o]) = quad?;
+            let (_g, [_s, p, o]) = quad?;
+            let pred: PadCondition = p.try_into()?;
+
+            match (pred, o) {
+                (PadCondition::Source, SimpleTerm::Iri(iri_ref)) => source = Some(iri_ref.to_iri_owned()?),
+                (PadCondition::Width, SimpleTerm::LiteralDatatype(val, _)) => width = Some(val.parse::<usize>()?),
+                (PadCondition::Fill, SimpleTerm::LiteralDatatype(val, _)) => {
+                    let mut chars = val.chars();
+                    fill = match (chars.next(), chars.next()) {
+                        (Some(ch), None) => ch,
+                        _ => return Err(ResolveError::InvalidQualifier("mapping:fill", val.to_string()).into()),
+                    };
+                }
+                (PadCondition::Source, other) => {
+                    return Err(unexpected_term("mapping:source", "a source field IRI", other));
+                }
+                (PadCondition::Width, other) => return Err(unexpected_term("mapping:width", "a literal", other)),
+                (PadCondition::Fill, other) => return Err(unexpected_term("mapping:fill", "a literal", other)),
+            }
+        }
 
-            let subject = match s {
-                SimpleTerm::LiteralDatatype(value, _type) => Literal::String(value.to_string()),
-                _ => unimplemented!(),
-            };
+        let source = source.ok_or(ResolveError::MissingQualifier("mapping:source"))?;
+        let width = width.ok_or(ResolveError::MissingQualifier("mapping:width"))?;
 
-            let mapped_to_iri = match p {
-                SimpleTerm::Iri(iri) => match reverse_map.get(&iri.to_iri_owned()?) {
-                    Some(iris) => Ok(iris),
-                    None => Err(ResolveError::IriNotFound(iri.to_string())),
-                }?,
-                _ => unimplemented!(),
-            };
+        Ok((source, width, fill))
+    }
 
-            let value = match o {
-                SimpleTerm::LiteralDatatype(value, _type) => Literal::String(value.to_string()),
-                _ => unimplemented!(),
-            };
+    /// Collect the qualifiers of a trim mapping into a `Map::TrimChars`
+    #[tracing::instrument(skip_all)]
+    pub fn collect_trim_chars(&self, node: &BnodeId<MownStr<'_>>, graph: &iref::Iri) -> Result<Map, TransformError> {
+        let mut source = None;
+        let mut chars = None;
 
+        for quad in self
+            .dataset
+            .source
+            .quads_matching([node], Any, Any, SingleGraph(graph.as_str()))
+        {
+            let (_g, [_s, p, o]) = quad?;
+            let pred: TrimCondition = p.try_into()?;
 
-            // copy the resolved data to all iris that are mapped to it. its
-            // possible to map the same source iri to multiple model iris which
-            // means we have to clone the data into all of them
-            let record = records.entry(subject.clone()).or_default();
-            for iri in mapped_to_iri {
-                if linked_fields.contains(&iri) {
-                    // add the record row index with the value of the linked field
-                    // as the key for looking up when resolving the linked dataset
-                    record_links
-                        .entry(iri.as_iri())
-                        .or_default()
-                        .entry(value.clone())
-                        .or_default()
-                        .push(subject.clone());
+            match (pred, o) {
+                (TrimCondition::Source, SimpleTerm::Iri(iri_ref)) => source = Some(iri_ref.to_iri_owned()?),
+                (TrimCondition::Characters, SimpleTerm::LiteralDatatype(val, _)) => chars = Some(val.to_string()),
+                (TrimCondition::Source, other) => {
+                    return Err(unexpected_term("mapping:source", "a source field IRI", other));
+                }
+                (TrimCondition::Characters, other) => {
+                    return Err(unexpected_term("mapping:characters", "a literal", other));
                 }
-
-                record.entry(iri.clone()).or_default().push(value.clone());
             }
         }
 
+        let source = source.ok_or(ResolveError::MissingQualifier("mapping:source"))?;
+        let chars = chars.ok_or(ResolveError::MissingQualifier("mapping:characters"))?;
 
-        for (key, graph, via) in linked {
-            debug!(?key, ?via, ?graph, "getting linked dataset matches");
-            let models = self.dataset.get_source_from_model(graph)?;
-            let mut models: Vec<&iref::Iri> = models.iter().map(|m| m.as_ref()).collect();
-            models.push(graph);
+        Ok(Map::TrimChars { source, chars })
+    }
+}
 
-            let linked_data = self.records(&[&key, &via], models.as_slice())?;
 
-            for (_k, values) in linked_data {
-                // get the first key value assigned to the through field
-                if let Some(keys) = values.get(via) {
-                    // look up rows that have matching values to the 'via' field
-                    // and extend it with the values on the linked dataset.
-                    let via_key = keys.first().unwrap().clone();
-                    let rows = record_links.get(&via).and_then(|map| map.get(&via_key));
-                    if let Some(rows) = rows {
-                        for idx in rows {
-                            records.entry(idx.clone()).or_default().extend(values.clone());
-                        }
-                    }
+/// The separator placed between the values of a composite link key. It is a control
+/// character so it won't appear in the values themselves.
+const COMPOSITE_KEY_SEPARATOR: &str = "\u{1f}";
+
+/// Get the keys that link a record to the records of another model.
+///
+/// A single via field links on each of its values. A composite key needs exactly one value
+/// for each of the fields and records missing any of them don't link at all.
+fn link_keys(record: &ValueMap, via: &[iref::IriBuf]) -> Result<Vec<Literal>, ResolveError> {
+    if let [iri] = via {
+        return Ok(record.get(iri).cloned().unwrap_or_default());
+    }
+
+    let mut components = Vec::new();
+    for iri in via {
+        let values = match record.get(iri) {
+            Some(values) if !values.is_empty() => values,
+            _ => return Ok(Vec::new()),
+        };
+
+        if values.iter().any(|value| value != &values[0]) {
+            return Err(ResolveError::AmbiguousMapping(iri.clone(), values.clone()));
+        }
+        components.push(values[0].lexical());
+    }
+
+    Ok(vec![Literal::String(components.join(COMPOSITE_KEY_SEPARATOR))])
+}
+
+
+/// Parse a quoted `mapping:when` triple into the field and the condition placed on it.
+fn condition_from_triple(triple: &[SimpleTerm<'static>; 3]) -> Result<(iref::IriBuf, Condition), TransformError> {
+    let [cond_s, cond_p, cond_o] = triple.spo();
+
+    let subject = match cond_s {
+        SimpleTerm::Iri(iri_ref) => iri_ref.to_iri_owned()?,
+        _ => unimplemented!(),
+    };
+
+    let condition = match MappingCondition::try_from(cond_p)? {
+        MappingCondition::Is => Condition::Is(Literal::try_from(cond_o)?),
+        MappingCondition::GreaterThan => Condition::GreaterThan(Literal::try_from(cond_o)?),
+        MappingCondition::LessThan => Condition::LessThan(Literal::try_from(cond_o)?),
+        // a list can't be quoted so compound conditions are always in a blank node
+        MappingCondition::And | MappingCondition::Or => {
+            let msg = "compound conditions must be in a blank node".to_string();
+            return Err(ResolveError::InvalidQualifier("mapping:when", msg).into());
+        }
+    };
+
+    Ok((subject, condition))
+}
+
+
+/// The error for a mapping qualifier with an object that isn't the kind of term expected.
+fn unexpected_term(qualifier: &'static str, expected: &str, term: &SimpleTerm<'_>) -> TransformError {
+    ResolveError::InvalidQualifier(qualifier, format!("expected {expected} but found {term:?}")).into()
+}
+
+
+/// The source fields of the mappings that only transform the values of a source.
+///
+/// The values of every source mapped into a field are collected into the field together, so
+/// without leaving these out the untransformed values would be copied into the field as well.
+/// A source that is also copied as is, eg. with `mapping:same`, is never left out.
+fn transformed_sources(mapping: &[Map]) -> Vec<&iref::IriBuf> {
+    let mut copied = Vec::new();
+    let mut transformed = Vec::new();
+
+    for field_map in mapping {
+        match field_map {
+            Map::Same(iri) | Map::Hash(iri) | Map::Uppercase(iri) | Map::Lowercase(iri) => copied.push(iri),
+            Map::Convert { source, .. }
+            | Map::Scale { source, .. }
+            | Map::Replace { source, .. }
+            | Map::PadLeft { source, .. }
+            | Map::PadRight { source, .. }
+            | Map::TrimChars { source, .. }
+            | Map::Trim(source) => {
+                if !transformed.contains(&source) {
+                    transformed.push(source);
                 }
             }
+            _ => {}
         }
+    }
 
+    transformed.retain(|iri| !copied.contains(iri));
+    transformed
+}
 
-        // filter records that dont match the condition placed on it
-        let records = records
+
+/// The values collected into a field without those loaded from the transformed sources.
+///
+/// The collected values are in no particular order so a loaded value is left out by
+/// removing one value equal to it.
+fn untransformed_values(
+    values: Option<&Vec<Literal>>,
+    loaded: Option<&ValueMap>,
+    transformed: &[&iref::IriBuf],
+) -> Vec<Literal> {
+    let mut values = values.cloned().unwrap_or_default();
+
+    for iri in transformed {
+        for value in source_values(loaded, iri).into_iter().flatten() {
+            if let Some(idx) = values.iter().position(|val| val == value) {
+                values.remove(idx);
+            }
+        }
+    }
+
+    values
+}
+
+
+/// The values of a record that were loaded from the source field.
+fn source_values<'a>(loaded: Option<&'a ValueMap>, source: &iref::Iri) -> Option<&'a Vec<Literal>> {
+    loaded?.get(source)
+}
+
+
+/// Convert all values from one unit into another.
+///
+/// Values that can't be converted are reported and dropped rather than failing the whole transform.
+fn convert_values(field_iri: &iref::Iri, values: Option<&Vec<Literal>>, from: &Unit, to: &Unit) -> Vec<Literal> {
+    let mut converted = Vec::new();
+    for value in values.into_iter().flatten() {
+        match from.convert_literal(value, to) {
+            Ok(val) => converted.push(val),
+            Err(err) => warn!(%field_iri, ?value, %err, "Failed to convert value"),
+        }
+    }
+    converted
+}
+
+
+/// Multiply all numeric values by the scale, formatted to 6 decimal places.
+///
+/// Values that aren't numbers are reported and dropped rather than failing the whole transform.
+fn scale_values(field_iri: &iref::Iri, values: Option<&Vec<Literal>>, scale: f64) -> Vec<Literal> {
+    let mut scaled = Vec::new();
+    for value in values.into_iter().flatten() {
+        let number = match value {
+            Literal::UInt64(val) => Ok(*val as f64),
+            Literal::String(val) => val.trim().parse::<f64>(),
+        };
+
+        match number {
+            Ok(number) => scaled.push(Literal::String(format!("{:.6}", number * scale))),
+            Err(err) => warn!(%field_iri, ?value, %err, "Failed to scale value"),
+        }
+    }
+    scaled
+}
+
+
+/// Get the values of the first field in the list that has any that aren't one of the null tokens.
+fn coalesce_values(fields: &ValueMap, iris: &[iref::IriBuf], null_tokens: &NullTokens) -> Vec<Literal> {
+    for iri in iris {
+        let values: Vec<Literal> = fields
+            .get(iri)
             .into_iter()
-            .filter(|(_idx, record)| {
-                for (iri, cond) in &conditions {
-                    if let Some(values) = record.get(*iri) {
-                        for value in values {
-                            if !cond.check(value) {
-                                return false;
-                            }
-                        }
-                    }
-                }
-                true
-            })
+            .flatten()
+            .filter(|literal| !null_tokens.is_null_literal(literal))
+            .cloned()
             .collect();
 
-        Ok(records)
+        if !values.is_empty() {
+            return values;
+        }
     }
 
-    /// Get the field mapping for the specified fields
-    #[tracing::instrument(skip_all)]
-    pub fn field_map(&self, fields: &[&iref::Iri], scope: &[&iref::Iri]) -> Result<FieldMap, TransformError> {
-        let mut resolved = FieldMap::new();
+    Vec::new()
+}
 
-        // convert the fields into a simple term for the iri
-        let mut terms: Vec<SimpleTerm> = Vec::new();
-        for iri in fields.iter() {
-            terms.push(iri.into_iri_term()?);
-        }
 
-        // convert the scope iri's in graph name matchers
-        let mut scope_terms = Vec::new();
-        for iri in scope.iter() {
-            scope_terms.push(Some(iri.into_iri_term()?));
+/// Change the case of a string value if the field has a case mapping.
+fn apply_case(value: &Literal, mapping: &[Map]) -> Literal {
+    let Literal::String(val) = value
+    else {
+        return value.clone();
+    };
+
+    for field_map in mapping {
+        match field_map {
+            Map::Uppercase(_iri) => return Literal::String(val.to_uppercase()),
+            Map::Lowercase(_iri) => return Literal::String(val.to_lowercase()),
+            _ => {}
         }
+    }
 
-        trace!(?terms, ?scope, "Matching triples");
-        for quad in self
-            .dataset
-            .source
-            .quads_matching(terms.as_slice(), Any, Any, scope_terms.as_slice())
-        {
-            let (g, [s, p, o]) = quad?;
-            let graph = match g {
-                Some(SimpleTerm::Iri(iri_ref)) => iri_ref.to_iri()?,
-                _ => unimplemented!(),
-            };
+    value.clone()
+}
 
-            // parse the predicate as a valid mapping term
-            let predicate: Mapping = p.try_into()?;
 
-            let map = match predicate {
-                // allows an iri to refer to a different iri. this is the most
-                // common option to map a common domain from one model to another
-                Mapping::Same => match o {
-                    SimpleTerm::Iri(iri_ref) => Map::Same(iri_ref.to_iri_owned()?),
-                    _ => unimplemented!(),
-                },
-                // the same as Same except that it indicates that it should hash
-                // the resolved value
-                Mapping::Hash => match o {
-                    SimpleTerm::Iri(iri_ref) => Map::Hash(iri_ref.to_iri_owned()?),
-                    _ => unimplemented!(),
-                },
-                // hash the first field that has a valid value
-                Mapping::HashFirst => match o {
-                    SimpleTerm::BlankNode(bnode_id) => {
-                        let mut iris = Vec::new();
-                        self.collect_iris(&mut iris, bnode_id, graph)?;
-                        Map::HashFirst(iris)
-                    }
-                    _ => unimplemented!(),
-                },
-                // combines all field values into one
-                Mapping::Combines => match o {
-                    SimpleTerm::BlankNode(bnode_id) => {
-                        let mut iris = Vec::new();
-                        self.collect_iris(&mut iris, bnode_id, graph)?;
-                        Map::Combines(iris)
-                    }
-                    _ => unimplemented!(),
-                },
-                // a filter condition to only return data if met
-                Mapping::When => match o {
-                    SimpleTerm::Triple(triple) => {
-                        let [cond_s, cond_p, cond_o] = triple.spo();
+enum Padding {
+    Left,
+    Right,
+}
 
-                        let subject = match cond_s {
-                            SimpleTerm::Iri(iri_ref) => iri_ref.to_iri_owned()?,
-                            _ => unimplemented!(),
-                        };
+/// Pad all values with the fill character until they are at least the width in characters.
+///
+/// Numeric values are padded using their lexical form which means the result is always a string.
+fn pad_values(values: Option<&Vec<Literal>>, width: usize, fill: char, padding: Padding) -> Vec<Literal> {
+    let mut padded = Vec::new();
+    for value in values.into_iter().flatten() {
+        let val = value.lexical();
+        let fill = fill.to_string().repeat(width.saturating_sub(val.chars().count()));
+
+        let val = match padding {
+            Padding::Left => format!("{fill}{val}"),
+            Padding::Right => format!("{val}{fill}"),
+        };
+        padded.push(Literal::String(val));
+    }
+    padded
+}
 
-                        let condition = match MappingCondition::try_from(cond_p)? {
-                            MappingCondition::Is => Condition::Is(Literal::try_from(cond_o)?),
-                        };
 
-                        Map::When(subject, condition)
-                    }
-                    _ => unimplemented!(),
-                },
+/// Remove the matching characters from the start and end of all string values.
+///
+/// Only string values are affected, any other literal is passed through as is.
+fn trim_values(values: Option<&Vec<Literal>>, pattern: impl Fn(char) -> bool) -> Vec<Literal> {
+    let mut trimmed = Vec::new();
+    for value in values.into_iter().flatten() {
+        let value = match value {
+            Literal::String(val) => Literal::String(val.trim_matches(&pattern).to_string()),
+            other => other.clone(),
+        };
+        trimmed.push(value);
+    }
+    trimmed
+}
 
-                // a directive to load the data from another graph
-                Mapping::From => match o {
-                    SimpleTerm::Triple(triple) => {
-                        let [cond_s, cond_p, cond_o] = triple.spo();
 
-                        let graph = match cond_s {
-                            SimpleTerm::Iri(iri_ref) => iri_ref.to_iri_owned()?,
-                            _ => unimplemented!(),
-                        };
+/// Apply all the replace mappings of the source to the values in the order they are defined.
+///
+/// Only string values are affected, any other literal is passed through as is.
+fn replace_values(values: Option<&Vec<Literal>>, mapping: &[Map], source: &iref::IriBuf) -> Vec<Literal> {
+    let mut replaced = Vec::new();
+    for value in values.into_iter().flatten() {
+        let value = match value {
+            Literal::String(val) => {
+                let mut val = val.clone();
+                for field_map in mapping {
+                    if let Map::Replace {
+                        source: replaced,
+                        from,
+                        to,
+                    } = field_map
+                        && replaced == source
+                    {
+                        val = val.replace(from.as_str(), to);
+                    }
+                }
+                Literal::String(val)
+            }
+            other => other.clone(),
+        };
+        replaced.push(value);
+    }
+    replaced
+}
 
-                        let via = match FromCondition::try_from(cond_p)? {
-                            FromCondition::Via => match cond_o {
-                                SimpleTerm::Iri(iri_ref) => iri_ref.to_iri_owned()?,
-                                _ => unimplemented!(),
-                            },
-                        };
 
-                        Map::From { graph, via }
-                    }
-                    _ => unimplemented!(),
-                },
-            };
+/// A source field that is copied as is into more than one field.
+#[derive(Debug, Clone)]
+pub struct MappingConflict {
+    pub source_iri: iref::IriBuf,
+    pub targets: Vec<iref::IriBuf>,
+}
 
 
-            // add the map to the common domain model
-            match s {
-                SimpleTerm::Iri(iri_ref) => resolved.entry(iri_ref.to_iri_owned()?).or_default().push(map),
-                _ => unimplemented!(),
-            };
-        }
+/// Build a lookup of the fields that each source field is mapped into.
+///
+/// The same mapping defined in more than one graph only appears once for a source field.
+pub fn reverse_map(map: &FieldMap) -> ReverseMap {
+    let mut reverse_map = ReverseMap::new();
 
-        Ok(resolved)
+    for (key, maps) in map.iter() {
+        for field in maps {
+            for mapped_from in map_sources(field) {
+                let targets = reverse_map.entry(mapped_from).or_default();
+                if !targets.contains(key) {
+                    targets.push(key.clone());
+                }
+            }
+        }
     }
 
-    /// Collect all the IRIs in a linked list specified by rdfs
-    #[tracing::instrument(skip_all)]
-    pub fn collect_iris(
-        &self,
-        iris: &mut Vec<iref::IriBuf>,
-        node: &BnodeId<MownStr<'_>>,
-        graph: &iref::Iri,
-    ) -> Result<(), TransformError> {
-        for quad in self
-            .dataset
-            .source
-            .quads_matching([node], Any, Any, GraphIriName(&graph))
-        {
-            let (_g, [_s, p, o]) = quad?;
-            let pred: Rdfs = p.try_into()?;
+    reverse_map
+}
 
-            match pred {
-                Rdfs::First => match o {
-                    SimpleTerm::Iri(iri_ref) => iris.push(iri_ref.to_iri_owned()?),
-                    _ => continue,
-                    // _ => unimplemented!(),
-                },
 
-                Rdfs::Rest => match o {
-                    SimpleTerm::BlankNode(bnode_id) => self.collect_iris(iris, bnode_id, graph)?,
-                    SimpleTerm::Iri(iri_ref) => match try_from_iri::<_, Rdfs>(iri_ref)? {
-                        Rdfs::Nil => return Ok(()),
-                        _ => unimplemented!(),
-                    },
-                    _ => unimplemented!(),
-                },
+/// The source fields that a mapping copies or derives its values from.
+fn map_sources(field: &Map) -> Vec<iref::IriBuf> {
+    match field {
+        Map::Same(iri) => vec![iri.clone()],
+        Map::Combines(iris) => iris.clone(),
+        Map::Hash(iri) => vec![iri.clone()],
+        Map::HashFirst(iris) => iris.clone(),
+        Map::Coalesce(iris) => iris.clone(),
+        Map::When(_iri, _condition) => vec![],
+        Map::From { .. } => vec![],
+        Map::Convert { source, .. } => vec![source.clone()],
+        Map::Scale { source, .. } => vec![source.clone()],
+        Map::Replace { source, .. } => vec![source.clone()],
+        Map::Uppercase(iri) => vec![iri.clone()],
+        Map::Lowercase(iri) => vec![iri.clone()],
+        Map::PadLeft { source, .. } => vec![source.clone()],
+        Map::PadRight { source, .. } => vec![source.clone()],
+        Map::Trim(iri) => vec![iri.clone()],
+        Map::TrimChars { source, .. } => vec![source.clone()],
+    }
+}
 
-                Rdfs::Nil => return Ok(()),
+
+/// Find the entity ids that are resolved for more than one subject.
+///
+/// Collisions are ordered by entity id and the subjects within them are ordered as well
+/// so that the reported collisions are the same between runs.
+fn shared_entity_ids(records: &RecordMap) -> Vec<(Literal, Vec<Literal>)> {
+    let entity_id: &iref::Iri = crate::rdf::Name::EntityId.as_ref();
+    let mut subjects: HashMap<&Literal, Vec<Literal>> = HashMap::new();
+
+    for (subject, record) in records.iter_records() {
+        for id in record.get(entity_id).into_iter().flatten() {
+            // a record with the same entity id mapped in multiple graphs isn't a collision
+            let subjects = subjects.entry(id).or_default();
+            if subjects.last() != Some(subject) {
+                subjects.push(subject.clone());
             }
         }
+    }
 
-        Ok(())
+    let mut shared: Vec<(Literal, Vec<Literal>)> = subjects
+        .into_iter()
+        .filter(|(_id, subjects)| subjects.len() > 1)
+        .map(|(id, mut subjects)| {
+            subjects.sort_by_cached_key(|subject| subject.lexical());
+            (id.clone(), subjects)
+        })
+        .collect();
+
+    shared.sort_by_cached_key(|(id, _subjects)| id.lexical());
+    shared
+}
+
+
+/// The key that entity ids are ordered by.
+///
+/// Ids that are whole numbers are ordered by their value ahead of all other ids, which are
+/// ordered by their lexical form. This keeps ids that are assigned by counting in the order
+/// they were assigned when they cross a digit boundary, such as `9` and `10`.
+fn entity_order(id: &Literal) -> (bool, u64, String) {
+    let lexical = id.lexical();
+    match lexical.parse::<u64>() {
+        Ok(number) => (false, number, lexical),
+        Err(_) => (true, 0, lexical),
     }
 }
 
 
+/// Find source fields that are mapped with `mapping:same` into more than one field.
+///
+/// This is usually a mistake in the schema, such as two fields reading the same column,
+/// which leads to the same value showing up in unrelated fields. Other mappings like
+/// `mapping:hash` are expected to derive a new value from a field that is also copied
+/// as is so they aren't considered a conflict.
+pub fn mapping_conflicts(map: &FieldMap) -> Vec<MappingConflict> {
+    let mut copies: HashMap<&iref::IriBuf, Vec<iref::IriBuf>> = HashMap::new();
+
+    for (key, maps) in map.iter() {
+        for field in maps {
+            if let Map::Same(iri) = field {
+                let targets = copies.entry(iri).or_default();
+                if !targets.contains(key) {
+                    targets.push(key.clone());
+                }
+            }
+        }
+    }
+
+    let mut conflicts: Vec<MappingConflict> = copies
+        .into_iter()
+        .filter(|(_source, targets)| targets.len() > 1)
+        .map(|(source, mut targets)| {
+            targets.sort();
+            MappingConflict {
+                source_iri: source.clone(),
+                targets,
+            }
+        })
+        .collect();
+
+    conflicts.sort_by(|a, b| a.source_iri.cmp(&b.source_iri));
+    conflicts
+}
+
+
 #[tracing::instrument(skip_all)]
 pub fn resolve_field_terms<'a>(
     fields: &Vec<&iref::Iri>,
@@ -473,7 +2425,7 @@ pub fn resolve_field_terms<'a>(
                 Map::Hash(mapping) => {
                     terms.insert(mapping.into_iri_term()?);
                 }
-                Map::HashFirst(iris) => {
+                Map::HashFirst(iris) | Map::Coalesce(iris) => {
                     // rather than resolving all the fields in the HashFirst mapping
                     // we iterate over it here since we only want to support the :same
                     // operator otherwise the complexity will drive deeper than it needs to be
@@ -511,7 +2463,21 @@ pub fn resolve_field_terms<'a>(
                     terms.insert(iri.into_iri_term()?);
                 }
                 Map::From { via, .. } => {
-                    terms.insert(via.into_iri_term()?);
+                    for iri in via {
+                        terms.insert(iri.into_iri_term()?);
+                    }
+                }
+                Map::Convert { source, .. } | Map::Scale { source, .. } => {
+                    terms.insert(source.into_iri_term()?);
+                }
+                Map::Replace { source, .. } => {
+                    terms.insert(source.into_iri_term()?);
+                }
+                Map::Uppercase(iri) | Map::Lowercase(iri) | Map::Trim(iri) => {
+                    terms.insert(iri.into_iri_term()?);
+                }
+                Map::PadLeft { source, .. } | Map::PadRight { source, .. } | Map::TrimChars { source, .. } => {
+                    terms.insert(source.into_iri_term()?);
                 }
             }
         }
@@ -521,40 +2487,759 @@ pub fn resolve_field_terms<'a>(
 }
 
 
-#[derive(Clone, Copy)]
-pub struct GraphIri<'a>(&'a Vec<&'a str>);
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::io::BufReader;
 
-impl<'a> GraphNameMatcher for GraphIri<'a> {
-    type Term = SimpleTerm<'static>;
+    use super::*;
+    use crate::dataset::{Dataset, Model};
+    use crate::readers::CsvReader;
+    use crate::testing::{SOURCE_PREFIXES, SYNTHETIC_SCHEMA, column_iri, load_source};
 
-    fn matches<T2: Term + ?Sized>(&self, graph_name: GraphName<&T2>) -> bool {
-        match graph_name {
-            // only include matching graph names
-            Some(t) => match t.as_simple() {
-                SimpleTerm::Iri(iri) => self.0.contains(&iri.as_str()),
-                _ => false,
-            },
-            // always include the default graph
-            None => false,
+    fn field(name: &str) -> iref::IriBuf {
+        iref::IriBuf::new(format!("http://arga.org.au/schemas/fields/{name}")).unwrap()
+    }
+
+    /// Resolve the sorted values of the field for every record in the model keyed by the entity id.
+    fn resolve_field(dataset: &Dataset, model: Model, field: &iref::Iri) -> BTreeMap<String, Vec<String>> {
+        let entity_id: &iref::Iri = crate::rdf::Name::EntityId.as_ref();
+        let scope = dataset.scope_iris(&[model]).unwrap();
+        let resolver = Resolver::new(dataset);
+        let records = resolver
+            .resolve_values(&[entity_id, field], &scope, &ResolveOptions::default())
+            .unwrap();
+
+        records
+            .iter_records()
+            .map(|(_subject, record)| {
+                let id = record.get_first_string(entity_id).unwrap().to_string();
+                let mut values: Vec<String> = record.get(field).into_iter().flatten().map(|v| v.lexical()).collect();
+                values.sort();
+                (id, values)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn convert_only_reads_its_own_source() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let kilobases = r#"
+            fields:entity_id mapping:same col:id .
+            fields:size mapping:same col:size .
+            fields:size mapping:convert [ mapping:source col:size_kb ; mapping:from_unit "kb" ; mapping:to_unit "bp" ] .
+        "#;
+        let base_pairs = r#"
+            fields:entity_id mapping:same col:id .
+            fields:size mapping:same col:length .
+        "#;
+
+        let rows: &[&[(&str, &str)]] = &[&[("id", "a"), ("size_kb", "1.5")], &[("id", "b"), ("size", "700")]];
+        load_source(&mut dataset, &Model::Assembly, "kilobases", kilobases, rows).unwrap();
+        let rows: &[&[(&str, &str)]] = &[&[("id", "c"), ("length", "800")]];
+        load_source(&mut dataset, &Model::Assembly, "base_pairs", base_pairs, rows).unwrap();
+
+        let sizes = resolve_field(&dataset, Model::Assembly, field("size").as_iri());
+        assert_eq!(sizes["a"], vec!["1500"]);
+        assert_eq!(sizes["b"], vec!["700"]);
+        assert_eq!(sizes["c"], vec!["800"]);
+    }
+
+    #[test]
+    fn lowercase_composes_with_same() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:same col:id .
+            fields:nucleic_acid_type mapping:same col:type .
+            fields:nucleic_acid_type mapping:lowercase col:type .
+        "#;
+        let rows: &[&[(&str, &str)]] = &[&[("id", "a"), ("type", "DNA")]];
+        load_source(&mut dataset, &Model::Extraction, "extractions", mappings, rows).unwrap();
+
+        let types = resolve_field(&dataset, Model::Extraction, field("nucleic_acid_type").as_iri());
+        assert_eq!(types["a"], vec!["dna"]);
+    }
+
+    #[test]
+    fn case_mapping_of_a_literal_is_invalid() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:same col:id .
+            fields:nucleic_acid_type mapping:lowercase "DNA" .
+        "#;
+        load_source(&mut dataset, &Model::Extraction, "extractions", mappings, &[]).unwrap();
+
+        let scope = dataset.scope_iris(&[Model::Extraction]).unwrap();
+        let field = field("nucleic_acid_type");
+        let result = Resolver::new(&dataset).field_map(&[field.as_iri()], &scope);
+        assert!(matches!(
+            result,
+            Err(TransformError::Resolve(ResolveError::InvalidQualifier("mapping:lowercase", _)))
+        ));
+    }
+
+    #[test]
+    fn case_mappings_from_trig_and_csv() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let graph = dataset.source_iri("bpa").unwrap();
+        let trig = format!(
+            "{SOURCE_PREFIXES}<{graph}> {{
+                fields:entity_id mapping:same col:sample_id .
+                fields:sex mapping:lowercase col:sex .
+                fields:life_stage mapping:uppercase col:life_stage .
+            }}"
+        );
+        dataset.load_trig(BufReader::new(trig.as_bytes())).unwrap();
+        dataset.register_source("bpa", &Model::Organism).unwrap();
+
+        let csv = "sample_id,sex,life_stage\nBPA-1,Female,adult\n";
+        dataset.load(CsvReader::new(csv.as_bytes()).unwrap(), "bpa").unwrap();
+
+        let scope = dataset.scope_iris(&[Model::Organism]).unwrap();
+        let (sex, life_stage) = (field("sex"), field("life_stage"));
+        let map = Resolver::new(&dataset)
+            .field_map(&[sex.as_iri(), life_stage.as_iri()], &scope)
+            .unwrap();
+        let column = iref::IriBuf::new(column_iri("sex")).unwrap();
+        assert!(matches!(map[&sex].as_slice(), [Map::Lowercase(iri)] if iri == &column));
+
+        assert_eq!(resolve_field(&dataset, Model::Organism, sex.as_iri())["BPA-1"], vec!["female"]);
+        assert_eq!(resolve_field(&dataset, Model::Organism, life_stage.as_iri())["BPA-1"], vec!["ADULT"]);
+    }
+
+    #[test]
+    fn hash_first_falls_through_null_tokens() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:hash_first ( fields:canonical_name fields:scientific_name ) .
+            fields:canonical_name mapping:same col:canonical .
+            fields:scientific_name mapping:same col:name .
+        "#;
+
+        // tokens are skipped when loading and counted in the stats
+        let rows: &[&[(&str, &str)]] = &[&[("canonical", "N/A"), ("name", "Canis lupus")]];
+        let stats = load_source(&mut dataset, &Model::Name, "skipped", mappings, rows).unwrap();
+        assert_eq!(stats.values_skipped_null, 1);
+
+        // values loaded before the tokens changed are skipped when resolving
+        dataset.set_null_tokens(NullTokens::blank_only());
+        let rows: &[&[(&str, &str)]] = &[&[("canonical", "N/A"), ("name", "Felis catus")]];
+        let stats = load_source(&mut dataset, &Model::Name, "loaded", mappings, rows).unwrap();
+        assert_eq!(stats.values_skipped_null, 0);
+        dataset.set_null_tokens(NullTokens::default());
+
+        // the fields listed by hash_first have to be resolved along with the entity id
+        let entity_id: &iref::Iri = crate::rdf::Name::EntityId.as_ref();
+        let (canonical, scientific) = (field("canonical_name"), field("scientific_name"));
+        let fields = [entity_id, canonical.as_iri(), scientific.as_iri()];
+        let scope = dataset.scope_iris(&[Model::Name]).unwrap();
+        let records = Resolver::new(&dataset)
+            .resolve_values(&fields, &scope, &ResolveOptions::default())
+            .unwrap();
+
+        let mut ids: Vec<&str> = records
+            .iter_records()
+            .map(|(_subject, record)| record.get_first_string(entity_id).unwrap())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["Canis lupus", "Felis catus"]);
+    }
+
+    #[test]
+    fn coalesce_falls_back_when_the_first_value_is_missing() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:coalesce ( fields:scientific_name fields:canonical_name ) .
+            fields:canonical_name mapping:same col:canonical .
+            fields:scientific_name mapping:same col:name .
+        "#;
+        let rows: &[&[(&str, &str)]] = &[
+            &[("name", "Felis catus"), ("canonical", "Felis")],
+            &[("canonical", "Canis lupus")],
+            &[("canonical", "Vulpes vulpes")],
+        ];
+        load_source(&mut dataset, &Model::Name, "names", mappings, rows).unwrap();
+
+        // blank values are skipped when loading so add one to the last record directly
+        let graph = dataset.source_iri("names").unwrap().into_iri_term().unwrap();
+        let subject: SimpleTerm = "names:2".into_term();
+        let name = iref::IriBuf::new(column_iri("name")).unwrap();
+        dataset
+            .source
+            .insert(&subject, name.into_iri_term().unwrap(), "", Some(&graph))
+            .unwrap();
+
+        let entity_id: &iref::Iri = crate::rdf::Name::EntityId.as_ref();
+        let (canonical, scientific) = (field("canonical_name"), field("scientific_name"));
+        let fields = [entity_id, canonical.as_iri(), scientific.as_iri()];
+        let scope = dataset.scope_iris(&[Model::Name]).unwrap();
+        let records = Resolver::new(&dataset)
+            .resolve_values(&fields, &scope, &ResolveOptions::default())
+            .unwrap();
+
+        // the values are used as is rather than hashed
+        let mut ids: Vec<&str> = records
+            .iter_records()
+            .map(|(_subject, record)| record.get_first_string(entity_id).unwrap())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["Canis lupus", "Felis catus", "Vulpes vulpes"]);
+    }
+
+    #[test]
+    fn coalesce_must_be_a_list() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:same col:id .
+            fields:scientific_name mapping:coalesce "name" .
+        "#;
+        load_source(&mut dataset, &Model::Name, "names", mappings, &[]).unwrap();
+
+        let scope = dataset.scope_iris(&[Model::Name]).unwrap();
+        let name = field("scientific_name");
+        let result = Resolver::new(&dataset).field_map(&[name.as_iri()], &scope);
+        assert!(matches!(
+            result,
+            Err(TransformError::Resolve(ResolveError::InvalidQualifier("mapping:coalesce", _)))
+        ));
+    }
+
+    #[test]
+    fn check_unique_entities_reports_hashed_collisions() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:hash col:organism_id .
+            fields:scientific_name mapping:same col:name .
+        "#;
+        let rows: &[&[(&str, &str)]] = &[
+            &[("organism_id", "o1"), ("name", "Felis catus")],
+            &[("organism_id", "o2"), ("name", "Canis lupus")],
+            &[("organism_id", "o1"), ("name", "Vulpes vulpes")],
+        ];
+        load_source(&mut dataset, &Model::Organism, "organisms", mappings, rows).unwrap();
+
+        let scope = dataset.scope_iris(&[Model::Organism]).unwrap();
+        let collisions = Resolver::new(&dataset).check_unique_entities(&scope).unwrap();
+        assert_eq!(collisions.len(), 1);
+
+        let collision = &collisions[0];
+        let subjects: Vec<String> = collision.subjects.iter().map(|subject| subject.lexical()).collect();
+        assert_eq!(subjects, vec!["organisms:0", "organisms:2"]);
+
+        // only the hashed source field is reported
+        let organism_id = iref::IriBuf::new(column_iri("organism_id")).unwrap();
+        for inputs in &collision.inputs {
+            assert_eq!(inputs.len(), 1);
+            assert_eq!(inputs[&organism_id], vec![Literal::from("o1")]);
         }
     }
-}
 
-#[derive(Clone, Copy)]
-pub struct GraphIriName<'a>(&'a iref::Iri);
+    #[test]
+    fn check_unique_entities_is_empty_for_unique_ids() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = "fields:entity_id mapping:hash col:organism_id .";
+        let rows: &[&[(&str, &str)]] = &[&[("organism_id", "o1")], &[("organism_id", "o2")]];
+        load_source(&mut dataset, &Model::Organism, "organisms", mappings, rows).unwrap();
+
+        let scope = dataset.scope_iris(&[Model::Organism]).unwrap();
+        assert!(
+            Resolver::new(&dataset)
+                .check_unique_entities(&scope)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    /// Organisms that link their publication from data products of mixed types.
+    fn data_products_dataset(condition: &str) -> Dataset {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let data_products = dataset.model_schema(&Model::DataProduct);
+
+        let products: &[&[(&str, &str)]] = &[
+            &[
+                ("product_id", "p1"),
+                ("organism_id", "o1"),
+                ("type", "publication"),
+                ("publication_id", "doi:10.1/a"),
+            ],
+            &[
+                ("product_id", "p2"),
+                ("organism_id", "o1"),
+                ("type", "sequence"),
+                ("publication_id", "doi:10.1/x"),
+            ],
+            &[
+                ("product_id", "p3"),
+                ("organism_id", "o2"),
+                ("type", "sequence"),
+                ("publication_id", "doi:10.1/b"),
+            ],
+            &[
+                ("product_id", "p4"),
+                ("organism_id", "o3"),
+                ("type", "publication"),
+                ("publication_id", "doi:10.1/c"),
+            ],
+        ];
+        let mappings = r#"
+            fields:entity_id mapping:same col:product_id .
+            fields:organism_id mapping:same col:organism_id .
+            fields:data_type mapping:same col:type .
+            fields:publication_id mapping:same col:publication_id .
+        "#;
+        load_source(&mut dataset, &Model::DataProduct, "data_products", mappings, products).unwrap();
+
+        let organisms: &[&[(&str, &str)]] = &[
+            &[("id", "o1"), ("organism_id", "o1")],
+            &[("id", "o2"), ("organism_id", "o2")],
+            &[("id", "o3"), ("organism_id", "o3")],
+        ];
+        let mappings = format!(
+            "fields:entity_id mapping:same col:id .
+            fields:organism_id mapping:same col:organism_id .
+            fields:publication_id mapping:from [
+                mapping:graph <{data_products}> ;
+                mapping:via fields:organism_id ;
+                {condition}
+            ] ."
+        );
+        load_source(&mut dataset, &Model::Organism, "organisms", &mappings, organisms).unwrap();
+        dataset
+    }
 
-impl<'a> GraphNameMatcher for GraphIriName<'a> {
-    type Term = SimpleTerm<'static>;
+    #[test]
+    fn from_only_joins_linked_records_that_pass_the_condition() {
+        let dataset = data_products_dataset(r#"mapping:when << fields:data_type mapping:is "publication" >>"#);
+        let publications = resolve_field(&dataset, Model::Organism, field("publication_id").as_iri());
 
-    fn matches<T2: Term + ?Sized>(&self, graph_name: GraphName<&T2>) -> bool {
-        match graph_name {
-            // only include matching graph names
-            Some(t) => match t.as_simple() {
-                SimpleTerm::Iri(iri) => self.0.eq(&iri.as_str()),
-                _ => false,
-            },
-            // always include the default graph
-            None => false,
+        assert_eq!(publications["o1"], vec!["doi:10.1/a"]);
+        assert!(publications["o2"].is_empty());
+        assert_eq!(publications["o3"], vec!["doi:10.1/c"]);
+    }
+
+    #[test]
+    fn from_without_a_condition_joins_every_linked_record() {
+        let dataset = data_products_dataset("");
+        let publications = resolve_field(&dataset, Model::Organism, field("publication_id").as_iri());
+
+        assert_eq!(publications["o1"], vec!["doi:10.1/a", "doi:10.1/x"]);
+        assert_eq!(publications["o2"], vec!["doi:10.1/b"]);
+    }
+
+    #[test]
+    fn quoted_links_must_use_via() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let data_products = dataset.source_iri("data_products").unwrap();
+        let mappings = format!(
+            "fields:entity_id mapping:same col:id .
+            fields:publication_id mapping:from << <{data_products}> mapping:graph fields:organism_id >> ."
+        );
+        load_source(&mut dataset, &Model::Organism, "organisms", &mappings, &[]).unwrap();
+
+        let scope = dataset.scope_iris(&[Model::Organism]).unwrap();
+        let publication = field("publication_id");
+        let result = Resolver::new(&dataset).field_map(&[publication.as_iri()], &scope);
+        assert!(matches!(result, Err(TransformError::Resolve(ResolveError::InvalidQualifier("mapping:from", _)))));
+    }
+
+    #[test]
+    fn compound_conditions_are_parsed_from_lists() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:same col:id .
+            fields:quality mapping:same col:quality .
+            fields:quality mapping:when [
+                mapping:and ( << fields:quality mapping:greater_than 5 >> [
+                    mapping:or ( << fields:quality mapping:less_than 10 >> << fields:quality mapping:is 20 >> )
+                ] )
+            ] .
+        "#;
+        load_source(&mut dataset, &Model::Name, "names", mappings, &[]).unwrap();
+
+        let scope = dataset.scope_iris(&[Model::Name]).unwrap();
+        let quality = field("quality");
+        let map = Resolver::new(&dataset).field_map(&[quality.as_iri()], &scope).unwrap();
+        let condition = map[&quality].iter().find_map(|map| match map {
+            Map::When(iri, condition) if iri == &quality => Some(condition),
+            _ => None,
+        });
+
+        let Some(Condition::And(members)) = condition
+        else {
+            panic!("expected an and condition but found {condition:?}");
+        };
+        assert!(matches!(members[0], Condition::GreaterThan(Literal::UInt64(5))));
+        assert!(matches!(&members[1], Condition::Or(inner) if inner.len() == 2));
+
+        let condition = condition.unwrap();
+        assert!(condition.check(&Literal::from("7")));
+        assert!(condition.check(&Literal::UInt64(20)));
+        assert!(!condition.check(&Literal::UInt64(15)));
+        assert!(!condition.check(&Literal::UInt64(3)));
+    }
+
+    #[test]
+    fn compound_conditions_must_be_on_one_field() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:same col:id .
+            fields:quality mapping:same col:quality .
+            fields:quality mapping:when [
+                mapping:or ( << fields:quality mapping:is "high" >> << fields:status mapping:is "active" >> )
+            ] .
+        "#;
+        load_source(&mut dataset, &Model::Name, "names", mappings, &[]).unwrap();
+
+        let scope = dataset.scope_iris(&[Model::Name]).unwrap();
+        let quality = field("quality");
+        let result = Resolver::new(&dataset).field_map(&[quality.as_iri()], &scope);
+        assert!(matches!(result, Err(TransformError::Resolve(ResolveError::InvalidQualifier("mapping:or", _)))));
+    }
+
+    /// Count the warnings about skipped source fields.
+    struct SkippedFieldWarnings(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+    struct Message(Option<String>);
+
+    impl tracing::field::Visit for Message {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "message" {
+                self.0 = Some(format!("{value:?}"));
+            }
+        }
+    }
+
+    impl tracing::Subscriber for SkippedFieldWarnings {
+        fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            let mut message = Message(None);
+            event.record(&mut message);
+            if message.0.as_deref() == Some("skipping source field without a mapping") {
+                self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+        }
+
+        fn enter(&self, _span: &tracing::span::Id) {}
+
+        fn exit(&self, _span: &tracing::span::Id) {}
+    }
+
+    /// A dataset where the source field of a `mapping:when` is matched without being mapped.
+    fn unknown_predicate_dataset() -> Dataset {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:same col:id .
+            fields:scientific_name mapping:same col:name .
+            fields:scientific_name mapping:when << col:status mapping:is "accepted" >> .
+        "#;
+        let rows: &[&[(&str, &str)]] = &[
+            &[("id", "a"), ("name", "Felis catus"), ("status", "accepted")],
+            &[("id", "b"), ("name", "Canis lupus"), ("status", "accepted")],
+        ];
+        load_source(&mut dataset, &Model::Name, "names", mappings, rows).unwrap();
+        dataset
+    }
+
+    #[test]
+    fn unknown_predicates_are_logged_once_when_skipped() {
+        let dataset = unknown_predicate_dataset();
+        let entity_id: &iref::Iri = crate::rdf::Name::EntityId.as_ref();
+        let name = field("scientific_name");
+        let scope = dataset.scope_iris(&[Model::Name]).unwrap();
+
+        let warnings = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let resolver = Resolver::new(&dataset);
+        tracing::subscriber::with_default(SkippedFieldWarnings(warnings.clone()), || {
+            for _ in 0..2 {
+                let records = resolver.records(&[entity_id, name.as_iri()], &scope, false).unwrap();
+                assert_eq!(records.len(), 2);
+            }
+        });
+
+        assert_eq!(warnings.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn unknown_predicates_are_an_error_with_the_options() {
+        let dataset = unknown_predicate_dataset();
+        let entity_id: &iref::Iri = crate::rdf::Name::EntityId.as_ref();
+        let name = field("scientific_name");
+        let scope = dataset.scope_iris(&[Model::Name]).unwrap();
+
+        let options = ResolveOptions {
+            on_unknown_predicate: UnknownPredicate::Error,
+            ..Default::default()
+        };
+        let result = Resolver::new(&dataset)
+            .with_options(options)
+            .records(&[entity_id, name.as_iri()], &scope, false);
+        assert!(matches!(
+            result,
+            Err(TransformError::Resolve(ResolveError::IriNotFound(iri))) if iri == column_iri("status")
+        ));
+    }
+
+    #[test]
+    fn trim_chars_strips_export_artifacts_from_its_own_source() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let trimmed = r#"
+            fields:entity_id mapping:same col:id .
+            fields:nucleic_acid_type mapping:trim_chars [
+                mapping:source col:raw_type ;
+                mapping:characters "\u0000;\""
+            ] .
+        "#;
+        let copied = r#"
+            fields:entity_id mapping:same col:id .
+            fields:nucleic_acid_type mapping:same col:type_name .
+        "#;
+
+        let rows: &[&[(&str, &str)]] = &[
+            &[("id", "a"), ("raw_type", "\0DNA;;")],
+            &[("id", "b"), ("raw_type", "\"RNA\";")],
+        ];
+        load_source(&mut dataset, &Model::Extraction, "trimmed", trimmed, rows).unwrap();
+        let rows: &[&[(&str, &str)]] = &[&[("id", "c"), ("type_name", ";DNA;")]];
+        load_source(&mut dataset, &Model::Extraction, "copied", copied, rows).unwrap();
+
+        let types = resolve_field(&dataset, Model::Extraction, field("nucleic_acid_type").as_iri());
+        assert_eq!(types["a"], vec!["DNA"]);
+        assert_eq!(types["b"], vec!["RNA"]);
+        assert_eq!(types["c"], vec![";DNA;"]);
+    }
+
+    #[test]
+    fn replace_only_applies_to_its_own_source() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:same col:id .
+            fields:extraction_method mapping:replace [
+                mapping:source col:method ;
+                mapping:from "_" ;
+                mapping:to " "
+            ] .
+            fields:extraction_method mapping:replace [
+                mapping:source col:method ;
+                mapping:from "kit" ;
+                mapping:to "Kit"
+            ] .
+            fields:extraction_method mapping:replace [
+                mapping:source col:protocol ;
+                mapping:from "-" ;
+                mapping:to " "
+            ] .
+        "#;
+        let rows: &[&[(&str, &str)]] = &[&[
+            ("id", "a"),
+            ("method", "spin-column_kit"),
+            ("protocol", "phenol-chloroform"),
+        ]];
+        load_source(&mut dataset, &Model::Extraction, "extractions", mappings, rows).unwrap();
+
+        let methods = resolve_field(&dataset, Model::Extraction, field("extraction_method").as_iri());
+        assert_eq!(methods["a"], vec!["phenol chloroform", "spin-column Kit"]);
+    }
+
+    #[test]
+    fn pad_and_trim_only_read_their_own_source() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:same col:id .
+            fields:extract_id mapping:pad_left [ mapping:source col:extract_number ; mapping:width "4" ] .
+            fields:extract_id mapping:trim col:extract_code .
+        "#;
+        let rows: &[&[(&str, &str)]] = &[
+            &[("id", "a"), ("extract_number", "12")],
+            &[("id", "b"), ("extract_code", " X7 ")],
+        ];
+        load_source(&mut dataset, &Model::Extraction, "extractions", mappings, rows).unwrap();
+
+        let ids = resolve_field(&dataset, Model::Extraction, field("extract_id").as_iri());
+        assert_eq!(ids["a"], vec!["0012"]);
+        assert_eq!(ids["b"], vec!["X7"]);
+    }
+
+    #[test]
+    fn scale_only_reads_its_own_source() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:same col:id .
+            fields:size mapping:same col:size .
+            fields:size mapping:scale [ mapping:source col:size_kb ; mapping:factor "1000" ] .
+        "#;
+        let rows: &[&[(&str, &str)]] = &[&[("id", "a"), ("size_kb", "1.5")], &[("id", "b"), ("size", "700")]];
+        load_source(&mut dataset, &Model::Assembly, "assemblies", mappings, rows).unwrap();
+
+        let sizes = resolve_field(&dataset, Model::Assembly, field("size").as_iri());
+        assert_eq!(sizes["a"], vec!["1500.000000"]);
+        assert_eq!(sizes["b"], vec!["700"]);
+    }
+
+    /// The sorted values of the field across all the records.
+    fn field_values(records: &RecordMap, field: &iref::Iri) -> Vec<String> {
+        let mut values: Vec<String> = records
+            .iter_records()
+            .flat_map(|(_subject, record)| record.get(field).into_iter().flatten().map(|v| v.lexical()))
+            .collect();
+        values.sort();
+        values
+    }
+
+    #[test]
+    fn records_only_include_the_default_graph_when_asked() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:same col:id .
+            fields:scientific_name mapping:same col:name .
+        "#;
+        let rows: &[&[(&str, &str)]] = &[&[("id", "a"), ("name", "Named")]];
+        load_source(&mut dataset, &Model::Name, "names", mappings, rows).unwrap();
+
+        // a record inserted straight into the default graph rather than loaded from a source
+        let column = iref::IriBuf::new(column_iri("name")).unwrap();
+        let subject: SimpleTerm = "stray".into_term();
+        dataset
+            .source
+            .insert(&subject, column.into_iri_term().unwrap(), "Stray", None::<&SimpleTerm>)
+            .unwrap();
+
+        let scope = dataset.scope_iris(&[Model::Name]).unwrap();
+        let field = field("scientific_name");
+        let resolver = Resolver::new(&dataset);
+
+        let named = resolver.records(&[field.as_iri()], &scope, false).unwrap();
+        assert_eq!(field_values(&named, field.as_iri()), vec!["Named"]);
+
+        let all = resolver.records(&[field.as_iri()], &scope, true).unwrap();
+        assert_eq!(field_values(&all, field.as_iri()), vec!["Named", "Stray"]);
+    }
+
+    #[test]
+    fn resolving_ignores_records_in_the_default_graph() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:same col:id .
+            fields:scientific_name mapping:same col:name .
+        "#;
+        let rows: &[&[(&str, &str)]] = &[&[("id", "a"), ("name", "Named")]];
+        load_source(&mut dataset, &Model::Name, "names", mappings, rows).unwrap();
+
+        // the source declarations are in the default graph as well as this record
+        let subject: SimpleTerm = "stray".into_term();
+        for (column, value) in [("id", "b"), ("name", "Stray")] {
+            let column = iref::IriBuf::new(column_iri(column)).unwrap();
+            dataset
+                .source
+                .insert(&subject, column.into_iri_term().unwrap(), value, None::<&SimpleTerm>)
+                .unwrap();
+        }
+
+        let names = resolve_field(&dataset, Model::Name, field("scientific_name").as_iri());
+        assert_eq!(names.len(), 1);
+        assert_eq!(names["a"], vec!["Named"]);
+    }
+
+    fn sorted_names(dataset: &Dataset) -> Vec<(String, Vec<String>)> {
+        use crate::rdf::{Name, NameField};
+
+        let scope = dataset.scope_iris(&[Model::Name]).unwrap();
+        let records: Vec<(Literal, Vec<NameField>)> = Resolver::new(dataset).resolve_sorted(Name::ALL, &scope).unwrap();
+
+        records
+            .into_iter()
+            .map(|(_subject, fields)| {
+                let mut entity_id = String::new();
+                let mut names = Vec::new();
+                for field in fields {
+                    match field {
+                        NameField::EntityId(id) => entity_id = id,
+                        NameField::ScientificName(name) => names.push(name),
+                        _ => {}
+                    }
+                }
+                (entity_id, names)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resolve_sorted_orders_records_by_entity_id() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:same col:id .
+            fields:scientific_name mapping:same col:name .
+        "#;
+        let rows: &[&[(&str, &str)]] = &[
+            &[("id", "c"), ("name", "Vulpes vulpes")],
+            &[("id", "a"), ("name", "Felis catus")],
+            &[("id", "9"), ("name", "Mus musculus")],
+            &[("id", "b"), ("name", "Canis lupus")],
+            &[("id", "10"), ("name", "Rattus rattus")],
+        ];
+        load_source(&mut dataset, &Model::Name, "names", mappings, rows).unwrap();
+
+        let first = sorted_names(&dataset);
+        let ids: Vec<&str> = first.iter().map(|(id, _)| id.as_str()).collect();
+        // numeric ids are ordered by their value ahead of the others
+        assert_eq!(ids, vec!["9", "10", "a", "b", "c"]);
+        assert_eq!(first[2].1, vec!["Felis catus"]);
+
+        for _ in 0..5 {
+            assert_eq!(sorted_names(&dataset), first);
         }
     }
+
+    fn incremental_ids(dataset: &Dataset, cursor: Option<&str>) -> Vec<String> {
+        use crate::rdf::{Name, NameField};
+
+        let scope = dataset.scope_iris(&[Model::Name]).unwrap();
+        let cursor = cursor.map(Literal::from);
+        let records: ResolvedRecords<NameField> = Resolver::new(dataset)
+            .resolve_incremental(Name::ALL, &scope, cursor.as_ref())
+            .unwrap();
+
+        let mut ids: Vec<String> = records
+            .into_values()
+            .flatten()
+            .filter_map(|field| match field {
+                NameField::EntityId(id) => Some(id),
+                _ => None,
+            })
+            .collect();
+        ids.sort_by_cached_key(|id| entity_order(&Literal::from(id.as_str())));
+        ids
+    }
+
+    #[test]
+    fn resolve_incremental_orders_ids_across_digit_boundaries() {
+        let mut dataset = Dataset::new(SYNTHETIC_SCHEMA).unwrap();
+        let mappings = r#"
+            fields:entity_id mapping:same col:id .
+            fields:scientific_name mapping:same col:name .
+        "#;
+        let rows: &[&[(&str, &str)]] = &[
+            &[("id", "8"), ("name", "Felis catus")],
+            &[("id", "9"), ("name", "Canis lupus")],
+            &[("id", "10"), ("name", "Vulpes vulpes")],
+            &[("id", "11"), ("name", "Mus musculus")],
+            &[("id", "100"), ("name", "Rattus rattus")],
+        ];
+        load_source(&mut dataset, &Model::Name, "names", mappings, rows).unwrap();
+
+        assert_eq!(incremental_ids(&dataset, None), vec!["8", "9", "10", "11", "100"]);
+        assert_eq!(incremental_ids(&dataset, Some("9")), vec!["10", "11", "100"]);
+        assert_eq!(incremental_ids(&dataset, Some("11")), vec!["100"]);
+        assert!(incremental_ids(&dataset, Some("100")).is_empty());
+    }
 }
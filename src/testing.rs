@@ -0,0 +1,161 @@
+use std::io::BufReader;
+
+use crate::dataset::{Dataset, Model, SchemaBuilder, TransformStats, Triple};
+use crate::errors::TransformError;
+use crate::rdf::{self, Literal};
+
+
+/// The schema IRI used by the synthetic datasets.
+pub const SYNTHETIC_SCHEMA: &str = "http://arga.org.au/schemas/maps/synthetic";
+
+/// The prefixes available to the mappings of `load_source`.
+pub const SOURCE_PREFIXES: &str = "@prefix mapping: <http://arga.org.au/schemas/mapping/> .
+@prefix fields: <http://arga.org.au/schemas/fields/> .
+@prefix col: <http://arga.org.au/schemas/maps/synthetic/> .
+";
+
+/// The source that the `scientific_name_authorship` of every synthetic record is linked from.
+pub const SYNTHETIC_LINKED_SOURCE: &str = "synthetic_linked";
+
+
+/// Generate a dataset of reproducible records along with the mappings to resolve them.
+///
+/// Every graph is a separate source of `records` rows that transforms into the name model.
+/// The rows are loaded with the source name as a prefix so that records from different
+/// graphs are never merged.
+/// Each row has an `id` and `name` column mapped to the `entity_id` and `scientific_name`
+/// of a name along with `fields` extra columns mapped to `fields:synthetic_N`.
+///
+/// The `scientific_name_authorship` is linked from the organism model with a `mapping:from`
+/// via the `scientific_name` so that resolving it exercises the linked dataset lookups while
+/// resolving the other fields doesn't.
+pub fn synthetic_dataset(records: usize, fields: usize, graphs: usize) -> Result<Dataset, TransformError> {
+    let mut dataset = Dataset::new(SYNTHETIC_SCHEMA)?;
+    let organism = dataset.model_schema(&Model::Organism);
+
+    for graph in 0..graphs {
+        let source = synthetic_source(graph);
+
+        let mut schema = SchemaBuilder::new()
+            .same(&column_iri("id"), iri(rdf::Name::EntityId))
+            .same(&column_iri("name"), iri(rdf::Name::ScientificName))
+            .from_model(iri(rdf::Name::ScientificNameAuthorship), organism.as_str(), iri(rdf::Name::ScientificName));
+
+        for (idx, field) in synthetic_fields(fields).iter().enumerate() {
+            schema = schema.same(&column_iri(&format!("synthetic_{idx}")), field.as_str());
+        }
+
+        dataset.load_schema(&source, &schema)?;
+        dataset.register_source(&source, &Model::Name)?;
+        let prefix = format!("{source}:");
+        dataset.load_with_prefix(synthetic_triples(records, fields, graph), &source, Some(&prefix))?;
+    }
+
+    // the organisms that the authorship of a name is linked from
+    let schema = SchemaBuilder::new()
+        .same(&column_iri("name"), iri(rdf::Name::ScientificName))
+        .same(&column_iri("authorship"), iri(rdf::Name::ScientificNameAuthorship));
+
+    dataset.load_schema(SYNTHETIC_LINKED_SOURCE, &schema)?;
+    dataset.register_source(SYNTHETIC_LINKED_SOURCE, &Model::Organism)?;
+    dataset.load(synthetic_linked_triples(records), SYNTHETIC_LINKED_SOURCE)?;
+
+    Ok(dataset)
+}
+
+
+/// Load the rows of a source along with its mappings and associate the source with the model.
+///
+/// The mappings are TriG statements written into the graph of the source and can use the
+/// `mapping:`, `fields:`, and `col:` prefixes from `SOURCE_PREFIXES`, where `col:` is the
+/// namespace of the loaded columns. Each row is a list of column and value pairs. The rows are
+/// loaded with the source name as a prefix so records from different sources are never merged.
+pub fn load_source(
+    dataset: &mut Dataset,
+    model: &Model,
+    source: &str,
+    mappings: &str,
+    rows: &[&[(&str, &str)]],
+) -> Result<TransformStats, TransformError> {
+    let graph = dataset.source_iri(source)?;
+    let trig = format!("{SOURCE_PREFIXES}<{graph}> {{\n{mappings}\n}}\n");
+    dataset.load_trig(BufReader::new(trig.as_bytes()))?;
+    dataset.register_source(source, model)?;
+
+    let prefix = format!("{source}:");
+    dataset.load_with_prefix(row_triples(rows), source, Some(&prefix))
+}
+
+
+/// Convert rows of column and value pairs into triples indexed by the position of the row.
+pub fn row_triples(rows: &[&[(&str, &str)]]) -> Vec<Result<Triple, TransformError>> {
+    let mut triples = Vec::new();
+    for (idx, row) in rows.iter().enumerate() {
+        for (column, value) in row.iter() {
+            triples.push(Ok((idx, column.to_string(), Literal::String(value.to_string()))));
+        }
+    }
+    triples
+}
+
+
+/// The triples loaded into the graph of a synthetic dataset.
+///
+/// These can be used on their own to measure loading without the cost of generating the data.
+pub fn synthetic_triples(records: usize, fields: usize, graph: usize) -> Vec<Result<Triple, TransformError>> {
+    let mut triples = Vec::with_capacity(records * (fields + 2));
+
+    for row in 0..records {
+        triples.push(Ok((row, "id".to_string(), Literal::String(format!("{graph}-{row}")))));
+        triples.push(Ok((row, "name".to_string(), Literal::String(synthetic_name(row)))));
+
+        for field in 0..fields {
+            triples.push(Ok((row, format!("synthetic_{field}"), Literal::String(format!("{row}:{field}")))));
+        }
+    }
+
+    triples
+}
+
+
+/// The IRIs of the extra fields in a synthetic dataset.
+pub fn synthetic_fields(fields: usize) -> Vec<iref::IriBuf> {
+    (0..fields)
+        .map(|field| {
+            let iri = format!("http://arga.org.au/schemas/fields/synthetic_{field}");
+            iref::IriBuf::new(iri).expect("synthetic field IRI is not valid")
+        })
+        .collect()
+}
+
+
+/// The name of the source for a graph in a synthetic dataset.
+pub fn synthetic_source(graph: usize) -> String {
+    format!("synthetic_{graph}")
+}
+
+
+fn synthetic_linked_triples(records: usize) -> Vec<Result<Triple, TransformError>> {
+    let mut triples = Vec::with_capacity(records * 2);
+
+    for row in 0..records {
+        triples.push(Ok((row, "name".to_string(), Literal::String(synthetic_name(row)))));
+        triples.push(Ok((row, "authorship".to_string(), Literal::String(format!("Author {row}")))));
+    }
+
+    triples
+}
+
+fn synthetic_name(row: usize) -> String {
+    format!("Synthetic species{row}")
+}
+
+/// The IRI of a column loaded into a synthetic dataset or with `load_source`.
+pub fn column_iri(header: &str) -> String {
+    format!("{SYNTHETIC_SCHEMA}/{header}")
+}
+
+fn iri(field: rdf::Name) -> &'static str {
+    let iri: &'static iref::Iri = field.into();
+    iri.as_str()
+}
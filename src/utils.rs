@@ -0,0 +1,128 @@
+use crate::errors::TransformError;
+use crate::rdf::{Quantity, Unit};
+
+
+/// The date formats that source data is known to use, tried in order.
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%d/%m/%Y", "%d %B %Y", "%B %d, %Y"];
+
+
+/// Parse a date in one of the formats used by sources and normalise it to `YYYY-MM-DD`.
+///
+/// A date with only a year, eg. `2021`, is normalised to the first day of that year
+/// as there is no date without a month and day.
+pub fn parse_date_flexible(s: &str) -> Result<String, TransformError> {
+    let value = s.trim();
+
+    for format in DATE_FORMATS {
+        if let Ok(date) = chrono::NaiveDate::parse_from_str(value, format) {
+            return Ok(date.format("%Y-%m-%d").to_string());
+        }
+    }
+
+    // chrono won't create a date without a month and day so a lone year is parsed as
+    // the first of january instead
+    if value.len() == 4
+        && value.chars().all(|c| c.is_ascii_digit())
+        && let Ok(date) = chrono::NaiveDate::parse_from_str(&format!("{value}-01-01"), "%Y-%m-%d")
+    {
+        return Ok(date.format("%Y-%m-%d").to_string());
+    }
+
+    Err(TransformError::DateParse(s.to_string()))
+}
+
+
+/// A distance normalised into metres along with its accuracy when one was given.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Distance {
+    pub metres: f64,
+    pub accuracy: Option<f64>,
+}
+
+
+/// Parse a distance such as `120 m`, `-15`, `15 ft`, or `120m ±5m` and convert it into metres.
+///
+/// A value without a unit is assumed to be in metres. The accuracy can follow the value
+/// after a `±` or `+/-` and is in the same unit as the value unless it has its own.
+pub fn parse_distance(s: &str) -> Option<Distance> {
+    let (value, accuracy) = match s.split_once('±').or_else(|| s.split_once("+/-")) {
+        Some((value, accuracy)) => (value, Some(accuracy)),
+        None => (s, None),
+    };
+
+    let (number, unit) = split_number(value)?;
+    let unit = distance_unit(unit, Unit::Metres)?;
+    let metres = unit.convert(number, &Unit::Metres)?;
+
+    let accuracy = match accuracy {
+        Some(accuracy) => {
+            let (number, accuracy_unit) = split_number(accuracy)?;
+            let accuracy_unit = distance_unit(accuracy_unit, unit)?;
+            Some(accuracy_unit.convert(number.abs(), &Unit::Metres)?)
+        }
+        None => None,
+    };
+
+    Some(Distance { metres, accuracy })
+}
+
+
+/// Split a value into the leading number and the unit that follows it.
+fn split_number(s: &str) -> Option<(f64, &str)> {
+    let s = s.trim();
+    let end = s
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | ',')))
+        .unwrap_or(s.len());
+
+    let number = s[..end].replace(',', "").parse::<f64>().ok()?;
+    Some((number, s[end..].trim()))
+}
+
+
+/// Parse a unit of distance, using the default when there isn't one.
+fn distance_unit(unit: &str, default: Unit) -> Option<Unit> {
+    let unit = match unit {
+        "" => default,
+        unit => unit.parse::<Unit>().ok()?,
+    };
+
+    match unit.quantity() {
+        Quantity::Distance => Some(unit),
+        _ => None,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_known_date_formats() {
+        let cases = [
+            ("2021-03-15", "2021-03-15"),
+            ("15/03/2021", "2021-03-15"),
+            ("15 March 2021", "2021-03-15"),
+            ("March 15, 2021", "2021-03-15"),
+            (" 2021-03-15 ", "2021-03-15"),
+            ("2021", "2021-01-01"),
+            // day first dates are preferred when the day and month could be swapped
+            ("01/02/2021", "2021-02-01"),
+        ];
+
+        for (value, expected) in cases {
+            assert_eq!(parse_date_flexible(value).unwrap(), expected, "{value}");
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_and_invalid_dates() {
+        // a year and month alone isn't a known format, nor are month first dates
+        let cases = ["2021-03", "03/2021", "02/13/2021", "31/02/2021", "21", "not a date", ""];
+
+        for value in cases {
+            let result = parse_date_flexible(value);
+            assert!(matches!(&result, Err(TransformError::DateParse(invalid)) if invalid == value), "{value}");
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod country;
+pub mod doi;
+pub mod orcid;
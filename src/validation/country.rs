@@ -0,0 +1,495 @@
+use unicode_normalization::UnicodeNormalization;
+
+
+/// The ISO 3166-1 alpha-2 code and short name of every country.
+const COUNTRIES: &[(&str, &str)] = &[
+    ("AD", "Andorra"),
+    ("AE", "United Arab Emirates"),
+    ("AF", "Afghanistan"),
+    ("AG", "Antigua and Barbuda"),
+    ("AI", "Anguilla"),
+    ("AL", "Albania"),
+    ("AM", "Armenia"),
+    ("AO", "Angola"),
+    ("AQ", "Antarctica"),
+    ("AR", "Argentina"),
+    ("AS", "American Samoa"),
+    ("AT", "Austria"),
+    ("AU", "Australia"),
+    ("AW", "Aruba"),
+    ("AX", "Åland Islands"),
+    ("AZ", "Azerbaijan"),
+    ("BA", "Bosnia and Herzegovina"),
+    ("BB", "Barbados"),
+    ("BD", "Bangladesh"),
+    ("BE", "Belgium"),
+    ("BF", "Burkina Faso"),
+    ("BG", "Bulgaria"),
+    ("BH", "Bahrain"),
+    ("BI", "Burundi"),
+    ("BJ", "Benin"),
+    ("BL", "Saint Barthélemy"),
+    ("BM", "Bermuda"),
+    ("BN", "Brunei Darussalam"),
+    ("BO", "Bolivia, Plurinational State of"),
+    ("BQ", "Bonaire, Sint Eustatius and Saba"),
+    ("BR", "Brazil"),
+    ("BS", "Bahamas"),
+    ("BT", "Bhutan"),
+    ("BV", "Bouvet Island"),
+    ("BW", "Botswana"),
+    ("BY", "Belarus"),
+    ("BZ", "Belize"),
+    ("CA", "Canada"),
+    ("CC", "Cocos (Keeling) Islands"),
+    ("CD", "Congo, Democratic Republic of the"),
+    ("CF", "Central African Republic"),
+    ("CG", "Congo"),
+    ("CH", "Switzerland"),
+    ("CI", "Côte d'Ivoire"),
+    ("CK", "Cook Islands"),
+    ("CL", "Chile"),
+    ("CM", "Cameroon"),
+    ("CN", "China"),
+    ("CO", "Colombia"),
+    ("CR", "Costa Rica"),
+    ("CU", "Cuba"),
+    ("CV", "Cabo Verde"),
+    ("CW", "Curaçao"),
+    ("CX", "Christmas Island"),
+    ("CY", "Cyprus"),
+    ("CZ", "Czechia"),
+    ("DE", "Germany"),
+    ("DJ", "Djibouti"),
+    ("DK", "Denmark"),
+    ("DM", "Dominica"),
+    ("DO", "Dominican Republic"),
+    ("DZ", "Algeria"),
+    ("EC", "Ecuador"),
+    ("EE", "Estonia"),
+    ("EG", "Egypt"),
+    ("EH", "Western Sahara"),
+    ("ER", "Eritrea"),
+    ("ES", "Spain"),
+    ("ET", "Ethiopia"),
+    ("FI", "Finland"),
+    ("FJ", "Fiji"),
+    ("FK", "Falkland Islands (Malvinas)"),
+    ("FM", "Micronesia, Federated States of"),
+    ("FO", "Faroe Islands"),
+    ("FR", "France"),
+    ("GA", "Gabon"),
+    ("GB", "United Kingdom of Great Britain and Northern Ireland"),
+    ("GD", "Grenada"),
+    ("GE", "Georgia"),
+    ("GF", "French Guiana"),
+    ("GG", "Guernsey"),
+    ("GH", "Ghana"),
+    ("GI", "Gibraltar"),
+    ("GL", "Greenland"),
+    ("GM", "Gambia"),
+    ("GN", "Guinea"),
+    ("GP", "Guadeloupe"),
+    ("GQ", "Equatorial Guinea"),
+    ("GR", "Greece"),
+    ("GS", "South Georgia and the South Sandwich Islands"),
+    ("GT", "Guatemala"),
+    ("GU", "Guam"),
+    ("GW", "Guinea-Bissau"),
+    ("GY", "Guyana"),
+    ("HK", "Hong Kong"),
+    ("HM", "Heard Island and McDonald Islands"),
+    ("HN", "Honduras"),
+    ("HR", "Croatia"),
+    ("HT", "Haiti"),
+    ("HU", "Hungary"),
+    ("ID", "Indonesia"),
+    ("IE", "Ireland"),
+    ("IL", "Israel"),
+    ("IM", "Isle of Man"),
+    ("IN", "India"),
+    ("IO", "British Indian Ocean Territory"),
+    ("IQ", "Iraq"),
+    ("IR", "Iran, Islamic Republic of"),
+    ("IS", "Iceland"),
+    ("IT", "Italy"),
+    ("JE", "Jersey"),
+    ("JM", "Jamaica"),
+    ("JO", "Jordan"),
+    ("JP", "Japan"),
+    ("KE", "Kenya"),
+    ("KG", "Kyrgyzstan"),
+    ("KH", "Cambodia"),
+    ("KI", "Kiribati"),
+    ("KM", "Comoros"),
+    ("KN", "Saint Kitts and Nevis"),
+    ("KP", "Korea, Democratic People's Republic of"),
+    ("KR", "Korea, Republic of"),
+    ("KW", "Kuwait"),
+    ("KY", "Cayman Islands"),
+    ("KZ", "Kazakhstan"),
+    ("LA", "Lao People's Democratic Republic"),
+    ("LB", "Lebanon"),
+    ("LC", "Saint Lucia"),
+    ("LI", "Liechtenstein"),
+    ("LK", "Sri Lanka"),
+    ("LR", "Liberia"),
+    ("LS", "Lesotho"),
+    ("LT", "Lithuania"),
+    ("LU", "Luxembourg"),
+    ("LV", "Latvia"),
+    ("LY", "Libya"),
+    ("MA", "Morocco"),
+    ("MC", "Monaco"),
+    ("MD", "Moldova, Republic of"),
+    ("ME", "Montenegro"),
+    ("MF", "Saint Martin (French part)"),
+    ("MG", "Madagascar"),
+    ("MH", "Marshall Islands"),
+    ("MK", "North Macedonia"),
+    ("ML", "Mali"),
+    ("MM", "Myanmar"),
+    ("MN", "Mongolia"),
+    ("MO", "Macao"),
+    ("MP", "Northern Mariana Islands"),
+    ("MQ", "Martinique"),
+    ("MR", "Mauritania"),
+    ("MS", "Montserrat"),
+    ("MT", "Malta"),
+    ("MU", "Mauritius"),
+    ("MV", "Maldives"),
+    ("MW", "Malawi"),
+    ("MX", "Mexico"),
+    ("MY", "Malaysia"),
+    ("MZ", "Mozambique"),
+    ("NA", "Namibia"),
+    ("NC", "New Caledonia"),
+    ("NE", "Niger"),
+    ("NF", "Norfolk Island"),
+    ("NG", "Nigeria"),
+    ("NI", "Nicaragua"),
+    ("NL", "Netherlands"),
+    ("NO", "Norway"),
+    ("NP", "Nepal"),
+    ("NR", "Nauru"),
+    ("NU", "Niue"),
+    ("NZ", "New Zealand"),
+    ("OM", "Oman"),
+    ("PA", "Panama"),
+    ("PE", "Peru"),
+    ("PF", "French Polynesia"),
+    ("PG", "Papua New Guinea"),
+    ("PH", "Philippines"),
+    ("PK", "Pakistan"),
+    ("PL", "Poland"),
+    ("PM", "Saint Pierre and Miquelon"),
+    ("PN", "Pitcairn"),
+    ("PR", "Puerto Rico"),
+    ("PS", "Palestine, State of"),
+    ("PT", "Portugal"),
+    ("PW", "Palau"),
+    ("PY", "Paraguay"),
+    ("QA", "Qatar"),
+    ("RE", "Réunion"),
+    ("RO", "Romania"),
+    ("RS", "Serbia"),
+    ("RU", "Russian Federation"),
+    ("RW", "Rwanda"),
+    ("SA", "Saudi Arabia"),
+    ("SB", "Solomon Islands"),
+    ("SC", "Seychelles"),
+    ("SD", "Sudan"),
+    ("SE", "Sweden"),
+    ("SG", "Singapore"),
+    ("SH", "Saint Helena, Ascension and Tristan da Cunha"),
+    ("SI", "Slovenia"),
+    ("SJ", "Svalbard and Jan Mayen"),
+    ("SK", "Slovakia"),
+    ("SL", "Sierra Leone"),
+    ("SM", "San Marino"),
+    ("SN", "Senegal"),
+    ("SO", "Somalia"),
+    ("SR", "Suriname"),
+    ("SS", "South Sudan"),
+    ("ST", "Sao Tome and Principe"),
+    ("SV", "El Salvador"),
+    ("SX", "Sint Maarten (Dutch part)"),
+    ("SY", "Syrian Arab Republic"),
+    ("SZ", "Eswatini"),
+    ("TC", "Turks and Caicos Islands"),
+    ("TD", "Chad"),
+    ("TF", "French Southern Territories"),
+    ("TG", "Togo"),
+    ("TH", "Thailand"),
+    ("TJ", "Tajikistan"),
+    ("TK", "Tokelau"),
+    ("TL", "Timor-Leste"),
+    ("TM", "Turkmenistan"),
+    ("TN", "Tunisia"),
+    ("TO", "Tonga"),
+    ("TR", "Türkiye"),
+    ("TT", "Trinidad and Tobago"),
+    ("TV", "Tuvalu"),
+    ("TW", "Taiwan, Province of China"),
+    ("TZ", "Tanzania, United Republic of"),
+    ("UA", "Ukraine"),
+    ("UG", "Uganda"),
+    ("UM", "United States Minor Outlying Islands"),
+    ("US", "United States of America"),
+    ("UY", "Uruguay"),
+    ("UZ", "Uzbekistan"),
+    ("VA", "Holy See"),
+    ("VC", "Saint Vincent and the Grenadines"),
+    ("VE", "Venezuela, Bolivarian Republic of"),
+    ("VG", "Virgin Islands (British)"),
+    ("VI", "Virgin Islands (U.S.)"),
+    ("VN", "Viet Nam"),
+    ("VU", "Vanuatu"),
+    ("WF", "Wallis and Futuna"),
+    ("WS", "Samoa"),
+    ("YE", "Yemen"),
+    ("YT", "Mayotte"),
+    ("ZA", "South Africa"),
+    ("ZM", "Zambia"),
+    ("ZW", "Zimbabwe"),
+];
+
+/// Other names countries are commonly written as in sources along with their code.
+const VARIANTS: &[(&str, &str)] = &[
+    ("Bolivia", "BO"),
+    ("Brunei", "BN"),
+    ("Burma", "MM"),
+    ("Cape Verde", "CV"),
+    ("Czech Republic", "CZ"),
+    ("Democratic Republic of the Congo", "CD"),
+    ("DR Congo", "CD"),
+    ("East Timor", "TL"),
+    ("England", "GB"),
+    ("Great Britain", "GB"),
+    ("Holland", "NL"),
+    ("Iran", "IR"),
+    ("Ivory Coast", "CI"),
+    ("Laos", "LA"),
+    ("Macau", "MO"),
+    ("Macedonia", "MK"),
+    ("Micronesia", "FM"),
+    ("Moldova", "MD"),
+    ("North Korea", "KP"),
+    ("Northern Ireland", "GB"),
+    ("Palestine", "PS"),
+    ("Republic of the Congo", "CG"),
+    ("Russia", "RU"),
+    ("Scotland", "GB"),
+    ("South Korea", "KR"),
+    ("Swaziland", "SZ"),
+    ("Syria", "SY"),
+    ("Taiwan", "TW"),
+    ("Tanzania", "TZ"),
+    ("Turkey", "TR"),
+    ("UAE", "AE"),
+    ("UK", "GB"),
+    ("United Kingdom", "GB"),
+    ("United States", "US"),
+    ("USA", "US"),
+    ("Vatican", "VA"),
+    ("Vatican City", "VA"),
+    ("Venezuela", "VE"),
+    ("Vietnam", "VN"),
+    ("Wales", "GB"),
+];
+
+/// The most edits a misspelt country name can be from a known name when matching fuzzily.
+const MAX_FUZZY_DISTANCE: usize = 2;
+
+/// Names shorter than this are too ambiguous to match fuzzily, eg. `Oman` and `Iran`.
+const MIN_FUZZY_LENGTH: usize = 6;
+
+
+/// Get the ISO 3166-1 alpha-2 code of a country name.
+///
+/// Names are compared without case, accents or punctuation so `Cote d'Ivoire` matches
+/// `Côte d'Ivoire`. Common variants like `USA`, `UK` and `Vietnam` are known as well as
+/// the short names from the standard, and a name that is already an upper case code returns
+/// the code.
+pub fn country_code(name: &str) -> Option<&'static str> {
+    let key = match_key(name);
+    if key.is_empty() {
+        return None;
+    }
+
+    let trimmed = name.trim();
+    if trimmed.len() == 2
+        && trimmed.chars().all(|c| c.is_ascii_uppercase())
+        && let Some(code) = normalise_code(trimmed)
+    {
+        return Some(code);
+    }
+
+    names()
+        .find(|(candidate, _code)| match_key(candidate) == key)
+        .map(|(_name, code)| code)
+}
+
+
+/// Get the ISO 3166-1 alpha-2 code of a country name, allowing for misspellings.
+///
+/// A name that doesn't match exactly is matched to the known name with the fewest edits
+/// so long as it's within `MAX_FUZZY_DISTANCE` and no other country is as close. Short
+/// names are never matched fuzzily as they're too close to other countries.
+pub fn country_code_fuzzy(name: &str) -> Option<&'static str> {
+    if let Some(code) = country_code(name) {
+        return Some(code);
+    }
+
+    let key = match_key(name);
+    if key.chars().count() < MIN_FUZZY_LENGTH {
+        return None;
+    }
+
+    let mut best: Option<(usize, &'static str)> = None;
+    let mut ambiguous = false;
+
+    for (candidate, code) in names() {
+        let distance = edit_distance(&key, &match_key(candidate));
+        if distance > MAX_FUZZY_DISTANCE {
+            continue;
+        }
+
+        match best {
+            Some((closest, best_code)) if distance == closest => ambiguous |= best_code != code,
+            Some((closest, _)) if distance > closest => {}
+            _ => {
+                best = Some((distance, code));
+                ambiguous = false;
+            }
+        }
+    }
+
+    match ambiguous {
+        true => None,
+        false => best.map(|(_distance, code)| code),
+    }
+}
+
+
+/// Normalise an alpha-2 country code into upper case, if it's a known code.
+pub fn normalise_code(code: &str) -> Option<&'static str> {
+    let code = code.trim().to_ascii_uppercase();
+    COUNTRIES
+        .iter()
+        .find(|(known, _name)| *known == code)
+        .map(|(known, _name)| *known)
+}
+
+
+/// Every known name of a country along with its code.
+fn names() -> impl Iterator<Item = (&'static str, &'static str)> {
+    let names = COUNTRIES.iter().map(|(code, name)| (*name, *code));
+    names.chain(VARIANTS.iter().copied())
+}
+
+
+/// The form of a name used to compare it, lower case with only letters and single spaces.
+///
+/// Accents are dropped by decomposing the name and only keeping the base letters, and the
+/// article `the` is ignored so `The Gambia` matches `Gambia`.
+fn match_key(name: &str) -> String {
+    let letters: String = name
+        .nfd()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+
+    let words: Vec<&str> = letters.split_whitespace().filter(|word| *word != "the").collect();
+    words.join(" ")
+}
+
+
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn country_code_matches_names_and_variants() {
+        let cases = [
+            ("Australia", Some("AU")),
+            ("australia", Some("AU")),
+            ("  New   Zealand ", Some("NZ")),
+            ("USA", Some("US")),
+            ("U.S.A.", Some("US")),
+            ("United States", Some("US")),
+            ("UK", Some("GB")),
+            ("Viet Nam", Some("VN")),
+            ("Vietnam", Some("VN")),
+            ("Côte d'Ivoire", Some("CI")),
+            ("Cote d'Ivoire", Some("CI")),
+            ("Korea, Republic of", Some("KR")),
+            ("South Korea", Some("KR")),
+            ("The Netherlands", Some("NL")),
+            ("AU", Some("AU")),
+            ("Narnia", None),
+            ("", None),
+        ];
+
+        for (name, expected) in cases {
+            assert_eq!(country_code(name), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn country_code_only_accepts_upper_case_codes() {
+        // a lower case word like `no` is more likely to be a value than Norway's code
+        assert_eq!(country_code("no"), None);
+        assert_eq!(country_code("NO"), Some("NO"));
+    }
+
+    #[test]
+    fn normalise_code_upper_cases_known_codes() {
+        assert_eq!(normalise_code(" au "), Some("AU"));
+        assert_eq!(normalise_code("gb"), Some("GB"));
+        assert_eq!(normalise_code("UK"), None);
+        assert_eq!(normalise_code("AUS"), None);
+    }
+
+    #[test]
+    fn country_code_fuzzy_matches_misspellings() {
+        assert_eq!(country_code("Astralia"), None);
+        assert_eq!(country_code_fuzzy("Astralia"), Some("AU"));
+        assert_eq!(country_code_fuzzy("Phillipines"), Some("PH"));
+        assert_eq!(country_code_fuzzy("Australia"), Some("AU"));
+    }
+
+    #[test]
+    fn country_code_fuzzy_rejects_ambiguous_and_short_names() {
+        // one edit from both Australia and Austria
+        assert_eq!(country_code_fuzzy("Austrlia"), None);
+        // one edit from Iran and Iraq but too short to match fuzzily anyway
+        assert_eq!(country_code_fuzzy("Iram"), None);
+        assert_eq!(country_code_fuzzy("Narnia"), None);
+    }
+
+    #[test]
+    fn edit_distance_counts_edits() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+}
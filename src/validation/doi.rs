@@ -0,0 +1,87 @@
+use crate::errors::ValidationError;
+
+
+/// The prefixes a DOI can be written with when given as a URL or URI, after lower casing.
+const PREFIXES: &[&str] = &[
+    "https://doi.org/",
+    "http://doi.org/",
+    "https://dx.doi.org/",
+    "http://dx.doi.org/",
+    "doi.org/",
+    "dx.doi.org/",
+    "doi:",
+];
+
+/// Punctuation that is left at the end of a DOI when it is taken from a citation.
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', ')', ']', '}', '"', '\''];
+
+
+/// Normalise a DOI into the lower case form without a resolver prefix, eg. `10.1000/xyz123`.
+///
+/// DOIs are case insensitive so lower casing them lets the same DOI written differently
+/// be compared for equality. The DOI must have a `10.` directory indicator followed by a
+/// numeric registrant code and a non-empty suffix.
+pub fn normalise(value: &str) -> Result<String, ValidationError> {
+    let lowered = value.trim().to_lowercase();
+    let doi = PREFIXES
+        .iter()
+        .find_map(|prefix| lowered.strip_prefix(prefix))
+        .unwrap_or(&lowered)
+        .trim()
+        .trim_end_matches(TRAILING_PUNCTUATION);
+
+    let malformed = || ValidationError::MalformedDoi(value.to_string());
+    let (prefix, suffix) = doi.split_once('/').ok_or_else(malformed)?;
+    let registrant = prefix.strip_prefix("10.").ok_or_else(malformed)?;
+
+    // registrant codes can have subdivisions, eg. 10.1000.10/123
+    let valid_registrant = registrant
+        .split('.')
+        .all(|code| !code.is_empty() && code.chars().all(|c| c.is_ascii_digit()));
+
+    if !valid_registrant || suffix.is_empty() || doi.chars().any(char::is_whitespace) {
+        return Err(malformed());
+    }
+
+    Ok(doi.to_string())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalise_strips_prefixes_and_punctuation() {
+        let cases = [
+            ("10.1000/xyz123", "10.1000/xyz123"),
+            ("10.1000/XYZ123", "10.1000/xyz123"),
+            ("doi:10.1000/xyz123", "10.1000/xyz123"),
+            ("DOI: 10.1000/xyz123", "10.1000/xyz123"),
+            ("https://doi.org/10.1000/xyz123", "10.1000/xyz123"),
+            ("http://dx.doi.org/10.1000/xyz123", "10.1000/xyz123"),
+            ("10.1000/xyz123.", "10.1000/xyz123"),
+            ("10.1000.10/abc(2020)01", "10.1000.10/abc(2020)01"),
+        ];
+
+        for (value, expected) in cases {
+            assert_eq!(normalise(value).as_deref(), Ok(expected), "{value}");
+        }
+    }
+
+    #[test]
+    fn normalise_rejects_malformed_dois() {
+        for value in [
+            "",
+            "xyz123",
+            "10.1000",
+            "10.1000/",
+            "11.1000/xyz",
+            "10.abc/xyz",
+            "10..1/xyz",
+            "10.1000/x yz",
+        ] {
+            assert_eq!(normalise(value), Err(ValidationError::MalformedDoi(value.to_string())), "{value}");
+        }
+    }
+}
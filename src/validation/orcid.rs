@@ -0,0 +1,146 @@
+use tracing::warn;
+
+use crate::errors::ValidationError;
+
+
+/// The prefixes an ORCID can be written with when given as a URL.
+const URL_PREFIXES: &[&str] = &["https://orcid.org/", "http://orcid.org/", "orcid.org/"];
+
+
+/// Normalise an ORCID into the hyphenated form, eg. `0000-0002-1825-0097`.
+///
+/// ORCIDs are accepted as bare ids or URLs and with any whitespace or hyphens. The
+/// checksum is verified with the ISO 7064 mod 11-2 algorithm and a lower case `x`
+/// check digit is upper cased.
+pub fn normalise(value: &str) -> Result<String, ValidationError> {
+    let trimmed = value.trim();
+    let id = URL_PREFIXES
+        .iter()
+        .find_map(|prefix| trimmed.strip_prefix(prefix))
+        .unwrap_or(trimmed);
+
+    let chars: Vec<char> = id
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    let malformed = || ValidationError::MalformedOrcid(value.to_string());
+    if chars.len() != 16 {
+        return Err(malformed());
+    }
+
+    let mut digits = Vec::with_capacity(15);
+    for c in &chars[..15] {
+        digits.push(c.to_digit(10).ok_or_else(malformed)?);
+    }
+
+    let check = chars[15];
+    if !check.is_ascii_digit() && check != 'X' {
+        return Err(malformed());
+    }
+    if check != check_digit(&digits) {
+        return Err(ValidationError::OrcidChecksum(value.to_string()));
+    }
+
+    let groups: Vec<String> = chars.chunks(4).map(|group| group.iter().collect()).collect();
+    Ok(groups.join("-"))
+}
+
+
+/// Normalise a resolved ORCID, dropping it with a warning if it isn't valid.
+pub fn normalise_or_warn(value: String) -> Option<String> {
+    match normalise(&value) {
+        Ok(orcid) => Some(orcid),
+        Err(err) => {
+            warn!(%err, "Dropping invalid ORCID");
+            None
+        }
+    }
+}
+
+
+/// Calculate the ISO 7064 mod 11-2 check digit of the base digits of an ORCID.
+fn check_digit(digits: &[u32]) -> char {
+    let mut total = 0;
+    for digit in digits {
+        total = (total + digit) * 2;
+    }
+
+    match (12 - total % 11) % 11 {
+        10 => 'X',
+        // the remainder is always below 10 here so this can't fail
+        digit => char::from_digit(digit, 10).unwrap_or('0'),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalise_accepts_valid_orcids() {
+        let cases = [
+            ("0000-0002-1825-0097", "0000-0002-1825-0097"),
+            ("0000000218250097", "0000-0002-1825-0097"),
+            (" 0000 0001 5109 3700 ", "0000-0001-5109-3700"),
+            ("0000-0002-1694-233X", "0000-0002-1694-233X"),
+            ("0000-0002-1694-233x", "0000-0002-1694-233X"),
+        ];
+
+        for (value, expected) in cases {
+            assert_eq!(normalise(value).as_deref(), Ok(expected), "{value}");
+        }
+    }
+
+    #[test]
+    fn normalise_strips_urls() {
+        let cases = [
+            "https://orcid.org/0000-0002-1825-0097",
+            "http://orcid.org/0000-0002-1825-0097",
+            "orcid.org/0000-0002-1825-0097",
+            " https://orcid.org/0000000218250097 ",
+        ];
+
+        for value in cases {
+            assert_eq!(normalise(value).as_deref(), Ok("0000-0002-1825-0097"), "{value}");
+        }
+    }
+
+    #[test]
+    fn normalise_rejects_invalid_checksums() {
+        for value in [
+            "0000-0002-1825-0098",
+            "0000-0002-1694-2330",
+            "https://orcid.org/0000-0001-5109-370X",
+        ] {
+            assert_eq!(normalise(value), Err(ValidationError::OrcidChecksum(value.to_string())), "{value}");
+        }
+    }
+
+    #[test]
+    fn normalise_rejects_malformed_orcids() {
+        let cases = [
+            "",
+            "0000-0002-1825",
+            "0000-0002-1825-00971",
+            "0000-000A-1825-0097",
+            "0000-0002-1825-009Y",
+            "https://example.org/0000-0002-1825-0097",
+            "Josiah Carberry",
+        ];
+
+        for value in cases {
+            assert_eq!(normalise(value), Err(ValidationError::MalformedOrcid(value.to_string())), "{value}");
+        }
+    }
+
+    #[test]
+    fn normalise_or_warn_drops_invalid_orcids() {
+        let orcid = normalise_or_warn("https://orcid.org/0000-0002-1825-0097".to_string());
+        assert_eq!(orcid.as_deref(), Some("0000-0002-1825-0097"));
+        assert_eq!(normalise_or_warn("0000-0002-1825-0098".to_string()), None);
+        assert_eq!(normalise_or_warn("not an orcid".to_string()), None);
+    }
+}